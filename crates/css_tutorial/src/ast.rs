@@ -3,19 +3,19 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
 // ANCHOR: ast_tree
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AstTree<T: Default + Serialize> {
     pub root: Option<Box<AstNode<T>>>,
 }
 // ANCHOR_END: ast_tree
 
 // ANCHOR: ast_node_type
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AstNodeType<T: Default + Serialize>(pub T);
 // ANCHOR_END: ast_node_type
 
 // ANCHOR: ast_node
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AstNode<T: Default + Serialize> {
     pub node_type: AstNodeType<T>,
     pub range: Range,
@@ -85,6 +85,20 @@ pub trait Visitor {
 
 
 impl<T: Default + Serialize> AstNode<T> {
+    // 由子节点组合出新节点，raw 和 range 的计算方式与 finish_node 相同
+    pub fn from_children(node_type: T, children: Vec<AstNode<T>>) -> Self {
+        let range = Range::new(
+            children.first().map(|n| n.range.start_pos).unwrap_or_default(),
+            children.last().map(|n| n.range.end_pos).unwrap_or_default(),
+        );
+        AstNode {
+            node_type: AstNodeType(node_type),
+            range,
+            raw: children.iter().map(|child| child.raw.as_str()).collect(),
+            children: Some(children),
+        }
+    }
+
     pub fn travel<F: Fn(&AstNode<T>)>(&mut self, cb: &F) {
         cb(&self);
     }
@@ -95,6 +109,20 @@ impl<T: Default + Serialize> AstNode<T> {
     }
 }
 
+impl AstNode<TokenType> {
+    pub fn check_type(&self, token_type: TokenType) -> bool {
+        self.node_type.0 == token_type
+    }
+
+    pub fn child_nodes(&self) -> &[AstNode<TokenType>] {
+        self.children.as_deref().unwrap_or_default()
+    }
+
+    pub fn find_child(&self, token_type: TokenType) -> Option<&AstNode<TokenType>> {
+        self.child_nodes().iter().find(|n| n.check_type(token_type))
+    }
+}
+
 impl<T: Debug + Default + Serialize> AstTreeBuilder<T> {
     pub fn new() -> Self {
         AstTreeBuilder {
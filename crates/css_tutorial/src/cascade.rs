@@ -0,0 +1,424 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{AstNode, AstTree, AstTreeBuilder},
+    codegen::{tokens_to_string, value_to_string},
+    html::{Document, NodeId},
+    lexer::Lexer,
+    parser::Parser,
+    selector::{compile_selector_list, SelectorList, Specificity},
+    token_type::TokenType,
+};
+
+// ANCHOR: origin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Origin {
+    UserAgent,
+    User,
+    Author,
+}
+// ANCHOR_END: origin
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CascadedDeclaration {
+    pub property: String,
+    pub value: String,
+    pub important: bool,
+    pub origin: Origin,
+    // 所在的 @layer，嵌套层用 . 连接
+    pub layer: Option<String>,
+    // 来自元素的 style 属性
+    pub inline: bool,
+    pub specificity: Specificity,
+    pub source_order: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ElementStyle {
+    pub element: NodeId,
+    // 每个属性胜出的声明，按层叠优先级从低到高排列
+    pub declarations: Vec<CascadedDeclaration>,
+}
+
+impl ElementStyle {
+    pub fn get(&self, property: &str) -> Option<&CascadedDeclaration> {
+        self.declarations.iter().find(|d| d.property == property)
+    }
+}
+
+// 样式表中的一条规则
+#[derive(Debug, Clone)]
+pub struct CascadeRule {
+    pub selectors: SelectorList,
+    pub declarations: Vec<CascadeDeclaration>,
+    pub origin: Origin,
+    // 未分层为 None
+    pub layer: Option<String>,
+    // 层在所属来源中的排序键
+    layer_key: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CascadeDeclaration {
+    pub property: String,
+    pub value: String,
+    pub important: bool,
+    pub source_order: usize,
+}
+
+// ANCHOR: cascade
+#[derive(Debug)]
+pub struct Cascade {
+    pub rules: Vec<CascadeRule>,
+    // @media 只按媒体类型匹配，带特性查询的规则不参与层叠
+    pub media_type: String,
+    layers: HashMap<Origin, LayerOrder>,
+    source_order: usize,
+}
+// ANCHOR_END: cascade
+
+// 记录层出现的顺序，子层挂在父层下面
+#[derive(Debug, Default)]
+struct LayerOrder {
+    names: Vec<String>,
+    anonymous: usize,
+}
+
+impl LayerOrder {
+    fn key(&mut self, name: &str) -> usize {
+        match self.names.iter().position(|n| n == name) {
+            Some(index) => index,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() - 1
+            }
+        }
+    }
+}
+
+impl Default for Cascade {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cascade {
+    pub fn new() -> Self {
+        Cascade {
+            rules: Vec::new(),
+            media_type: "screen".to_string(),
+            layers: HashMap::new(),
+            source_order: 0,
+        }
+    }
+
+    pub fn add_stylesheet(&mut self, origin: Origin, tree: &AstTree<TokenType>) {
+        if let Some(root) = &tree.root {
+            self.collect(origin, root.child_nodes(), &mut Vec::new());
+        }
+    }
+
+    fn collect(&mut self, origin: Origin, items: &[AstNode<TokenType>], layer: &mut Vec<String>) {
+        for item in items {
+            match item.node_type.0 {
+                TokenType::RuleList => self.collect_rule(origin, item, layer),
+                TokenType::AtRule => self.collect_at_rule(origin, item, layer),
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_at_rule(
+        &mut self,
+        origin: Origin,
+        node: &AstNode<TokenType>,
+        layer: &mut Vec<String>,
+    ) {
+        let children = node.child_nodes();
+        let name = children
+            .first()
+            .map(|n| n.raw.to_ascii_lowercase())
+            .unwrap_or_default();
+        let params = node
+            .find_child(TokenType::AtRuleParams)
+            .map(|p| tokens_to_string(p.child_nodes()))
+            .unwrap_or_default();
+        let has_block = node.find_child(TokenType::LeftCurlyBracket).is_some();
+        match name.as_str() {
+            "@media" if media_matches(&params, &self.media_type) => {
+                self.collect(origin, children, layer)
+            }
+            "@supports" => self.collect(origin, children, layer),
+            "@layer" if has_block => {
+                let names = match params.trim() {
+                    "" => {
+                        let order = self.layers.entry(origin).or_default();
+                        order.anonymous += 1;
+                        vec![format!("<anonymous-{}>", order.anonymous)]
+                    }
+                    params => params.split('.').map(|s| s.trim().to_string()).collect(),
+                };
+                let depth = layer.len();
+                for name in names {
+                    layer.push(name);
+                    self.register_layer(origin, layer);
+                }
+                self.collect(origin, children, layer);
+                layer.truncate(depth);
+            }
+            "@layer" => {
+                // @layer a, b.c;
+                for name in params.split(',') {
+                    let depth = layer.len();
+                    layer.extend(name.split('.').map(|s| s.trim().to_string()));
+                    self.register_layer(origin, layer);
+                    layer.truncate(depth);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 注册层及其所有父层，返回排序键
+    fn register_layer(&mut self, origin: Origin, layer: &[String]) -> Vec<usize> {
+        let order = self.layers.entry(origin).or_default();
+        (1..=layer.len())
+            .map(|depth| order.key(&layer[..depth].join(".")))
+            .collect()
+    }
+
+    fn collect_rule(&mut self, origin: Origin, node: &AstNode<TokenType>, layer: &[String]) {
+        let Some(selector) = node.find_child(TokenType::SelectorList) else {
+            return;
+        };
+        let Some(list) = node.find_child(TokenType::DeclarationList) else {
+            return;
+        };
+        let declarations = self.collect_declarations(list);
+        let layer_key = if layer.is_empty() {
+            Vec::new()
+        } else {
+            self.register_layer(origin, layer)
+        };
+        self.rules.push(CascadeRule {
+            selectors: compile_selector_list(selector),
+            declarations,
+            origin,
+            layer: (!layer.is_empty()).then(|| layer.join(".")),
+            layer_key,
+        });
+    }
+
+    fn collect_declarations(&mut self, list: &AstNode<TokenType>) -> Vec<CascadeDeclaration> {
+        let mut result = Vec::new();
+        for declaration in list.child_nodes() {
+            if !declaration.check_type(TokenType::Declaration) {
+                continue;
+            }
+            self.source_order += 1;
+            result.push(CascadeDeclaration {
+                property: property_name(declaration),
+                value: declaration
+                    .find_child(TokenType::Expression)
+                    .map(value_to_string)
+                    .unwrap_or_default(),
+                important: declaration.find_child(TokenType::Important).is_some(),
+                source_order: self.source_order,
+            });
+        }
+        result
+    }
+
+    // 层叠排序键，越大优先级越高
+    fn sort_key(
+        &self,
+        declaration: &CascadedDeclaration,
+        layer_key: &[usize],
+    ) -> (u8, bool, Vec<usize>, Specificity, usize) {
+        let origin_rank = match (declaration.origin, declaration.important) {
+            (Origin::UserAgent, false) => 0,
+            (Origin::User, false) => 1,
+            (Origin::Author, false) => 2,
+            (Origin::Author, true) => 3,
+            (Origin::User, true) => 4,
+            (Origin::UserAgent, true) => 5,
+        };
+        // 未分层的样式排在所有层之后，!important 时顺序反转
+        let mut layer_rank: Vec<usize> = layer_key.to_vec();
+        layer_rank.push(usize::MAX);
+        if declaration.important {
+            layer_rank = layer_rank.iter().map(|k| usize::MAX - k).collect();
+        }
+        (
+            origin_rank,
+            declaration.inline,
+            layer_rank,
+            declaration.specificity,
+            declaration.source_order,
+        )
+    }
+
+    // ANCHOR: compute
+    pub fn compute(&self, document: &Document) -> Vec<ElementStyle> {
+        let mut inline_order = self.source_order;
+        document
+            .elements()
+            .into_iter()
+            .map(|element| {
+                let mut matched: Vec<(CascadedDeclaration, Vec<usize>)> = Vec::new();
+                for rule in &self.rules {
+                    let Some(specificity) = rule.selectors.matching_specificity(document, element)
+                    else {
+                        continue;
+                    };
+                    for declaration in &rule.declarations {
+                        matched.push((
+                            CascadedDeclaration {
+                                property: declaration.property.clone(),
+                                value: declaration.value.clone(),
+                                important: declaration.important,
+                                origin: rule.origin,
+                                layer: rule.layer.clone(),
+                                inline: false,
+                                specificity,
+                                source_order: declaration.source_order,
+                            },
+                            rule.layer_key.clone(),
+                        ));
+                    }
+                }
+                let style = document
+                    .element(element)
+                    .and_then(|e| e.get_attribute("style"))
+                    .unwrap_or_default();
+                for declaration in parse_style_attribute(style) {
+                    inline_order += 1;
+                    matched.push((
+                        CascadedDeclaration {
+                            inline: true,
+                            source_order: inline_order,
+                            ..declaration
+                        },
+                        Vec::new(),
+                    ));
+                }
+                matched.sort_by_cached_key(|(declaration, layer_key)| {
+                    self.sort_key(declaration, layer_key)
+                });
+
+                let mut declarations: Vec<CascadedDeclaration> = Vec::new();
+                for (declaration, _) in matched.into_iter().rev() {
+                    if declarations.iter().all(|d| d.property != declaration.property) {
+                        declarations.push(declaration);
+                    }
+                }
+                declarations.reverse();
+                ElementStyle {
+                    element,
+                    declarations,
+                }
+            })
+            .collect()
+    }
+    // ANCHOR_END: compute
+}
+
+pub fn property_name(declaration: &AstNode<TokenType>) -> String {
+    let name = declaration
+        .find_child(TokenType::Property)
+        .map(|p| p.raw.clone())
+        .unwrap_or_default();
+    if name.starts_with("--") {
+        name
+    } else {
+        name.to_ascii_lowercase()
+    }
+}
+
+// 解析元素 style 属性中的声明
+pub fn parse_style_attribute(style: &str) -> Vec<CascadedDeclaration> {
+    if style.trim().is_empty() {
+        return Vec::new();
+    }
+    let source = format!("{{{}}}", style);
+    let mut lexer = Lexer::new(&source);
+    let mut builder = AstTreeBuilder::new();
+    let mut parser = Parser::new(&mut lexer, &mut builder);
+    parser.parse_declaration_list();
+    builder.finish();
+    let Some(root) = &builder.ast_tree.root else {
+        return Vec::new();
+    };
+    root.child_nodes()
+        .iter()
+        .filter(|n| n.check_type(TokenType::Declaration))
+        .map(|declaration| CascadedDeclaration {
+            property: property_name(declaration),
+            value: declaration
+                .find_child(TokenType::Expression)
+                .map(value_to_string)
+                .unwrap_or_default(),
+            important: declaration.find_child(TokenType::Important).is_some(),
+            origin: Origin::Author,
+            layer: None,
+            inline: true,
+            specificity: Specificity::default(),
+            source_order: 0,
+        })
+        .collect()
+}
+
+// 只比较媒体类型，例如 screen、print、all
+pub fn media_matches(params: &str, media_type: &str) -> bool {
+    params.split(',').any(|query| {
+        let words: Vec<String> = query
+            .split_whitespace()
+            .map(|w| w.to_ascii_lowercase())
+            .collect();
+        match words.as_slice() {
+            [] => true,
+            [only, ty] if only == "only" => ty == "all" || ty == media_type,
+            [not, ty] if not == "not" => ty != "all" && ty != media_type,
+            [ty] => ty == "all" || ty == media_type,
+            _ => false,
+        }
+    })
+}
+
+// ANCHOR: print_styles
+// 输出每个元素的层叠结果，便于做快照测试
+pub fn print_styles(document: &Document, styles: &[ElementStyle]) -> String {
+    let mut out = String::new();
+    for style in styles {
+        let mut depth = 0;
+        let mut current = document.parent_element(style.element);
+        while let Some(parent) = current {
+            depth += 1;
+            current = document.parent_element(parent);
+        }
+        let element = document.element(style.element).unwrap();
+        let mut name = element.tag_name.clone();
+        if let Some(id) = element.id() {
+            name.push_str(&format!("#{}", id));
+        }
+        for class in element.classes() {
+            name.push_str(&format!(".{}", class));
+        }
+        out.push_str(&format!("{}{}\n", "  ".repeat(depth), name));
+        for declaration in &style.declarations {
+            out.push_str(&format!(
+                "{}  {}: {}{}\n",
+                "  ".repeat(depth),
+                declaration.property,
+                declaration.value,
+                if declaration.important {
+                    " !important"
+                } else {
+                    ""
+                }
+            ));
+        }
+    }
+    out
+}
+// ANCHOR_END: print_styles
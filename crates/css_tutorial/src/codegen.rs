@@ -0,0 +1,404 @@
+use crate::{
+    ast::{AstNode, AstTree},
+    token_type::TokenType,
+};
+
+// ANCHOR: codegen
+// 将语法树重新输出为 css 文本
+#[derive(Debug, Default)]
+pub struct Codegen {
+    out: String,
+    indent: usize,
+}
+// ANCHOR_END: codegen
+
+pub fn generate(tree: &AstTree<TokenType>) -> String {
+    match &tree.root {
+        Some(root) => node_to_string(root),
+        None => String::new(),
+    }
+}
+
+pub fn node_to_string(node: &AstNode<TokenType>) -> String {
+    let mut codegen = Codegen::default();
+    codegen.node(node);
+    codegen.out
+}
+
+impl Codegen {
+    fn write_indent(&mut self) {
+        for _ in 0..self.indent {
+            self.out.push_str("  ");
+        }
+    }
+
+    fn node(&mut self, node: &AstNode<TokenType>) {
+        match node.node_type.0 {
+            TokenType::Stylesheets => self.block_items(node.child_nodes()),
+            TokenType::RuleList => self.rule(node),
+            TokenType::AtRule => self.at_rule(node),
+            TokenType::DeclarationList => self.declaration_list(node),
+            TokenType::Declaration => self.out.push_str(&declaration_to_string(node)),
+            TokenType::SelectorList | TokenType::Selector | TokenType::SimpleSelect => {
+                self.out.push_str(&selector_to_string(node))
+            }
+            TokenType::Expression => self.out.push_str(&value_to_string(node)),
+            TokenType::Import
+            | TokenType::ChartSet
+            | TokenType::VariableDeclaration
+            | TokenType::DetachedRulesetCall
+            | TokenType::ContentRule
+            | TokenType::ReturnRule
+            | TokenType::ExtendRule
+            | TokenType::MediumList
+            | TokenType::AtRuleParams => {
+                let text = tokens_to_string(node.child_nodes());
+                self.out.push_str(text.trim_end_matches(';'));
+                if !node.check_type(TokenType::MediumList)
+                    && !node.check_type(TokenType::AtRuleParams)
+                {
+                    self.out.push(';');
+                }
+            }
+            TokenType::MixinRule
+            | TokenType::IncludeRule
+            | TokenType::FunctionRule
+            | TokenType::IfRule
+            | TokenType::ElseRule
+            | TokenType::EachRule
+            | TokenType::ForRule
+            | TokenType::WhileRule => self.scss_block(node),
+            TokenType::Page => {
+                let children = node.child_nodes();
+                let head = children
+                    .iter()
+                    .position(|n| n.check_type(TokenType::DeclarationList))
+                    .unwrap_or(children.len());
+                self.out.push_str(&tokens_to_string(&children[..head]));
+                if let Some(list) = children.get(head) {
+                    self.out.push(' ');
+                    self.declaration_list(list);
+                }
+            }
+            _ => self.out.push_str(&node.raw),
+        }
+    }
+
+    // 样式表或块中的规则，每个占一行
+    fn block_items(&mut self, items: &[AstNode<TokenType>]) {
+        for item in items {
+            match item.node_type.0 {
+                TokenType::Semi
+                | TokenType::CDOToken
+                | TokenType::CDCToken
+                | TokenType::LeftCurlyBracket
+                | TokenType::RightCurlyBracket => continue,
+                _ => {}
+            }
+            self.write_indent();
+            self.node(item);
+            if item.check_type(TokenType::Declaration) {
+                self.out.push(';');
+            }
+            self.out.push('\n');
+        }
+    }
+
+    fn rule(&mut self, node: &AstNode<TokenType>) {
+        if let Some(selector) = node.find_child(TokenType::SelectorList) {
+            self.out.push_str(&selector_to_string(selector));
+        }
+        if let Some(guard) = node.find_child(TokenType::Guard) {
+            self.out.push(' ');
+            self.out.push_str(&guard_to_string(guard));
+        }
+        match node.find_child(TokenType::DeclarationList) {
+            Some(list) => {
+                self.out.push(' ');
+                self.declaration_list(list);
+            }
+            // less mixin 调用 .a();
+            None => {
+                if node.find_child(TokenType::Important).is_some() {
+                    self.out.push_str(" !important");
+                }
+                self.out.push(';');
+            }
+        }
+    }
+
+    // scss 的 @mixin、@if 等，@else 跟在 @if 的块后面
+    fn scss_block(&mut self, node: &AstNode<TokenType>) {
+        let children = node.child_nodes();
+        let head = children
+            .iter()
+            .position(|n| n.check_type(TokenType::DeclarationList))
+            .unwrap_or(children.len());
+        let head_text = tokens_to_string(&children[..head]);
+        self.out.push_str(head_text.trim_end_matches(';'));
+        if head == children.len() {
+            self.out.push(';');
+            return;
+        }
+        for child in &children[head..] {
+            if child.check_type(TokenType::DeclarationList) {
+                self.out.push(' ');
+                self.declaration_list(child);
+            } else if child.check_type(TokenType::ElseRule) {
+                self.out.push(' ');
+                self.scss_block(child);
+            }
+        }
+    }
+
+    fn declaration_list(&mut self, node: &AstNode<TokenType>) {
+        self.out.push_str("{\n");
+        self.indent += 1;
+        for item in node.child_nodes() {
+            match item.node_type.0 {
+                TokenType::Declaration => {
+                    self.write_indent();
+                    self.out.push_str(&declaration_to_string(item));
+                    self.out.push_str(";\n");
+                }
+                TokenType::Semi | TokenType::LeftCurlyBracket | TokenType::RightCurlyBracket => {}
+                _ => self.block_items(std::slice::from_ref(item)),
+            }
+        }
+        self.indent -= 1;
+        self.write_indent();
+        self.out.push('}');
+    }
+
+    fn at_rule(&mut self, node: &AstNode<TokenType>) {
+        let children = node.child_nodes();
+        if let Some(name) = children.first() {
+            self.out.push_str(&name.raw);
+        }
+        if let Some(params) = node.find_child(TokenType::AtRuleParams) {
+            let params = tokens_to_string(params.child_nodes());
+            if !params.is_empty() {
+                self.out.push(' ');
+                self.out.push_str(&params);
+            }
+        }
+        if let Some(list) = node.find_child(TokenType::DeclarationList) {
+            self.out.push(' ');
+            self.declaration_list(list);
+        } else if node.find_child(TokenType::LeftCurlyBracket).is_some() {
+            self.out.push_str(" {\n");
+            self.indent += 1;
+            self.block_items(&children[2..]);
+            self.indent -= 1;
+            self.write_indent();
+            self.out.push('}');
+        } else {
+            self.out.push(';');
+        }
+    }
+}
+
+pub fn declaration_to_string(node: &AstNode<TokenType>) -> String {
+    let mut result = String::new();
+    if let Some(property) = node.find_child(TokenType::Property) {
+        result.push_str(&property.raw);
+    }
+    result.push_str(": ");
+    if let Some(expr) = node.find_child(TokenType::Expression) {
+        result.push_str(&value_to_string(expr));
+    }
+    if node.find_child(TokenType::Important).is_some() {
+        result.push_str(" !important");
+    }
+    result
+}
+
+// less 守卫 when (@a > 0) and not (iscolor(@c))
+pub fn guard_to_string(node: &AstNode<TokenType>) -> String {
+    let mut result = String::new();
+    for item in node.child_nodes() {
+        let text = match item.node_type.0 {
+            TokenType::GuardCondition => guard_to_string(item),
+            TokenType::Expression => value_to_string(item),
+            _ => item.raw.clone(),
+        };
+        let tight = result.is_empty()
+            || result.ends_with('(')
+            || item.check_type(TokenType::RightParenthesis)
+            || item.check_type(TokenType::Comma);
+        if !tight {
+            result.push(' ');
+        }
+        result.push_str(&text);
+    }
+    result
+}
+
+// ANCHOR: value_to_string
+// 输出属性值，term 之间按源码是否相邻决定是否补空格
+pub fn value_to_string(node: &AstNode<TokenType>) -> String {
+    let mut result = String::new();
+    let mut prev: Option<&AstNode<TokenType>> = None;
+    for item in node.child_nodes() {
+        if item.check_type(TokenType::Comment) {
+            continue;
+        }
+        let text = match item.node_type.0 {
+            TokenType::Term => term_to_string(item),
+            TokenType::Expression => value_to_string(item),
+            TokenType::Function => function_to_string(item),
+            TokenType::Operator => item.raw.trim().to_string(),
+            _ => item.raw.clone(),
+        };
+        if let Some(prev) = prev {
+            if need_space(prev, item) {
+                result.push(' ');
+            }
+        }
+        result.push_str(&text);
+        prev = Some(item);
+    }
+    result
+}
+// ANCHOR_END: value_to_string
+
+fn is_comma(node: &AstNode<TokenType>) -> bool {
+    node.check_type(TokenType::Operator) && node.raw.trim() == ","
+}
+
+// scss 的比较运算符和取余运算符两边有空格
+fn is_spaced_operator(node: &AstNode<TokenType>) -> bool {
+    node.check_type(TokenType::Operator)
+        && matches!(node.raw.trim(), "==" | "!=" | ">=" | "<=" | ">" | "<" | "%")
+}
+
+fn need_space(prev: &AstNode<TokenType>, cur: &AstNode<TokenType>) -> bool {
+    if is_comma(prev) || is_spaced_operator(prev) || is_spaced_operator(cur) {
+        return true;
+    }
+    if is_comma(cur)
+        || prev.check_type(TokenType::Operator)
+        || cur.check_type(TokenType::Operator)
+        || prev.check_type(TokenType::LeftParenthesis)
+        || cur.check_type(TokenType::RightParenthesis)
+    {
+        return false;
+    }
+    // 合成节点没有可靠的位置信息，统一补空格
+    !(cur.range.start_pos > 0 && prev.range.end_pos == cur.range.start_pos)
+}
+
+pub fn term_to_string(node: &AstNode<TokenType>) -> String {
+    node.child_nodes()
+        .iter()
+        .map(|child| match child.node_type.0 {
+            TokenType::Function => function_to_string(child),
+            TokenType::Expression => value_to_string(child),
+            TokenType::Map => tokens_to_string(child.child_nodes()),
+            _ => child.raw.clone(),
+        })
+        .collect()
+}
+
+pub fn function_to_string(node: &AstNode<TokenType>) -> String {
+    node.child_nodes()
+        .iter()
+        .map(|child| match child.node_type.0 {
+            TokenType::Expression => value_to_string(child),
+            TokenType::SelectorList => selector_to_string(child),
+            TokenType::ArgumentList => tokens_to_string(child.child_nodes()),
+            _ => child.raw.clone(),
+        })
+        .collect()
+}
+
+// ANCHOR: selector_to_string
+// 选择器之间没有组合符时说明是后代选择器
+pub fn selector_to_string(node: &AstNode<TokenType>) -> String {
+    match node.node_type.0 {
+        TokenType::SelectorList => {
+            let mut result = String::new();
+            let mut prev: Option<&AstNode<TokenType>> = None;
+            for item in node.child_nodes() {
+                match item.node_type.0 {
+                    TokenType::Comma => {
+                        result.push_str(", ");
+                        prev = None;
+                        continue;
+                    }
+                    TokenType::Selector
+                        if prev.is_some_and(|p| selector_combinator(p).is_none()) =>
+                    {
+                        result.push(' ');
+                    }
+                    _ => {}
+                }
+                result.push_str(&selector_to_string(item));
+                prev = Some(item);
+            }
+            result
+        }
+        TokenType::Selector => node
+            .child_nodes()
+            .iter()
+            .map(|child| match child.node_type.0 {
+                TokenType::SimpleSelect => selector_to_string(child),
+                TokenType::Comment => String::new(),
+                _ => format!(" {} ", child.raw),
+            })
+            .collect(),
+        TokenType::SimpleSelect => node
+            .child_nodes()
+            .iter()
+            .map(|child| match child.node_type.0 {
+                TokenType::Function => function_to_string(child),
+                TokenType::Comment => String::new(),
+                _ => child.raw.clone(),
+            })
+            .collect(),
+        _ => node.raw.clone(),
+    }
+}
+// ANCHOR_END: selector_to_string
+
+// 选择器末尾的组合符 > + ~
+pub fn selector_combinator(node: &AstNode<TokenType>) -> Option<&AstNode<TokenType>> {
+    node.child_nodes().last().filter(|n| {
+        n.check_type(TokenType::MoreThan)
+            || n.check_type(TokenType::Plus)
+            || n.check_type(TokenType::Wave)
+    })
+}
+
+// 按源码位置还原 token 序列之间的空白
+pub fn tokens_to_string(tokens: &[AstNode<TokenType>]) -> String {
+    let mut result = String::new();
+    let mut prev: Option<&AstNode<TokenType>> = None;
+    for token in tokens {
+        if token.check_type(TokenType::Comment) {
+            continue;
+        }
+        let text = match token.node_type.0 {
+            TokenType::Expression => value_to_string(token),
+            TokenType::Function => function_to_string(token),
+            TokenType::Term => term_to_string(token),
+            TokenType::SelectorList | TokenType::Selector => selector_to_string(token),
+            _ if token.children.is_some() => tokens_to_string(token.child_nodes()),
+            _ => token.raw.clone(),
+        };
+        if let Some(prev) = prev {
+            let adjacent = prev.range.end_pos == token.range.start_pos && token.range.start_pos > 0;
+            if !adjacent
+                && !prev.check_type(TokenType::LeftParenthesis)
+                && !token.check_type(TokenType::RightParenthesis)
+                && !token.check_type(TokenType::Comma)
+                && !token.check_type(TokenType::Semi)
+            {
+                result.push(' ');
+            }
+        }
+        result.push_str(&text);
+        prev = Some(token);
+    }
+    result
+}
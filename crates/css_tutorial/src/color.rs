@@ -0,0 +1,1289 @@
+use std::fmt::{self, Display};
+
+use regex::Regex;
+
+use crate::{
+    ast::AstNode,
+    codegen::{function_to_string, term_to_string, value_to_string},
+    math::simplify_math,
+    token_type::TokenType,
+    values::{format_number, short_number, Value},
+};
+
+// ANCHOR: color_space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    Srgb,
+    SrgbLinear,
+    DisplayP3,
+    A98Rgb,
+    ProphotoRgb,
+    Rec2020,
+    XyzD50,
+    XyzD65,
+    Hsl,
+    Hwb,
+    Lab,
+    Lch,
+    Oklab,
+    Oklch,
+}
+// ANCHOR_END: color_space
+
+impl ColorSpace {
+    // color() 和 color-mix() 中使用的名字
+    pub fn from_name(name: &str) -> Option<Self> {
+        let space = match name.to_ascii_lowercase().as_str() {
+            "srgb" => ColorSpace::Srgb,
+            "srgb-linear" => ColorSpace::SrgbLinear,
+            "display-p3" => ColorSpace::DisplayP3,
+            "a98-rgb" => ColorSpace::A98Rgb,
+            "prophoto-rgb" => ColorSpace::ProphotoRgb,
+            "rec2020" => ColorSpace::Rec2020,
+            "xyz-d50" => ColorSpace::XyzD50,
+            "xyz" | "xyz-d65" => ColorSpace::XyzD65,
+            "hsl" => ColorSpace::Hsl,
+            "hwb" => ColorSpace::Hwb,
+            "lab" => ColorSpace::Lab,
+            "lch" => ColorSpace::Lch,
+            "oklab" => ColorSpace::Oklab,
+            "oklch" => ColorSpace::Oklch,
+            _ => return None,
+        };
+        Some(space)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorSpace::Srgb => "srgb",
+            ColorSpace::SrgbLinear => "srgb-linear",
+            ColorSpace::DisplayP3 => "display-p3",
+            ColorSpace::A98Rgb => "a98-rgb",
+            ColorSpace::ProphotoRgb => "prophoto-rgb",
+            ColorSpace::Rec2020 => "rec2020",
+            ColorSpace::XyzD50 => "xyz-d50",
+            ColorSpace::XyzD65 => "xyz-d65",
+            ColorSpace::Hsl => "hsl",
+            ColorSpace::Hwb => "hwb",
+            ColorSpace::Lab => "lab",
+            ColorSpace::Lch => "lch",
+            ColorSpace::Oklab => "oklab",
+            ColorSpace::Oklch => "oklch",
+        }
+    }
+
+    // 色相分量的下标
+    pub fn hue_index(&self) -> Option<usize> {
+        match self {
+            ColorSpace::Hsl | ColorSpace::Hwb => Some(0),
+            ColorSpace::Lch | ColorSpace::Oklch => Some(2),
+            _ => None,
+        }
+    }
+
+    // 可以用 color() 函数表示的空间
+    pub fn is_predefined(&self) -> bool {
+        !matches!(
+            self,
+            ColorSpace::Hsl
+                | ColorSpace::Hwb
+                | ColorSpace::Lab
+                | ColorSpace::Lch
+                | ColorSpace::Oklab
+                | ColorSpace::Oklch
+        )
+    }
+
+    fn is_rgb(&self) -> bool {
+        matches!(
+            self,
+            ColorSpace::Srgb
+                | ColorSpace::SrgbLinear
+                | ColorSpace::DisplayP3
+                | ColorSpace::A98Rgb
+                | ColorSpace::ProphotoRgb
+                | ColorSpace::Rec2020
+        )
+    }
+}
+
+// ANCHOR: color
+// 分量使用各空间在 css 中的参考范围，srgb 类为 0..1，缺失分量 (none) 用 NaN 表示
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub space: ColorSpace,
+    pub components: [f64; 3],
+    pub alpha: f64,
+}
+// ANCHOR_END: color
+
+type Matrix = [[f64; 3]; 3];
+
+fn multiply(m: &Matrix, v: [f64; 3]) -> [f64; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+// 以下矩阵来自 css color 4 规范的示例代码
+const LIN_SRGB_TO_XYZ: Matrix = [
+    [506752.0 / 1228815.0, 87881.0 / 245763.0, 12673.0 / 70218.0],
+    [87098.0 / 409605.0, 175762.0 / 245763.0, 12673.0 / 175545.0],
+    [7918.0 / 409605.0, 87881.0 / 737289.0, 1001167.0 / 1053270.0],
+];
+const XYZ_TO_LIN_SRGB: Matrix = [
+    [12831.0 / 3959.0, -329.0 / 214.0, -1974.0 / 3959.0],
+    [-851781.0 / 878810.0, 1648619.0 / 878810.0, 36519.0 / 878810.0],
+    [705.0 / 12673.0, -2585.0 / 12673.0, 705.0 / 667.0],
+];
+const LIN_P3_TO_XYZ: Matrix = [
+    [608311.0 / 1250200.0, 189793.0 / 714400.0, 198249.0 / 1000160.0],
+    [35783.0 / 156275.0, 247089.0 / 357200.0, 198249.0 / 2500400.0],
+    [0.0, 32229.0 / 714400.0, 5220557.0 / 5000800.0],
+];
+const XYZ_TO_LIN_P3: Matrix = [
+    [446124.0 / 178915.0, -333277.0 / 357830.0, -72051.0 / 178915.0],
+    [-14852.0 / 17905.0, 63121.0 / 35810.0, 423.0 / 17905.0],
+    [11844.0 / 330415.0, -50337.0 / 660830.0, 316169.0 / 330415.0],
+];
+const LIN_A98_TO_XYZ: Matrix = [
+    [573536.0 / 994567.0, 263643.0 / 1420810.0, 187206.0 / 994567.0],
+    [591459.0 / 1989134.0, 6239551.0 / 9945670.0, 374412.0 / 4972835.0],
+    [53769.0 / 1989134.0, 351524.0 / 4972835.0, 4929758.0 / 4972835.0],
+];
+const XYZ_TO_LIN_A98: Matrix = [
+    [1829569.0 / 896150.0, -506331.0 / 896150.0, -308931.0 / 896150.0],
+    [-851781.0 / 878810.0, 1648619.0 / 878810.0, 36519.0 / 878810.0],
+    [16779.0 / 1248040.0, -147721.0 / 1248040.0, 1266979.0 / 1248040.0],
+];
+// prophoto 使用 D50 白点
+#[allow(clippy::excessive_precision)]
+const LIN_PROPHOTO_TO_XYZ_D50: Matrix = [
+    [0.7977666449006423, 0.13518129740053308, 0.0313477341283922858],
+    [0.2880748288194013, 0.711835234241873, 0.00008993693872564],
+    [0.0, 0.0, 0.8251046025104602],
+];
+const XYZ_D50_TO_LIN_PROPHOTO: Matrix = [
+    [1.3457868816471583, -0.25557208737979464, -0.05110186497554526],
+    [-0.5446307051249019, 1.5082477428451468, 0.02052744743642139],
+    [0.0, 0.0, 1.2119675456389452],
+];
+const LIN_REC2020_TO_XYZ: Matrix = [
+    [63426534.0 / 99577255.0, 20160776.0 / 139408157.0, 47086771.0 / 278816314.0],
+    [26158966.0 / 99577255.0, 472592308.0 / 697040785.0, 8267143.0 / 139408157.0],
+    [0.0, 19567812.0 / 697040785.0, 295819943.0 / 278816314.0],
+];
+const XYZ_TO_LIN_REC2020: Matrix = [
+    [30757411.0 / 17917100.0, -6372589.0 / 17917100.0, -4539589.0 / 17917100.0],
+    [-19765991.0 / 29648200.0, 47925759.0 / 29648200.0, 467509.0 / 29648200.0],
+    [792561.0 / 44930125.0, -1921689.0 / 44930125.0, 42328811.0 / 44930125.0],
+];
+// Bradford 白点变换
+const D50_TO_D65: Matrix = [
+    [0.955473421488075, -0.02309845494876471, 0.06325924320057072],
+    [-0.0283697093338637, 1.0099953980813041, 0.021041441191917323],
+    [0.012314014864481998, -0.020507649298898964, 1.330365926242124],
+];
+const D65_TO_D50: Matrix = [
+    [1.0479297925449969, 0.022946870601609652, -0.05019226628920524],
+    [0.02962780877005599, 0.9904344267538799, -0.017073799063418826],
+    [-0.009243040646204504, 0.015055191490298152, 0.7518742814281371],
+];
+#[allow(clippy::excessive_precision)]
+const XYZ_TO_LMS: Matrix = [
+    [0.8190224379967030, 0.3619062600528904, -0.1288737815209879],
+    [0.0329836539323885, 0.9292868615863434, 0.0361446663506424],
+    [0.0481771893596242, 0.2642395317527308, 0.6335478284694309],
+];
+#[allow(clippy::excessive_precision)]
+const LMS_TO_XYZ: Matrix = [
+    [1.2268798758459243, -0.5578149944602171, 0.2813910456659647],
+    [-0.0405757452148008, 1.1122868032803170, -0.0717110580655164],
+    [-0.0763729366746601, -0.4214933324022432, 1.5869240198367816],
+];
+#[allow(clippy::excessive_precision)]
+const LMS_TO_OKLAB: Matrix = [
+    [0.2104542683093140, 0.7936177747023054, -0.0040720430116193],
+    [1.9779985324311684, -2.4285922420485799, 0.4505937096174110],
+    [0.0259040424655478, 0.7827717124575296, -0.8086757549230774],
+];
+#[allow(clippy::excessive_precision)]
+const OKLAB_TO_LMS: Matrix = [
+    [1.0, 0.3963377773761749, 0.2158037573099136],
+    [1.0, -0.1055613458156586, -0.0638541728258133],
+    [1.0, -0.0894841775298119, -1.2914855480194092],
+];
+
+const D50_WHITE: [f64; 3] = [0.3457 / 0.3585, 1.0, (1.0 - 0.3457 - 0.3585) / 0.3585];
+const LAB_KAPPA: f64 = 24389.0 / 27.0;
+const LAB_EPSILON: f64 = 216.0 / 24389.0;
+
+fn srgb_to_linear(c: f64) -> f64 {
+    let abs = c.abs();
+    if abs <= 0.04045 {
+        c / 12.92
+    } else {
+        c.signum() * ((abs + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f64) -> f64 {
+    let abs = c.abs();
+    if abs > 0.0031308 {
+        c.signum() * (1.055 * abs.powf(1.0 / 2.4) - 0.055)
+    } else {
+        12.92 * c
+    }
+}
+
+fn a98_to_linear(c: f64) -> f64 {
+    c.signum() * c.abs().powf(563.0 / 256.0)
+}
+
+fn linear_to_a98(c: f64) -> f64 {
+    c.signum() * c.abs().powf(256.0 / 563.0)
+}
+
+fn prophoto_to_linear(c: f64) -> f64 {
+    let abs = c.abs();
+    if abs <= 16.0 / 512.0 {
+        c / 16.0
+    } else {
+        c.signum() * abs.powf(1.8)
+    }
+}
+
+fn linear_to_prophoto(c: f64) -> f64 {
+    let abs = c.abs();
+    if abs >= 1.0 / 512.0 {
+        c.signum() * abs.powf(1.0 / 1.8)
+    } else {
+        16.0 * c
+    }
+}
+
+const REC2020_ALPHA: f64 = 1.09929682680944;
+const REC2020_BETA: f64 = 0.018053968510807;
+
+fn rec2020_to_linear(c: f64) -> f64 {
+    let abs = c.abs();
+    if abs < REC2020_BETA * 4.5 {
+        c / 4.5
+    } else {
+        c.signum() * ((abs + REC2020_ALPHA - 1.0) / REC2020_ALPHA).powf(1.0 / 0.45)
+    }
+}
+
+fn linear_to_rec2020(c: f64) -> f64 {
+    let abs = c.abs();
+    if abs > REC2020_BETA {
+        c.signum() * (REC2020_ALPHA * abs.powf(0.45) - (REC2020_ALPHA - 1.0))
+    } else {
+        4.5 * c
+    }
+}
+
+// 去掉浮点误差导致的 359.999...
+fn normalize_hue(hue: f64) -> f64 {
+    let hue = hue.rem_euclid(360.0);
+    if 360.0 - hue < 1e-9 {
+        0.0
+    } else {
+        hue
+    }
+}
+
+fn map3(v: [f64; 3], f: fn(f64) -> f64) -> [f64; 3] {
+    [f(v[0]), f(v[1]), f(v[2])]
+}
+
+pub(crate) fn hsl_to_srgb([hue, saturation, lightness]: [f64; 3]) -> [f64; 3] {
+    let hue = hue.rem_euclid(360.0);
+    let (saturation, lightness) = (saturation / 100.0, lightness / 100.0);
+    let f = |n: f64| {
+        let k = (n + hue / 30.0) % 12.0;
+        let a = saturation * lightness.min(1.0 - lightness);
+        lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    [f(0.0), f(8.0), f(4.0)]
+}
+
+pub(crate) fn srgb_to_hsl([red, green, blue]: [f64; 3]) -> [f64; 3] {
+    let max = red.max(green).max(blue);
+    let min = red.min(green).min(blue);
+    let lightness = (min + max) / 2.0;
+    let d = max - min;
+    let (mut hue, mut saturation) = (0.0, 0.0);
+    if d != 0.0 {
+        saturation = if lightness == 0.0 || lightness == 1.0 {
+            0.0
+        } else {
+            (max - lightness) / lightness.min(1.0 - lightness)
+        };
+        hue = if max == red {
+            (green - blue) / d + if green < blue { 6.0 } else { 0.0 }
+        } else if max == green {
+            (blue - red) / d + 2.0
+        } else {
+            (red - green) / d + 4.0
+        } * 60.0;
+    }
+    if saturation < 0.0 {
+        hue += 180.0;
+        saturation = saturation.abs();
+    }
+    [normalize_hue(hue), saturation * 100.0, lightness * 100.0]
+}
+
+fn hwb_to_srgb([hue, white, black]: [f64; 3]) -> [f64; 3] {
+    let (white, black) = (white / 100.0, black / 100.0);
+    if white + black >= 1.0 {
+        let gray = white / (white + black);
+        return [gray; 3];
+    }
+    hsl_to_srgb([hue, 100.0, 50.0]).map(|c| c * (1.0 - white - black) + white)
+}
+
+fn srgb_to_hwb(rgb: [f64; 3]) -> [f64; 3] {
+    let hue = srgb_to_hsl(rgb)[0];
+    let white = rgb[0].min(rgb[1]).min(rgb[2]);
+    let black = 1.0 - rgb[0].max(rgb[1]).max(rgb[2]);
+    [hue, white * 100.0, black * 100.0]
+}
+
+fn xyz_d50_to_lab(xyz: [f64; 3]) -> [f64; 3] {
+    let f = |i: usize| {
+        let v = xyz[i] / D50_WHITE[i];
+        if v > LAB_EPSILON {
+            v.cbrt()
+        } else {
+            (LAB_KAPPA * v + 16.0) / 116.0
+        }
+    };
+    let (f0, f1, f2) = (f(0), f(1), f(2));
+    [116.0 * f1 - 16.0, 500.0 * (f0 - f1), 200.0 * (f1 - f2)]
+}
+
+fn lab_to_xyz_d50([lightness, a, b]: [f64; 3]) -> [f64; 3] {
+    let f1 = (lightness + 16.0) / 116.0;
+    let f0 = a / 500.0 + f1;
+    let f2 = f1 - b / 200.0;
+    let x = if f0.powi(3) > LAB_EPSILON {
+        f0.powi(3)
+    } else {
+        (116.0 * f0 - 16.0) / LAB_KAPPA
+    };
+    let y = if lightness > LAB_KAPPA * LAB_EPSILON {
+        f1.powi(3)
+    } else {
+        lightness / LAB_KAPPA
+    };
+    let z = if f2.powi(3) > LAB_EPSILON {
+        f2.powi(3)
+    } else {
+        (116.0 * f2 - 16.0) / LAB_KAPPA
+    };
+    [x * D50_WHITE[0], y * D50_WHITE[1], z * D50_WHITE[2]]
+}
+
+fn rectangular_to_polar([lightness, a, b]: [f64; 3]) -> [f64; 3] {
+    let chroma = (a * a + b * b).sqrt();
+    let hue = normalize_hue(b.atan2(a).to_degrees());
+    [lightness, chroma, hue]
+}
+
+fn polar_to_rectangular([lightness, chroma, hue]: [f64; 3]) -> [f64; 3] {
+    let hue = hue.to_radians();
+    [lightness, chroma * hue.cos(), chroma * hue.sin()]
+}
+
+fn xyz_to_oklab(xyz: [f64; 3]) -> [f64; 3] {
+    let lms = multiply(&XYZ_TO_LMS, xyz);
+    multiply(&LMS_TO_OKLAB, lms.map(f64::cbrt))
+}
+
+fn oklab_to_xyz(oklab: [f64; 3]) -> [f64; 3] {
+    let lms = multiply(&OKLAB_TO_LMS, oklab);
+    multiply(&LMS_TO_XYZ, lms.map(|c| c.powi(3)))
+}
+
+impl Color {
+    pub fn new(space: ColorSpace, components: [f64; 3], alpha: f64) -> Self {
+        Color {
+            space,
+            components,
+            alpha,
+        }
+    }
+
+    // 0..255 的 rgb
+    pub fn rgb(red: f64, green: f64, blue: f64, alpha: f64) -> Self {
+        Color::new(
+            ColorSpace::Srgb,
+            [red / 255.0, green / 255.0, blue / 255.0],
+            alpha,
+        )
+    }
+
+    // ANCHOR: to_xyz
+    // 所有空间都经由 D65 的 xyz 转换
+    pub fn to_xyz(&self) -> [f64; 3] {
+        let c = self.components.map(|c| if c.is_nan() { 0.0 } else { c });
+        match self.space {
+            ColorSpace::Srgb => multiply(&LIN_SRGB_TO_XYZ, map3(c, srgb_to_linear)),
+            ColorSpace::SrgbLinear => multiply(&LIN_SRGB_TO_XYZ, c),
+            ColorSpace::DisplayP3 => multiply(&LIN_P3_TO_XYZ, map3(c, srgb_to_linear)),
+            ColorSpace::A98Rgb => multiply(&LIN_A98_TO_XYZ, map3(c, a98_to_linear)),
+            ColorSpace::ProphotoRgb => multiply(
+                &D50_TO_D65,
+                multiply(&LIN_PROPHOTO_TO_XYZ_D50, map3(c, prophoto_to_linear)),
+            ),
+            ColorSpace::Rec2020 => multiply(&LIN_REC2020_TO_XYZ, map3(c, rec2020_to_linear)),
+            ColorSpace::XyzD50 => multiply(&D50_TO_D65, c),
+            ColorSpace::XyzD65 => c,
+            ColorSpace::Hsl => multiply(&LIN_SRGB_TO_XYZ, map3(hsl_to_srgb(c), srgb_to_linear)),
+            ColorSpace::Hwb => multiply(&LIN_SRGB_TO_XYZ, map3(hwb_to_srgb(c), srgb_to_linear)),
+            ColorSpace::Lab => multiply(&D50_TO_D65, lab_to_xyz_d50(c)),
+            ColorSpace::Lch => multiply(&D50_TO_D65, lab_to_xyz_d50(polar_to_rectangular(c))),
+            ColorSpace::Oklab => oklab_to_xyz(c),
+            ColorSpace::Oklch => oklab_to_xyz(polar_to_rectangular(c)),
+        }
+    }
+
+    pub fn from_xyz(space: ColorSpace, xyz: [f64; 3], alpha: f64) -> Self {
+        let srgb = || map3(multiply(&XYZ_TO_LIN_SRGB, xyz), linear_to_srgb);
+        let components = match space {
+            ColorSpace::Srgb => srgb(),
+            ColorSpace::SrgbLinear => multiply(&XYZ_TO_LIN_SRGB, xyz),
+            ColorSpace::DisplayP3 => map3(multiply(&XYZ_TO_LIN_P3, xyz), linear_to_srgb),
+            ColorSpace::A98Rgb => map3(multiply(&XYZ_TO_LIN_A98, xyz), linear_to_a98),
+            ColorSpace::ProphotoRgb => map3(
+                multiply(&XYZ_D50_TO_LIN_PROPHOTO, multiply(&D65_TO_D50, xyz)),
+                linear_to_prophoto,
+            ),
+            ColorSpace::Rec2020 => map3(multiply(&XYZ_TO_LIN_REC2020, xyz), linear_to_rec2020),
+            ColorSpace::XyzD50 => multiply(&D65_TO_D50, xyz),
+            ColorSpace::XyzD65 => xyz,
+            ColorSpace::Hsl => srgb_to_hsl(srgb()),
+            ColorSpace::Hwb => srgb_to_hwb(srgb()),
+            ColorSpace::Lab => xyz_d50_to_lab(multiply(&D65_TO_D50, xyz)),
+            ColorSpace::Lch => rectangular_to_polar(xyz_d50_to_lab(multiply(&D65_TO_D50, xyz))),
+            ColorSpace::Oklab => xyz_to_oklab(xyz),
+            ColorSpace::Oklch => rectangular_to_polar(xyz_to_oklab(xyz)),
+        };
+        Color::new(space, components, alpha)
+    }
+    // ANCHOR_END: to_xyz
+
+    pub fn convert(&self, space: ColorSpace) -> Color {
+        if space == self.space {
+            return *self;
+        }
+        Color::from_xyz(space, self.to_xyz(), self.alpha)
+    }
+
+    pub fn to_srgb(&self) -> Color {
+        self.convert(ColorSpace::Srgb)
+    }
+
+    // ANCHOR: gamut_map
+    // css color 4 的色域映射：在 oklch 中降低彩度直到裁剪后的差异小于 JND
+    pub fn gamut_map_srgb(&self) -> Color {
+        const JND: f64 = 0.02;
+        const EPSILON: f64 = 0.0001;
+        let origin = self.convert(ColorSpace::Oklch);
+        let lightness = origin.components[0];
+        if lightness >= 1.0 {
+            return Color::new(ColorSpace::Srgb, [1.0; 3], self.alpha);
+        }
+        if lightness <= 0.0 {
+            return Color::new(ColorSpace::Srgb, [0.0; 3], self.alpha);
+        }
+        let srgb = self.to_srgb();
+        if srgb.in_gamut() {
+            return srgb;
+        }
+        let clip = |color: &Color| {
+            let mut clipped = color.to_srgb();
+            clipped.components = clipped.components.map(|c| c.clamp(0.0, 1.0));
+            clipped
+        };
+        let delta_eok = |a: &Color, b: &Color| {
+            let (a, b) = (a.convert(ColorSpace::Oklab), b.convert(ColorSpace::Oklab));
+            a.components
+                .iter()
+                .zip(b.components.iter())
+                .map(|(x, y)| (x - y).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        };
+        let mut current = origin;
+        let mut clipped = clip(&current);
+        if delta_eok(&clipped, &current) < JND {
+            return clipped;
+        }
+        let (mut min, mut max) = (0.0, origin.components[1]);
+        let mut min_in_gamut = true;
+        while max - min > EPSILON {
+            let chroma = (min + max) / 2.0;
+            current.components[1] = chroma;
+            if min_in_gamut && current.in_srgb_gamut() {
+                min = chroma;
+                continue;
+            }
+            clipped = clip(&current);
+            let delta = delta_eok(&clipped, &current);
+            if delta < JND {
+                if JND - delta < EPSILON {
+                    return clipped;
+                }
+                min_in_gamut = false;
+                min = chroma;
+            } else {
+                max = chroma;
+            }
+        }
+        clipped
+    }
+    // ANCHOR_END: gamut_map
+
+    // 是否落在 srgb 色域内
+    pub fn in_srgb_gamut(&self) -> bool {
+        self.to_srgb().in_gamut()
+    }
+
+    // rgb 类空间分量都在 0..1 之间
+    pub fn in_gamut(&self) -> bool {
+        match self.space {
+            space if space.is_rgb() => self
+                .components
+                .iter()
+                .all(|c| c.is_nan() || (-0.000_01..=1.000_01).contains(c)),
+            ColorSpace::Hsl | ColorSpace::Hwb => self.to_srgb().in_gamut(),
+            _ => true,
+        }
+    }
+
+    // 不透明度为 1 的 srgb 值，0..255
+    pub fn to_rgb8(&self) -> [u8; 4] {
+        let srgb = self.to_srgb();
+        let byte = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let [r, g, b] = srgb.components.map(|c| if c.is_nan() { 0.0 } else { c });
+        let alpha = if self.alpha.is_nan() { 0.0 } else { self.alpha };
+        [byte(r), byte(g), byte(b), byte(alpha)]
+    }
+
+    // 可以省略成 #rgb 时输出短格式
+    pub fn to_hex(&self) -> String {
+        let [r, g, b, a] = self.to_rgb8();
+        let bytes: Vec<u8> = if a == 255 { vec![r, g, b] } else { vec![r, g, b, a] };
+        if bytes.iter().all(|b| b >> 4 == b & 0xf) {
+            bytes.iter().fold("#".to_string(), |s, b| s + &format!("{:x}", b & 0xf))
+        } else {
+            bytes.iter().fold("#".to_string(), |s, b| s + &format!("{:02x}", b))
+        }
+    }
+
+    // ANCHOR: to_shortest
+    // 压缩时使用的最短写法，srgb 色域内的颜色输出 hex 或更短的颜色名
+    pub fn to_shortest(&self) -> String {
+        let legacy = matches!(
+            self.space,
+            ColorSpace::Srgb | ColorSpace::Hsl | ColorSpace::Hwb
+        );
+        if legacy && self.in_gamut() {
+            let hex = self.to_hex();
+            let [r, g, b, a] = self.to_rgb8();
+            if a == 255 {
+                let value = (r as u32) << 16 | (g as u32) << 8 | b as u32;
+                if let Some((name, _)) = NAMED_COLORS
+                    .iter()
+                    .filter(|(_, v)| *v == value)
+                    .min_by_key(|(name, _)| name.len())
+                {
+                    if name.len() < hex.len() {
+                        return name.to_string();
+                    }
+                }
+            }
+            return hex;
+        }
+        self.serialize(short_number)
+    }
+    // ANCHOR_END: to_shortest
+
+    fn serialize(&self, number: fn(f64) -> String) -> String {
+        let component = |c: f64| {
+            if c.is_nan() {
+                "none".to_string()
+            } else {
+                number(c)
+            }
+        };
+        let alpha = if self.alpha >= 1.0 {
+            String::new()
+        } else {
+            format!(" / {}", component(self.alpha))
+        };
+        let [a, b, c] = self.components.map(component);
+        if self.space.is_predefined() {
+            format!("color({} {} {} {}{})", self.space.name(), a, b, c, alpha)
+        } else {
+            format!("{}({} {} {}{})", self.space.name(), a, b, c, alpha)
+        }
+    }
+}
+
+impl Display for Color {
+    // 与规范的序列化一致：srgb 类颜色输出 rgb()，其余保留自身的函数
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.space {
+            ColorSpace::Hsl | ColorSpace::Hwb => write!(f, "{}", self.to_srgb()),
+            ColorSpace::Srgb => {
+                let [r, g, b] = self
+                    .components
+                    .map(|c| format_number((c.clamp(0.0, 1.0) * 255.0).round()));
+                if self.alpha >= 1.0 {
+                    write!(f, "rgb({}, {}, {})", r, g, b)
+                } else {
+                    write!(f, "rgba({}, {}, {}, {})", r, g, b, format_number(self.alpha))
+                }
+            }
+            _ => write!(f, "{}", self.serialize(format_number)),
+        }
+    }
+}
+
+// ANCHOR: parse_color
+impl Color {
+    pub fn parse(source: &str) -> Option<Color> {
+        let source = source.trim();
+        if let Some(hex) = source.strip_prefix('#') {
+            return parse_hex(hex);
+        }
+        let lower = source.to_ascii_lowercase();
+        if lower == "transparent" {
+            return Some(Color::rgb(0.0, 0.0, 0.0, 0.0));
+        }
+        if let Ok(index) = NAMED_COLORS.binary_search_by(|(name, _)| (*name).cmp(lower.as_str())) {
+            let value = NAMED_COLORS[index].1;
+            return Some(Color::rgb(
+                (value >> 16) as f64,
+                (value >> 8 & 0xff) as f64,
+                (value & 0xff) as f64,
+                1.0,
+            ));
+        }
+        let open = source.find('(')?;
+        let body = source[open + 1..].strip_suffix(')')?;
+        let args = split_args(body)?;
+        let name = lower[..open].trim();
+        if matches!(args.first(), Some(Arg::Word(word)) if word.eq_ignore_ascii_case("from")) {
+            return parse_relative(name, &args[1..]);
+        }
+        match name {
+            "rgb" | "rgba" => parse_rgb(&args),
+            "hsl" | "hsla" => parse_polar_rgb(ColorSpace::Hsl, &args),
+            "hwb" => parse_polar_rgb(ColorSpace::Hwb, &args),
+            "lab" => parse_lab(ColorSpace::Lab, &args),
+            "lch" => parse_lab(ColorSpace::Lch, &args),
+            "oklab" => parse_lab(ColorSpace::Oklab, &args),
+            "oklch" => parse_lab(ColorSpace::Oklch, &args),
+            "color" => parse_color_function(&args),
+            "color-mix" => parse_color_mix(&args),
+            _ => None,
+        }
+    }
+
+    // 从 Term、HashToken、IdentToken 或 Function 节点解析
+    pub fn from_node(node: &AstNode<TokenType>) -> Option<Color> {
+        let text = match node.node_type.0 {
+            TokenType::Term => term_to_string(node),
+            TokenType::Function => function_to_string(node),
+            TokenType::Expression => value_to_string(node),
+            _ => node.raw.clone(),
+        };
+        Color::parse(&text)
+    }
+}
+// ANCHOR_END: parse_color
+
+pub fn is_color(source: &str) -> bool {
+    Color::parse(source).is_some()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Arg {
+    Word(String),
+    Comma,
+    Slash,
+}
+
+// 按顶层的空白、逗号和斜杠切分函数参数，括号内的内容保持完整
+fn split_args(body: &str) -> Option<Vec<Arg>> {
+    let mut args = Vec::new();
+    let mut word = String::new();
+    let mut depth = 0;
+    let flush = |word: &mut String, args: &mut Vec<Arg>| {
+        if !word.is_empty() {
+            args.push(Arg::Word(std::mem::take(word)));
+        }
+    };
+    for ch in body.chars() {
+        match ch {
+            '(' => {
+                depth += 1;
+                word.push(ch);
+            }
+            ')' => {
+                depth -= 1;
+                word.push(ch);
+            }
+            _ if depth > 0 => word.push(ch),
+            ',' | '/' => {
+                flush(&mut word, &mut args);
+                args.push(if ch == ',' { Arg::Comma } else { Arg::Slash });
+            }
+            _ if ch.is_whitespace() => flush(&mut word, &mut args),
+            _ => word.push(ch),
+        }
+    }
+    flush(&mut word, &mut args);
+    (depth == 0).then_some(args)
+}
+
+// 拆出三个分量和可选的 alpha，兼容旧的逗号语法
+fn components_and_alpha(args: &[Arg]) -> Option<(Vec<&str>, Option<&str>)> {
+    let legacy = args.contains(&Arg::Comma);
+    let mut words = Vec::new();
+    let mut alpha = None;
+    let mut after_slash = false;
+    for (index, arg) in args.iter().enumerate() {
+        match arg {
+            Arg::Word(word) if after_slash => alpha = Some(word.as_str()),
+            Arg::Word(word) => words.push(word.as_str()),
+            Arg::Slash if !legacy => after_slash = true,
+            Arg::Comma if legacy && index % 2 == 1 => {}
+            _ => return None,
+        }
+    }
+    if legacy && words.len() == 4 {
+        alpha = words.pop();
+    }
+    (words.len() == 3).then_some((words, alpha))
+}
+
+// 百分比按 percent_scale 换算，none 为 NaN
+fn parse_number(word: &str, percent_scale: f64) -> Option<f64> {
+    if word.eq_ignore_ascii_case("none") {
+        return Some(f64::NAN);
+    }
+    match Value::parse(word)? {
+        Value::Number(n) => Some(n),
+        Value::Percentage(n) => Some(n / 100.0 * percent_scale),
+        _ => None,
+    }
+}
+
+fn parse_hue(word: &str) -> Option<f64> {
+    if word.eq_ignore_ascii_case("none") {
+        return Some(f64::NAN);
+    }
+    match Value::parse(word)? {
+        Value::Number(n) => Some(n),
+        Value::Angle(angle) => angle.to_canonical(),
+        _ => None,
+    }
+}
+
+// 负数截断为 0，保留 none
+fn non_negative(value: f64) -> f64 {
+    if value < 0.0 {
+        0.0
+    } else {
+        value
+    }
+}
+
+fn parse_alpha(word: Option<&str>) -> Option<f64> {
+    match word {
+        None => Some(1.0),
+        Some(word) => parse_number(word, 1.0).map(|a| if a.is_nan() { a } else { a.clamp(0.0, 1.0) }),
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits: Vec<u8> = match hex.len() {
+        3 | 4 => hex
+            .chars()
+            .map(|c| c.to_digit(16).unwrap() as u8 * 17)
+            .collect(),
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect(),
+        _ => return None,
+    };
+    let alpha = digits.get(3).map_or(1.0, |a| *a as f64 / 255.0);
+    Some(Color::rgb(
+        digits[0] as f64,
+        digits[1] as f64,
+        digits[2] as f64,
+        alpha,
+    ))
+}
+
+fn parse_rgb(args: &[Arg]) -> Option<Color> {
+    let (words, alpha) = components_and_alpha(args)?;
+    let mut components = [0.0; 3];
+    for (index, word) in words.iter().enumerate() {
+        // 旧语法会把超出范围的值截断
+        components[index] = (parse_number(word, 255.0)? / 255.0).clamp(0.0, 1.0);
+    }
+    Some(Color::new(ColorSpace::Srgb, components, parse_alpha(alpha)?))
+}
+
+fn parse_polar_rgb(space: ColorSpace, args: &[Arg]) -> Option<Color> {
+    let (words, alpha) = components_and_alpha(args)?;
+    let components = [
+        parse_hue(words[0])?,
+        non_negative(parse_number(words[1], 100.0)?),
+        parse_number(words[2], 100.0)?,
+    ];
+    Some(Color::new(space, components, parse_alpha(alpha)?))
+}
+
+fn parse_lab(space: ColorSpace, args: &[Arg]) -> Option<Color> {
+    let (words, alpha) = components_and_alpha(args)?;
+    // 百分比的参考范围
+    let (lightness, second, third) = match space {
+        ColorSpace::Lab => (100.0, 125.0, 125.0),
+        ColorSpace::Lch => (100.0, 150.0, 0.0),
+        ColorSpace::Oklab => (1.0, 0.4, 0.4),
+        _ => (1.0, 0.4, 0.0),
+    };
+    let polar = space.hue_index().is_some();
+    let components = [
+        non_negative(parse_number(words[0], lightness)?),
+        parse_number(words[1], second)?,
+        if polar {
+            parse_hue(words[2])?
+        } else {
+            parse_number(words[2], third)?
+        },
+    ];
+    let mut components = components;
+    if polar {
+        components[1] = non_negative(components[1]);
+    }
+    Some(Color::new(space, components, parse_alpha(alpha)?))
+}
+
+fn parse_color_function(args: &[Arg]) -> Option<Color> {
+    let (Arg::Word(name), rest) = args.split_first()? else {
+        return None;
+    };
+    let space = ColorSpace::from_name(name).filter(|s| s.is_predefined())?;
+    let (words, alpha) = components_and_alpha(rest)?;
+    let mut components = [0.0; 3];
+    for (index, word) in words.iter().enumerate() {
+        components[index] = parse_number(word, 1.0)?;
+    }
+    Some(Color::new(space, components, parse_alpha(alpha)?))
+}
+
+// ANCHOR: relative_color
+// 相对颜色语法 oklch(from <color> l c h / alpha)，把通道名替换成原色的值后按普通语法解析
+fn parse_relative(name: &str, args: &[Arg]) -> Option<Color> {
+    let (Arg::Word(origin), mut rest) = args.split_first()? else {
+        return None;
+    };
+    let origin = Color::parse(origin)?;
+    let space = match name {
+        "rgb" | "rgba" => ColorSpace::Srgb,
+        "color" => {
+            let (Arg::Word(space), tail) = rest.split_first()? else {
+                return None;
+            };
+            rest = tail;
+            ColorSpace::from_name(space).filter(|s| s.is_predefined())?
+        }
+        "hsla" => ColorSpace::Hsl,
+        _ => ColorSpace::from_name(name)?,
+    };
+    let converted = origin.convert(space);
+    let channels = match (name, space) {
+        ("rgb" | "rgba", _) => ["r", "g", "b"],
+        (_, ColorSpace::XyzD50 | ColorSpace::XyzD65) => ["x", "y", "z"],
+        (_, ColorSpace::Hsl) => ["h", "s", "l"],
+        (_, ColorSpace::Hwb) => ["h", "w", "b"],
+        (_, ColorSpace::Lab | ColorSpace::Oklab) => ["l", "a", "b"],
+        (_, ColorSpace::Lch | ColorSpace::Oklch) => ["l", "c", "h"],
+        _ => ["r", "g", "b"],
+    };
+    let scale = if name.starts_with("rgb") { 255.0 } else { 1.0 };
+    let mut values: Vec<(&str, f64)> = channels
+        .iter()
+        .zip(converted.components)
+        .map(|(channel, value)| (*channel, value * scale))
+        .collect();
+    values.push(("alpha", origin.alpha));
+    let number = |value: f64| {
+        if value.is_nan() {
+            "none".to_string()
+        } else {
+            format_number(value)
+        }
+    };
+    let substitute = |word: &str| -> Option<String> {
+        if let Some((_, value)) = values.iter().find(|(channel, _)| *channel == word) {
+            return Some(number(*value));
+        }
+        if !word.contains('(') {
+            return Some(word.to_string());
+        }
+        let mut expression = word.to_string();
+        for (channel, value) in &values {
+            let pattern = Regex::new(&format!(r"\b{}\b", channel)).ok()?;
+            expression = pattern
+                .replace_all(&expression, number(*value).as_str())
+                .into_owned();
+        }
+        simplify_math(&expression).ok()
+    };
+    let mut text = if name == "color" {
+        format!("color({}", space.name())
+    } else {
+        format!("{}(", name)
+    };
+    for (index, arg) in rest.iter().enumerate() {
+        if index > 0 || name == "color" {
+            text.push(' ');
+        }
+        match arg {
+            Arg::Word(word) => text.push_str(&substitute(word)?),
+            Arg::Slash => text.push('/'),
+            Arg::Comma => return None,
+        }
+    }
+    text.push(')');
+    Color::parse(&text)
+}
+// ANCHOR_END: relative_color
+
+// ANCHOR: color_mix
+// color-mix(in <space> [<hue> hue], <color> [<p>], <color> [<p>])
+fn parse_color_mix(args: &[Arg]) -> Option<Color> {
+    let mut groups = args.split(|a| *a == Arg::Comma);
+    let words = |group: &[Arg]| -> Option<Vec<String>> {
+        group
+            .iter()
+            .map(|a| match a {
+                Arg::Word(w) => Some(w.clone()),
+                _ => None,
+            })
+            .collect()
+    };
+    let method = words(groups.next()?)?;
+    let (space, hue) = match method.as_slice() {
+        [keyword, space] if keyword.eq_ignore_ascii_case("in") => {
+            (ColorSpace::from_name(space)?, HueInterpolation::Shorter)
+        }
+        [keyword, space, hue, suffix]
+            if keyword.eq_ignore_ascii_case("in") && suffix.eq_ignore_ascii_case("hue") =>
+        {
+            let space = ColorSpace::from_name(space)?;
+            space.hue_index()?;
+            (space, HueInterpolation::from_name(hue)?)
+        }
+        _ => return None,
+    };
+    let mut parse_item = || -> Option<(Color, Option<f64>)> {
+        let item = words(groups.next()?)?;
+        let (color, percentage) = match item.as_slice() {
+            [color] => (color, None),
+            [a, b] if a.ends_with('%') => (b, Some(a)),
+            [a, b] => (a, Some(b)),
+            _ => return None,
+        };
+        let percentage = match percentage {
+            Some(p) => match Value::parse(p)? {
+                Value::Percentage(p) if (0.0..=100.0).contains(&p) => Some(p / 100.0),
+                _ => return None,
+            },
+            None => None,
+        };
+        Some((Color::parse(color)?, percentage))
+    };
+    let (first, p1) = parse_item()?;
+    let (second, p2) = parse_item()?;
+    if groups.next().is_some() {
+        return None;
+    }
+    let (p1, p2) = match (p1, p2) {
+        (None, None) => (0.5, 0.5),
+        (Some(p1), None) => (p1, 1.0 - p1),
+        (None, Some(p2)) => (1.0 - p2, p2),
+        (Some(p1), Some(p2)) => (p1, p2),
+    };
+    let sum = p1 + p2;
+    if sum == 0.0 {
+        return None;
+    }
+    let mut result = mix(&first, &second, p2 / sum, space, hue);
+    // 百分比之和小于 100% 时结果变得透明
+    if sum < 1.0 {
+        result.alpha *= sum;
+    }
+    Some(result)
+}
+// ANCHOR_END: color_mix
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HueInterpolation {
+    Shorter,
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
+impl HueInterpolation {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "shorter" => Some(HueInterpolation::Shorter),
+            "longer" => Some(HueInterpolation::Longer),
+            "increasing" => Some(HueInterpolation::Increasing),
+            "decreasing" => Some(HueInterpolation::Decreasing),
+            _ => None,
+        }
+    }
+}
+
+// 转换到插值空间，无彩色的色相视为缺失
+fn to_interpolation_space(color: &Color, space: ColorSpace) -> Color {
+    let mut converted = color.convert(space);
+    if let Some(hue) = space.hue_index() {
+        let chroma = match space {
+            ColorSpace::Hsl => converted.components[1],
+            ColorSpace::Hwb => 100.0 - converted.components[1] - converted.components[2],
+            _ => converted.components[1],
+        };
+        if space != color.space && chroma.abs() < 1e-6 {
+            converted.components[hue] = f64::NAN;
+        }
+    }
+    converted
+}
+
+// 在指定空间中按预乘 alpha 插值，amount 为第二个颜色的权重
+pub fn mix(
+    first: &Color,
+    second: &Color,
+    amount: f64,
+    space: ColorSpace,
+    hue: HueInterpolation,
+) -> Color {
+    let mut a = to_interpolation_space(first, space);
+    let mut b = to_interpolation_space(second, space);
+    // 缺失的分量取另一个颜色的值
+    for (x, y) in a.components.iter_mut().zip(b.components.iter_mut()) {
+        if x.is_nan() {
+            *x = *y;
+        } else if y.is_nan() {
+            *y = *x;
+        }
+    }
+    if a.alpha.is_nan() {
+        a.alpha = b.alpha;
+    } else if b.alpha.is_nan() {
+        b.alpha = a.alpha;
+    }
+    let hue_index = space.hue_index();
+    if let Some(h) = hue_index {
+        let (h1, h2) = (&mut a.components[h], &mut b.components[h]);
+        if !h1.is_nan() {
+            let diff = *h2 - *h1;
+            match hue {
+                HueInterpolation::Shorter if diff > 180.0 => *h1 += 360.0,
+                HueInterpolation::Shorter if diff < -180.0 => *h2 += 360.0,
+                HueInterpolation::Longer if 0.0 < diff && diff < 180.0 => *h1 += 360.0,
+                HueInterpolation::Longer if -180.0 < diff && diff <= 0.0 => *h2 += 360.0,
+                HueInterpolation::Increasing if diff < 0.0 => *h2 += 360.0,
+                HueInterpolation::Decreasing if diff > 0.0 => *h1 += 360.0,
+                _ => {}
+            }
+        }
+    }
+    let alpha = a.alpha * (1.0 - amount) + b.alpha * amount;
+    let mut components = [0.0; 3];
+    for (i, component) in components.iter_mut().enumerate() {
+        if Some(i) == hue_index {
+            let value = a.components[i] * (1.0 - amount) + b.components[i] * amount;
+            *component = if value.is_nan() { value } else { normalize_hue(value) };
+        } else {
+            let value = a.components[i] * a.alpha * (1.0 - amount)
+                + b.components[i] * b.alpha * amount;
+            *component = if alpha == 0.0 { value } else { value / alpha };
+        }
+    }
+    Color::new(space, components, alpha)
+}
+
+// ANCHOR: named_colors
+// 按名字排序，便于二分查找
+pub const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+// ANCHOR_END: named_colors
@@ -0,0 +1,238 @@
+use std::{
+    fmt::{self, Display},
+    sync::OnceLock,
+};
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    ast::{AstNode, AstTree},
+    cascade::property_name,
+    codegen::selector_to_string,
+    range::Range,
+    targets::{Browser, Targets, Version},
+    token_type::TokenType,
+};
+
+// ANCHOR: compat_issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureKind {
+    Property,
+    Value,
+    Selector,
+    AtRule,
+    Function,
+}
+
+// 目标浏览器不支持的特性，since 为 None 表示该浏览器还没有支持
+#[derive(Debug, Clone)]
+pub struct CompatIssue {
+    // 数据中的路径，例如 css.selectors.has
+    pub feature: String,
+    pub name: String,
+    pub kind: FeatureKind,
+    pub browser: Browser,
+    pub target: Version,
+    pub since: Option<Version>,
+    pub range: Range,
+}
+// ANCHOR_END: compat_issue
+
+impl Display for CompatIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not supported by {} {}",
+            self.name,
+            self.browser.name(),
+            self.target
+        )?;
+        match self.since {
+            Some(since) => write!(f, " (supported since {})", since),
+            None => write!(f, " (not supported)"),
+        }
+    }
+}
+
+// 与 MDN browser-compat-data 中的浏览器名对应
+fn mdn_name(browser: Browser) -> &'static str {
+    match browser {
+        Browser::Chrome => "chrome",
+        Browser::Edge => "edge",
+        Browser::Firefox => "firefox",
+        Browser::Safari => "safari",
+        Browser::IosSaf => "safari_ios",
+        Browser::Opera => "opera",
+        Browser::Samsung => "samsunginternet_android",
+        Browser::Android => "webview_android",
+        Browser::Ie => "ie",
+    }
+}
+
+fn compat_data() -> &'static Value {
+    static DATA: OnceLock<Value> = OnceLock::new();
+    DATA.get_or_init(|| serde_json::from_str(include_str!("../data/compat.json")).unwrap())
+}
+
+// ANCHOR: support_since
+// 没有数据时返回 None，不支持时返回 Some(None)
+// 只看无前缀、不需要开启实验开关的实现
+fn support_since(compat: &Value, browser: Browser) -> Option<Option<Version>> {
+    let support = compat.get("support")?.get(mdn_name(browser))?;
+    let statements = match support {
+        Value::Array(list) => list.iter().collect(),
+        statement => vec![statement],
+    };
+    let statement = statements.into_iter().find(|s| {
+        s.get("prefix").is_none() && s.get("alternative_name").is_none() && s.get("flags").is_none()
+    });
+    Some(match statement.and_then(|s| s.get("version_added")) {
+        Some(Value::String(version)) => Version::parse(version.trim_start_matches('≤')),
+        Some(Value::Bool(true)) => Some(Version::new(0, 0)),
+        _ => None,
+    })
+}
+// ANCHOR_END: support_since
+
+// ANCHOR: check_compat
+// 检查样式表中目标浏览器不支持的属性、属性值、选择器、at 规则和函数
+pub fn check_compat(tree: &AstTree<TokenType>, targets: &Targets) -> Vec<CompatIssue> {
+    let mut checker = Checker {
+        targets,
+        issues: Vec::new(),
+    };
+    if let Some(root) = &tree.root {
+        checker.node(root);
+    }
+    checker.issues
+}
+// ANCHOR_END: check_compat
+
+struct Checker<'a> {
+    targets: &'a Targets,
+    issues: Vec<CompatIssue>,
+}
+
+impl Checker<'_> {
+    fn node(&mut self, node: &AstNode<TokenType>) {
+        match node.node_type.0 {
+            TokenType::Declaration => self.declaration(node),
+            TokenType::SelectorList => self.selector(node),
+            TokenType::AtRule => {
+                if let Some(name) = node.child_nodes().first() {
+                    let name = name.raw.to_ascii_lowercase();
+                    let name = name.trim_start_matches('@');
+                    self.feature(
+                        &["at-rules", name],
+                        &format!("@{}", name),
+                        FeatureKind::AtRule,
+                        node.range,
+                    );
+                }
+            }
+            _ => {}
+        }
+        for child in node.child_nodes() {
+            self.node(child);
+        }
+    }
+
+    fn declaration(&mut self, node: &AstNode<TokenType>) {
+        let property = property_name(node);
+        if property.starts_with("--") {
+            return;
+        }
+        self.feature(
+            &["properties", &property],
+            &property,
+            FeatureKind::Property,
+            node.range,
+        );
+        let mut words = Vec::new();
+        if let Some(expr) = node.find_child(TokenType::Expression) {
+            value_words(expr, &mut words);
+        }
+        for word in words {
+            let name = word.raw.to_ascii_lowercase();
+            if let Some(function) = name.strip_suffix('(') {
+                self.feature(
+                    &["types", function],
+                    &format!("{})", name),
+                    FeatureKind::Function,
+                    word.range,
+                );
+            } else {
+                self.feature(
+                    &["properties", &property, &name],
+                    &format!("{}: {}", property, name),
+                    FeatureKind::Value,
+                    word.range,
+                );
+            }
+        }
+    }
+
+    fn selector(&mut self, node: &AstNode<TokenType>) {
+        static PSEUDO: OnceLock<Regex> = OnceLock::new();
+        let pattern = PSEUDO.get_or_init(|| Regex::new(r"::?([a-zA-Z-]+)(\()?").unwrap());
+        let text = selector_to_string(node);
+        for caps in pattern.captures_iter(&text) {
+            // 跳过伪元素 ::before
+            if caps[0].starts_with("::") {
+                continue;
+            }
+            let name = caps[1].to_ascii_lowercase();
+            let display = if caps.get(2).is_some() {
+                format!(":{}()", name)
+            } else {
+                format!(":{}", name)
+            };
+            self.feature(
+                &["selectors", &name],
+                &display,
+                FeatureKind::Selector,
+                node.range,
+            );
+        }
+    }
+
+    fn feature(&mut self, path: &[&str], name: &str, kind: FeatureKind, range: Range) {
+        let mut data = &compat_data()["css"];
+        for key in path {
+            match data.get(key) {
+                Some(child) => data = child,
+                None => return,
+            }
+        }
+        let Some(compat) = data.get("__compat") else {
+            return;
+        };
+        for (browser, target) in self.targets.iter() {
+            let Some(since) = support_since(compat, browser) else {
+                continue;
+            };
+            if since.is_none_or(|since| target < since) {
+                self.issues.push(CompatIssue {
+                    feature: format!("css.{}", path.join(".")),
+                    name: name.to_string(),
+                    kind,
+                    browser,
+                    target,
+                    since,
+                    range,
+                });
+            }
+        }
+    }
+}
+
+// 值中的标识符和函数名，嵌套在函数参数中的也会收集
+fn value_words<'a>(node: &'a AstNode<TokenType>, words: &mut Vec<&'a AstNode<TokenType>>) {
+    for child in node.child_nodes() {
+        match child.node_type.0 {
+            TokenType::IdentToken | TokenType::FunctionToken => words.push(child),
+            _ => value_words(child, words),
+        }
+    }
+}
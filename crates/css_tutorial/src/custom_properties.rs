@@ -0,0 +1,461 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use crate::{
+    ast::{AstNode, AstNodeType, AstTree},
+    cascade::property_name,
+    codegen::selector_to_string,
+    downlevel::semi,
+    range::Range,
+    token_type::TokenType,
+};
+
+// ANCHOR: var_options
+#[derive(Debug, Clone, Default)]
+pub struct VarOptions {
+    // 在替换后的声明后面保留原始声明，替换结果作为不支持 var() 的浏览器的回退值
+    pub keep_vars: bool,
+}
+// ANCHOR_END: var_options
+
+// ANCHOR: var_scope
+#[derive(Debug, Clone)]
+pub struct CustomProperty {
+    pub name: String,
+    // Expression 节点
+    pub value: AstNode<TokenType>,
+    pub range: Range,
+}
+
+// 同一条规则中声明的自定义属性
+#[derive(Debug, Clone)]
+pub struct VarScope {
+    pub selector: String,
+    // 在 @media 等 at 规则内部，是否生效取决于运行环境
+    pub conditional: bool,
+    pub properties: Vec<CustomProperty>,
+}
+// ANCHOR_END: var_scope
+
+impl VarScope {
+    // :root 和 html 上的定义对所有元素生效
+    pub fn is_root(&self) -> bool {
+        !self.conditional && matches!(self.selector.as_str(), ":root" | "html")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum VarIssue {
+    // 互相引用的自定义属性，按引用顺序排列
+    Cycle { names: Vec<String>, range: Range },
+    // 没有定义也没有默认值的 var()
+    Undefined { name: String, range: Range },
+}
+
+impl VarIssue {
+    pub fn range(&self) -> Range {
+        match self {
+            VarIssue::Cycle { range, .. } | VarIssue::Undefined { range, .. } => *range,
+        }
+    }
+}
+
+impl Display for VarIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VarIssue::Cycle { names, .. } => {
+                write!(f, "custom properties form a cycle: {}", names.join(" -> "))?;
+                write!(f, " -> {}", names[0])
+            }
+            VarIssue::Undefined { name, .. } => {
+                write!(f, "`{}` is not defined and has no fallback", name)
+            }
+        }
+    }
+}
+
+// ANCHOR: collect_custom_properties
+pub fn collect_custom_properties(tree: &AstTree<TokenType>) -> Vec<VarScope> {
+    let mut scopes = Vec::new();
+    if let Some(root) = &tree.root {
+        collect_scopes(root, false, &mut scopes);
+    }
+    scopes
+}
+// ANCHOR_END: collect_custom_properties
+
+fn collect_scopes(node: &AstNode<TokenType>, conditional: bool, scopes: &mut Vec<VarScope>) {
+    match node.node_type.0 {
+        TokenType::RuleList => {
+            let properties = node
+                .find_child(TokenType::DeclarationList)
+                .map(custom_properties)
+                .unwrap_or_default();
+            if !properties.is_empty() {
+                scopes.push(VarScope {
+                    selector: node
+                        .find_child(TokenType::SelectorList)
+                        .map(selector_to_string)
+                        .unwrap_or_default(),
+                    conditional,
+                    properties,
+                });
+            }
+        }
+        TokenType::AtRule => {
+            for child in node.child_nodes() {
+                collect_scopes(child, true, scopes);
+            }
+        }
+        _ => {
+            for child in node.child_nodes() {
+                collect_scopes(child, conditional, scopes);
+            }
+        }
+    }
+}
+
+fn custom_properties(list: &AstNode<TokenType>) -> Vec<CustomProperty> {
+    list.child_nodes()
+        .iter()
+        .filter(|n| n.check_type(TokenType::Declaration))
+        .filter_map(|declaration| {
+            let name = property_name(declaration);
+            let value = declaration.find_child(TokenType::Expression)?;
+            name.starts_with("--").then(|| CustomProperty {
+                name,
+                value: value.clone(),
+                range: declaration.range,
+            })
+        })
+        .collect()
+}
+
+// ANCHOR: var_reference
+struct VarReference {
+    name: String,
+    // 逗号后面的部分，组成新的 Expression 节点
+    fallback: Option<AstNode<TokenType>>,
+    range: Range,
+}
+
+fn var_reference(term: &AstNode<TokenType>) -> Option<VarReference> {
+    if !term.check_type(TokenType::Term) {
+        return None;
+    }
+    let function = term.find_child(TokenType::Function)?;
+    let name = function.find_child(TokenType::FunctionToken)?;
+    if !name.raw.eq_ignore_ascii_case("var(") {
+        return None;
+    }
+    let args = function.find_child(TokenType::Expression)?.child_nodes();
+    let comma = args
+        .iter()
+        .position(|n| n.check_type(TokenType::Operator) && n.raw == ",");
+    Some(VarReference {
+        name: args.first()?.raw.clone(),
+        fallback: comma.map(|i| AstNode {
+            node_type: AstNodeType(TokenType::Expression),
+            children: Some(args[i + 1..].to_vec()),
+            ..Default::default()
+        }),
+        range: term.range,
+    })
+}
+
+// 收集节点中所有的 var()，包括默认值中嵌套的
+fn var_references(node: &AstNode<TokenType>, references: &mut Vec<VarReference>) {
+    for child in node.child_nodes() {
+        if let Some(reference) = var_reference(child) {
+            references.push(reference);
+        }
+        var_references(child, references);
+    }
+}
+// ANCHOR_END: var_reference
+
+// 某个元素上可见的自定义属性，后面的定义覆盖前面的
+struct Env<'a> {
+    properties: HashMap<&'a str, &'a CustomProperty>,
+    order: Vec<&'a str>,
+}
+
+impl<'a> Env<'a> {
+    fn new(scopes: &[&'a VarScope]) -> Self {
+        let mut env = Env {
+            properties: HashMap::new(),
+            order: Vec::new(),
+        };
+        for property in scopes.iter().flat_map(|scope| &scope.properties) {
+            if env.properties.insert(&property.name, property).is_none() {
+                env.order.push(&property.name);
+            }
+        }
+        env
+    }
+
+    // ANCHOR: find_cycles
+    fn find_cycles(&self) -> Vec<Vec<String>> {
+        let mut state = HashMap::new();
+        let mut path = Vec::new();
+        let mut cycles = Vec::new();
+        for name in &self.order {
+            if !state.contains_key(name) {
+                self.visit(name, &mut state, &mut path, &mut cycles);
+            }
+        }
+        cycles
+    }
+
+    // state 为 false 表示正在访问，true 表示已经访问完
+    fn visit(
+        &self,
+        name: &'a str,
+        state: &mut HashMap<&'a str, bool>,
+        path: &mut Vec<&'a str>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        state.insert(name, false);
+        path.push(name);
+        let mut references = Vec::new();
+        var_references(&self.properties[name].value, &mut references);
+        for reference in references {
+            let Some((&dep, _)) = self.properties.get_key_value(reference.name.as_str()) else {
+                continue;
+            };
+            match state.get(dep) {
+                Some(false) => {
+                    let start = path.iter().position(|n| *n == dep).unwrap();
+                    cycles.push(path[start..].iter().map(|n| n.to_string()).collect());
+                }
+                Some(true) => {}
+                None => self.visit(dep, state, path, cycles),
+            }
+        }
+        path.pop();
+        state.insert(name, true);
+    }
+    // ANCHOR_END: find_cycles
+}
+
+// ANCHOR: check_vars
+// 报告自定义属性之间的循环引用，以及没有定义也没有默认值的 var()
+pub fn check_vars(tree: &AstTree<TokenType>) -> Vec<VarIssue> {
+    let scopes = collect_custom_properties(tree);
+    let roots: Vec<&VarScope> = scopes.iter().filter(|scope| scope.is_root()).collect();
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+    for scope in &scopes {
+        let mut visible = roots.clone();
+        if !scope.is_root() {
+            visible.push(scope);
+        }
+        let env = Env::new(&visible);
+        for names in env.find_cycles() {
+            let mut key = names.clone();
+            key.sort();
+            if seen.insert(key) {
+                issues.push(VarIssue::Cycle {
+                    range: env.properties[names[0].as_str()].range,
+                    names,
+                });
+            }
+        }
+    }
+
+    let defined: HashSet<&str> = scopes
+        .iter()
+        .flat_map(|scope| &scope.properties)
+        .map(|property| property.name.as_str())
+        .collect();
+    let mut references = Vec::new();
+    if let Some(root) = &tree.root {
+        var_references(root, &mut references);
+    }
+    for reference in references {
+        if reference.fallback.is_none() && !defined.contains(reference.name.as_str()) {
+            issues.push(VarIssue::Undefined {
+                name: reference.name,
+                range: reference.range,
+            });
+        }
+    }
+    issues.sort_by_key(|issue| issue.range().start_pos);
+    issues
+}
+// ANCHOR_END: check_vars
+
+// ANCHOR: resolve_vars
+// 用 :root 和所在规则中的定义替换 var()，返回替换的声明数量
+// 其他规则中也定义过的属性取决于具体元素，不做替换
+pub fn resolve_vars(tree: &mut AstTree<TokenType>, options: &VarOptions) -> usize {
+    let scopes = collect_custom_properties(tree);
+    let roots: Vec<&VarScope> = scopes.iter().filter(|scope| scope.is_root()).collect();
+    // 每个属性在几个 :root 以外的规则中定义
+    let mut scoped: HashMap<&str, usize> = HashMap::new();
+    for scope in scopes.iter().filter(|scope| !scope.is_root()) {
+        let names: HashSet<&str> = scope.properties.iter().map(|p| p.name.as_str()).collect();
+        for name in names {
+            *scoped.entry(name).or_default() += 1;
+        }
+    }
+    match &mut tree.root {
+        Some(root) => resolve_node(root, &roots, &scoped, options),
+        None => 0,
+    }
+}
+// ANCHOR_END: resolve_vars
+
+fn resolve_node(
+    node: &mut AstNode<TokenType>,
+    roots: &[&VarScope],
+    scoped: &HashMap<&str, usize>,
+    options: &VarOptions,
+) -> usize {
+    let Some(children) = &mut node.children else {
+        return 0;
+    };
+    if !node.node_type.0.eq(&TokenType::DeclarationList) {
+        return children
+            .iter_mut()
+            .map(|child| resolve_node(child, roots, scoped, options))
+            .sum();
+    }
+    let local = VarScope {
+        selector: String::new(),
+        conditional: false,
+        properties: custom_properties(node),
+    };
+    let mut visible = roots.to_vec();
+    visible.push(&local);
+    let env = Env::new(&visible);
+    let resolver = Resolver {
+        cyclic: env.find_cycles().into_iter().flatten().collect(),
+        env,
+        local: &local,
+        scoped,
+    };
+
+    let Some(children) = &mut node.children else {
+        return 0;
+    };
+    let mut count = 0;
+    let mut result = Vec::with_capacity(children.len());
+    for child in children.drain(..) {
+        if !child.check_type(TokenType::Declaration) {
+            let mut child = child;
+            count += resolve_node(&mut child, roots, scoped, options);
+            result.push(child);
+            continue;
+        }
+        let mut references = Vec::new();
+        var_references(&child, &mut references);
+        if references.is_empty() || property_name(&child).starts_with("--") {
+            result.push(child);
+            continue;
+        }
+        let mut resolved = child.clone();
+        let value = resolved
+            .children
+            .iter_mut()
+            .flatten()
+            .find(|n| n.check_type(TokenType::Expression));
+        let changed = value.is_some_and(|value| {
+            resolver.substitute(value, &mut Vec::new()).is_ok() && !value.child_nodes().is_empty()
+        });
+        if !changed {
+            result.push(child);
+            continue;
+        }
+        count += 1;
+        result.push(resolved);
+        if options.keep_vars {
+            result.push(semi());
+            result.push(child);
+        }
+    }
+    *children = result;
+    count
+}
+
+// 无法替换的原因
+enum Unresolved {
+    // 引用的属性未定义或处于循环中，并且没有默认值
+    Invalid,
+    // 属性的值取决于具体元素
+    Unknown,
+}
+
+struct Resolver<'a> {
+    env: Env<'a>,
+    cyclic: HashSet<String>,
+    local: &'a VarScope,
+    scoped: &'a HashMap<&'a str, usize>,
+}
+
+impl Resolver<'_> {
+    // 把 node 中的 var() 替换成对应的值
+    fn substitute(
+        &self,
+        node: &mut AstNode<TokenType>,
+        stack: &mut Vec<String>,
+    ) -> Result<(), Unresolved> {
+        let Some(children) = &mut node.children else {
+            return Ok(());
+        };
+        let mut i = 0;
+        while i < children.len() {
+            match var_reference(&children[i]) {
+                Some(reference) => {
+                    let value = self.resolve(&reference, stack)?;
+                    let len = value.len();
+                    children.splice(i..i + 1, value);
+                    i += len;
+                }
+                None => {
+                    self.substitute(&mut children[i], stack)?;
+                    i += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve(
+        &self,
+        reference: &VarReference,
+        stack: &mut Vec<String>,
+    ) -> Result<Vec<AstNode<TokenType>>, Unresolved> {
+        let name = reference.name.as_str();
+        let is_local = self.local.properties.iter().any(|p| p.name == name);
+        // 只有所在规则定义的属性可以替换，多个规则都定义时取决于具体元素
+        match self.scoped.get(name) {
+            Some(1) if is_local => {}
+            Some(_) => return Err(Unresolved::Unknown),
+            None => {}
+        }
+        if let Some(property) = self.env.properties.get(name) {
+            if !self.cyclic.contains(name) && !stack.iter().any(|n| n == name) {
+                let mut value = property.value.clone();
+                stack.push(name.to_string());
+                let result = self.substitute(&mut value, stack);
+                stack.pop();
+                match result {
+                    Ok(()) => return Ok(value.children.unwrap_or_default()),
+                    Err(Unresolved::Unknown) => return Err(Unresolved::Unknown),
+                    Err(Unresolved::Invalid) => {}
+                }
+            }
+        }
+        match &reference.fallback {
+            Some(fallback) => {
+                let mut fallback = fallback.clone();
+                self.substitute(&mut fallback, stack)?;
+                Ok(fallback.children.unwrap_or_default())
+            }
+            None => Err(Unresolved::Invalid),
+        }
+    }
+}
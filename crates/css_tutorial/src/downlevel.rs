@@ -0,0 +1,208 @@
+use crate::{
+    ast::{AstNode, AstNodeType, AstTree},
+    codegen::term_to_string,
+    color::Color,
+    parser::parse_value,
+    targets::{Browser, Targets, Version},
+    token_type::TokenType,
+};
+
+// ANCHOR: downlevel_options
+#[derive(Debug, Clone, Default)]
+pub struct DownlevelOptions {
+    // 在降级后的声明后面保留原始声明，支持的浏览器会使用后者
+    pub keep_modern: bool,
+    // 目标浏览器都支持的颜色写法不做降级，为空时全部降级
+    pub targets: Targets,
+}
+// ANCHOR_END: downlevel_options
+
+// 旧浏览器不支持的颜色函数
+const MODERN_COLOR_FUNCTIONS: [&str; 7] = [
+    "lab(",
+    "lch(",
+    "oklab(",
+    "oklch(",
+    "color(",
+    "color-mix(",
+    "hwb(",
+];
+
+pub fn is_modern_color(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    MODERN_COLOR_FUNCTIONS.iter().any(|f| lower.starts_with(f)) || lower.contains("(from ")
+}
+
+// ANCHOR: color_support
+// 各浏览器开始支持这些颜色写法的版本，没有列出的浏览器不支持
+const COLOR_SUPPORT: [(&str, &[(Browser, Version)]); 7] = [
+    (
+        "(from ",
+        &[
+            (Browser::Chrome, Version::new(119, 0)),
+            (Browser::Edge, Version::new(119, 0)),
+            (Browser::Firefox, Version::new(128, 0)),
+            (Browser::Safari, Version::new(18, 0)),
+            (Browser::IosSaf, Version::new(18, 0)),
+            (Browser::Opera, Version::new(105, 0)),
+            (Browser::Samsung, Version::new(25, 0)),
+            (Browser::Android, Version::new(119, 0)),
+        ],
+    ),
+    (
+        "color-mix(",
+        &[
+            (Browser::Chrome, Version::new(111, 0)),
+            (Browser::Edge, Version::new(111, 0)),
+            (Browser::Firefox, Version::new(113, 0)),
+            (Browser::Safari, Version::new(16, 2)),
+            (Browser::IosSaf, Version::new(16, 2)),
+            (Browser::Opera, Version::new(97, 0)),
+            (Browser::Samsung, Version::new(22, 0)),
+            (Browser::Android, Version::new(111, 0)),
+        ],
+    ),
+    (
+        "hwb(",
+        &[
+            (Browser::Chrome, Version::new(101, 0)),
+            (Browser::Edge, Version::new(101, 0)),
+            (Browser::Firefox, Version::new(96, 0)),
+            (Browser::Safari, Version::new(15, 0)),
+            (Browser::IosSaf, Version::new(15, 0)),
+            (Browser::Opera, Version::new(87, 0)),
+            (Browser::Samsung, Version::new(19, 0)),
+            (Browser::Android, Version::new(101, 0)),
+        ],
+    ),
+    ("lab(", LAB_SUPPORT),
+    ("lch(", LAB_SUPPORT),
+    ("oklab(", LAB_SUPPORT),
+    ("color(", LAB_SUPPORT),
+];
+
+const LAB_SUPPORT: &[(Browser, Version)] = &[
+    (Browser::Chrome, Version::new(111, 0)),
+    (Browser::Edge, Version::new(111, 0)),
+    (Browser::Firefox, Version::new(113, 0)),
+    (Browser::Safari, Version::new(15, 4)),
+    (Browser::IosSaf, Version::new(15, 4)),
+    (Browser::Opera, Version::new(97, 0)),
+    (Browser::Samsung, Version::new(22, 0)),
+    (Browser::Android, Version::new(111, 0)),
+];
+// ANCHOR_END: color_support
+
+// 目标中有浏览器不支持这种颜色写法时需要回退值
+pub fn needs_fallback(text: &str, targets: &Targets) -> bool {
+    if targets.is_empty() {
+        return true;
+    }
+    let lower = text.to_ascii_lowercase();
+    let support = COLOR_SUPPORT
+        .iter()
+        .find(|(feature, _)| lower.contains(feature))
+        .map_or(LAB_SUPPORT, |(_, support)| *support);
+    targets.iter().any(|(browser, version)| {
+        support
+            .iter()
+            .find(|(b, _)| *b == browser)
+            .is_none_or(|(_, since)| version < *since)
+    })
+}
+
+// 映射到 srgb 色域后输出，带透明度时使用 rgba() 兼容不支持 #rrggbbaa 的浏览器
+pub fn fallback_color(color: &Color) -> String {
+    let mapped = color.gamut_map_srgb();
+    if mapped.alpha >= 1.0 {
+        mapped.to_shortest()
+    } else {
+        mapped.to_string()
+    }
+}
+
+// ANCHOR: downlevel_colors
+// 把现代颜色函数改写成 srgb 的回退值，返回改写的声明数量
+pub fn downlevel_colors(tree: &mut AstTree<TokenType>, options: &DownlevelOptions) -> usize {
+    match &mut tree.root {
+        Some(root) => downlevel_node(root, options),
+        None => 0,
+    }
+}
+// ANCHOR_END: downlevel_colors
+
+fn downlevel_node(node: &mut AstNode<TokenType>, options: &DownlevelOptions) -> usize {
+    let Some(children) = &mut node.children else {
+        return 0;
+    };
+    if !node.node_type.0.eq(&TokenType::DeclarationList) {
+        return children
+            .iter_mut()
+            .map(|child| downlevel_node(child, options))
+            .sum();
+    }
+    let mut count = 0;
+    let mut result = Vec::with_capacity(children.len());
+    for child in children.drain(..) {
+        if !child.check_type(TokenType::Declaration) {
+            let mut child = child;
+            count += downlevel_node(&mut child, options);
+            result.push(child);
+            continue;
+        }
+        let mut fallback = child.clone();
+        let changed = fallback
+            .children
+            .iter_mut()
+            .flatten()
+            .filter(|n| n.check_type(TokenType::Expression))
+            .any(|expr| rewrite_value(expr, &options.targets));
+        if !changed {
+            result.push(child);
+            continue;
+        }
+        count += 1;
+        result.push(fallback);
+        if options.keep_modern {
+            result.push(semi());
+            result.push(child);
+        }
+    }
+    *children = result;
+    count
+}
+
+pub(crate) fn semi() -> AstNode<TokenType> {
+    AstNode {
+        node_type: AstNodeType(TokenType::Semi),
+        raw: ";".to_string(),
+        ..Default::default()
+    }
+}
+
+// 递归替换 Expression 中的颜色，函数参数里的颜色（如渐变）也会处理
+fn rewrite_value(node: &mut AstNode<TokenType>, targets: &Targets) -> bool {
+    let mut changed = false;
+    for child in node.children.iter_mut().flatten() {
+        if child.check_type(TokenType::Term) {
+            let text = term_to_string(child);
+            if is_modern_color(&text) && needs_fallback(&text, targets) {
+                if let Some(term) = Color::parse(&text)
+                    .and_then(|color| parse_value(&fallback_color(&color)))
+                    .and_then(|expr| expr.find_child(TokenType::Term).cloned())
+                {
+                    *child = AstNode {
+                        range: child.range,
+                        ..term
+                    };
+                    changed = true;
+                    continue;
+                }
+            }
+        }
+        if child.children.is_some() {
+            changed |= rewrite_value(child, targets);
+        }
+    }
+    changed
+}
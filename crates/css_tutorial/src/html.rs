@@ -0,0 +1,460 @@
+// ANCHOR: html
+// 简易 html 解析，只满足选择器匹配和样式计算的需要
+pub type NodeId = usize;
+
+#[derive(Debug, Clone)]
+pub struct Element {
+    pub tag_name: String,
+    pub attributes: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeData {
+    Document,
+    Doctype(String),
+    Element(Element),
+    Text(String),
+    Comment(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct HtmlNode {
+    pub data: NodeData,
+    pub parent: Option<NodeId>,
+    pub children: Vec<NodeId>,
+}
+
+// 所有节点存放在数组里，节点之间用下标关联
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub nodes: Vec<HtmlNode>,
+}
+// ANCHOR_END: html
+
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param",
+    "source", "track", "wbr",
+];
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["script", "style", "textarea", "title"];
+
+impl Element {
+    pub fn get_attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_attribute(&mut self, name: &str, value: &str) {
+        match self
+            .attributes
+            .iter_mut()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.attributes.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn remove_attribute(&mut self, name: &str) {
+        self.attributes.retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.get_attribute("id")
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.get_attribute("class")
+            .unwrap_or_default()
+            .split_ascii_whitespace()
+    }
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Document {
+            nodes: vec![HtmlNode {
+                data: NodeData::Document,
+                parent: None,
+                children: Vec::new(),
+            }],
+        }
+    }
+
+    pub fn parse(source: &str) -> Self {
+        let mut parser = HtmlParser {
+            source,
+            pos: 0,
+            document: Document::new(),
+            open: vec![0],
+        };
+        parser.parse();
+        parser.document
+    }
+
+    pub fn root(&self) -> NodeId {
+        0
+    }
+
+    pub fn node(&self, id: NodeId) -> &HtmlNode {
+        &self.nodes[id]
+    }
+
+    pub fn element(&self, id: NodeId) -> Option<&Element> {
+        match &self.nodes[id].data {
+            NodeData::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    pub fn element_mut(&mut self, id: NodeId) -> Option<&mut Element> {
+        match &mut self.nodes[id].data {
+            NodeData::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    pub fn append(&mut self, parent: NodeId, data: NodeData) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(HtmlNode {
+            data,
+            parent: Some(parent),
+            children: Vec::new(),
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    // 从父节点上摘除，节点本身仍留在数组中
+    pub fn detach(&mut self, id: NodeId) {
+        if let Some(parent) = self.nodes[id].parent.take() {
+            self.nodes[parent].children.retain(|child| *child != id);
+        }
+    }
+
+    pub fn parent_element(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id]
+            .parent
+            .filter(|parent| self.element(*parent).is_some())
+    }
+
+    pub fn element_children(&self, id: NodeId) -> Vec<NodeId> {
+        self.nodes[id]
+            .children
+            .iter()
+            .copied()
+            .filter(|child| self.element(*child).is_some())
+            .collect()
+    }
+
+    // 同一父节点下的兄弟元素（包含自身）
+    pub fn element_siblings(&self, id: NodeId) -> Vec<NodeId> {
+        match self.nodes[id].parent {
+            Some(parent) => self.element_children(parent),
+            None => vec![id],
+        }
+    }
+
+    pub fn previous_element_sibling(&self, id: NodeId) -> Option<NodeId> {
+        let siblings = self.element_siblings(id);
+        let index = siblings.iter().position(|s| *s == id)?;
+        index.checked_sub(1).map(|i| siblings[i])
+    }
+
+    // 按文档顺序返回所有挂在树上的元素
+    pub fn elements(&self) -> Vec<NodeId> {
+        let mut result = Vec::new();
+        let mut stack = vec![self.root()];
+        while let Some(id) = stack.pop() {
+            if self.element(id).is_some() {
+                result.push(id);
+            }
+            stack.extend(self.nodes[id].children.iter().rev());
+        }
+        result
+    }
+
+    pub fn elements_by_tag_name(&self, tag_name: &str) -> Vec<NodeId> {
+        self.elements()
+            .into_iter()
+            .filter(|id| {
+                self.element(*id)
+                    .is_some_and(|e| e.tag_name.eq_ignore_ascii_case(tag_name))
+            })
+            .collect()
+    }
+
+    // 元素内的文本内容
+    pub fn text_content(&self, id: NodeId) -> String {
+        let mut result = String::new();
+        for child in &self.nodes[id].children {
+            match &self.nodes[*child].data {
+                NodeData::Text(text) => result.push_str(text),
+                NodeData::Element(_) => result.push_str(&self.text_content(*child)),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    pub fn set_text_content(&mut self, id: NodeId, text: &str) {
+        for child in self.nodes[id].children.clone() {
+            self.detach(child);
+        }
+        self.append(id, NodeData::Text(text.to_string()));
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        self.write_node(self.root(), &mut out);
+        out
+    }
+
+    fn write_node(&self, id: NodeId, out: &mut String) {
+        match &self.nodes[id].data {
+            NodeData::Document => {}
+            NodeData::Doctype(doctype) => {
+                out.push_str(&format!("<!{}>", doctype));
+                return;
+            }
+            NodeData::Text(text) => {
+                out.push_str(text);
+                return;
+            }
+            NodeData::Comment(comment) => {
+                out.push_str(&format!("<!--{}-->", comment));
+                return;
+            }
+            NodeData::Element(element) => {
+                out.push('<');
+                out.push_str(&element.tag_name);
+                for (name, value) in &element.attributes {
+                    out.push(' ');
+                    out.push_str(name);
+                    if !value.is_empty() {
+                        out.push_str(&format!("=\"{}\"", escape_attribute(value)));
+                    }
+                }
+                out.push('>');
+                if is_void_element(&element.tag_name) {
+                    return;
+                }
+            }
+        }
+        for child in &self.nodes[id].children {
+            self.write_node(*child, out);
+        }
+        if let Some(element) = self.element(id) {
+            out.push_str(&format!("</{}>", element.tag_name));
+        }
+    }
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_void_element(tag_name: &str) -> bool {
+    VOID_ELEMENTS.contains(&tag_name.to_ascii_lowercase().as_str())
+}
+
+fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+fn unescape_entities(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+struct HtmlParser<'a> {
+    source: &'a str,
+    pos: usize,
+    document: Document,
+    // 尚未闭合的元素栈
+    open: Vec<NodeId>,
+}
+
+impl<'a> HtmlParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn current(&self) -> NodeId {
+        *self.open.last().unwrap()
+    }
+
+    fn cur_char(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn advance(&mut self) {
+        if let Some(ch) = self.cur_char() {
+            self.pos += ch.len_utf8();
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.cur_char().is_some_and(|ch| ch.is_whitespace()) {
+            self.advance();
+        }
+    }
+
+    // 读取直到 pattern，返回之前的内容并跳过 pattern
+    fn read_until(&mut self, pattern: &str) -> &'a str {
+        let rest = self.rest();
+        match rest.find(pattern) {
+            Some(index) => {
+                self.pos += index + pattern.len();
+                &rest[..index]
+            }
+            None => {
+                self.pos = self.source.len();
+                rest
+            }
+        }
+    }
+
+    fn parse(&mut self) {
+        while self.pos < self.source.len() {
+            let rest = self.rest();
+            if rest.starts_with("<!--") {
+                self.pos += 4;
+                let comment = self.read_until("-->");
+                self.document
+                    .append(self.current(), NodeData::Comment(comment.to_string()));
+            } else if rest.starts_with("<!") {
+                self.pos += 2;
+                let doctype = self.read_until(">");
+                self.document
+                    .append(self.current(), NodeData::Doctype(doctype.to_string()));
+            } else if rest.starts_with("</") {
+                self.pos += 2;
+                let name = self.read_until(">").trim().to_ascii_lowercase();
+                self.close_element(&name);
+            } else if rest.starts_with('<')
+                && rest[1..].starts_with(|ch: char| ch.is_ascii_alphabetic())
+            {
+                self.advance();
+                self.parse_element();
+            } else {
+                // 跳过第一个字符，它可能是多字节字符或不能开始标签的 <
+                let first = rest.chars().next().map_or(1, char::len_utf8);
+                let end = rest[first..]
+                    .find('<')
+                    .map(|i| i + first)
+                    .unwrap_or(rest.len());
+                self.pos += end;
+                self.document
+                    .append(self.current(), NodeData::Text(rest[..end].to_string()));
+            }
+        }
+    }
+
+    fn close_element(&mut self, name: &str) {
+        let position = self.open.iter().rposition(|id| {
+            self.document
+                .element(*id)
+                .is_some_and(|e| e.tag_name.eq_ignore_ascii_case(name))
+        });
+        if let Some(position) = position {
+            self.open.truncate(position);
+        }
+    }
+
+    fn read_name(&mut self) -> &'a str {
+        let start = self.pos;
+        while self
+            .cur_char()
+            .is_some_and(|ch| !ch.is_whitespace() && !matches!(ch, '>' | '/' | '='))
+        {
+            self.advance();
+        }
+        &self.source[start..self.pos]
+    }
+
+    fn parse_element(&mut self) {
+        let tag_name = self.read_name().to_ascii_lowercase();
+        let mut attributes = Vec::new();
+        let mut self_closing = false;
+        loop {
+            self.skip_whitespace();
+            match self.cur_char() {
+                None => break,
+                Some('>') => {
+                    self.advance();
+                    break;
+                }
+                Some('/') => {
+                    self.advance();
+                    self_closing = true;
+                }
+                Some(_) => {
+                    let name = self.read_name().to_ascii_lowercase();
+                    if name.is_empty() {
+                        self.advance();
+                        continue;
+                    }
+                    self.skip_whitespace();
+                    let mut value = String::new();
+                    if self.cur_char() == Some('=') {
+                        self.advance();
+                        self.skip_whitespace();
+                        value = match self.cur_char() {
+                            Some(quote @ ('"' | '\'')) => {
+                                self.advance();
+                                self.read_until(&quote.to_string()).to_string()
+                            }
+                            _ => self.read_name().to_string(),
+                        };
+                    }
+                    attributes.push((name, unescape_entities(&value)));
+                }
+            }
+        }
+
+        // 隐式闭合：<li> 和 <p> 不能直接嵌套同名元素
+        if matches!(tag_name.as_str(), "li" | "p" | "option")
+            && self
+                .document
+                .element(self.current())
+                .is_some_and(|e| e.tag_name == tag_name)
+        {
+            self.open.pop();
+        }
+
+        let id = self.document.append(
+            self.current(),
+            NodeData::Element(Element {
+                tag_name: tag_name.clone(),
+                attributes,
+            }),
+        );
+        if self_closing || is_void_element(&tag_name) {
+            return;
+        }
+        if RAW_TEXT_ELEMENTS.contains(&tag_name.as_str()) {
+            let rest = self.rest();
+            let end = rest
+                .to_ascii_lowercase()
+                .find(&format!("</{}", tag_name))
+                .unwrap_or(rest.len());
+            if end > 0 {
+                self.document
+                    .append(id, NodeData::Text(rest[..end].to_string()));
+            }
+            self.pos += end;
+            self.read_until(">");
+            return;
+        }
+        self.open.push(id);
+    }
+}
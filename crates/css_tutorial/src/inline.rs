@@ -0,0 +1,160 @@
+use crate::{
+    ast::{AstNode, AstTree},
+    cascade::{Cascade, Origin},
+    codegen::node_to_string,
+    html::{Document, NodeId},
+    parser::parse_stylesheet,
+    selector::{compile_selector_list, join_selector_list, split_selector_list},
+    token_type::TokenType,
+};
+
+// ANCHOR: inline_options
+#[derive(Debug, Clone)]
+pub struct InlineOptions {
+    // 无法内联的 @media、@font-face 等规则保留在 <style> 中
+    pub keep_at_rules: bool,
+    // 带 :hover、::before 等的规则保留在 <style> 中
+    pub keep_pseudos: bool,
+}
+// ANCHOR_END: inline_options
+
+impl Default for InlineOptions {
+    fn default() -> Self {
+        InlineOptions {
+            keep_at_rules: true,
+            keep_pseudos: true,
+        }
+    }
+}
+
+// 这些元素不会被渲染，不写入 style 属性
+const SKIP_ELEMENTS: [&str; 8] = [
+    "head", "style", "script", "meta", "link", "title", "base", "template",
+];
+
+// ANCHOR: inline_css
+// 把 <style> 中的规则按层叠顺序写入元素的 style 属性
+pub fn inline_css(html: &str, options: &InlineOptions) -> String {
+    let mut document = Document::parse(html);
+    let mut inlinable = Vec::new();
+
+    for style in document.elements_by_tag_name("style") {
+        // <style data-embed> 原样保留
+        if let Some(element) = document.element_mut(style) {
+            if element.get_attribute("data-embed").is_some() {
+                element.remove_attribute("data-embed");
+                continue;
+            }
+        }
+        let tree = parse_stylesheet(&document.text_content(style));
+        let mut residual = Vec::new();
+        if let Some(root) = &tree.root {
+            for item in root.child_nodes() {
+                match item.node_type.0 {
+                    TokenType::RuleList => {
+                        let (inline, rest) = split_rule(item);
+                        inlinable.extend(inline);
+                        if options.keep_pseudos {
+                            residual.extend(rest);
+                        }
+                    }
+                    TokenType::AtRule | TokenType::Import | TokenType::ChartSet | TokenType::Page
+                        if options.keep_at_rules =>
+                    {
+                        residual.push(item.clone());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if residual.is_empty() {
+            document.detach(style);
+        } else {
+            let css: String = residual
+                .iter()
+                .map(|node| node_to_string(node) + "\n")
+                .collect();
+            document.set_text_content(style, &format!("\n{}", css));
+        }
+    }
+
+    let sheet = AstTree {
+        root: Some(Box::new(AstNode::from_children(
+            TokenType::Stylesheets,
+            inlinable,
+        ))),
+    };
+    let mut cascade = Cascade::new();
+    cascade.add_stylesheet(Origin::Author, &sheet);
+    for style in cascade.compute(&document) {
+        if style.declarations.iter().all(|d| d.inline) || is_skipped(&document, style.element) {
+            continue;
+        }
+        let value = style
+            .declarations
+            .iter()
+            .map(|d| {
+                format!(
+                    "{}: {}{};",
+                    d.property,
+                    d.value,
+                    if d.important { " !important" } else { "" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Some(element) = document.element_mut(style.element) {
+            element.set_attribute("style", &value);
+        }
+    }
+    document.to_html()
+}
+// ANCHOR_END: inline_css
+
+// 把一条规则拆成可内联和不可内联两部分
+fn split_rule(rule: &AstNode<TokenType>) -> (Option<AstNode<TokenType>>, Option<AstNode<TokenType>>) {
+    let Some(selector) = rule.find_child(TokenType::SelectorList) else {
+        return (None, Some(rule.clone()));
+    };
+    let (inline, rest): (Vec<_>, Vec<_>) = split_selector_list(selector)
+        .into_iter()
+        .partition(|group| {
+            let compiled = compile_selector_list(&AstNode::from_children(
+                TokenType::SelectorList,
+                group.clone(),
+            ));
+            compiled
+                .0
+                .iter()
+                .all(|s| !s.has_pseudo_element() && !s.has_dynamic_pseudo_class())
+        });
+    let with_selector = |groups: Vec<Vec<AstNode<TokenType>>>| {
+        if groups.is_empty() {
+            return None;
+        }
+        let mut rule = rule.clone();
+        if let Some(children) = &mut rule.children {
+            for child in children.iter_mut() {
+                if child.check_type(TokenType::SelectorList) {
+                    *child = join_selector_list(groups.clone());
+                }
+            }
+        }
+        Some(rule)
+    };
+    (with_selector(inline), with_selector(rest))
+}
+
+fn is_skipped(document: &Document, element: NodeId) -> bool {
+    let mut current = Some(element);
+    while let Some(id) = current {
+        if document
+            .element(id)
+            .is_some_and(|e| SKIP_ELEMENTS.contains(&e.tag_name.as_str()))
+        {
+            return true;
+        }
+        current = document.parent_element(id);
+    }
+    false
+}
@@ -0,0 +1,622 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    ast::{AstNode, AstTree},
+    codegen::{generate, selector_to_string, value_to_string},
+    lexer::Syntax,
+    optimizer::leaf,
+    parser::{parse_selector, parse_stylesheet_with_syntax},
+    range::Range,
+    token_type::TokenType,
+};
+
+mod extend;
+mod functions;
+mod guard;
+mod import;
+mod mixin;
+mod operation;
+mod translate;
+
+use extend::has_extend;
+pub(crate) use extend::{apply_extends, media_query, Extend, ExtendMatch};
+use import::{import_content, Importer};
+use mixin::is_mixin_definition;
+pub use translate::{less_to_scss, ScssTranslation, TranslateWarning};
+
+// ANCHOR: less_error
+#[derive(Debug, Clone)]
+pub struct LessError {
+    pub message: String,
+    pub range: Range,
+}
+// ANCHOR_END: less_error
+
+impl LessError {
+    fn new(message: impl Into<String>, range: Range) -> Self {
+        LessError {
+            message: message.into(),
+            range,
+        }
+    }
+}
+
+impl Display for LessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// ANCHOR: less_options
+#[derive(Debug, Clone, Default)]
+pub struct LessOptions {
+    // 源码所在的文件，@import 首先在它所在的目录中查找，没有时使用当前目录
+    pub filename: Option<PathBuf>,
+    // 找不到导入的文件时依次在这些目录中查找
+    pub include_paths: Vec<PathBuf>,
+}
+// ANCHOR_END: less_options
+
+// ANCHOR: compile_less
+// 编译 less 源码，输出普通的 css
+pub fn compile_less(source: &str) -> Result<String, LessError> {
+    compile_less_with_options(source, &LessOptions::default())
+}
+
+// 读取并编译 less 文件
+pub fn compile_less_file<P: AsRef<Path>>(
+    path: P,
+    options: &LessOptions,
+) -> Result<String, LessError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path).map_err(|error| {
+        LessError::new(
+            format!("failed to read '{}': {}", path.display(), error),
+            Range::default(),
+        )
+    })?;
+    let options = LessOptions {
+        filename: Some(path.to_path_buf()),
+        ..options.clone()
+    };
+    compile_less_with_options(&source, &options)
+}
+
+pub fn compile_less_with_options(source: &str, options: &LessOptions) -> Result<String, LessError> {
+    let mut tree = parse_stylesheet_with_syntax(source, Syntax::Less);
+    if let Some(root) = &mut tree.root {
+        Importer::new(options).load(root)?;
+    }
+    Ok(generate(&eval_less(&tree)?))
+}
+
+// 求值变量并展开嵌套规则，返回的语法树中只有普通的 css 节点
+pub fn eval_less(tree: &AstTree<TokenType>) -> Result<AstTree<TokenType>, LessError> {
+    let Some(root) = &tree.root else {
+        return Ok(AstTree { root: None });
+    };
+    let frame = Frame::new(root.child_nodes());
+    let mut out = Vec::new();
+    let mut evaluator = Evaluator::default();
+    evaluator.body(root.child_nodes(), None, &[&frame], &mut out)?;
+    apply_extends(&mut out, &evaluator.extends, false, Syntax::Css);
+    // 和 lessc 一样，保留的 css @import 移到 @charset 之后、其他规则之前
+    out.sort_by_key(|node| {
+        !(node.check_type(TokenType::ChartSet) || node.check_type(TokenType::Import))
+    });
+    Ok(AstTree {
+        root: Some(Box::new(AstNode::from_children(
+            TokenType::Stylesheets,
+            out,
+        ))),
+    })
+}
+// ANCHOR_END: compile_less
+
+// ANCHOR: frame
+// 一个块中定义的变量和规则，变量是惰性求值的，同名变量以块中最后一次定义为准
+#[derive(Default)]
+struct Frame<'a> {
+    variables: HashMap<String, AstNode<TokenType>>,
+    // 调用 mixin 时从中查找定义
+    rules: Vec<&'a AstNode<TokenType>>,
+}
+
+impl<'a> Frame<'a> {
+    fn new(items: &'a [AstNode<TokenType>]) -> Self {
+        let mut frame = Frame::default();
+        frame.add(items);
+        frame
+    }
+
+    // 导入文件中的变量和 mixin 与导入处在同一个作用域
+    fn add(&mut self, items: &'a [AstNode<TokenType>]) {
+        for item in items {
+            if let Some(content) = item
+                .check_type(TokenType::Import)
+                .then(|| import_content(item))
+                .flatten()
+            {
+                self.add(content.child_nodes());
+            }
+            if item.check_type(TokenType::RuleList)
+                && item.find_child(TokenType::DeclarationList).is_some()
+            {
+                self.rules.push(item);
+            }
+            if !item.check_type(TokenType::VariableDeclaration)
+                || item.find_child(TokenType::Colon).is_none()
+            {
+                continue;
+            }
+            if let Some(name) = item.find_child(TokenType::Variable) {
+                let value = item
+                    .find_child(TokenType::Expression)
+                    .cloned()
+                    .unwrap_or_else(|| AstNode::from_children(TokenType::Expression, vec![]));
+                self.variables.insert(name.raw.clone(), value);
+            }
+        }
+    }
+}
+// ANCHOR_END: frame
+
+#[derive(Default)]
+struct Evaluator {
+    // 正在求值的变量，用于发现循环定义
+    evaluating: Vec<String>,
+    // 正在求值的规则和 mixin，规则不能调用自身
+    rules: Vec<*const AstNode<TokenType>>,
+    // 求值过程中遇到的 :extend()，最后统一处理
+    extends: Vec<Extend>,
+}
+
+impl Evaluator {
+    // ANCHOR: body
+    // 求值块中的内容，selectors 为所在规则展开后的选择器
+    // 声明合并成一条规则放在最前面，嵌套的规则和冒泡的 at 规则依次放在后面
+    fn body<'a>(
+        &mut self,
+        items: &'a [AstNode<TokenType>],
+        selectors: Option<&[String]>,
+        frames: &[&Frame<'a>],
+        out: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), LessError> {
+        let mut declarations = Vec::new();
+        let mut nested = Vec::new();
+        self.items(items, selectors, frames, &mut declarations, &mut nested)?;
+        if let Some(selectors) = selectors {
+            if declarations
+                .iter()
+                .any(|n| n.check_type(TokenType::Declaration))
+            {
+                out.push(make_rule(selectors, declarations));
+            }
+        }
+        out.extend(nested);
+        Ok(())
+    }
+
+    // 不在规则中时声明也放在 nested 中，保持原有顺序
+    fn items<'a>(
+        &mut self,
+        items: &'a [AstNode<TokenType>],
+        selectors: Option<&[String]>,
+        frames: &[&Frame<'a>],
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), LessError> {
+        for item in items {
+            match item.node_type.0 {
+                TokenType::Declaration => {
+                    let declaration = self.declaration(item, frames)?;
+                    match selectors {
+                        Some(_) => declarations.push(declaration),
+                        None => nested.push(declaration),
+                    }
+                }
+                // 带参数的 mixin 定义不输出
+                TokenType::RuleList if is_mixin_definition(item) => {}
+                TokenType::RuleList if item.find_child(TokenType::DeclarationList).is_some() => {
+                    self.rule(item, selectors, frames, nested)?;
+                }
+                // &:extend(.a);
+                TokenType::RuleList if has_extend(item) => {
+                    if let Some(list) = item.find_child(TokenType::SelectorList) {
+                        for selector in complex_selectors(list) {
+                            let selector = self.interpolate(&selector, item.range, frames)?;
+                            self.extend(&selector, selectors);
+                        }
+                    }
+                }
+                TokenType::RuleList => {
+                    let mut output = Vec::new();
+                    self.call(item, selectors, frames, &mut output, nested)?;
+                    match selectors {
+                        Some(_) => declarations.extend(output),
+                        None => nested.extend(output),
+                    }
+                }
+                TokenType::DetachedRulesetCall => {
+                    let mut output = Vec::new();
+                    self.detached(item, selectors, frames, &mut output, nested)?;
+                    match selectors {
+                        Some(_) => declarations.extend(output),
+                        None => nested.extend(output),
+                    }
+                }
+                TokenType::AtRule => self.at_rule(item, selectors, frames, nested)?,
+                TokenType::Import => self.import(item, selectors, frames, declarations, nested)?,
+                // 单行注释不输出
+                TokenType::Comment if item.raw.starts_with("//") => {}
+                TokenType::Comment => match selectors {
+                    Some(_) => declarations.push(item.clone()),
+                    None => nested.push(item.clone()),
+                },
+                TokenType::VariableDeclaration
+                | TokenType::Semi
+                | TokenType::LeftCurlyBracket
+                | TokenType::RightCurlyBracket
+                | TokenType::CDOToken
+                | TokenType::CDCToken => {}
+                _ => nested.push(item.clone()),
+            }
+        }
+        Ok(())
+    }
+    // ANCHOR_END: body
+
+    fn rule<'a>(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        parents: Option<&[String]>,
+        frames: &[&Frame<'a>],
+        out: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), LessError> {
+        // css 守卫 .a when (@mode = dark)
+        if let Some(guard) = node.find_child(TokenType::Guard) {
+            if !self.guard(guard, frames, false)? {
+                return Ok(());
+            }
+        }
+        let mut own = Vec::new();
+        for selector in node
+            .find_child(TokenType::SelectorList)
+            .map(complex_selectors)
+            .unwrap_or_default()
+        {
+            let selector = self.interpolate(&selector, node.range, frames)?;
+            own.push(self.extend(&selector, parents));
+        }
+        let selectors = match parents {
+            Some(parents) => own
+                .iter()
+                .flat_map(|selector| join_selector(parents, selector))
+                .collect(),
+            None => own,
+        };
+        let items = node
+            .find_child(TokenType::DeclarationList)
+            .map(|list| list.child_nodes())
+            .unwrap_or_default();
+        let frame = Frame::new(items);
+        let mut frames = frames.to_vec();
+        frames.push(&frame);
+        self.rules.push(node);
+        let result = self.body(items, Some(&selectors), &frames, out);
+        self.rules.pop();
+        result
+    }
+
+    // ANCHOR: at_rule
+    // 规则中的 @media 等会冒泡到外层，内部的声明使用所在规则的选择器
+    fn at_rule<'a>(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+        frames: &[&Frame<'a>],
+        out: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), LessError> {
+        let children = node.child_nodes();
+        let mut head = Vec::new();
+        for child in children {
+            match child.node_type.0 {
+                TokenType::AtRuleParams => head.push(self.params(child, frames)?),
+                TokenType::DeclarationList | TokenType::LeftCurlyBracket => break,
+                _ => head.push(child.clone()),
+            }
+        }
+        let name = head
+            .first()
+            .map(|n| n.raw.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        if let Some(list) = node.find_child(TokenType::DeclarationList) {
+            // @font-face 等只包含声明的 at 规则
+            let items = list.child_nodes();
+            let frame = Frame::new(items);
+            let mut frames = frames.to_vec();
+            frames.push(&frame);
+            let mut declarations = Vec::new();
+            self.body(items, None, &frames, &mut declarations)?;
+            head.push(declaration_list(declarations));
+        } else if let Some(start) = children
+            .iter()
+            .position(|n| n.check_type(TokenType::LeftCurlyBracket))
+        {
+            let end = children.len() - 1;
+            let items = &children[start + 1..end.max(start + 1)];
+            let frame = Frame::new(items);
+            let mut frames = frames.to_vec();
+            frames.push(&frame);
+            // 关键帧中的选择器与外层规则无关
+            let selectors = selectors.filter(|_| !name.ends_with("keyframes"));
+            let mut inner = Vec::new();
+            self.body(items, selectors, &frames, &mut inner)?;
+            if inner.is_empty() {
+                return Ok(());
+            }
+            head.push(leaf(TokenType::LeftCurlyBracket, "{"));
+            head.extend(inner);
+            head.push(leaf(TokenType::RightCurlyBracket, "}"));
+        }
+        out.push(AstNode::from_children(TokenType::AtRule, head));
+        Ok(())
+    }
+    // ANCHOR_END: at_rule
+
+    // 参数中的变量，例如 @media @phone
+    fn params(
+        &mut self,
+        node: &AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+    ) -> Result<AstNode<TokenType>, LessError> {
+        let mut params = node.clone();
+        for child in params.children.iter_mut().flatten() {
+            if child.check_type(TokenType::AtKeywordToken) {
+                let value = self.variable(child, frames)?;
+                *child = AstNode {
+                    range: child.range,
+                    ..leaf(TokenType::IdentToken, &value_to_string(&value))
+                };
+            } else if child.check_type(TokenType::IdentToken) && child.raw.contains("@{") {
+                child.raw = self.interpolate(&child.raw, child.range, frames)?;
+            }
+        }
+        Ok(params)
+    }
+
+    fn declaration(
+        &mut self,
+        node: &AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+    ) -> Result<AstNode<TokenType>, LessError> {
+        let mut declaration = node.clone();
+        for child in declaration.children.iter_mut().flatten() {
+            if child.check_type(TokenType::Expression) {
+                *child = self.value(child, frames)?;
+            } else if child.check_type(TokenType::Property) && child.raw.contains("@{") {
+                let name = self.interpolate(&child.raw, child.range, frames)?;
+                *child = AstNode {
+                    range: child.range,
+                    ..AstNode::from_children(
+                        TokenType::Property,
+                        vec![leaf(TokenType::IdentToken, &name)],
+                    )
+                };
+            }
+        }
+        Ok(declaration)
+    }
+
+    // ANCHOR: value
+    // 替换值中的变量引用和字符串插值，计算运算和内置函数
+    fn value(
+        &mut self,
+        expr: &AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+    ) -> Result<AstNode<TokenType>, LessError> {
+        let children = self.operate(expr.child_nodes(), frames, true)?;
+        Ok(AstNode {
+            range: expr.range,
+            ..AstNode::from_children(expr.node_type.0, children)
+        })
+    }
+
+    fn substitute(
+        &mut self,
+        node: &mut AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+    ) -> Result<(), LessError> {
+        let Some(children) = &mut node.children else {
+            return Ok(());
+        };
+        let mut i = 0;
+        while i < children.len() {
+            let child = &mut children[i];
+            if child.check_type(TokenType::Term) {
+                if let Some(variable) = child.find_child(TokenType::Variable) {
+                    let value = self.variable(variable, frames)?;
+                    let value = value.children.unwrap_or_default();
+                    let len = value.len();
+                    children.splice(i..i + 1, value);
+                    i += len;
+                    continue;
+                }
+            }
+            if child.check_type(TokenType::Str) || child.check_type(TokenType::UrlToken) {
+                child.raw = self.interpolate(&child.raw, child.range, frames)?;
+            } else {
+                self.substitute(child, frames)?;
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+    // ANCHOR_END: value
+
+    // ANCHOR: variable
+    // @name 或 @@name，后者先求出 @name 的值再把它作为变量名
+    fn variable(
+        &mut self,
+        node: &AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+    ) -> Result<AstNode<TokenType>, LessError> {
+        let name = match node.raw.strip_prefix("@@") {
+            Some(inner) => {
+                let value = self.lookup(&format!("@{}", inner), node.range, frames)?;
+                format!("@{}", unquote(&value_to_string(&value)))
+            }
+            None => node.raw.clone(),
+        };
+        self.lookup(&name, node.range, frames)
+    }
+
+    // 从内向外查找变量，在定义变量的作用域中求值
+    fn lookup(
+        &mut self,
+        name: &str,
+        range: Range,
+        frames: &[&Frame<'_>],
+    ) -> Result<AstNode<TokenType>, LessError> {
+        let Some(index) = frames
+            .iter()
+            .rposition(|frame| frame.variables.contains_key(name))
+        else {
+            return Err(LessError::new(
+                format!("variable {} is undefined", name),
+                range,
+            ));
+        };
+        let index = first_position(frames, index);
+        // 分离规则集只在调用时求值
+        let value = &frames[index].variables[name];
+        if ruleset(value).is_some() {
+            return Ok(value.clone());
+        }
+        if self.evaluating.iter().any(|n| n == name) {
+            return Err(LessError::new(
+                format!("recursive variable definition for {}", name),
+                range,
+            ));
+        }
+        self.evaluating.push(name.to_string());
+        let result = self.value(&frames[index].variables[name], &frames[..=index]);
+        self.evaluating.pop();
+        result
+    }
+    // ANCHOR_END: variable
+
+    // 字符串中的 @{name}，字符串类型的值插入时去掉引号
+    fn interpolate(
+        &mut self,
+        text: &str,
+        range: Range,
+        frames: &[&Frame<'_>],
+    ) -> Result<String, LessError> {
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("@{") {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            let name = format!("@{}", &rest[start + 2..start + len]);
+            let value = self.lookup(&name, range, frames)?;
+            result.push_str(&rest[..start]);
+            result.push_str(unquote(&value_to_string(&value)));
+            rest = &rest[start + len + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+}
+
+// @detached: { ... } 的值
+fn ruleset(value: &AstNode<TokenType>) -> Option<&AstNode<TokenType>> {
+    match value.child_nodes() {
+        [term] if term.check_type(TokenType::Term) => term.find_child(TokenType::DeclarationList),
+        _ => None,
+    }
+}
+
+// 调用 mixin 时定义处的作用域会重复出现在栈中，取第一次出现的位置
+fn first_position(frames: &[&Frame<'_>], index: usize) -> usize {
+    frames
+        .iter()
+        .position(|frame| std::ptr::eq(*frame, frames[index]))
+        .unwrap_or(index)
+}
+
+pub fn unquote(text: &str) -> &str {
+    let quoted = text.len() >= 2
+        && (text.starts_with('"') && text.ends_with('"')
+            || text.starts_with('\'') && text.ends_with('\''));
+    if quoted {
+        &text[1..text.len() - 1]
+    } else {
+        text
+    }
+}
+
+// ANCHOR: join_selector
+// 选择器列表中以逗号分隔的每个选择器
+pub(crate) fn complex_selectors(list: &AstNode<TokenType>) -> Vec<String> {
+    list.child_nodes()
+        .split(|n| n.check_type(TokenType::Comma))
+        .map(|group| {
+            let group = AstNode::from_children(TokenType::SelectorList, group.to_vec());
+            selector_to_string(&group).trim().to_string()
+        })
+        .collect()
+}
+
+// 把嵌套的选择器与外层选择器组合，& 代表外层选择器，没有 & 时作为后代选择器
+pub(crate) fn join_selector(parents: &[String], selector: &str) -> Vec<String> {
+    if !selector.contains('&') {
+        return parents
+            .iter()
+            .map(|parent| format!("{} {}", parent, selector))
+            .collect();
+    }
+    let mut parts = selector.split('&');
+    let mut result = vec![parts.next().unwrap_or_default().to_string()];
+    for part in parts {
+        result = result
+            .iter()
+            .flat_map(|prefix| {
+                parents
+                    .iter()
+                    .map(move |parent| format!("{}{}{}", prefix, parent, part))
+            })
+            .collect();
+    }
+    result
+}
+// ANCHOR_END: join_selector
+
+fn make_rule(selectors: &[String], declarations: Vec<AstNode<TokenType>>) -> AstNode<TokenType> {
+    let text = selectors.join(", ");
+    let selector = parse_selector(&text).unwrap_or_else(|| leaf(TokenType::SelectorList, &text));
+    AstNode::from_children(
+        TokenType::RuleList,
+        vec![selector, declaration_list(declarations)],
+    )
+}
+
+pub(crate) fn declaration_list(items: Vec<AstNode<TokenType>>) -> AstNode<TokenType> {
+    let mut children = vec![leaf(TokenType::LeftCurlyBracket, "{")];
+    for item in items {
+        let is_declaration = item.check_type(TokenType::Declaration);
+        children.push(item);
+        if is_declaration {
+            children.push(leaf(TokenType::Semi, ";"));
+        }
+    }
+    children.push(leaf(TokenType::RightCurlyBracket, "}"));
+    AstNode::from_children(TokenType::DeclarationList, children)
+}
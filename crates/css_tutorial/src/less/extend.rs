@@ -0,0 +1,298 @@
+use super::{complex_selectors, join_selector, Evaluator};
+use crate::{
+    ast::AstNode, codegen::tokens_to_string, lexer::Syntax, parser::parse_selector_with_syntax,
+    token_type::TokenType,
+};
+
+// ANCHOR: extend
+// .c:extend(.a all) 让 .c 出现在所有匹配 .a 的规则中
+pub(crate) struct Extend {
+    selector: String,
+    target: String,
+    // 带 all 时匹配选择器中的一部分，否则只匹配完全相同的选择器
+    all: bool,
+    // 所在的 @media 的查询条件，从外到内，只能扩展同一个 @media 中的规则
+    media: Vec<String>,
+}
+
+// 每个 extend 的匹配结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum ExtendMatch {
+    #[default]
+    None,
+    Matched,
+    // 匹配到了其他 @media 中的规则
+    AcrossMedia,
+}
+// ANCHOR_END: extend
+
+impl Extend {
+    // 目标选择器按 syntax 解析，scss 中可以是占位符 %name
+    pub(crate) fn new(selector: String, target: &str, all: bool, syntax: Syntax) -> Self {
+        Extend {
+            selector,
+            target: normalize(target, syntax),
+            all,
+            media: Vec::new(),
+        }
+    }
+
+    pub(crate) fn in_media(mut self, media: &[String]) -> Self {
+        self.media = media.to_vec();
+        self
+    }
+}
+
+// 选择器中是否有 :extend()，例如 &:extend(.a);
+pub(super) fn has_extend(node: &AstNode<TokenType>) -> bool {
+    node.find_child(TokenType::SelectorList)
+        .is_some_and(|list| list.raw.contains(":extend("))
+}
+
+impl Evaluator {
+    // 去掉选择器中的 :extend()，与外层选择器组合后记录下来，返回剩下的选择器
+    pub(super) fn extend(&mut self, selector: &str, parents: Option<&[String]>) -> String {
+        let (rest, targets) = split_extends(selector);
+        if targets.is_empty() {
+            return rest;
+        }
+        let extenders = match parents {
+            Some(parents) => join_selector(parents, &rest),
+            None => vec![rest.clone()],
+        };
+        for extender in extenders {
+            for (target, all) in &targets {
+                self.extends
+                    .push(Extend::new(extender.clone(), target, *all, Syntax::Css));
+            }
+        }
+        rest
+    }
+}
+
+// .c:extend(.a, .b all) 拆分为 .c 和 [(.a, false), (.b, true)]
+pub(super) fn split_extends(selector: &str) -> (String, Vec<(String, bool)>) {
+    let mut rest = String::new();
+    let mut targets = Vec::new();
+    let mut text = selector;
+    while let Some(start) = text.find(":extend(") {
+        rest.push_str(&text[..start]);
+        let inner = &text[start + ":extend(".len()..];
+        let mut depth = 0;
+        let end = inner
+            .char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' if depth == 0 => return true,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                false
+            })
+            .map_or(inner.len(), |(i, _)| i);
+        for target in inner[..end].split(',') {
+            let target = target.trim();
+            let (target, all) = match target.strip_suffix(" all") {
+                Some(target) => (target.trim_end(), true),
+                None => (target, false),
+            };
+            if !target.is_empty() {
+                targets.push((target.to_string(), all));
+            }
+        }
+        text = &inner[(end + 1).min(inner.len())..];
+    }
+    rest.push_str(text);
+    (rest.trim().to_string(), targets)
+}
+
+// 与输出时相同的格式，便于比较
+fn normalize(selector: &str, syntax: Syntax) -> String {
+    parse_selector_with_syntax(selector, syntax)
+        .and_then(|list| complex_selectors(&list).into_iter().next())
+        .unwrap_or_else(|| selector.trim().to_string())
+}
+
+// ANCHOR: apply_extends
+// 求值结束后把扩展的选择器加入匹配的规则，包括 @media 等 at 规则中的规则
+// reference 导入的内容只保留扩展出的选择器，没有被扩展的规则不输出
+// 返回每个 extend 的匹配结果
+pub(crate) fn apply_extends(
+    nodes: &mut Vec<AstNode<TokenType>>,
+    extends: &[Extend],
+    reference: bool,
+    syntax: Syntax,
+) -> Vec<ExtendMatch> {
+    let mut matches = vec![ExtendMatch::None; extends.len()];
+    let mut media = Vec::new();
+    extend_nodes(nodes, extends, reference, syntax, &mut media, &mut matches);
+    matches
+}
+
+fn extend_nodes(
+    nodes: &mut Vec<AstNode<TokenType>>,
+    extends: &[Extend],
+    reference: bool,
+    syntax: Syntax,
+    media: &mut Vec<String>,
+    matches: &mut [ExtendMatch],
+) {
+    let mut i = 0;
+    while i < nodes.len() {
+        let node = &mut nodes[i];
+        match node.node_type.0 {
+            TokenType::RuleList => {
+                let Some(list) = node.find_child(TokenType::SelectorList) else {
+                    i += 1;
+                    continue;
+                };
+                let selectors = complex_selectors(list);
+                let mut extended = extend_selectors(&selectors, extends, media, matches);
+                if reference {
+                    extended.drain(..selectors.len());
+                    if extended.is_empty() {
+                        nodes.remove(i);
+                        continue;
+                    }
+                } else if extended.len() == selectors.len() {
+                    i += 1;
+                    continue;
+                }
+                if let Some(list) = parse_selector_with_syntax(&extended.join(", "), syntax) {
+                    let mut children = node.child_nodes().to_vec();
+                    for child in children.iter_mut() {
+                        if child.check_type(TokenType::SelectorList) {
+                            *child = list;
+                            break;
+                        }
+                    }
+                    *node = AstNode::from_children(TokenType::RuleList, children);
+                }
+            }
+            // reference 导入的内容
+            TokenType::Stylesheets => {
+                let mut items = node.children.take().unwrap_or_default();
+                extend_nodes(&mut items, extends, true, syntax, media, matches);
+                let len = items.len();
+                nodes.splice(i..i + 1, items);
+                i += len;
+                continue;
+            }
+            TokenType::AtRule => {
+                let Some(children) = &mut node.children else {
+                    i += 1;
+                    continue;
+                };
+                let query = media_query(children);
+                if let Some(query) = &query {
+                    media.push(query.clone());
+                }
+                extend_nodes(children, extends, reference, syntax, media, matches);
+                if query.is_some() {
+                    media.pop();
+                }
+                // 不包含扩展出的规则的 at 规则不输出
+                let has_rules = children
+                    .iter()
+                    .any(|n| n.check_type(TokenType::RuleList) || n.check_type(TokenType::AtRule));
+                if reference && !has_rules {
+                    nodes.remove(i);
+                    continue;
+                }
+            }
+            // reference 导入的声明和注释等不输出
+            TokenType::Declaration | TokenType::Comment | TokenType::Import | TokenType::Token
+                if reference =>
+            {
+                nodes.remove(i);
+                continue;
+            }
+            _ => {
+                if let Some(children) = &mut node.children {
+                    extend_nodes(children, extends, reference, syntax, media, matches);
+                }
+            }
+        }
+        i += 1;
+    }
+}
+
+// @media 的查询条件，与 scss 求值时记录的格式相同
+pub(crate) fn media_query(children: &[AstNode<TokenType>]) -> Option<String> {
+    let name = children.first()?;
+    if !name.raw.eq_ignore_ascii_case("@media") {
+        return None;
+    }
+    let params = children
+        .iter()
+        .find(|n| n.check_type(TokenType::AtRuleParams))
+        .map(|params| tokens_to_string(params.child_nodes()))
+        .unwrap_or_default();
+    Some(params)
+}
+
+// 扩展出的选择器也会继续被扩展，但不会再被产生它的 extend 匹配
+// @media 中的 extend 不扩展其他地方的规则
+fn extend_selectors(
+    selectors: &[String],
+    extends: &[Extend],
+    media: &[String],
+    matches: &mut [ExtendMatch],
+) -> Vec<String> {
+    let mut result: Vec<(String, Option<usize>)> =
+        selectors.iter().map(|s| (s.clone(), None)).collect();
+    let mut i = 0;
+    while i < result.len() {
+        for (index, extend) in extends.iter().enumerate() {
+            if result[i].1 == Some(index) {
+                continue;
+            }
+            let Some(selector) = replace(&result[i].0, extend) else {
+                continue;
+            };
+            if !extend.media.is_empty() && extend.media != media {
+                matches[index] = ExtendMatch::AcrossMedia;
+                continue;
+            }
+            if matches[index] == ExtendMatch::None {
+                matches[index] = ExtendMatch::Matched;
+            }
+            if !result.iter().any(|(s, _)| *s == selector) {
+                result.push((selector, Some(index)));
+            }
+        }
+        i += 1;
+    }
+    result.into_iter().map(|(s, _)| s).collect()
+}
+
+fn replace(selector: &str, extend: &Extend) -> Option<String> {
+    if !extend.all {
+        return (selector == extend.target).then(|| extend.selector.clone());
+    }
+    let is_name = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    let starts_with_name = extend.target.starts_with(is_name);
+    let mut result = String::new();
+    let mut rest = selector;
+    let mut found = false;
+    while let Some(start) = rest.find(&extend.target) {
+        let end = start + extend.target.len();
+        let before = rest[..start].chars().next_back();
+        let after = rest[end..].chars().next();
+        // .a 不匹配 .ab，a 不匹配 .a
+        let boundary = !(after.is_some_and(is_name)
+            || starts_with_name && before.is_some_and(|c| is_name(c) || ".#:".contains(c)));
+        result.push_str(&rest[..start]);
+        result.push_str(if boundary {
+            &extend.selector
+        } else {
+            &extend.target
+        });
+        found = found || boundary;
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    found.then_some(result)
+}
+// ANCHOR_END: apply_extends
@@ -0,0 +1,266 @@
+use super::{
+    operation::{color_node, node_text, number_node, Operand},
+    unquote, Evaluator, Frame, LessError,
+};
+use crate::{
+    ast::AstNode,
+    codegen::value_to_string,
+    color::{hsl_to_srgb, srgb_to_hsl},
+    optimizer::leaf,
+    token_type::TokenType,
+};
+
+type Arg = Vec<AstNode<TokenType>>;
+
+impl Evaluator {
+    // ANCHOR: function
+    // 参数分别求值后调用内置函数，不认识的函数只替换参数中的变量
+    pub(super) fn function(
+        &mut self,
+        node: &AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+    ) -> Result<Vec<AstNode<TokenType>>, LessError> {
+        let name = node
+            .find_child(TokenType::FunctionToken)
+            .map(|token| token.raw.trim_end_matches('(').to_ascii_lowercase())
+            .unwrap_or_default();
+        // calc() 中的运算交给浏览器
+        let math = name != "calc";
+        let items = node
+            .find_child(TokenType::Expression)
+            .map(|expr| expr.child_nodes())
+            .unwrap_or_default();
+        let mut args = Vec::new();
+        for group in items.split(is_comma) {
+            args.push(self.operate(group, frames, math)?);
+        }
+
+        if let Some(result) = builtin(&name, &args) {
+            return result.ok_or_else(|| {
+                LessError::new(format!("error evaluating function `{}`", name), node.range)
+            });
+        }
+
+        let mut children = Vec::new();
+        let commas = items.iter().filter(|n| is_comma(n));
+        let mut args = args.into_iter();
+        if let Some(first) = args.next() {
+            children.extend(first);
+        }
+        for (comma, arg) in commas.zip(args) {
+            children.push(comma.clone());
+            children.extend(arg);
+        }
+        let mut function = node.clone();
+        for child in function.children.iter_mut().flatten() {
+            if child.check_type(TokenType::Expression) {
+                *child = AstNode::from_children(TokenType::Expression, children);
+                break;
+            }
+        }
+        Ok(vec![AstNode::from_children(
+            TokenType::Term,
+            vec![function],
+        )])
+    }
+    // ANCHOR_END: function
+}
+
+fn is_comma(node: &AstNode<TokenType>) -> bool {
+    node.check_type(TokenType::Operator) && node.raw.trim() == ","
+}
+
+// ANCHOR: builtin
+// 不是内置函数时返回 None，参数不正确时返回 Some(None)
+fn builtin(name: &str, args: &[Arg]) -> Option<Option<Vec<AstNode<TokenType>>>> {
+    let result = match name {
+        "darken" | "lighten" => adjust_lightness(name == "darken", args),
+        "fade" => (|| {
+            let [r, g, b, _] = color(args.first()?)?;
+            let (amount, _) = number(args.get(1)?)?;
+            Some(vec![color_node([r, g, b, amount / 100.0])])
+        })(),
+        "spin" => (|| {
+            let [h, s, l, a] = hsla(args.first()?)?;
+            let (amount, _) = number(args.get(1)?)?;
+            Some(vec![from_hsla([(h + amount).rem_euclid(360.0), s, l, a])])
+        })(),
+        "mix" => (|| {
+            let first = color(args.first()?)?;
+            let second = color(args.get(1)?)?;
+            let weight = match args.get(2) {
+                Some(arg) => number(arg)?.0,
+                None => 50.0,
+            };
+            Some(vec![mix(first, second, weight / 100.0)])
+        })(),
+        "percentage" => (|| {
+            let (value, _) = number(args.first()?)?;
+            Some(vec![number_node(value * 100.0, "%")])
+        })(),
+        "round" => (|| {
+            let (value, unit) = number(args.first()?)?;
+            let places = match args.get(1) {
+                Some(arg) => number(arg)?.0,
+                None => 0.0,
+            };
+            let factor = 10f64.powi(places as i32);
+            Some(vec![number_node((value * factor).round() / factor, &unit)])
+        })(),
+        "ceil" | "floor" => (|| {
+            let (value, unit) = number(args.first()?)?;
+            let value = if name == "ceil" {
+                value.ceil()
+            } else {
+                value.floor()
+            };
+            Some(vec![number_node(value, &unit)])
+        })(),
+        "e" => (|| {
+            let text = text(args.first()?);
+            Some(vec![term(TokenType::IdentToken, unquote(&text))])
+        })(),
+        "%" => format(args),
+        "extract" => (|| {
+            let (index, _) = number(args.get(1)?)?;
+            let items = list_items(args.first()?);
+            let item = items.get((index as usize).checked_sub(1)?)?;
+            Some(item.clone())
+        })(),
+        "length" => (|| {
+            let items = list_items(args.first()?);
+            Some(vec![number_node(items.len() as f64, "")])
+        })(),
+        _ => return None,
+    };
+    Some(result)
+}
+// ANCHOR_END: builtin
+
+fn text(arg: &[AstNode<TokenType>]) -> String {
+    value_to_string(&AstNode::from_children(TokenType::Expression, arg.to_vec()))
+}
+
+fn number(arg: &[AstNode<TokenType>]) -> Option<(f64, String)> {
+    match Operand::parse(&text(arg))? {
+        Operand::Number(value, unit) => Some((value, unit.to_string())),
+        Operand::Color(_) => None,
+    }
+}
+
+fn color(arg: &[AstNode<TokenType>]) -> Option<[f64; 4]> {
+    match Operand::parse(&text(arg))? {
+        Operand::Color(rgba) => Some(rgba),
+        Operand::Number(..) => None,
+    }
+}
+
+fn term(token_type: TokenType, text: &str) -> AstNode<TokenType> {
+    AstNode::from_children(TokenType::Term, vec![leaf(token_type, text)])
+}
+
+// ANCHOR: hsl
+// 色相 0..360，饱和度和亮度 0..100
+fn hsla(arg: &[AstNode<TokenType>]) -> Option<[f64; 4]> {
+    let [r, g, b, a] = color(arg)?;
+    let [h, s, l] = srgb_to_hsl([r / 255.0, g / 255.0, b / 255.0]);
+    Some([if h.is_nan() { 0.0 } else { h }, s, l, a])
+}
+
+fn from_hsla([h, s, l, a]: [f64; 4]) -> AstNode<TokenType> {
+    let [r, g, b] = hsl_to_srgb([h, s.clamp(0.0, 100.0), l.clamp(0.0, 100.0)]);
+    color_node([r * 255.0, g * 255.0, b * 255.0, a])
+}
+
+// darken(@c, 10%) 减少亮度，第三个参数为 relative 时按当前亮度的比例调整
+fn adjust_lightness(darken: bool, args: &[Arg]) -> Option<Vec<AstNode<TokenType>>> {
+    let [h, s, l, a] = hsla(args.first()?)?;
+    let (mut amount, _) = number(args.get(1)?)?;
+    if args.get(2).is_some_and(|arg| text(arg) == "relative") {
+        amount *= l / 100.0;
+    }
+    let l = if darken { l - amount } else { l + amount };
+    Some(vec![from_hsla([h, s, l, a])])
+}
+// ANCHOR_END: hsl
+
+// 与 less 相同，权重同时考虑两个颜色的不透明度
+fn mix(first: [f64; 4], second: [f64; 4], p: f64) -> AstNode<TokenType> {
+    let w = p * 2.0 - 1.0;
+    let a = first[3] - second[3];
+    let w1 = (if w * a == -1.0 {
+        w
+    } else {
+        (w + a) / (1.0 + w * a)
+    } + 1.0)
+        / 2.0;
+    let w2 = 1.0 - w1;
+    let mut rgba = [0.0; 4];
+    for i in 0..3 {
+        rgba[i] = first[i] * w1 + second[i] * w2;
+    }
+    rgba[3] = first[3] * p + second[3] * (1.0 - p);
+    color_node(rgba)
+}
+
+// ANCHOR: format
+// %("%d px", 10)，%s 插入去掉引号的字符串，大写的占位符会进行 url 编码
+fn format(args: &[Arg]) -> Option<Vec<AstNode<TokenType>>> {
+    let template = text(args.first()?);
+    let quote = template.chars().next().filter(|c| *c == '"' || *c == '\'');
+    let mut rest = unquote(&template);
+    let mut values = args[1..].iter();
+    let mut result = String::new();
+    while let Some(index) = rest.find('%') {
+        result.push_str(&rest[..index]);
+        let placeholder = rest[index + 1..].chars().next();
+        rest = &rest[index + placeholder.map_or(1, |c| 1 + c.len_utf8())..];
+        let Some(placeholder) = placeholder.filter(|c| "sSdDaA".contains(*c)) else {
+            result.push('%');
+            result.extend(placeholder);
+            continue;
+        };
+        let value = values.next().map(|arg| text(arg)).unwrap_or_default();
+        let value = if placeholder.eq_ignore_ascii_case(&'s') {
+            unquote(&value).to_string()
+        } else {
+            value
+        };
+        if placeholder.is_ascii_uppercase() {
+            result.push_str(&encode_uri_component(&value));
+        } else {
+            result.push_str(&value);
+        }
+    }
+    result.push_str(rest);
+    let text = match quote {
+        Some(quote) => format!("{}{}{}", quote, result, quote),
+        None => result,
+    };
+    Some(vec![term(TokenType::Str, &text)])
+}
+
+fn encode_uri_component(text: &str) -> String {
+    let mut result = String::new();
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&byte) {
+            result.push(byte as char);
+        } else {
+            result.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    result
+}
+// ANCHOR_END: format
+
+// 逗号分隔的列表，没有逗号时按空格分隔
+fn list_items(arg: &[AstNode<TokenType>]) -> Vec<Arg> {
+    let groups: Vec<Arg> = arg.split(is_comma).map(|group| group.to_vec()).collect();
+    if groups.len() > 1 {
+        return groups;
+    }
+    arg.iter()
+        .filter(|node| !node.check_type(TokenType::Comment) && !node_text(node).is_empty())
+        .map(|node| vec![node.clone()])
+        .collect()
+}
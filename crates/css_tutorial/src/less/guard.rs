@@ -0,0 +1,202 @@
+use std::cmp::Ordering;
+
+use super::{unquote, Evaluator, Frame, LessError};
+use crate::{
+    ast::AstNode,
+    codegen::{function_to_string, value_to_string},
+    color::is_color,
+    token_type::TokenType,
+    values::split_number,
+};
+
+impl Evaluator {
+    // ANCHOR: guard
+    // 求值 when 守卫，default 为 default() 的值
+    // and 的优先级高于 or 和逗号
+    pub(super) fn guard(
+        &mut self,
+        guard: &AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+        default: bool,
+    ) -> Result<bool, LessError> {
+        self.conditions(guard.child_nodes(), frames, default)
+    }
+
+    fn conditions(
+        &mut self,
+        items: &[AstNode<TokenType>],
+        frames: &[&Frame<'_>],
+        default: bool,
+    ) -> Result<bool, LessError> {
+        let mut any = false;
+        let mut all = true;
+        for item in items {
+            match item.node_type.0 {
+                TokenType::GuardCondition => {
+                    let value = self.condition(item, frames, default)?;
+                    all = all && value;
+                }
+                TokenType::Comma => {
+                    any = any || all;
+                    all = true;
+                }
+                TokenType::IdentToken if item.raw.eq_ignore_ascii_case("or") => {
+                    any = any || all;
+                    all = true;
+                }
+                _ => {}
+            }
+        }
+        Ok(any || all)
+    }
+
+    fn condition(
+        &mut self,
+        node: &AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+        default: bool,
+    ) -> Result<bool, LessError> {
+        let children = node.child_nodes();
+        let negate = children
+            .first()
+            .is_some_and(|n| n.check_type(TokenType::IdentToken));
+        let value = if children
+            .iter()
+            .any(|n| n.check_type(TokenType::GuardCondition))
+        {
+            self.conditions(children, frames, default)?
+        } else {
+            let mut operands = Vec::new();
+            for expr in children
+                .iter()
+                .filter(|n| n.check_type(TokenType::Expression))
+            {
+                operands.push(self.operand(expr, frames, default)?);
+            }
+            match (node.find_child(TokenType::Operator), operands.as_slice()) {
+                (Some(op), [left, right]) => compare(&op.raw, left, right),
+                (None, [value]) => value == "true",
+                _ => false,
+            }
+        };
+        Ok(value != negate)
+    }
+
+    // 守卫中的值，类型检查函数和 default() 求值为 true 或 false
+    fn operand(
+        &mut self,
+        expr: &AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+        default: bool,
+    ) -> Result<String, LessError> {
+        let value = self.value(expr, frames)?;
+        let function = match value.child_nodes() {
+            [term] => term.find_child(TokenType::Function),
+            _ => None,
+        };
+        if let Some(function) = function {
+            if let Some(result) = type_check(function, default) {
+                return Ok(result.to_string());
+            }
+        }
+        Ok(value_to_string(&value))
+    }
+    // ANCHOR_END: guard
+}
+
+// ANCHOR: compare
+// 数值按单位比较，单位不同时不相等也没有大小关系，其他值按文本比较是否相等
+fn compare(op: &str, left: &str, right: &str) -> bool {
+    let ordering = match (number(left), number(right)) {
+        (Some((a, unit_a)), Some((b, unit_b))) => {
+            if unit_a.is_empty() || unit_b.is_empty() || unit_a.eq_ignore_ascii_case(unit_b) {
+                a.partial_cmp(&b)
+            } else {
+                None
+            }
+        }
+        _ => (unquote(left) == unquote(right)).then_some(Ordering::Equal),
+    };
+    let Some(ordering) = ordering else {
+        return false;
+    };
+    match op {
+        "=" => ordering.is_eq(),
+        ">" => ordering.is_gt(),
+        "<" => ordering.is_lt(),
+        ">=" => ordering.is_ge(),
+        "<=" | "=<" => ordering.is_le(),
+        _ => false,
+    }
+}
+// ANCHOR_END: compare
+
+// 只有数值和单位，例如 10px、50%
+fn number(text: &str) -> Option<(f64, &str)> {
+    let (value, unit) = split_number(text)?;
+    unit.chars()
+        .all(|c| c.is_ascii_alphabetic() || c == '%')
+        .then_some((value, unit))
+}
+
+// ANCHOR: type_check
+// iscolor(@c) 等类型检查函数，其他函数返回 None
+fn type_check(function: &AstNode<TokenType>, default: bool) -> Option<bool> {
+    let name = function
+        .find_child(TokenType::FunctionToken)?
+        .raw
+        .trim_end_matches('(')
+        .to_ascii_lowercase();
+    let args: Vec<&[AstNode<TokenType>]> = function
+        .find_child(TokenType::Expression)
+        .map(|expr| {
+            expr.child_nodes()
+                .split(|n| n.check_type(TokenType::Operator) && n.raw.trim() == ",")
+                .collect()
+        })
+        .unwrap_or_default();
+    let text = args
+        .first()
+        .map(|arg| value_to_string(&AstNode::from_children(TokenType::Expression, arg.to_vec())))
+        .unwrap_or_default();
+    // 只有一个 token 的值
+    let single = match args.first() {
+        Some([term]) => term.child_nodes().first(),
+        _ => None,
+    };
+    let unit =
+        |expected: &str| number(&text).is_some_and(|(_, unit)| unit.eq_ignore_ascii_case(expected));
+    let result = match name.as_str() {
+        "default" => default,
+        "iscolor" => is_color(&text),
+        "isnumber" => number(&text).is_some(),
+        "isstring" => single.is_some_and(|n| n.check_type(TokenType::Str)),
+        "iskeyword" => {
+            single.is_some_and(|n| n.check_type(TokenType::IdentToken)) && !is_color(&text)
+        }
+        "isurl" => single.is_some_and(|n| {
+            n.check_type(TokenType::UrlToken)
+                || n.check_type(TokenType::Function)
+                    && function_to_string(n)
+                        .to_ascii_lowercase()
+                        .starts_with("url(")
+        }),
+        "ispixel" => unit("px"),
+        "isem" => unit("em"),
+        "ispercentage" => unit("%"),
+        "isunit" => {
+            let expected = args
+                .get(1)
+                .map(|arg| {
+                    let arg = AstNode::from_children(TokenType::Expression, arg.to_vec());
+                    unquote(&value_to_string(&arg)).to_string()
+                })
+                .unwrap_or_default();
+            unit(&expected)
+        }
+        "isruleset" => single.is_some_and(|n| n.check_type(TokenType::DeclarationList)),
+        _ => return None,
+    };
+    Some(result)
+}
+// ANCHOR_END: type_check
@@ -0,0 +1,220 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{unquote, Evaluator, Frame, LessError, LessOptions};
+use crate::{
+    ast::AstNode, lexer::Syntax, optimizer::leaf, parser::parse_stylesheet_with_syntax,
+    token_type::TokenType,
+};
+
+// ANCHOR: importer
+// 在求值之前读取 @import 的 less 文件，解析结果作为 Stylesheets 子节点放在 Import 节点中
+pub(super) struct Importer<'o> {
+    options: &'o LessOptions,
+    // once 导入时跳过已经导入过的文件
+    loaded: HashSet<PathBuf>,
+    // 当前的导入链，用于发现循环导入
+    chain: Vec<(PathBuf, String)>,
+}
+// ANCHOR_END: importer
+
+// @import (reference, optional) "a" 中的选项
+pub(super) fn import_options(node: &AstNode<TokenType>) -> Vec<String> {
+    node.child_nodes()
+        .iter()
+        .take_while(|n| !n.check_type(TokenType::Str) && !n.check_type(TokenType::UrlToken))
+        .filter(|n| n.check_type(TokenType::IdentToken))
+        .map(|n| n.raw.to_ascii_lowercase())
+        .collect()
+}
+
+impl<'o> Importer<'o> {
+    pub(super) fn new(options: &'o LessOptions) -> Self {
+        let mut chain = Vec::new();
+        if let Some(filename) = &options.filename {
+            let path = fs::canonicalize(filename).unwrap_or_else(|_| filename.clone());
+            chain.push((path, filename.display().to_string()));
+        }
+        Importer {
+            options,
+            loaded: chain.iter().map(|(path, _)| path.clone()).collect(),
+            chain,
+        }
+    }
+
+    // 当前文件所在的目录，没有文件名时为当前目录
+    fn dir(&self) -> PathBuf {
+        self.chain
+            .last()
+            .and_then(|(path, _)| path.parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    }
+
+    // ANCHOR: load
+    pub(super) fn load(&mut self, node: &mut AstNode<TokenType>) -> Result<(), LessError> {
+        let Some(children) = &mut node.children else {
+            return Ok(());
+        };
+        for child in children.iter_mut() {
+            if child.check_type(TokenType::Import) {
+                self.import(child)?;
+            } else {
+                self.load(child)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn import(&mut self, node: &mut AstNode<TokenType>) -> Result<(), LessError> {
+        let options = import_options(node);
+        let has = |name: &str| options.iter().any(|option| option == name);
+        // url() 和 .css 文件保留为 css 的 @import
+        let Some(name) = node
+            .find_child(TokenType::Str)
+            .map(|n| unquote(&n.raw).to_string())
+        else {
+            return Ok(());
+        };
+        let is_css = has("css")
+            || (name.ends_with(".css") || node.find_child(TokenType::MediumList).is_some())
+                && !has("less")
+                && !has("inline");
+        if is_css {
+            return Ok(());
+        }
+        let file = if Path::new(&name).extension().is_some() || has("inline") {
+            name.clone()
+        } else {
+            format!("{}.less", name)
+        };
+
+        let mut tried = Vec::new();
+        let mut found = None;
+        for dir in std::iter::once(self.dir()).chain(self.options.include_paths.iter().cloned()) {
+            let path = dir.join(&file);
+            if path.is_file() {
+                found = Some(path);
+                break;
+            }
+            tried.push(path.display().to_string());
+        }
+        let Some(path) = found else {
+            if has("optional") {
+                push_content(node, Vec::new());
+                return Ok(());
+            }
+            return Err(LessError::new(
+                format!("'{}' wasn't found. Tried - {}", file, tried.join(", ")),
+                node.range,
+            ));
+        };
+        let path = fs::canonicalize(&path).unwrap_or(path);
+
+        if self.chain.iter().any(|(p, _)| *p == path) {
+            let chain: Vec<&str> = self
+                .chain
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .chain([file.as_str()])
+                .collect();
+            return Err(LessError::new(
+                format!("circular import: {}", chain.join(" -> ")),
+                node.range,
+            ));
+        }
+        if !has("multiple") && !self.loaded.insert(path.clone()) {
+            push_content(node, Vec::new());
+            return Ok(());
+        }
+
+        let source = fs::read_to_string(&path).map_err(|error| {
+            LessError::new(format!("failed to read '{}': {}", file, error), node.range)
+        })?;
+        if has("inline") {
+            push_content(node, vec![leaf(TokenType::Token, source.trim_end())]);
+            return Ok(());
+        }
+        let tree = parse_stylesheet_with_syntax(&source, Syntax::Less);
+        let Some(mut root) = tree.root else {
+            push_content(node, Vec::new());
+            return Ok(());
+        };
+        self.chain.push((path, file));
+        let result = self.load(&mut root);
+        self.chain.pop();
+        result?;
+        push_content(node, root.children.unwrap_or_default());
+        Ok(())
+    }
+    // ANCHOR_END: load
+}
+
+fn push_content(node: &mut AstNode<TokenType>, items: Vec<AstNode<TokenType>>) {
+    let content = AstNode {
+        range: node.range,
+        ..AstNode::from_children(TokenType::Stylesheets, items)
+    };
+    node.children.get_or_insert_with(Vec::new).push(content);
+}
+
+// 导入文件的内容，css 导入没有内容
+pub(super) fn import_content(node: &AstNode<TokenType>) -> Option<&AstNode<TokenType>> {
+    node.find_child(TokenType::Stylesheets)
+}
+
+impl Evaluator {
+    // ANCHOR: import
+    // reference 导入的内容放在单独的 Stylesheets 中，只输出被 :extend() 扩展的规则
+    pub(super) fn import<'a>(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+        frames: &[&Frame<'a>],
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), LessError> {
+        let Some(content) = import_content(node) else {
+            // 去掉选项后作为 css 的 @import 输出
+            let children = node.child_nodes();
+            let start = children
+                .iter()
+                .position(|n| n.check_type(TokenType::Str) || n.check_type(TokenType::UrlToken))
+                .unwrap_or(children.len());
+            let mut children = children[..start.min(1)].to_vec();
+            children.extend_from_slice(&node.child_nodes()[start..]);
+            nested.push(AstNode::from_children(TokenType::Import, children));
+            return Ok(());
+        };
+        if !import_options(node)
+            .iter()
+            .any(|option| option == "reference")
+        {
+            return self.items(
+                content.child_nodes(),
+                selectors,
+                frames,
+                declarations,
+                nested,
+            );
+        }
+        let mut hidden = Vec::new();
+        let mut hidden_nested = Vec::new();
+        self.items(
+            content.child_nodes(),
+            selectors,
+            frames,
+            &mut hidden,
+            &mut hidden_nested,
+        )?;
+        nested.push(AstNode::from_children(
+            TokenType::Stylesheets,
+            hidden_nested,
+        ));
+        Ok(())
+    }
+    // ANCHOR_END: import
+}
@@ -0,0 +1,464 @@
+use std::collections::HashMap;
+
+use super::{first_position, ruleset, Evaluator, Frame, LessError};
+use crate::{
+    ast::AstNode,
+    codegen::{selector_to_string, value_to_string},
+    optimizer::leaf,
+    token_type::TokenType,
+};
+
+// mixin 互相调用的最大深度
+const MAX_DEPTH: usize = 256;
+
+// ANCHOR: param
+// mixin 定义中的参数 .m(@a; @b: 2; dark; @rest...)
+enum Param {
+    Variable {
+        name: String,
+        default: Option<AstNode<TokenType>>,
+    },
+    // 只有传入相同的值时才匹配
+    Value(String),
+    Rest(Option<String>),
+}
+
+// 调用时传入的参数，已经在调用处求值
+struct Arg {
+    name: Option<String>,
+    value: AstNode<TokenType>,
+}
+// ANCHOR_END: param
+
+// 选择器中带有参数列表的规则是 mixin 定义，不会输出
+pub(super) fn is_mixin_definition(node: &AstNode<TokenType>) -> bool {
+    node.find_child(TokenType::DeclarationList).is_some()
+        && node
+            .find_child(TokenType::SelectorList)
+            .is_some_and(|list| selector_function(list).is_some())
+}
+
+impl Evaluator {
+    // ANCHOR: call
+    // 展开 mixin 调用，声明放入 declarations，嵌套的规则放入 nested
+    // 所有匹配的定义都会展开
+    pub(super) fn call<'a>(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+        frames: &[&Frame<'a>],
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), LessError> {
+        let Some(list) = node.find_child(TokenType::SelectorList) else {
+            return Ok(());
+        };
+        let text = selector_to_string(list);
+        let Some(path) = rule_paths(list).into_iter().next() else {
+            return Err(LessError::new(
+                format!("`{}` is not a valid mixin call", text),
+                node.range,
+            ));
+        };
+        let candidates = find_mixins(&path, frames);
+        if candidates.is_empty() {
+            return Err(LessError::new(
+                format!("{} is undefined", path.join(" > ")),
+                node.range,
+            ));
+        }
+        if self.rules.len() >= MAX_DEPTH {
+            return Err(LessError::new(
+                format!("too many nested mixin calls at `{}`", text),
+                node.range,
+            ));
+        }
+        let args = match selector_function(list) {
+            Some(function) => self.args(function, frames)?,
+            None => Vec::new(),
+        };
+
+        // default() 为真时才匹配的定义单独展开，没有其他定义匹配时才使用
+        let mut output = Vec::new();
+        let mut fallback = (Vec::new(), Vec::new());
+        let mut found = false;
+        let mut matched = false;
+        for (index, namespaces, rule) in candidates {
+            // 普通规则不能在自身中调用自身
+            let definition = rule
+                .find_child(TokenType::SelectorList)
+                .and_then(selector_function);
+            if definition.is_none() && self.rules.contains(&(rule as *const _)) {
+                continue;
+            }
+            let params = definition.map(params).unwrap_or_default();
+            let Some(variables) = bind(&params, &args) else {
+                continue;
+            };
+            matched = true;
+
+            let items = rule
+                .find_child(TokenType::DeclarationList)
+                .map(|list| list.child_nodes())
+                .unwrap_or_default();
+            // 先查找 mixin 自身的作用域，再查找定义处的作用域，最后是调用处
+            let namespaces: Vec<Frame> = namespaces
+                .iter()
+                .map(|ns| {
+                    Frame::new(
+                        ns.find_child(TokenType::DeclarationList)
+                            .map(|list| list.child_nodes())
+                            .unwrap_or_default(),
+                    )
+                })
+                .collect();
+            let arguments = Frame {
+                variables,
+                rules: Vec::new(),
+            };
+            let body = Frame::new(items);
+            let mut scope = frames.to_vec();
+            scope.extend_from_slice(&frames[..=index]);
+            scope.extend(namespaces.iter());
+            scope.push(&arguments);
+            let (declarations, nested) = match rule.find_child(TokenType::Guard) {
+                Some(guard) if !self.guard(guard, &scope, false)? => {
+                    if !self.guard(guard, &scope, true)? {
+                        continue;
+                    }
+                    (&mut fallback.0, &mut fallback.1)
+                }
+                _ => {
+                    found = true;
+                    (&mut output, &mut *nested)
+                }
+            };
+            scope.push(&body);
+
+            self.rules.push(rule);
+            let result = self.items(items, selectors, &scope, declarations, nested);
+            self.rules.pop();
+            result?;
+        }
+        if !matched {
+            return Err(LessError::new(
+                format!("No matching definition was found for `{}`", text),
+                node.range,
+            ));
+        }
+        if !found {
+            output = fallback.0;
+            nested.extend(fallback.1);
+        }
+
+        if node.find_child(TokenType::Important).is_some() {
+            for item in output.iter_mut() {
+                if item.check_type(TokenType::Declaration)
+                    && item.find_child(TokenType::Important).is_none()
+                {
+                    if let Some(children) = &mut item.children {
+                        children.push(leaf(TokenType::Important, "!important"));
+                    }
+                }
+            }
+        }
+        declarations.extend(output);
+        Ok(())
+    }
+    // ANCHOR_END: call
+
+    // ANCHOR: detached
+    // @detached(); 展开分离规则集，定义处的变量优先于调用处
+    pub(super) fn detached<'a>(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+        frames: &[&Frame<'a>],
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), LessError> {
+        let name = node
+            .find_child(TokenType::Variable)
+            .map(|n| n.raw.as_str())
+            .unwrap_or_default();
+        let Some(index) = frames
+            .iter()
+            .rposition(|frame| frame.variables.contains_key(name))
+        else {
+            return Err(LessError::new(
+                format!("variable {} is undefined", name),
+                node.range,
+            ));
+        };
+        let index = first_position(frames, index);
+        let Some(list) = ruleset(&frames[index].variables[name]) else {
+            return Err(LessError::new(
+                format!("{} is not a detached ruleset", name),
+                node.range,
+            ));
+        };
+        if self.rules.len() >= MAX_DEPTH {
+            return Err(LessError::new(
+                format!("too many nested mixin calls at `{}()`", name),
+                node.range,
+            ));
+        }
+        let items = list.child_nodes();
+        let body = Frame::new(items);
+        let mut scope = frames.to_vec();
+        scope.extend_from_slice(&frames[..=index]);
+        scope.push(&body);
+        self.rules.push(node);
+        let result = self.items(items, selectors, &scope, declarations, nested);
+        self.rules.pop();
+        result
+    }
+    // ANCHOR_END: detached
+
+    fn args(
+        &mut self,
+        function: &AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+    ) -> Result<Vec<Arg>, LessError> {
+        let mut args = Vec::new();
+        for child in function.child_nodes() {
+            match child.node_type.0 {
+                TokenType::Expression => args.push(Arg {
+                    name: None,
+                    value: self.value(child, frames)?,
+                }),
+                TokenType::VariableDeclaration => {
+                    let (Some(name), Some(expr)) = (
+                        child.find_child(TokenType::Variable),
+                        child.find_child(TokenType::Expression),
+                    ) else {
+                        continue;
+                    };
+                    args.push(Arg {
+                        name: Some(name.raw.clone()),
+                        value: self.value(expr, frames)?,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(args)
+    }
+}
+
+// ANCHOR: find_mixins
+// 从内向外查找 mixin 定义，在最近一个有匹配的作用域中返回所有匹配的定义
+// 结果中包含定义所在作用域的下标和外层的命名空间
+#[allow(clippy::type_complexity)]
+fn find_mixins<'a>(
+    path: &[String],
+    frames: &[&Frame<'a>],
+) -> Vec<(usize, Vec<&'a AstNode<TokenType>>, &'a AstNode<TokenType>)> {
+    for (index, frame) in frames.iter().enumerate().rev() {
+        let mut found = Vec::new();
+        for rule in &frame.rules {
+            search(rule, path, &mut Vec::new(), &mut found);
+        }
+        if !found.is_empty() {
+            let index = first_position(frames, index);
+            return found
+                .into_iter()
+                .map(|(namespaces, rule)| (index, namespaces, rule))
+                .collect();
+        }
+    }
+    Vec::new()
+}
+
+// #ns > .m() 先匹配 #ns，再在 #ns 的规则中查找 .m
+#[allow(clippy::type_complexity)]
+fn search<'a>(
+    rule: &'a AstNode<TokenType>,
+    path: &[String],
+    namespaces: &mut Vec<&'a AstNode<TokenType>>,
+    found: &mut Vec<(Vec<&'a AstNode<TokenType>>, &'a AstNode<TokenType>)>,
+) {
+    let Some(list) = rule.find_child(TokenType::SelectorList) else {
+        return;
+    };
+    for own in rule_paths(list) {
+        if !path.starts_with(&own) {
+            continue;
+        }
+        if own.len() == path.len() {
+            found.push((namespaces.clone(), rule));
+            continue;
+        }
+        let items = rule
+            .find_child(TokenType::DeclarationList)
+            .map(|list| list.child_nodes())
+            .unwrap_or_default();
+        namespaces.push(rule);
+        for item in items {
+            if item.check_type(TokenType::RuleList)
+                && item.find_child(TokenType::DeclarationList).is_some()
+            {
+                search(item, &path[own.len()..], namespaces, found);
+            }
+        }
+        namespaces.pop();
+    }
+}
+// ANCHOR_END: find_mixins
+
+// ANCHOR: rule_paths
+// 选择器列表中可以作为 mixin 调用的选择器，只能由类名、id 和 > 组成
+// 例如 #ns > .m() 和 #ns.m 都是 ["#ns", ".m"]
+pub(super) fn rule_paths(list: &AstNode<TokenType>) -> Vec<Vec<String>> {
+    list.child_nodes()
+        .split(|n| n.check_type(TokenType::Comma))
+        .filter_map(|group| {
+            let mut path = Vec::new();
+            group
+                .iter()
+                .all(|node| collect_path(node, &mut path))
+                .then_some(path)
+        })
+        .filter(|path| !path.is_empty())
+        .collect()
+}
+
+fn collect_path(node: &AstNode<TokenType>, path: &mut Vec<String>) -> bool {
+    match node.node_type.0 {
+        TokenType::Selector | TokenType::SimpleSelect => node
+            .child_nodes()
+            .iter()
+            .all(|child| collect_path(child, path)),
+        TokenType::Class | TokenType::HashToken => {
+            path.push(node.raw.clone());
+            true
+        }
+        TokenType::SelectorFunction => {
+            let name = node
+                .find_child(TokenType::FunctionToken)
+                .map(|f| f.raw.trim_end_matches('('))
+                .unwrap_or_default();
+            path.push(format!(".{}", name));
+            true
+        }
+        TokenType::MoreThan | TokenType::Comment => true,
+        _ => false,
+    }
+}
+// ANCHOR_END: rule_paths
+
+pub(super) fn selector_function(node: &AstNode<TokenType>) -> Option<&AstNode<TokenType>> {
+    if node.check_type(TokenType::SelectorFunction) {
+        return Some(node);
+    }
+    node.child_nodes().iter().find_map(selector_function)
+}
+
+fn params(function: &AstNode<TokenType>) -> Vec<Param> {
+    let mut params = Vec::new();
+    for child in function.child_nodes() {
+        match child.node_type.0 {
+            TokenType::VariableDeclaration => {
+                if let Some(name) = child.find_child(TokenType::Variable) {
+                    params.push(Param::Variable {
+                        name: name.raw.clone(),
+                        default: child.find_child(TokenType::Expression).cloned(),
+                    });
+                }
+            }
+            TokenType::Variable => params.push(Param::Rest(
+                child
+                    .find_child(TokenType::AtKeywordToken)
+                    .map(|name| name.raw.clone()),
+            )),
+            TokenType::Ellipsis => params.push(Param::Rest(None)),
+            TokenType::Expression => match single_variable(child) {
+                Some(name) => params.push(Param::Variable {
+                    name,
+                    default: None,
+                }),
+                None => params.push(Param::Value(value_to_string(child))),
+            },
+            _ => {}
+        }
+    }
+    params
+}
+
+// 只包含一个变量的参数，例如 .m(@a) 中的 @a
+pub(super) fn single_variable(expr: &AstNode<TokenType>) -> Option<String> {
+    match expr.child_nodes() {
+        [term] => term.find_child(TokenType::Variable).map(|v| v.raw.clone()),
+        _ => None,
+    }
+}
+
+// ANCHOR: bind
+// 先按名字绑定，剩下的参数按位置绑定，无法匹配时返回 None
+// 默认值和 @arguments 在 mixin 的作用域中惰性求值
+fn bind(params: &[Param], args: &[Arg]) -> Option<HashMap<String, AstNode<TokenType>>> {
+    let mut variables = HashMap::new();
+    for arg in args {
+        let Some(name) = &arg.name else {
+            continue;
+        };
+        let known = params
+            .iter()
+            .any(|param| matches!(param, Param::Variable { name: n, .. } if n == name));
+        if !known {
+            return None;
+        }
+        variables.insert(name.clone(), arg.value.clone());
+    }
+
+    let mut positional = args.iter().filter(|arg| arg.name.is_none());
+    let mut arguments = Vec::new();
+    for param in params {
+        match param {
+            Param::Variable { name, default } => {
+                if !variables.contains_key(name) {
+                    let value = match positional.next() {
+                        Some(arg) => arg.value.clone(),
+                        None => default.clone()?,
+                    };
+                    variables.insert(name.clone(), value);
+                }
+                arguments.push(variable_term(name));
+            }
+            Param::Value(pattern) => {
+                let arg = positional.next()?;
+                if value_to_string(&arg.value) != *pattern {
+                    return None;
+                }
+                arguments.push(arg.value.clone());
+            }
+            Param::Rest(name) => {
+                let rest: Vec<_> = positional.by_ref().map(|arg| arg.value.clone()).collect();
+                arguments.extend(rest.iter().cloned());
+                if let Some(name) = name {
+                    variables.insert(
+                        name.clone(),
+                        AstNode::from_children(TokenType::Expression, rest),
+                    );
+                }
+            }
+        }
+    }
+    if positional.next().is_some() {
+        return None;
+    }
+    variables.insert(
+        "@arguments".to_string(),
+        AstNode::from_children(TokenType::Expression, arguments),
+    );
+    Some(variables)
+}
+// ANCHOR_END: bind
+
+fn variable_term(name: &str) -> AstNode<TokenType> {
+    let variable = AstNode::from_children(
+        TokenType::Variable,
+        vec![leaf(TokenType::AtKeywordToken, name)],
+    );
+    AstNode::from_children(TokenType::Term, vec![variable])
+}
@@ -0,0 +1,352 @@
+use super::{Evaluator, Frame, LessError};
+use crate::{
+    ast::AstNode,
+    codegen::{function_to_string, term_to_string},
+    color::Color,
+    optimizer::leaf,
+    token_type::TokenType,
+    values::{convert, format_number, split_number},
+};
+
+// ANCHOR: expr
+// 值中的运算表达式，运算符之外的节点原样作为操作数
+enum Expr<'n> {
+    Node(&'n AstNode<TokenType>),
+    // -@a
+    Negate(&'n AstNode<TokenType>, Box<Expr<'n>>),
+    Binary(char, &'n AstNode<TokenType>, Box<Expr<'n>>, Box<Expr<'n>>),
+    // 括号中的内容，除法只在括号中计算
+    Paren(
+        &'n AstNode<TokenType>,
+        Vec<Expr<'n>>,
+        Option<&'n AstNode<TokenType>>,
+    ),
+}
+// ANCHOR_END: expr
+
+// ANCHOR: operand
+// 可以参与运算的值
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Operand<'a> {
+    Number(f64, &'a str),
+    // 0..255 的 rgb 分量和 0..1 的不透明度
+    Color([f64; 4]),
+}
+
+impl<'a> Operand<'a> {
+    pub(super) fn parse(text: &'a str) -> Option<Self> {
+        if let Some((value, unit)) = split_number(text) {
+            let valid = unit.chars().all(|c| c.is_ascii_alphabetic() || c == '%');
+            return valid.then_some(Operand::Number(value, unit));
+        }
+        let color = Color::parse(text)?;
+        let [r, g, b] = color.to_srgb().components.map(|c| c * 255.0);
+        Some(Operand::Color([r, g, b, color.alpha]))
+    }
+}
+// ANCHOR_END: operand
+
+// 按 less 的格式输出数值和颜色，不透明的颜色输出 #rrggbb
+pub(super) fn number_node(value: f64, unit: &str) -> AstNode<TokenType> {
+    let text = format!("{}{}", format_number(value), unit);
+    let token_type = match unit {
+        "" => TokenType::Digital,
+        "%" => TokenType::PercentageToken,
+        _ => TokenType::Dimension,
+    };
+    AstNode::from_children(TokenType::Term, vec![leaf(token_type, &text)])
+}
+
+pub(super) fn color_node([r, g, b, a]: [f64; 4]) -> AstNode<TokenType> {
+    let byte = |c: f64| c.clamp(0.0, 255.0).round() as u8;
+    let alpha = a.clamp(0.0, 1.0);
+    let text = if alpha == 1.0 {
+        format!("#{:02x}{:02x}{:02x}", byte(r), byte(g), byte(b))
+    } else {
+        format!(
+            "rgba({}, {}, {}, {})",
+            byte(r),
+            byte(g),
+            byte(b),
+            format_number(alpha)
+        )
+    };
+    let token_type = if alpha == 1.0 {
+        TokenType::HashToken
+    } else {
+        TokenType::IdentToken
+    };
+    AstNode::from_children(TokenType::Term, vec![leaf(token_type, &text)])
+}
+
+pub(super) fn node_text(node: &AstNode<TokenType>) -> String {
+    match node.node_type.0 {
+        TokenType::Term => term_to_string(node),
+        TokenType::Function => function_to_string(node),
+        _ => node.raw.clone(),
+    }
+}
+
+impl Evaluator {
+    // ANCHOR: operate
+    // 替换变量、调用内置函数并计算运算，math 为 false 时只替换变量，例如 calc() 中
+    pub(super) fn operate(
+        &mut self,
+        items: &[AstNode<TokenType>],
+        frames: &[&Frame<'_>],
+        math: bool,
+    ) -> Result<Vec<AstNode<TokenType>>, LessError> {
+        let mut parser = ExprParser { items, pos: 0 };
+        let mut result = Vec::new();
+        for expr in parser.list(false) {
+            result.extend(self.eval_expr(&expr, frames, math)?);
+        }
+        Ok(result)
+    }
+    // ANCHOR_END: operate
+
+    fn eval_expr(
+        &mut self,
+        expr: &Expr<'_>,
+        frames: &[&Frame<'_>],
+        math: bool,
+    ) -> Result<Vec<AstNode<TokenType>>, LessError> {
+        match expr {
+            Expr::Node(node) => self.term(node, frames),
+            Expr::Negate(minus, inner) => {
+                let value = self.eval_expr(inner, frames, math)?;
+                if let ([single], true) = (value.as_slice(), math) {
+                    let text = node_text(single);
+                    if let Some(Operand::Number(n, unit)) = Operand::parse(&text) {
+                        return Ok(vec![number_node(-n, unit)]);
+                    }
+                }
+                let mut result = vec![(*minus).clone()];
+                result.extend(value);
+                Ok(result)
+            }
+            Expr::Binary(op, node, left, right) => {
+                let left = self.eval_expr(left, frames, math)?;
+                let right = self.eval_expr(right, frames, math)?;
+                if let ([a], [b], true) = (left.as_slice(), right.as_slice(), math) {
+                    let (a, b) = (node_text(a), node_text(b));
+                    if let (Some(a), Some(b)) = (Operand::parse(&a), Operand::parse(&b)) {
+                        if let Some(result) = calculate(*op, a, b) {
+                            return Ok(vec![result]);
+                        }
+                    }
+                }
+                let mut result = left;
+                result.push((*node).clone());
+                result.extend(right);
+                Ok(result)
+            }
+            Expr::Paren(open, inner, close) => {
+                let mut value = Vec::new();
+                for expr in inner {
+                    value.extend(self.eval_expr(expr, frames, math)?);
+                }
+                // 括号中只剩一个数值或颜色时去掉括号
+                if let ([single], true) = (value.as_slice(), math) {
+                    if Operand::parse(&node_text(single)).is_some() {
+                        return Ok(value);
+                    }
+                }
+                let mut result = vec![(*open).clone()];
+                result.extend(value);
+                result.extend(close.cloned());
+                Ok(result)
+            }
+        }
+    }
+
+    fn term(
+        &mut self,
+        node: &AstNode<TokenType>,
+        frames: &[&Frame<'_>],
+    ) -> Result<Vec<AstNode<TokenType>>, LessError> {
+        if node.check_type(TokenType::Term) {
+            if let Some(variable) = node.find_child(TokenType::Variable) {
+                return Ok(self
+                    .variable(variable, frames)?
+                    .children
+                    .unwrap_or_default());
+            }
+            if let Some(function) = node.find_child(TokenType::Function) {
+                return self.function(function, frames);
+            }
+            // ~"..." 输出字符串的内容
+            if let Some(text) = node.find_child(TokenType::Str) {
+                let escaped = text.raw.strip_prefix('~');
+                if let Some(raw) =
+                    escaped.or(node.find_child(TokenType::Wave).map(|_| text.raw.as_str()))
+                {
+                    let text = self.interpolate(raw, text.range, frames)?;
+                    let text = super::unquote(&text);
+                    return Ok(vec![AstNode::from_children(
+                        TokenType::Term,
+                        vec![leaf(TokenType::IdentToken, text)],
+                    )]);
+                }
+            }
+        }
+        let mut node = node.clone();
+        self.substitute(&mut node, frames)?;
+        Ok(vec![node])
+    }
+}
+
+// ANCHOR: calculate
+// 单位不同时把右侧换算成左侧的单位，无法换算时直接使用数值
+// 颜色与数值运算时数值作用于每个分量
+fn calculate(op: char, a: Operand<'_>, b: Operand<'_>) -> Option<AstNode<TokenType>> {
+    let apply = |x: f64, y: f64| match op {
+        '+' => Some(x + y),
+        '-' => Some(x - y),
+        '*' => Some(x * y),
+        '/' if y != 0.0 => Some(x / y),
+        _ => None,
+    };
+    match (a, b) {
+        (Operand::Number(x, unit_a), Operand::Number(y, unit_b)) => {
+            let unit = if unit_a.is_empty() { unit_b } else { unit_a };
+            let y = if unit_a.is_empty() || unit_b.is_empty() {
+                y
+            } else {
+                convert(y, unit_b, unit_a).unwrap_or(y)
+            };
+            Some(number_node(apply(x, y)?, unit))
+        }
+        (Operand::Color(x), Operand::Color(y)) => {
+            let mut rgba = [0.0; 4];
+            for i in 0..3 {
+                rgba[i] = apply(x[i], y[i])?;
+            }
+            rgba[3] = x[3] * (1.0 - y[3]) + y[3];
+            Some(color_node(rgba))
+        }
+        (Operand::Color(c), Operand::Number(n, _)) | (Operand::Number(n, _), Operand::Color(c)) => {
+            let mut rgba = c;
+            for channel in rgba.iter_mut().take(3) {
+                *channel = apply(*channel, n)?;
+            }
+            Some(color_node(rgba))
+        }
+    }
+}
+// ANCHOR_END: calculate
+
+// ANCHOR: expr_parser
+// 优先级爬升解析运算，空格分隔的值作为列表中的多个表达式
+// 减号前有空格后面没有空格时是负号，例如 @a -@b 是两个值
+struct ExprParser<'n> {
+    items: &'n [AstNode<TokenType>],
+    pos: usize,
+}
+
+impl<'n> ExprParser<'n> {
+    fn list(&mut self, in_paren: bool) -> Vec<Expr<'n>> {
+        let mut list = Vec::new();
+        while let Some(item) = self.items.get(self.pos) {
+            if in_paren && item.check_type(TokenType::RightParenthesis) {
+                break;
+            }
+            if self.is_operand() {
+                list.push(self.expr(0, in_paren));
+            } else {
+                list.push(Expr::Node(item));
+                self.pos += 1;
+            }
+        }
+        list
+    }
+
+    fn expr(&mut self, min_prec: u8, in_paren: bool) -> Expr<'n> {
+        let mut left = self.unary();
+        while let Some((op, node)) = self.binary_op(in_paren) {
+            let prec = if matches!(op, '*' | '/') { 2 } else { 1 };
+            if prec < min_prec {
+                break;
+            }
+            self.pos += 1;
+            let right = self.expr(prec + 1, in_paren);
+            left = Expr::Binary(op, node, Box::new(left), Box::new(right));
+        }
+        left
+    }
+
+    fn unary(&mut self) -> Expr<'n> {
+        let item = &self.items[self.pos];
+        self.pos += 1;
+        if op_char(item) == Some('-') && self.items.get(self.pos).is_some() {
+            return Expr::Negate(item, Box::new(self.unary()));
+        }
+        if item.check_type(TokenType::LeftParenthesis) {
+            let inner = self.list(true);
+            let close = self
+                .items
+                .get(self.pos)
+                .filter(|n| n.check_type(TokenType::RightParenthesis));
+            if close.is_some() {
+                self.pos += 1;
+            }
+            return Expr::Paren(item, inner, close);
+        }
+        Expr::Node(item)
+    }
+
+    // 后面还有操作数的二元运算符
+    fn binary_op(&self, in_paren: bool) -> Option<(char, &'n AstNode<TokenType>)> {
+        let node = self.items.get(self.pos)?;
+        let op = op_char(node)?;
+        if op == '/' && !in_paren {
+            return None;
+        }
+        if op == '-' && self.is_negation() {
+            return None;
+        }
+        let next = self.items.get(self.pos + 1)?;
+        (op_char(next).is_none() || op_char(next) == Some('-')).then_some((op, node))
+    }
+
+    fn is_operand(&self) -> bool {
+        let Some(item) = self.items.get(self.pos) else {
+            return false;
+        };
+        match op_char(item) {
+            Some('-') => self.is_negation(),
+            Some(_) => false,
+            None => item.check_type(TokenType::Term) || item.check_type(TokenType::LeftParenthesis),
+        }
+    }
+
+    // 减号紧贴着后面的值
+    fn is_negation(&self) -> bool {
+        let minus = &self.items[self.pos];
+        let Some(next) = self.items.get(self.pos + 1) else {
+            return false;
+        };
+        let before = self.pos == 0 || {
+            let prev = &self.items[self.pos - 1];
+            prev.range.end_pos < minus.range.start_pos || op_char(prev).is_some()
+        };
+        before && next.range.start_pos == minus.range.end_pos && minus.range.end_pos > 0
+    }
+}
+
+fn op_char(node: &AstNode<TokenType>) -> Option<char> {
+    if !node.check_type(TokenType::Term) {
+        return None;
+    }
+    match node.child_nodes() {
+        [token] => match token.node_type.0 {
+            TokenType::Plus => Some('+'),
+            TokenType::Minus => Some('-'),
+            TokenType::Asterisk => Some('*'),
+            TokenType::ForwardSlash => Some('/'),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+// ANCHOR_END: expr_parser
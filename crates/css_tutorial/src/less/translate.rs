@@ -0,0 +1,794 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use super::{
+    complex_selectors,
+    extend::{has_extend, split_extends},
+    mixin::{is_mixin_definition, rule_paths, selector_function, single_variable},
+};
+use crate::{
+    ast::{AstNode, AstTree},
+    codegen::{selector_to_string, tokens_to_string, value_to_string},
+    optimizer::leaf,
+    range::Range,
+    token_type::TokenType,
+};
+
+// less 中有而 scss 中没有对应写法的函数
+const LESS_ONLY_FUNCTIONS: [&str; 14] = [
+    "contrast",
+    "luma",
+    "luminance",
+    "escape",
+    "replace",
+    "data-uri",
+    "svg-gradient",
+    "range",
+    "each",
+    "image-size",
+    "image-width",
+    "image-height",
+    "fadein",
+    "fadeout",
+];
+
+// ANCHOR: translation
+#[derive(Debug, Clone, Default)]
+pub struct ScssTranslation {
+    pub code: String,
+    // 无法直接翻译的写法，需要手动修改
+    pub warnings: Vec<TranslateWarning>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TranslateWarning {
+    pub message: String,
+    pub range: Range,
+}
+// ANCHOR_END: translation
+
+impl Display for TranslateWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// ANCHOR: less_to_scss
+// 把 less 语法树改写为 scss 源码：@var 改为 $var，mixin 改为 @mixin 和 @include，
+// 守卫改为 @if，:extend 改为 @extend，~"..." 和 e() 改为 unquote()
+pub fn less_to_scss(tree: &AstTree<TokenType>) -> ScssTranslation {
+    let Some(root) = &tree.root else {
+        return ScssTranslation::default();
+    };
+    let mut translator = Translator::default();
+    translator.collect(root.child_nodes());
+    translator.block_items(root.child_nodes());
+    ScssTranslation {
+        code: translator.out,
+        warnings: translator.warnings,
+    }
+}
+// ANCHOR_END: less_to_scss
+
+#[derive(Default)]
+struct Translator {
+    out: String,
+    indent: usize,
+    warnings: Vec<TranslateWarning>,
+    // 带参数列表的 mixin 定义及其数量，同名的多个定义在 scss 中无法重载
+    mixins: HashMap<String, usize>,
+    // 普通规则的选择器，被当作 mixin 调用时改为 @extend
+    rulesets: HashSet<String>,
+}
+
+impl Translator {
+    fn warn(&mut self, message: impl Into<String>, range: Range) {
+        self.warnings.push(TranslateWarning {
+            message: message.into(),
+            range,
+        });
+    }
+
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.out.push_str("  ");
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn block(&mut self, head: &str, items: &[AstNode<TokenType>]) {
+        self.line(&format!("{} {{", head));
+        self.indent += 1;
+        self.block_items(items);
+        self.indent -= 1;
+        self.line("}");
+    }
+
+    // 翻译前先找出所有 mixin 定义和普通规则
+    fn collect(&mut self, items: &[AstNode<TokenType>]) {
+        for item in items {
+            if is_mixin_definition(item) {
+                if let Some(name) = item
+                    .find_child(TokenType::SelectorList)
+                    .and_then(selector_function)
+                    .map(function_name)
+                {
+                    *self.mixins.entry(name).or_default() += 1;
+                }
+            } else if let Some(list) = item
+                .find_child(TokenType::SelectorList)
+                .filter(|_| item.find_child(TokenType::DeclarationList).is_some())
+            {
+                self.rulesets.extend(complex_selectors(list));
+            }
+            if let Some(list) = item.find_child(TokenType::DeclarationList) {
+                self.collect(list.child_nodes());
+            } else if item.check_type(TokenType::AtRule) {
+                self.collect(item.child_nodes());
+            }
+        }
+    }
+
+    fn block_items(&mut self, items: &[AstNode<TokenType>]) {
+        // less 变量延迟求值，可以先使用后声明，scss 中变量要放在最前面
+        let (variables, others): (Vec<_>, Vec<_>) = items
+            .iter()
+            .partition(|item| item.check_type(TokenType::VariableDeclaration));
+        for item in variables.into_iter().chain(others) {
+            match item.node_type.0 {
+                TokenType::Declaration => {
+                    let declaration = self.declaration(item);
+                    self.line(&format!("{};", declaration));
+                }
+                TokenType::VariableDeclaration => self.variable_declaration(item),
+                TokenType::RuleList if is_mixin_definition(item) => self.mixin(item),
+                TokenType::RuleList if item.find_child(TokenType::DeclarationList).is_some() => {
+                    self.rule(item)
+                }
+                TokenType::RuleList if has_extend(item) => {
+                    let text = self.selector(item);
+                    for (target, _) in split_extends(&text).1 {
+                        self.line(&format!("@extend {};", target));
+                    }
+                }
+                TokenType::RuleList => self.include(item),
+                // @detached(); 翻译为没有参数的 mixin
+                TokenType::DetachedRulesetCall => {
+                    let name = item
+                        .find_child(TokenType::Variable)
+                        .map(|n| n.raw.trim_start_matches('@'))
+                        .unwrap_or_default();
+                    self.line(&format!("@include {};", name));
+                }
+                TokenType::AtRule => self.at_rule(item),
+                TokenType::Import => self.import(item),
+                TokenType::Comment => self.line(&item.raw),
+                TokenType::Semi
+                | TokenType::LeftCurlyBracket
+                | TokenType::RightCurlyBracket
+                | TokenType::CDOToken
+                | TokenType::CDCToken => {}
+                _ => self.line(&item.raw),
+            }
+        }
+    }
+
+    fn declaration(&mut self, node: &AstNode<TokenType>) -> String {
+        let property = node
+            .find_child(TokenType::Property)
+            .map(|n| interpolate(&n.raw))
+            .unwrap_or_default();
+        let value = match node.find_child(TokenType::Expression) {
+            Some(expr) => self.value(expr),
+            None => String::new(),
+        };
+        let important = match node.find_child(TokenType::Important) {
+            Some(_) => " !important",
+            None => "",
+        };
+        format!("{}: {}{}", property, value, important)
+    }
+
+    // @detached: { ... } 改为 @mixin detached { ... }
+    fn variable_declaration(&mut self, node: &AstNode<TokenType>) {
+        let name = node
+            .find_child(TokenType::Variable)
+            .map(|n| n.raw.trim_start_matches('@'))
+            .unwrap_or_default();
+        let Some(expr) = node.find_child(TokenType::Expression) else {
+            return;
+        };
+        let ruleset = match expr.child_nodes() {
+            [term] => term.find_child(TokenType::DeclarationList),
+            _ => None,
+        };
+        match ruleset {
+            Some(list) => self.block(&format!("@mixin {}", name), list.child_nodes()),
+            None => {
+                let value = self.value(expr);
+                self.line(&format!("${}: {};", name, value));
+            }
+        }
+    }
+
+    // 选择器中的 :extend() 改为块中的 @extend，带守卫的规则放在 @if 中
+    fn rule(&mut self, node: &AstNode<TokenType>) {
+        let (selector, targets) = split_extends(&self.selector(node));
+        let condition = node
+            .find_child(TokenType::Guard)
+            .and_then(|g| self.guard(g));
+        if let Some(condition) = &condition {
+            self.line(&format!("@if {} {{", condition));
+            self.indent += 1;
+        }
+        self.line(&format!("{} {{", selector));
+        self.indent += 1;
+        for (target, _) in targets {
+            self.line(&format!("@extend {};", target));
+        }
+        if let Some(list) = node.find_child(TokenType::DeclarationList) {
+            self.block_items(list.child_nodes());
+        }
+        self.indent -= 1;
+        self.line("}");
+        if condition.is_some() {
+            self.indent -= 1;
+            self.line("}");
+        }
+    }
+
+    fn selector(&mut self, node: &AstNode<TokenType>) -> String {
+        node.find_child(TokenType::SelectorList)
+            .map(|list| interpolate(&selector_to_string(list)))
+            .unwrap_or_default()
+    }
+
+    // ANCHOR: mixin
+    // .m(@a; @b: 2) when (@a > 0) { } 改为 @mixin m($a, $b: 2) { @if $a > 0 { } }
+    fn mixin(&mut self, node: &AstNode<TokenType>) {
+        let Some(function) = node
+            .find_child(TokenType::SelectorList)
+            .and_then(selector_function)
+        else {
+            return;
+        };
+        let name = function_name(function);
+        if self.mixins.get(&name).is_some_and(|count| *count > 1) {
+            self.warn(
+                format!(
+                    "mixin `{}` has several definitions, SCSS mixins can't be overloaded",
+                    name
+                ),
+                node.range,
+            );
+        }
+        let mut params = Vec::new();
+        let mut optional = false;
+        for child in function.child_nodes() {
+            match child.node_type.0 {
+                TokenType::Expression => match single_variable(child) {
+                    Some(variable) => {
+                        let variable = variable.replacen('@', "$", 1);
+                        // scss 中必需的参数要放在有默认值的参数前面
+                        if optional {
+                            self.warn(
+                                format!(
+                                    "required parameter `{}` of mixin `{}` follows an optional one",
+                                    variable, name
+                                ),
+                                child.range,
+                            );
+                        }
+                        params.push(variable);
+                    }
+                    None => self.warn(
+                        format!(
+                            "mixin `{}` matches the value `{}`, SCSS mixins can't match arguments",
+                            name,
+                            value_to_string(child)
+                        ),
+                        child.range,
+                    ),
+                },
+                TokenType::VariableDeclaration => {
+                    let variable = child
+                        .find_child(TokenType::Variable)
+                        .map(|n| n.raw.replacen('@', "$", 1))
+                        .unwrap_or_default();
+                    let value = match child.find_child(TokenType::Expression) {
+                        Some(expr) => self.value(expr),
+                        None => String::new(),
+                    };
+                    optional = true;
+                    params.push(format!("{}: {}", variable, value));
+                }
+                TokenType::Variable => params.push(child.raw.replacen('@', "$", 1)),
+                TokenType::Ellipsis => params.push("$args...".to_string()),
+                _ => {}
+            }
+        }
+        let head = match params.is_empty() {
+            true => format!("@mixin {}", name),
+            false => format!("@mixin {}({})", name, params.join(", ")),
+        };
+        let items = node
+            .find_child(TokenType::DeclarationList)
+            .map(|list| list.child_nodes())
+            .unwrap_or_default();
+        match node
+            .find_child(TokenType::Guard)
+            .and_then(|g| self.guard(g))
+        {
+            Some(condition) => {
+                self.line(&format!("{} {{", head));
+                self.indent += 1;
+                self.block(&format!("@if {}", condition), items);
+                self.indent -= 1;
+                self.line("}");
+            }
+            None => self.block(&head, items),
+        }
+    }
+    // ANCHOR_END: mixin
+
+    // ANCHOR: include
+    // .m(1; 2); 改为 @include m(1, 2);，没有定义为 mixin 的普通规则改为 @extend
+    fn include(&mut self, node: &AstNode<TokenType>) {
+        let Some(list) = node.find_child(TokenType::SelectorList) else {
+            return;
+        };
+        let Some(path) = rule_paths(list).into_iter().next() else {
+            self.line(&format!("{};", node.raw));
+            return;
+        };
+        let name = path
+            .last()
+            .map(|n| n.trim_start_matches(['.', '#']).to_string())
+            .unwrap_or_default();
+        if path.len() > 1 {
+            self.warn(
+                format!(
+                    "namespace `{}` of mixin `{}` was dropped",
+                    path[..path.len() - 1].join(" "),
+                    name
+                ),
+                node.range,
+            );
+        }
+        if node.find_child(TokenType::Important).is_some() {
+            self.warn(
+                format!(
+                    "`!important` after the call of mixin `{}` was dropped",
+                    name
+                ),
+                node.range,
+            );
+        }
+        let selector = selector_to_string(list);
+        let selector = selector.trim_end_matches("()");
+        if !self.mixins.contains_key(&name) && self.rulesets.contains(selector) {
+            self.warn(
+                format!(
+                    "ruleset `{}` used as a mixin was translated to @extend",
+                    selector
+                ),
+                node.range,
+            );
+            self.line(&format!("@extend {};", selector));
+            return;
+        }
+        let args = selector_function(list)
+            .map(|function| self.args(function))
+            .unwrap_or_default();
+        match args.is_empty() {
+            true => self.line(&format!("@include {};", name)),
+            false => self.line(&format!("@include {}({});", name, args.join(", "))),
+        }
+    }
+
+    // 用分号分隔参数时，参数中的逗号是列表，需要加上括号
+    fn args(&mut self, function: &AstNode<TokenType>) -> Vec<String> {
+        let semicolon = function.find_child(TokenType::Semi).is_some();
+        let mut args = Vec::new();
+        for child in function.child_nodes() {
+            match child.node_type.0 {
+                TokenType::Expression => {
+                    let value = self.value(child);
+                    let list = child
+                        .child_nodes()
+                        .iter()
+                        .any(|n| n.check_type(TokenType::Operator) && n.raw.trim() == ",");
+                    match semicolon && list {
+                        true => args.push(format!("({})", value)),
+                        false => args.push(value),
+                    }
+                }
+                TokenType::VariableDeclaration => {
+                    let variable = child
+                        .find_child(TokenType::Variable)
+                        .map(|n| n.raw.replacen('@', "$", 1))
+                        .unwrap_or_default();
+                    let value = match child.find_child(TokenType::Expression) {
+                        Some(expr) => self.value(expr),
+                        None => String::new(),
+                    };
+                    args.push(format!("{}: {}", variable, value));
+                }
+                TokenType::Variable => args.push(child.raw.replacen('@', "$", 1)),
+                _ => {}
+            }
+        }
+        args
+    }
+    // ANCHOR_END: include
+
+    // ANCHOR: guard
+    // 逗号和 or 改为 or，= 改为 ==，iscolor() 等类型检查改为 type-of() 和 unit()
+    fn guard(&mut self, guard: &AstNode<TokenType>) -> Option<String> {
+        let mut result = String::new();
+        let mut connector = "";
+        for item in guard.child_nodes() {
+            match item.node_type.0 {
+                TokenType::GuardCondition => {
+                    // default() 没有对应的写法，忽略这个条件
+                    if let Some(condition) = self.condition(item) {
+                        if !result.is_empty() {
+                            result.push_str(connector);
+                        }
+                        result.push_str(&condition);
+                    }
+                }
+                TokenType::Comma => connector = " or ",
+                TokenType::IdentToken if item.raw.eq_ignore_ascii_case("or") => connector = " or ",
+                TokenType::IdentToken if item.raw.eq_ignore_ascii_case("and") => {
+                    connector = " and "
+                }
+                _ => {}
+            }
+        }
+        (!result.is_empty()).then_some(result)
+    }
+
+    fn condition(&mut self, node: &AstNode<TokenType>) -> Option<String> {
+        let mut parts = Vec::new();
+        for child in node.child_nodes() {
+            match child.node_type.0 {
+                TokenType::Expression => parts.push(self.operand(child)?),
+                TokenType::Operator => parts.push(match child.raw.trim() {
+                    "=" => "==".to_string(),
+                    "=<" => "<=".to_string(),
+                    op => op.to_string(),
+                }),
+                TokenType::GuardCondition => parts.push(format!("({})", self.condition(child)?)),
+                _ => {}
+            }
+        }
+        let condition = parts.join(" ");
+        let negate = node
+            .find_child(TokenType::IdentToken)
+            .is_some_and(|n| n.raw.eq_ignore_ascii_case("not"));
+        Some(match negate {
+            true => format!("not ({})", condition),
+            false => condition,
+        })
+    }
+
+    fn operand(&mut self, expr: &AstNode<TokenType>) -> Option<String> {
+        let function = match expr.child_nodes() {
+            [term] => term.find_child(TokenType::Function),
+            _ => None,
+        };
+        let Some(function) = function else {
+            return Some(self.value(expr));
+        };
+        let name = function_name(function).to_ascii_lowercase();
+        let args: Vec<String> = function
+            .find_child(TokenType::Expression)
+            .map(|args| {
+                args.child_nodes()
+                    .split(|n| n.check_type(TokenType::Operator) && n.raw.trim() == ",")
+                    .map(|group| {
+                        self.value(&AstNode::from_children(
+                            TokenType::Expression,
+                            group.to_vec(),
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let value = args.first().cloned().unwrap_or_default();
+        let check = match name.as_str() {
+            "default" => {
+                self.warn(
+                    "guard `default()` has no SCSS equivalent and was dropped",
+                    function.range,
+                );
+                return None;
+            }
+            "iscolor" => format!("type-of({}) == color", value),
+            "isnumber" => format!("type-of({}) == number", value),
+            "isstring" => format!("type-of({}) == string", value),
+            "islist" => format!("type-of({}) == list", value),
+            "ispixel" => format!("unit({}) == \"px\"", value),
+            "isem" => format!("unit({}) == \"em\"", value),
+            "ispercentage" => format!("unit({}) == \"%\"", value),
+            "isunit" => format!(
+                "unit({}) == \"{}\"",
+                value,
+                args.get(1)
+                    .map_or("", |unit| unit.trim_matches(['"', '\'']))
+            ),
+            _ => return Some(self.value(expr)),
+        };
+        Some(check)
+    }
+    // ANCHOR_END: guard
+
+    // @media @phone 中单独的变量需要插值，(min-width: @w) 中的变量直接改名
+    fn at_rule(&mut self, node: &AstNode<TokenType>) {
+        let children = node.child_nodes();
+        let name = children.first().map(|n| n.raw.as_str()).unwrap_or_default();
+        if name.eq_ignore_ascii_case("@plugin") {
+            self.warn("@plugin has no SCSS equivalent and was dropped", node.range);
+            return;
+        }
+        let mut head = name.to_string();
+        if let Some(params) = node.find_child(TokenType::AtRuleParams) {
+            let mut tokens = params.child_nodes().to_vec();
+            for i in 0..tokens.len() {
+                let text = match tokens[i].node_type.0 {
+                    TokenType::AtKeywordToken => {
+                        let variable = &tokens[i].raw[1..];
+                        match i > 0 && tokens[i - 1].check_type(TokenType::Colon) {
+                            true => format!("${}", variable),
+                            false => format!("#{{${}}}", variable),
+                        }
+                    }
+                    TokenType::Str => self.string(&tokens[i].raw),
+                    _ => interpolate(&tokens[i].raw),
+                };
+                tokens[i].raw = text;
+            }
+            let params = tokens_to_string(&tokens);
+            if !params.is_empty() {
+                head.push(' ');
+                head.push_str(&params);
+            }
+        }
+        if let Some(list) = node.find_child(TokenType::DeclarationList) {
+            self.block(&head, list.child_nodes());
+        } else if let Some(start) = children
+            .iter()
+            .position(|n| n.check_type(TokenType::LeftCurlyBracket))
+        {
+            self.block(&head, &children[start + 1..]);
+        } else {
+            self.line(&format!("{};", head));
+        }
+    }
+
+    // @import (reference) "a.less"; 去掉选项和 .less 扩展名
+    fn import(&mut self, node: &AstNode<TokenType>) {
+        let mut tokens = Vec::new();
+        let mut options = false;
+        for child in node.child_nodes() {
+            match child.node_type.0 {
+                TokenType::LeftParenthesis if tokens.len() == 1 => options = true,
+                TokenType::RightParenthesis if options => options = false,
+                TokenType::IdentToken | TokenType::Comma if options => {
+                    if child.check_type(TokenType::IdentToken) && child.raw != "css" {
+                        self.warn(
+                            format!(
+                                "@import option `{}` has no SCSS equivalent and was dropped",
+                                child.raw
+                            ),
+                            child.range,
+                        );
+                    }
+                }
+                TokenType::Str => {
+                    let mut path = child.clone();
+                    path.raw = path.raw.replace(".less\"", "\"").replace(".less'", "'");
+                    tokens.push(path);
+                }
+                TokenType::Semi => {}
+                _ => tokens.push(child.clone()),
+            }
+        }
+        self.line(&format!("{};", tokens_to_string(&tokens)));
+    }
+
+    // ANCHOR: value
+    // 改写值中的变量、字符串和函数后按原来的格式输出
+    fn value(&mut self, expr: &AstNode<TokenType>) -> String {
+        let mut expr = expr.clone();
+        self.rewrite(&mut expr);
+        value_to_string(&expr)
+    }
+
+    fn rewrite(&mut self, node: &mut AstNode<TokenType>) {
+        let Some(children) = &mut node.children else {
+            return;
+        };
+        for child in children.iter_mut() {
+            match child.node_type.0 {
+                TokenType::Variable => self.variable(child),
+                TokenType::DollarVariable => self.warn(
+                    format!("property accessor `{}` has no SCSS equivalent", child.raw),
+                    child.range,
+                ),
+                TokenType::Str => child.raw = self.string(&child.raw),
+                TokenType::Function => {
+                    self.rewrite(child);
+                    self.function(child);
+                }
+                TokenType::DeclarationList => self.warn(
+                    "detached rulesets can only be translated when assigned to a variable",
+                    child.range,
+                ),
+                _ if child.children.is_some() => self.rewrite(child),
+                _ => child.raw = interpolate(&child.raw),
+            }
+        }
+    }
+
+    fn variable(&mut self, node: &mut AstNode<TokenType>) {
+        if node.raw.starts_with("@@") {
+            self.warn(
+                format!("variable variable `{}` has no SCSS equivalent", node.raw),
+                node.range,
+            );
+            // scss 中 @@ 不是合法的值，改为注释留给手动修改
+            node.raw = format!("/* {} */", node.raw);
+            node.children = None;
+            return;
+        }
+        if node.raw == "@arguments" {
+            self.warn(
+                "`@arguments` has no SCSS equivalent, use a `$args...` parameter",
+                node.range,
+            );
+        }
+        node.raw = node.raw.replacen('@', "$", 1);
+        if let Some(children) = &mut node.children {
+            for child in children.iter_mut() {
+                child.raw = child.raw.replacen('@', "$", 1);
+            }
+        }
+    }
+
+    // ~"..." 改为 unquote("...")，@{var} 改为 #{$var}
+    fn string(&self, raw: &str) -> String {
+        match raw.strip_prefix('~') {
+            Some(text) => format!("unquote({})", interpolate(text)),
+            None => interpolate(raw),
+        }
+    }
+
+    // 参数已经改写，这里只改写函数本身
+    fn function(&mut self, node: &mut AstNode<TokenType>) {
+        let name = function_name(node).to_ascii_lowercase();
+        let rename = match name.as_str() {
+            "e" => "unquote",
+            "spin" => "adjust-hue",
+            "extract" => "nth",
+            "fade" => "rgba",
+            "tint" | "shade" => "mix",
+            "%" => {
+                let text = self.format(node);
+                *node = AstNode {
+                    range: node.range,
+                    ..leaf(TokenType::Str, &text)
+                };
+                return;
+            }
+            _ => {
+                if LESS_ONLY_FUNCTIONS.contains(&name.as_str()) {
+                    self.warn(
+                        format!("Less function `{}()` has no SCSS equivalent", name),
+                        node.range,
+                    );
+                }
+                return;
+            }
+        };
+        let Some(children) = &mut node.children else {
+            return;
+        };
+        for child in children.iter_mut() {
+            if child.check_type(TokenType::FunctionToken) {
+                child.raw = format!("{}(", rename);
+            }
+            // tint(@c, 10%) 是与白色混合，shade() 是与黑色混合
+            if child.check_type(TokenType::Expression) && rename == "mix" {
+                let color = if name == "tint" { "white" } else { "black" };
+                if let Some(args) = &mut child.children {
+                    args.insert(0, leaf(TokenType::Operator, ","));
+                    args.insert(
+                        0,
+                        AstNode::from_children(
+                            TokenType::Term,
+                            vec![leaf(TokenType::IdentToken, color)],
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    // %("%d px", @a) 改为 "#{$a} px"
+    fn format(&mut self, node: &AstNode<TokenType>) -> String {
+        let args: Vec<String> = node
+            .find_child(TokenType::Expression)
+            .map(|args| {
+                args.child_nodes()
+                    .split(|n| n.check_type(TokenType::Operator) && n.raw.trim() == ",")
+                    .map(|group| {
+                        value_to_string(&AstNode::from_children(
+                            TokenType::Expression,
+                            group.to_vec(),
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let Some((template, args)) = args.split_first() else {
+            return "\"\"".to_string();
+        };
+        let mut args = args.iter();
+        let mut result = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => result.push('%'),
+                Some(kind @ ('d' | 's' | 'a' | 'D' | 'S' | 'A')) => {
+                    if kind.is_ascii_uppercase() {
+                        self.warn(
+                            format!("URL encoding of `%{}` in %() was dropped", kind),
+                            node.range,
+                        );
+                    }
+                    let arg = args.next().map_or("", |arg| arg.as_str());
+                    result.push_str(&format!("#{{{}}}", arg));
+                }
+                Some(other) => {
+                    result.push('%');
+                    result.push(other);
+                }
+                None => result.push('%'),
+            }
+        }
+        result
+    }
+    // ANCHOR_END: value
+}
+
+// .m( 或 e( 中的名称
+fn function_name(node: &AstNode<TokenType>) -> String {
+    node.find_child(TokenType::FunctionToken)
+        .map(|n| n.raw.trim_end_matches('(').to_string())
+        .unwrap_or_default()
+}
+
+// @{var} 改为 #{$var}
+fn interpolate(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("@{") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        result.push_str(&format!("#{{${}}}", &rest[start + 2..start + end]));
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
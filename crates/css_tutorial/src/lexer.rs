@@ -13,6 +13,18 @@ pub enum ErrorKind {
     CommentTokenError,
 }
 
+// ANCHOR: syntax
+// 源码的语法，less 模式下支持 // 注释、@{name} 插值、~"..." 转义和 @@name
+// scss 模式下支持 // 注释、#{...} 插值、$name 变量、%name 占位符和 != 运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Syntax {
+    #[default]
+    Css,
+    Less,
+    Scss,
+}
+// ANCHOR_END: syntax
+
 // ANCHOR: lexer
 #[derive(Debug)]
 pub struct Lexer<'a> {
@@ -29,12 +41,18 @@ pub struct Lexer<'a> {
 
     /** 原始输入*/
     pub source_code: &'a str,
+
+    syntax: Syntax,
 }
 // ANCHOR_END: lexer
 
 impl<'a> Lexer<'a> {
     // ANCHOR: new
     pub fn new(source_code: &'a str) -> Self {
+        Self::with_syntax(source_code, Syntax::Css)
+    }
+
+    pub fn with_syntax(source_code: &'a str, syntax: Syntax) -> Self {
         let mut lexer = Self {
             chars: source_code.char_indices(),
             pos_index: 0,
@@ -42,11 +60,16 @@ impl<'a> Lexer<'a> {
             peek_token: None,
             peek_peek_token: None,
             source_code,
+            syntax,
         };
         lexer.advance();
         lexer
     }
 
+    pub fn syntax(&self) -> Syntax {
+        self.syntax
+    }
+
     pub fn get_sub_string_by_raw(&self, start: usize, end: usize) -> String {
         self.source_code[start..end].to_string()
     }
@@ -75,7 +98,7 @@ impl<'a> Lexer<'a> {
 
     pub fn get_peek_token(&mut self) -> Option<&Token> {
         if let None = self.peek_token {
-            if  self.peek_peek_token.is_some() {
+            if self.peek_peek_token.is_some() {
                 self.peek_peek_token = None;
                 self.peek_token = self.peek_peek_token.take();
             } else {
@@ -102,8 +125,8 @@ impl<'a> Lexer<'a> {
             token = self.peek_token.take().unwrap();
             if self.peek_peek_token.is_some() {
                 self.peek_token = self.peek_peek_token.take();
-             }
-        }else{
+            }
+        } else {
             token = self.get_token();
         }
         return token;
@@ -134,12 +157,32 @@ impl<'a> Lexer<'a> {
                 }
                 '/' => return self.try_comment(),
                 '!' => return self.parse_exclamation(),
-                ch @ ('^' | '*' | '~' | '|') => return self.parse_attr_rule(ch),
+                '~' if self.syntax == Syntax::Less
+                    && matches!(self.peek_ch(), Some('"' | '\'')) =>
+                {
+                    return self.parse_escape()
+                }
+                '$' if self.syntax == Syntax::Scss
+                    && self.peek_ch().is_some_and(|ch| self.check_ch(ch)) =>
+                {
+                    return self.parse_dollar_variable()
+                }
+                ch @ ('^' | '*' | '~' | '|' | '$') => return self.parse_attr_rule(ch),
                 '\'' | '"' => return self.string_token(),
                 '@' => {
                     return self.parse_at_word();
                 }
                 '#' => return self.parse_hash(),
+                '.' if self.source_code[self.pos_index..].starts_with("...") => {
+                    return self.parse_ellipsis()
+                }
+                // less 格式化函数 %("%d", 1)
+                '%' if self.syntax == Syntax::Less
+                    && self.source_code[self.pos_index..].starts_with("%(") =>
+                {
+                    return self.parse_format_function()
+                }
+                '%' if self.syntax == Syntax::Scss => return self.parse_percent(),
                 ch if ch.is_ascii_digit() || ch == '.' || ch == '+' || ch == '-' => {
                     return self.parse_digit_token();
                 }
@@ -237,6 +280,17 @@ impl<'a> Lexer<'a> {
                             self.advance()
                         }
                     }
+                } else if self.syntax != Syntax::Css && matches!(self.peek_ch(), Some('/')) {
+                    // 单行注释到行尾结束
+                    while self.cur_char().is_some_and(|ch| ch != '\n') {
+                        self.advance();
+                    }
+                    let end_pos = self.pos_index;
+                    return Token::new(
+                        TokenType::Comment,
+                        Range::new(start_pos, end_pos),
+                        self.get_sub_string_by_raw(start_pos, end_pos),
+                    );
                 } else {
                     self.advance();
                     let end_pos = self.pos_index;
@@ -374,6 +428,9 @@ impl<'a> Lexer<'a> {
         if let Some(value) = self.parse_url_token(&mut token, start_pos) {
             return value;
         }
+        if self.is_interpolation() {
+            return self.interpolated_ident(start_pos);
+        }
         if matches!(self.cur_char(), Some('(')) {
             self.advance();
             let end_pos = self.pos_index;
@@ -387,7 +444,14 @@ impl<'a> Lexer<'a> {
     }
     fn parse_at_word(&mut self) -> Token {
         let start_pos = self.pos_index;
+        if self.is_interpolation() {
+            return self.interpolated_ident(start_pos);
+        }
         self.advance();
+        // less 的可变变量名 @@name
+        if self.syntax == Syntax::Less && matches!(self.cur_char(), Some('@')) {
+            self.advance();
+        }
 
         self.ident_token();
 
@@ -399,8 +463,119 @@ impl<'a> Lexer<'a> {
         );
     }
 
+    // ANCHOR: interpolation
+    // 选择器和属性名中的插值，例如 less 的 .@{name}-icon 和 scss 的 #{$prop}-color
+    fn is_interpolation(&mut self) -> bool {
+        let open = match self.syntax {
+            Syntax::Css => return false,
+            Syntax::Less => '@',
+            Syntax::Scss => '#',
+        };
+        self.cur_char() == Some(open) && matches!(self.peek_ch(), Some('{'))
+    }
+
+    fn interpolated_ident(&mut self, start_pos: usize) -> Token {
+        loop {
+            if self.is_interpolation() {
+                while let Some(ch) = self.cur_char() {
+                    self.advance();
+                    if ch == '}' {
+                        break;
+                    }
+                }
+            } else if self
+                .cur_char()
+                .is_some_and(|ch| ch.is_alphanumeric() || ch == '-' || ch == '_' || !ch.is_ascii())
+            {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        let end_pos = self.pos_index;
+        Token::new(
+            TokenType::IdentToken,
+            Range::new(start_pos, end_pos),
+            self.get_sub_string_by_raw(start_pos, end_pos),
+        )
+    }
+    // ANCHOR_END: interpolation
+
+    // less 转义字符串 ~"..."，作为以 ~ 开头的字符串 token
+    fn parse_escape(&mut self) -> Token {
+        let start_pos = self.pos_index;
+        self.advance();
+        let string = self.string_token();
+        let end_pos = string.get_range().end_pos;
+        Token::new(
+            TokenType::Str,
+            Range::new(start_pos, end_pos),
+            self.get_sub_string_by_raw(start_pos, end_pos),
+        )
+    }
+
+    // scss 变量 $name
+    fn parse_dollar_variable(&mut self) -> Token {
+        let start_pos = self.pos_index;
+        self.advance();
+        self.ident_token();
+        let end_pos = self.pos_index;
+        Token::new(
+            TokenType::DollarVariable,
+            Range::new(start_pos, end_pos),
+            self.get_sub_string_by_raw(start_pos, end_pos),
+        )
+    }
+
+    // scss 占位符选择器 %name，单独的 % 是取余运算符
+    fn parse_percent(&mut self) -> Token {
+        let start_pos = self.pos_index;
+        self.advance();
+        let token_type = if self.cur_char().is_some_and(|ch| self.check_ch(ch)) {
+            self.ident_token();
+            TokenType::PlaceholderToken
+        } else {
+            TokenType::Percent
+        };
+        let end_pos = self.pos_index;
+        Token::new(
+            token_type,
+            Range::new(start_pos, end_pos),
+            self.get_sub_string_by_raw(start_pos, end_pos),
+        )
+    }
+
+    // less mixin 的可变参数 @rest...
+    fn parse_ellipsis(&mut self) -> Token {
+        let start_pos = self.pos_index;
+        for _ in 0..3 {
+            self.advance();
+        }
+        let end_pos = self.pos_index;
+        Token::new(
+            TokenType::Ellipsis,
+            Range::new(start_pos, end_pos),
+            self.get_sub_string_by_raw(start_pos, end_pos),
+        )
+    }
+
+    fn parse_format_function(&mut self) -> Token {
+        let start_pos = self.pos_index;
+        self.advance();
+        self.advance();
+        let end_pos = self.pos_index;
+        Token::new(
+            TokenType::FunctionToken,
+            Range::new(start_pos, end_pos),
+            self.get_sub_string_by_raw(start_pos, end_pos),
+        )
+    }
+
     fn parse_hash(&mut self) -> Token {
         let start_pos = self.pos_index;
+        if self.is_interpolation() {
+            return self.interpolated_ident(start_pos);
+        }
         self.advance();
         self.match_word();
         let end_pos = self.pos_index;
@@ -424,17 +599,16 @@ impl<'a> Lexer<'a> {
                     Range::new(start_pos, end_pos),
                     self.get_sub_string_by_raw(start_pos, end_pos),
                 )
-            } else if matches!(self.cur_char(),Some(ch) if !ch.is_whitespace() && !matches!(ch,';'|')'|'}'))
+            } else if matches!(self.cur_char(),Some(ch) if self.check_ch(ch) && !ch.is_ascii_digit())
             {
-                if self.check_peek_token_by_type(TokenType::IdentToken) {
-                    self.eat_token();
-                    let end_pos = self.pos_index;
-                    return Token::new(
-                        TokenType::Dimension,
-                        Range::new(start_pos, end_pos),
-                        self.get_sub_string_by_raw(start_pos, end_pos),
-                    );
-                }
+                // 直接读取单位，不能借用 peek_token，否则会覆盖已经预读的 token
+                self.ident_token();
+                let end_pos = self.pos_index;
+                return Token::new(
+                    TokenType::Dimension,
+                    Range::new(start_pos, end_pos),
+                    self.get_sub_string_by_raw(start_pos, end_pos),
+                );
             }
         }
         if token.check_type(TokenType::Minus) {
@@ -484,6 +658,12 @@ impl<'a> Lexer<'a> {
                     Range::new(start_pos, end_pos),
                     self.get_sub_string_by_raw(start_pos, end_pos),
                 );
+            } else if ch == '$' {
+                return Token::new(
+                    TokenType::SuffixMatch,
+                    Range::new(start_pos, end_pos),
+                    self.get_sub_string_by_raw(start_pos, end_pos),
+                );
             } else {
                 return Token::new(
                     TokenType::Dashmatch,
@@ -511,6 +691,16 @@ impl<'a> Lexer<'a> {
     fn parse_exclamation(&mut self) -> Token {
         let start_pos = self.pos_index;
         self.advance();
+        // scss 的 != 运算符
+        if self.syntax == Syntax::Scss && matches!(self.cur_char(), Some('=')) {
+            self.advance();
+            let end_pos = self.pos_index;
+            return Token::new(
+                TokenType::NotEqual,
+                Range::new(start_pos, end_pos),
+                self.get_sub_string_by_raw(start_pos, end_pos),
+            );
+        }
         loop {
             if self.check_peek_token_by_type(TokenType::Comment) {
                 self.eat_token();
@@ -1,9 +1,28 @@
 pub mod ast;
+pub mod cascade;
+pub mod codegen;
+pub mod color;
+pub mod compat;
+pub mod custom_properties;
+pub mod downlevel;
+pub mod html;
+pub mod inline;
+pub mod less;
 pub mod lexer;
+pub mod math;
+pub mod minify;
+pub mod optimizer;
 pub mod parser;
+pub mod prefixer;
+pub mod purge;
 pub mod range;
+pub mod scss;
+pub mod selector;
+pub mod shorthands;
+pub mod targets;
 pub mod token;
 pub mod token_type;
+pub mod values;
 
 use ast::AstTreeBuilder;
 use lexer::Lexer;
@@ -0,0 +1,664 @@
+use std::fmt::{self, Display};
+
+use crate::{
+    ast::AstNode,
+    codegen::function_to_string,
+    token_type::TokenType,
+    values::{Angle, Frequency, Length, Resolution, Time, Value},
+};
+
+// ANCHOR: math_expr
+// css values 4 中的计算树，加减乘除都是多元节点
+#[derive(Debug, Clone, PartialEq)]
+pub enum MathExpr {
+    Value(Value),
+    // var()、env() 等无法静态求值的部分
+    Opaque(String),
+    Sum(Vec<MathExpr>),
+    Negate(Box<MathExpr>),
+    Product(Vec<MathExpr>),
+    // 1 / x
+    Invert(Box<MathExpr>),
+    // min() max() clamp()
+    Function(String, Vec<MathExpr>),
+}
+// ANCHOR_END: math_expr
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathType {
+    Number,
+    Percentage,
+    Length,
+    Angle,
+    Time,
+    Frequency,
+    Resolution,
+    Flex,
+    // 含有 var() 时类型未知
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MathError {
+    UnexpectedToken(String),
+    UnexpectedEnd,
+    // + - 两侧必须有空白
+    MissingWhitespace(String),
+    UnknownUnit(String),
+    TypeMismatch(MathType, MathType),
+    DivideByNonNumber(MathType),
+    ArgumentCount(String),
+    // 计算结果超出浮点数范围
+    NotFinite,
+}
+
+const MATH_FUNCTIONS: [&str; 4] = ["calc", "min", "max", "clamp"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum MathToken {
+    Number(String),
+    Ident(String),
+    // 数学函数名
+    Function(String),
+    Opaque(String),
+    Operator(char),
+    LeftParenthesis,
+    RightParenthesis,
+    Comma,
+}
+
+// 记录 token 前后是否有空白，用于判断 + - 是否合法
+#[derive(Debug)]
+struct SpannedToken {
+    token: MathToken,
+    space_before: bool,
+}
+
+fn tokenize(source: &str) -> Result<Vec<SpannedToken>, MathError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens: Vec<SpannedToken> = Vec::new();
+    let mut index = 0;
+    let mut space_before = false;
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_' || c == '%';
+    while index < chars.len() {
+        let ch = chars[index];
+        if ch.is_whitespace() {
+            space_before = true;
+            index += 1;
+            continue;
+        }
+        let starts_number = |i: usize| {
+            chars.get(i).is_some_and(|c| c.is_ascii_digit())
+                || (chars.get(i) == Some(&'.')
+                    && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()))
+        };
+        // 与 css 词法一致，紧跟数字的 + - 属于数值本身
+        let token = if starts_number(index) || matches!(ch, '+' | '-') && starts_number(index + 1) {
+            let start = index;
+            index += 1;
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                index += 1;
+            }
+            // 指数
+            if index + 1 < chars.len()
+                && matches!(chars[index], 'e' | 'E')
+                && (chars[index + 1].is_ascii_digit()
+                    || (matches!(chars[index + 1], '+' | '-')
+                        && chars.get(index + 2).is_some_and(|c| c.is_ascii_digit())))
+            {
+                index += 2;
+                while index < chars.len() && chars[index].is_ascii_digit() {
+                    index += 1;
+                }
+            }
+            while index < chars.len() && is_ident_char(chars[index]) {
+                index += 1;
+            }
+            MathToken::Number(chars[start..index].iter().collect())
+        } else if ch.is_alphabetic()
+            || ch == '-' && chars.get(index + 1).is_some_and(|c| c.is_alphabetic() || *c == '-')
+        {
+            let start = index;
+            while index < chars.len() && is_ident_char(chars[index]) {
+                index += 1;
+            }
+            let name: String = chars[start..index].iter().collect();
+            if chars.get(index) == Some(&'(') {
+                if MATH_FUNCTIONS.contains(&name.to_ascii_lowercase().as_str()) {
+                    index += 1;
+                    MathToken::Function(name.to_ascii_lowercase())
+                } else {
+                    // 原样保留整个函数
+                    let mut depth = 0;
+                    while index < chars.len() {
+                        match chars[index] {
+                            '(' => depth += 1,
+                            ')' => {
+                                depth -= 1;
+                                if depth == 0 {
+                                    index += 1;
+                                    break;
+                                }
+                            }
+                            _ => {}
+                        }
+                        index += 1;
+                    }
+                    MathToken::Opaque(chars[start..index].iter().collect())
+                }
+            } else {
+                MathToken::Ident(name)
+            }
+        } else {
+            index += 1;
+            match ch {
+                '+' | '-' | '*' | '/' => MathToken::Operator(ch),
+                '(' => MathToken::LeftParenthesis,
+                ')' => MathToken::RightParenthesis,
+                ',' => MathToken::Comma,
+                _ => return Err(MathError::UnexpectedToken(ch.to_string())),
+            }
+        };
+        tokens.push(SpannedToken {
+            token,
+            space_before,
+        });
+        space_before = false;
+    }
+    Ok(tokens)
+}
+
+struct MathParser {
+    tokens: Vec<SpannedToken>,
+    pos: usize,
+}
+
+impl MathParser {
+    fn peek(&self) -> Option<&MathToken> {
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    fn next(&mut self) -> Result<MathToken, MathError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .map(|t| t.token.clone())
+            .ok_or(MathError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: MathToken) -> Result<(), MathError> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(MathError::UnexpectedToken(format!("{:?}", token))),
+        }
+    }
+
+    // sum := product (('+' | '-') product)*
+    fn parse_sum(&mut self) -> Result<MathExpr, MathError> {
+        let mut children = vec![self.parse_product()?];
+        while let Some(MathToken::Operator(op @ ('+' | '-'))) = self.peek().cloned() {
+            let space_before = self.tokens[self.pos].space_before;
+            let space_after = self
+                .tokens
+                .get(self.pos + 1)
+                .is_some_and(|t| t.space_before);
+            if !space_before || !space_after {
+                return Err(MathError::MissingWhitespace(op.to_string()));
+            }
+            self.pos += 1;
+            let operand = self.parse_product()?;
+            children.push(if op == '-' {
+                MathExpr::Negate(Box::new(operand))
+            } else {
+                operand
+            });
+        }
+        Ok(if children.len() == 1 {
+            children.pop().unwrap()
+        } else {
+            MathExpr::Sum(children)
+        })
+    }
+
+    // product := value (('*' | '/') value)*
+    fn parse_product(&mut self) -> Result<MathExpr, MathError> {
+        let mut children = vec![self.parse_value()?];
+        while let Some(MathToken::Operator(op @ ('*' | '/'))) = self.peek().cloned() {
+            self.pos += 1;
+            let operand = self.parse_value()?;
+            children.push(if op == '/' {
+                MathExpr::Invert(Box::new(operand))
+            } else {
+                operand
+            });
+        }
+        Ok(if children.len() == 1 {
+            children.pop().unwrap()
+        } else {
+            MathExpr::Product(children)
+        })
+    }
+
+    fn parse_value(&mut self) -> Result<MathExpr, MathError> {
+        match self.next()? {
+            MathToken::Number(text) => match Value::parse(&text) {
+                Some(Value::Dimension(_, unit)) => Err(MathError::UnknownUnit(unit)),
+                Some(value) => Ok(MathExpr::Value(value)),
+                None => Err(MathError::UnexpectedToken(text)),
+            },
+            MathToken::Ident(name) => match name.to_ascii_lowercase().as_str() {
+                "e" => Ok(MathExpr::Value(Value::Number(std::f64::consts::E))),
+                "pi" => Ok(MathExpr::Value(Value::Number(std::f64::consts::PI))),
+                _ => Ok(MathExpr::Opaque(name)),
+            },
+            MathToken::Opaque(text) => Ok(MathExpr::Opaque(text)),
+            MathToken::LeftParenthesis => {
+                let expr = self.parse_sum()?;
+                self.expect(MathToken::RightParenthesis)?;
+                Ok(expr)
+            }
+            MathToken::Function(name) => {
+                let mut args = vec![self.parse_sum()?];
+                while self.peek() == Some(&MathToken::Comma) {
+                    self.pos += 1;
+                    args.push(self.parse_sum()?);
+                }
+                self.expect(MathToken::RightParenthesis)?;
+                match (name.as_str(), args.len()) {
+                    // 嵌套的 calc 直接展开
+                    ("calc", 1) => Ok(args.pop().unwrap()),
+                    ("clamp", 3) | ("min", _) | ("max", _) => Ok(MathExpr::Function(name, args)),
+                    _ => Err(MathError::ArgumentCount(name)),
+                }
+            }
+            token => Err(MathError::UnexpectedToken(format!("{:?}", token))),
+        }
+    }
+}
+
+// ANCHOR: parse_math
+// 解析 calc(100% - 2 * 10px) 这样的数学函数
+pub fn parse_math(source: &str) -> Result<MathExpr, MathError> {
+    let mut parser = MathParser {
+        tokens: tokenize(source)?,
+        pos: 0,
+    };
+    let expr = parser.parse_value()?;
+    if let Some(token) = parser.peek() {
+        return Err(MathError::UnexpectedToken(format!("{:?}", token)));
+    }
+    expr.resolve_type()?;
+    Ok(expr)
+}
+// ANCHOR_END: parse_math
+
+// 从语法树中的 Function 节点解析
+pub fn parse_math_node(node: &AstNode<TokenType>) -> Result<MathExpr, MathError> {
+    parse_math(&function_to_string(node))
+}
+
+pub fn is_math_function(name: &str) -> bool {
+    MATH_FUNCTIONS.contains(&name.trim_end_matches('(').to_ascii_lowercase().as_str())
+}
+
+// 化简并输出，失败时返回错误
+pub fn simplify_math(source: &str) -> Result<String, MathError> {
+    let expr = parse_math(source)?.simplify();
+    if !expr.is_finite() {
+        return Err(MathError::NotFinite);
+    }
+    Ok(expr.to_css())
+}
+
+fn value_type(value: &Value) -> MathType {
+    match value {
+        Value::Number(_) => MathType::Number,
+        Value::Percentage(_) => MathType::Percentage,
+        Value::Length(_) => MathType::Length,
+        Value::Angle(_) => MathType::Angle,
+        Value::Time(_) => MathType::Time,
+        Value::Frequency(_) => MathType::Frequency,
+        Value::Resolution(_) => MathType::Resolution,
+        Value::Flex(_) => MathType::Flex,
+        Value::Dimension(..) => MathType::Unknown,
+    }
+}
+
+// 加法两侧的类型合并，百分比可以和长度相加
+fn add_types(left: MathType, right: MathType) -> Result<MathType, MathError> {
+    use MathType::*;
+    match (left, right) {
+        (Unknown, other) | (other, Unknown) => Ok(other),
+        (a, b) if a == b => Ok(a),
+        (Percentage, other) | (other, Percentage) if other != Number => Ok(other),
+        (a, b) => Err(MathError::TypeMismatch(a, b)),
+    }
+}
+
+// 保留单位，替换数值
+fn with_number(value: &Value, number: f64) -> Value {
+    match value {
+        Value::Number(_) => Value::Number(number),
+        Value::Percentage(_) => Value::Percentage(number),
+        Value::Flex(_) => Value::Flex(number),
+        Value::Dimension(_, unit) => Value::Dimension(number, unit.clone()),
+        Value::Length(l) => Value::Length(Length::new(number, l.unit)),
+        Value::Angle(a) => Value::Angle(Angle::new(number, a.unit)),
+        Value::Time(t) => Value::Time(Time::new(number, t.unit)),
+        Value::Frequency(f) => Value::Frequency(Frequency::new(number, f.unit)),
+        Value::Resolution(r) => Value::Resolution(Resolution::new(number, r.unit)),
+    }
+}
+
+fn scale(value: &Value, factor: f64) -> Value {
+    with_number(value, value.number() * factor)
+}
+
+// 能静态换算到规范单位的值
+fn is_convertible(value: &Value) -> bool {
+    match value {
+        Value::Length(l) => l.is_absolute(),
+        Value::Angle(_) | Value::Time(_) | Value::Frequency(_) | Value::Resolution(_) => true,
+        _ => false,
+    }
+}
+
+// 两个值能否合并，能则返回同一单位下的两个数值和该单位
+fn combine(left: &Value, right: &Value) -> Option<(f64, f64, Value)> {
+    if value_type(left) != value_type(right) {
+        return None;
+    }
+    if left.unit().eq_ignore_ascii_case(right.unit()) {
+        return Some((left.number(), right.number(), left.clone()));
+    }
+    if is_convertible(left) && is_convertible(right) {
+        let (left, right) = (left.canonicalize(), right.canonicalize());
+        // 换算溢出时保留原来的单位，不能合并
+        return (left.unit() == right.unit()).then(|| (left.number(), right.number(), left));
+    }
+    None
+}
+
+impl MathExpr {
+    pub fn resolve_type(&self) -> Result<MathType, MathError> {
+        match self {
+            MathExpr::Value(value) => Ok(value_type(value)),
+            MathExpr::Opaque(_) => Ok(MathType::Unknown),
+            MathExpr::Negate(inner) => inner.resolve_type(),
+            MathExpr::Invert(inner) => match inner.resolve_type()? {
+                MathType::Number | MathType::Unknown => Ok(MathType::Number),
+                other => Err(MathError::DivideByNonNumber(other)),
+            },
+            MathExpr::Sum(children) | MathExpr::Function(_, children) => {
+                children.iter().try_fold(MathType::Unknown, |acc, child| {
+                    add_types(acc, child.resolve_type()?)
+                })
+            }
+            MathExpr::Product(children) => {
+                let mut result = MathType::Number;
+                for child in children {
+                    let ty = child.resolve_type()?;
+                    result = match (result, ty) {
+                        (MathType::Number, other) | (other, MathType::Number) => other,
+                        (MathType::Unknown, other) | (other, MathType::Unknown) => other,
+                        (a, b) => return Err(MathError::TypeMismatch(a, b)),
+                    };
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    // ANCHOR: simplify
+    pub fn simplify(self) -> MathExpr {
+        match self {
+            MathExpr::Negate(inner) => match inner.simplify() {
+                MathExpr::Value(value) => MathExpr::Value(scale(&value, -1.0)),
+                MathExpr::Negate(inner) => *inner,
+                MathExpr::Sum(children) => MathExpr::Sum(
+                    children
+                        .into_iter()
+                        .map(|c| MathExpr::Negate(Box::new(c)).simplify())
+                        .collect(),
+                ),
+                other => MathExpr::Negate(Box::new(other)),
+            },
+            MathExpr::Invert(inner) => match inner.simplify() {
+                MathExpr::Value(Value::Number(n)) if n != 0.0 => MathExpr::Value(Value::Number(1.0 / n)),
+                MathExpr::Invert(inner) => *inner,
+                other => MathExpr::Invert(Box::new(other)),
+            },
+            MathExpr::Sum(children) => simplify_sum(children),
+            MathExpr::Product(children) => simplify_product(children),
+            MathExpr::Function(name, args) => {
+                let args: Vec<MathExpr> = args.into_iter().map(MathExpr::simplify).collect();
+                let values: Option<Vec<&Value>> = args
+                    .iter()
+                    .map(|a| match a {
+                        MathExpr::Value(v) => Some(v),
+                        _ => None,
+                    })
+                    .collect();
+                match values.and_then(|values| evaluate_function(&name, &values)) {
+                    Some(value) => MathExpr::Value(value),
+                    None => MathExpr::Function(name, args),
+                }
+            }
+            other => other,
+        }
+    }
+    // ANCHOR_END: simplify
+
+    pub fn is_finite(&self) -> bool {
+        match self {
+            MathExpr::Value(value) => value.number().is_finite(),
+            MathExpr::Opaque(_) => true,
+            MathExpr::Negate(inner) | MathExpr::Invert(inner) => inner.is_finite(),
+            MathExpr::Sum(children)
+            | MathExpr::Product(children)
+            | MathExpr::Function(_, children) => children.iter().all(MathExpr::is_finite),
+        }
+    }
+
+    // 顶层输出，单个值时去掉 calc()
+    pub fn to_css(&self) -> String {
+        match self {
+            MathExpr::Value(_) | MathExpr::Function(..) => self.to_string(),
+            _ => format!("calc({})", self),
+        }
+    }
+
+    fn fmt_child(&self, f: &mut fmt::Formatter<'_>, in_product: bool) -> fmt::Result {
+        match self {
+            MathExpr::Sum(_) if in_product => write!(f, "({})", self),
+            MathExpr::Negate(inner) if in_product => write!(f, "(-1 * {})", inner),
+            _ => write!(f, "{}", self),
+        }
+    }
+}
+
+fn simplify_sum(children: Vec<MathExpr>) -> MathExpr {
+    let mut flat = Vec::new();
+    for child in children {
+        match child.simplify() {
+            MathExpr::Sum(inner) => flat.extend(inner),
+            other => flat.push(other),
+        }
+    }
+    let mut result: Vec<MathExpr> = Vec::new();
+    for child in flat {
+        if let MathExpr::Value(value) = &child {
+            let merged = result.iter_mut().any(|existing| {
+                if let MathExpr::Value(current) = existing {
+                    if let Some((a, b, unit)) = combine(current, value) {
+                        *current = with_number(&unit, a + b);
+                        return true;
+                    }
+                }
+                false
+            });
+            if merged {
+                continue;
+            }
+        }
+        result.push(child);
+    }
+    // 去掉和为 0 的项，但至少保留一项
+    if result.len() > 1 {
+        result.retain(|c| !matches!(c, MathExpr::Value(v) if v.is_zero() && !matches!(v, Value::Number(_))));
+    }
+    match result.len() {
+        0 => MathExpr::Value(Value::Number(0.0)),
+        1 => result.pop().unwrap(),
+        _ => MathExpr::Sum(result),
+    }
+}
+
+fn simplify_product(children: Vec<MathExpr>) -> MathExpr {
+    let mut factor = 1.0;
+    let mut rest = Vec::new();
+    for child in children {
+        match child.simplify() {
+            MathExpr::Value(Value::Number(n)) => factor *= n,
+            MathExpr::Product(inner) => {
+                for item in inner {
+                    match item {
+                        MathExpr::Value(Value::Number(n)) => factor *= n,
+                        other => rest.push(other),
+                    }
+                }
+            }
+            other => rest.push(other),
+        }
+    }
+    match rest.len() {
+        0 => MathExpr::Value(Value::Number(factor)),
+        1 => match rest.pop().unwrap() {
+            MathExpr::Value(value) => MathExpr::Value(scale(&value, factor)),
+            // 数字分配到加法的每一项
+            MathExpr::Sum(items) if factor != 1.0 => simplify_sum(
+                items
+                    .into_iter()
+                    .map(|item| MathExpr::Product(vec![MathExpr::Value(Value::Number(factor)), item]))
+                    .collect(),
+            ),
+            other if factor == 1.0 => other,
+            other => MathExpr::Product(vec![MathExpr::Value(Value::Number(factor)), other]),
+        },
+        _ => {
+            if factor != 1.0 {
+                rest.insert(0, MathExpr::Value(Value::Number(factor)));
+            }
+            MathExpr::Product(rest)
+        }
+    }
+}
+
+// min max clamp 的参数都能换算到同一单位时直接求值，结果保留选中参数原来的单位
+fn evaluate_function(name: &str, values: &[&Value]) -> Option<Value> {
+    let first = values.first()?;
+    let unit = if values.iter().all(|v| v.unit().eq_ignore_ascii_case(first.unit())) {
+        (*first).clone()
+    } else {
+        first.canonicalize()
+    };
+    let numbers: Vec<f64> = values
+        .iter()
+        .map(|v| combine(&unit, v).map(|(_, b, _)| b))
+        .collect::<Option<_>>()?;
+    // 相等时取前面的参数
+    let pick = |better: fn(f64, f64) -> bool| {
+        (1..numbers.len()).fold(0, |best, index| {
+            if better(numbers[index], numbers[best]) {
+                index
+            } else {
+                best
+            }
+        })
+    };
+    let index = match name {
+        "min" => pick(|a, b| a < b),
+        "max" => pick(|a, b| a > b),
+        "clamp" => {
+            let inner = if numbers[2] < numbers[1] { 2 } else { 1 };
+            if numbers[0] > numbers[inner] {
+                0
+            } else {
+                inner
+            }
+        }
+        _ => return None,
+    };
+    Some((*values[index]).clone())
+}
+
+impl Display for MathExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MathExpr::Value(value) => write!(f, "{}", value),
+            MathExpr::Opaque(text) => write!(f, "{}", text),
+            MathExpr::Sum(children) => {
+                for (index, child) in children.iter().enumerate() {
+                    if index == 0 {
+                        child.fmt_child(f, false)?;
+                        continue;
+                    }
+                    match child {
+                        MathExpr::Negate(inner) => {
+                            write!(f, " - ")?;
+                            inner.fmt_child(f, true)?;
+                        }
+                        MathExpr::Value(value) if value.number() < 0.0 => {
+                            write!(f, " - {}", scale(value, -1.0))?
+                        }
+                        _ => {
+                            write!(f, " + ")?;
+                            child.fmt_child(f, false)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            MathExpr::Negate(inner) => {
+                write!(f, "-1 * ")?;
+                inner.fmt_child(f, true)
+            }
+            MathExpr::Product(children) => {
+                for (index, child) in children.iter().enumerate() {
+                    match child {
+                        MathExpr::Invert(inner) => {
+                            if index == 0 {
+                                write!(f, "1")?;
+                            }
+                            write!(f, " / ")?;
+                            inner.fmt_child(f, true)?;
+                        }
+                        _ => {
+                            if index > 0 {
+                                write!(f, " * ")?;
+                            }
+                            child.fmt_child(f, true)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            MathExpr::Invert(inner) => {
+                write!(f, "1 / ")?;
+                inner.fmt_child(f, true)
+            }
+            MathExpr::Function(name, args) => {
+                write!(f, "{}(", name)?;
+                for (index, arg) in args.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
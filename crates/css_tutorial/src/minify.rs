@@ -0,0 +1,380 @@
+use crate::{
+    ast::{AstNode, AstTree},
+    cascade::property_name,
+    codegen::{function_to_string, selector_combinator, value_to_string},
+    color::Color,
+    math::{is_math_function, simplify_math},
+    optimizer::{
+        is_license_comment, merge_adjacent_rules, merge_longhands, remove_duplicate_declarations,
+        remove_empty_rules,
+    },
+    token_type::TokenType,
+    values::{short_number, Value},
+};
+
+// ANCHOR: minify
+// 压缩输出：先在语法树上做结构优化，再去掉所有不必要的空白和注释
+pub fn minify(tree: &AstTree<TokenType>) -> String {
+    let mut tree = tree.clone();
+    remove_empty_rules(&mut tree);
+    merge_adjacent_rules(&mut tree);
+    remove_duplicate_declarations(&mut tree);
+    merge_longhands(&mut tree);
+    // 合并简写后声明可能变得一样，再合并一次
+    merge_adjacent_rules(&mut tree);
+    let mut out = String::new();
+    if let Some(root) = &tree.root {
+        write_items(root.child_nodes(), &mut out);
+    }
+    out
+}
+// ANCHOR_END: minify
+
+fn write_items(items: &[AstNode<TokenType>], out: &mut String) {
+    for item in items {
+        write_node(item, out);
+    }
+}
+
+fn write_node(node: &AstNode<TokenType>, out: &mut String) {
+    match node.node_type.0 {
+        TokenType::RuleList => {
+            if let Some(selector) = node.find_child(TokenType::SelectorList) {
+                out.push_str(&minify_selector(selector));
+            }
+            match node.find_child(TokenType::DeclarationList) {
+                Some(list) => write_declaration_list(list, out),
+                None => out.push(';'),
+            }
+        }
+        TokenType::AtRule => write_at_rule(node, out),
+        TokenType::Declaration => out.push_str(&minify_declaration(node)),
+        TokenType::Import
+        | TokenType::ChartSet
+        | TokenType::VariableDeclaration
+        | TokenType::MediumList => {
+            out.push_str(compact_tokens(node.child_nodes()).trim_end_matches(';'));
+            out.push(';');
+        }
+        TokenType::Page => {
+            let children = node.child_nodes();
+            let head = children
+                .iter()
+                .position(|n| n.check_type(TokenType::DeclarationList))
+                .unwrap_or(children.len());
+            out.push_str(&compact_tokens(&children[..head]));
+            if let Some(list) = children.get(head) {
+                write_declaration_list(list, out);
+            }
+        }
+        TokenType::Comment if is_license_comment(node) => out.push_str(&node.raw),
+        TokenType::Comment
+        | TokenType::Semi
+        | TokenType::CDOToken
+        | TokenType::CDCToken
+        | TokenType::LeftCurlyBracket
+        | TokenType::RightCurlyBracket => {}
+        _ => out.push_str(node.raw.trim()),
+    }
+}
+
+// 最后一个声明后面的分号可以省略
+fn write_declaration_list(node: &AstNode<TokenType>, out: &mut String) {
+    out.push('{');
+    let mut need_semi = false;
+    for item in node.child_nodes() {
+        let mut text = String::new();
+        write_node(item, &mut text);
+        if text.is_empty() {
+            continue;
+        }
+        if need_semi {
+            out.push(';');
+        }
+        out.push_str(&text);
+        need_semi = item.check_type(TokenType::Declaration);
+    }
+    out.push('}');
+}
+
+fn write_at_rule(node: &AstNode<TokenType>, out: &mut String) {
+    let children = node.child_nodes();
+    if let Some(name) = children.first() {
+        out.push_str(&name.raw);
+    }
+    if let Some(params) = node.find_child(TokenType::AtRuleParams) {
+        let params = compact_tokens(params.child_nodes());
+        if !params.is_empty() {
+            if !params.starts_with('(') && !params.starts_with('"') {
+                out.push(' ');
+            }
+            out.push_str(&params);
+        }
+    }
+    if let Some(list) = node.find_child(TokenType::DeclarationList) {
+        write_declaration_list(list, out);
+    } else if node.find_child(TokenType::LeftCurlyBracket).is_some() {
+        out.push('{');
+        write_items(&children[2..], out);
+        out.push('}');
+    } else {
+        out.push(';');
+    }
+}
+
+// ANCHOR: minify_declaration
+pub fn minify_declaration(node: &AstNode<TokenType>) -> String {
+    let property = property_name(node);
+    let mut result = property.clone();
+    result.push(':');
+    if let Some(expr) = node.find_child(TokenType::Expression) {
+        // 自定义属性的值原样保留
+        if property.starts_with("--") {
+            result.push_str(&value_to_string(expr));
+        } else {
+            result.push_str(&minify_value(expr, &property));
+        }
+    }
+    if node.find_child(TokenType::Important).is_some() {
+        result.push_str("!important");
+    }
+    result
+}
+// ANCHOR_END: minify_declaration
+
+// ANCHOR: minify_value
+// 压缩属性值中的数值和颜色，逗号和斜杠两侧不需要空格
+pub fn minify_value(node: &AstNode<TokenType>, property: &str) -> String {
+    let mut result = String::new();
+    let mut prev: Option<&AstNode<TokenType>> = None;
+    for item in node.child_nodes() {
+        if item.check_type(TokenType::Comment) {
+            continue;
+        }
+        let text = match item.node_type.0 {
+            TokenType::Term => minify_term(item, property),
+            TokenType::Expression => minify_value(item, property),
+            TokenType::Function => minify_function(item, property),
+            TokenType::Operator => item.raw.trim().to_string(),
+            _ => item.raw.clone(),
+        };
+        if let Some(prev) = prev {
+            if need_space(prev, item) {
+                result.push(' ');
+            }
+        }
+        result.push_str(&text);
+        prev = Some(item);
+    }
+    result
+}
+// ANCHOR_END: minify_value
+
+fn need_space(prev: &AstNode<TokenType>, cur: &AstNode<TokenType>) -> bool {
+    if prev.check_type(TokenType::Operator)
+        || cur.check_type(TokenType::Operator)
+        || prev.check_type(TokenType::LeftParenthesis)
+        || cur.check_type(TokenType::RightParenthesis)
+    {
+        return false;
+    }
+    !(cur.range.start_pos > 0 && prev.range.end_pos == cur.range.start_pos)
+}
+
+fn minify_term(node: &AstNode<TokenType>, property: &str) -> String {
+    let [child] = node.child_nodes() else {
+        return node
+            .child_nodes()
+            .iter()
+            .map(|child| minify_term(child, property))
+            .collect();
+    };
+    match child.node_type.0 {
+        TokenType::Function => minify_function(child, property),
+        TokenType::Expression => minify_value(child, property),
+        TokenType::Digital | TokenType::Dimension | TokenType::PercentageToken => {
+            minify_number(&child.raw, property != "flex")
+        }
+        TokenType::HashToken => shorter_color(&child.raw),
+        TokenType::IdentToken if is_color_property(property) => shorter_color(&child.raw),
+        _ => child.raw.clone(),
+    }
+}
+
+fn minify_function(node: &AstNode<TokenType>, property: &str) -> String {
+    let source = function_to_string(node);
+    let name = node
+        .child_nodes()
+        .first()
+        .map(|n| n.raw.to_ascii_lowercase())
+        .unwrap_or_default();
+    if is_math_function(&name) {
+        // 化简失败时只去掉多余空白
+        return match simplify_math(&source) {
+            Ok(text) if !text.contains('(') => minify_number(&text, property != "flex"),
+            Ok(text) => compact_math(&text),
+            Err(_) => compact_math(&source),
+        };
+    }
+    match name.as_str() {
+        "rgb(" | "rgba(" | "hsl(" | "hsla(" | "hwb(" => return shorter_color(&source),
+        "var(" | "env(" | "url(" => return source,
+        _ => {}
+    }
+    node.child_nodes()
+        .iter()
+        .map(|child| match child.node_type.0 {
+            TokenType::Expression => minify_value(child, property),
+            _ => child.raw.clone(),
+        })
+        .collect()
+}
+
+// 加减号两侧必须保留空格
+fn compact_math(source: &str) -> String {
+    source
+        .replace(", ", ",")
+        .replace(" * ", "*")
+        .replace(" / ", "/")
+}
+
+// ANCHOR: minify_number
+// 0.50px => .5px，长度为 0 时可以省略单位
+pub fn minify_number(source: &str, drop_zero_unit: bool) -> String {
+    let Some(value) = Value::parse(source) else {
+        return source.to_string();
+    };
+    let number = short_number(value.number());
+    match &value {
+        Value::Length(_) if drop_zero_unit && value.is_zero() => "0".to_string(),
+        Value::Time(time) => {
+            let ms = short_number(value.canonicalize().number());
+            let seconds = short_number(value.canonicalize().number() / 1000.0);
+            if time.unit.as_str() == "ms" && seconds.len() + 1 < ms.len() + 2 {
+                format!("{}s", seconds)
+            } else {
+                format!("{}{}", number, time.unit.as_str())
+            }
+        }
+        _ => format!("{}{}", number, value.unit()),
+    }
+}
+// ANCHOR_END: minify_number
+
+fn shorter_color(source: &str) -> String {
+    match Color::parse(source) {
+        Some(color) => {
+            let short = color.to_shortest();
+            if short.len() < source.len() {
+                short
+            } else {
+                source.to_string()
+            }
+        }
+        None => source.to_string(),
+    }
+}
+
+// 只在这些属性中把颜色名当作颜色，避免改写 animation-name 之类的标识符
+fn is_color_property(property: &str) -> bool {
+    matches!(property, "color" | "fill" | "stroke" | "caret-color")
+        || property.ends_with("-color")
+        || property.ends_with("-shadow")
+        || [
+            "background",
+            "border",
+            "outline",
+            "column-rule",
+            "text-decoration",
+        ]
+        .iter()
+        .any(|p| property.starts_with(p))
+}
+
+// ANCHOR: minify_selector
+// 选择器组之间和组合符两侧不需要空格
+pub fn minify_selector(node: &AstNode<TokenType>) -> String {
+    match node.node_type.0 {
+        TokenType::SelectorList => {
+            let mut result = String::new();
+            let mut prev: Option<&AstNode<TokenType>> = None;
+            for item in node.child_nodes() {
+                match item.node_type.0 {
+                    TokenType::Comma => {
+                        result.push(',');
+                        prev = None;
+                        continue;
+                    }
+                    TokenType::Comment => continue,
+                    TokenType::Selector
+                        if prev.is_some_and(|p| selector_combinator(p).is_none()) =>
+                    {
+                        result.push(' ');
+                    }
+                    _ => {}
+                }
+                result.push_str(&minify_selector(item));
+                prev = Some(item);
+            }
+            result
+        }
+        TokenType::Selector => node
+            .child_nodes()
+            .iter()
+            .map(|child| match child.node_type.0 {
+                TokenType::SimpleSelect => minify_selector(child),
+                TokenType::Comment => String::new(),
+                _ => child.raw.clone(),
+            })
+            .collect(),
+        TokenType::SimpleSelect => node
+            .child_nodes()
+            .iter()
+            .map(|child| match child.node_type.0 {
+                TokenType::Function => function_to_string(child),
+                TokenType::Comment => String::new(),
+                _ => child.raw.clone(),
+            })
+            .collect(),
+        _ => node.raw.clone(),
+    }
+}
+// ANCHOR_END: minify_selector
+
+// at 规则参数只在标识符之间保留空格，例如 screen and (max-width:100px)
+fn compact_tokens(tokens: &[AstNode<TokenType>]) -> String {
+    let mut result = String::new();
+    let mut prev: Option<&AstNode<TokenType>> = None;
+    for token in tokens {
+        if token.check_type(TokenType::Comment) {
+            continue;
+        }
+        let text = match token.node_type.0 {
+            TokenType::Expression => minify_value(token, ""),
+            TokenType::Function => minify_function(token, ""),
+            TokenType::Term => minify_term(token, ""),
+            TokenType::SelectorList | TokenType::Selector => minify_selector(token),
+            _ if token.children.is_some() => compact_tokens(token.child_nodes()),
+            _ => token.raw.trim().to_string(),
+        };
+        if let Some(prev) = prev {
+            let adjacent = prev.range.end_pos == token.range.start_pos && token.range.start_pos > 0;
+            let tight = |n: &AstNode<TokenType>| {
+                n.check_type(TokenType::Colon)
+                    || n.check_type(TokenType::Comma)
+                    || n.check_type(TokenType::Semi)
+            };
+            if !adjacent
+                && !prev.check_type(TokenType::LeftParenthesis)
+                && !token.check_type(TokenType::RightParenthesis)
+                && !tight(prev)
+                && !tight(token)
+            {
+                result.push(' ');
+            }
+        }
+        result.push_str(&text);
+        prev = Some(token);
+    }
+    result
+}
@@ -0,0 +1,526 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{AstNode, AstNodeType, AstTree},
+    cascade::property_name,
+    codegen::{declaration_to_string, selector_to_string, value_to_string},
+    shorthands::{longhand_to_node, longhands, Longhand, CSS_WIDE_KEYWORDS},
+    token_type::TokenType,
+};
+
+// ANCHOR: mergeable
+// 可以安全合并的简写，border 系列按从大到小的顺序尝试
+// background、font、transition 等会重置额外的子属性，不做合并
+const MERGEABLE: [&str; 16] = [
+    "border",
+    "border-width",
+    "border-style",
+    "border-color",
+    "border-top",
+    "border-right",
+    "border-bottom",
+    "border-left",
+    "border-radius",
+    "margin",
+    "padding",
+    "inset",
+    "outline",
+    "flex",
+    "grid-area",
+    "list-style",
+];
+// ANCHOR_END: mergeable
+
+// ANCHOR: merge_longhands
+// 规则中包含某个简写的全部长属性时替换成最短的简写，返回合并的次数
+pub fn merge_longhands(tree: &mut AstTree<TokenType>) -> usize {
+    match &mut tree.root {
+        Some(root) => merge_node(root),
+        None => 0,
+    }
+}
+// ANCHOR_END: merge_longhands
+
+fn merge_node(node: &mut AstNode<TokenType>) -> usize {
+    let is_list = node.check_type(TokenType::DeclarationList);
+    let Some(children) = &mut node.children else {
+        return 0;
+    };
+    let mut count: usize = children.iter_mut().map(merge_node).sum();
+    if is_list {
+        for shorthand in MERGEABLE {
+            if merge_shorthand(children, shorthand) {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+fn merge_shorthand(children: &mut Vec<AstNode<TokenType>>, shorthand: &str) -> bool {
+    let Some(names) = longhands(shorthand) else {
+        return false;
+    };
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for (index, child) in children.iter().enumerate() {
+        if !child.check_type(TokenType::Declaration) {
+            continue;
+        }
+        let property = property_name(child);
+        if let Some(name) = names.iter().find(|n| **n == property) {
+            // 重复的长属性可能是回退写法，不合并
+            if positions.insert(name, index).is_some() {
+                return false;
+            }
+        } else if property == shorthand
+            || longhands(&property).is_some_and(|other| other.iter().any(|n| names.contains(n)))
+        {
+            // 规则中还有会影响这些长属性的其他简写
+            return false;
+        } else if shorthand == "border" && property.starts_with("border-image") {
+            // border 会重置 border-image
+            return false;
+        }
+    }
+    if positions.len() != names.len() {
+        return false;
+    }
+    let declarations: Vec<&AstNode<TokenType>> =
+        names.iter().map(|n| &children[positions[n.as_str()]]).collect();
+    let important = declarations[0].find_child(TokenType::Important).is_some();
+    if declarations
+        .iter()
+        .any(|d| d.find_child(TokenType::Important).is_some() != important)
+    {
+        return false;
+    }
+    let values: Vec<String> = declarations
+        .iter()
+        .map(|d| {
+            d.find_child(TokenType::Expression)
+                .map(value_to_string)
+                .unwrap_or_default()
+        })
+        .collect();
+    let Some(value) = compose(shorthand, &values) else {
+        return false;
+    };
+    let Some(node) = longhand_to_node(&Longhand {
+        property: shorthand.to_string(),
+        value,
+        important,
+    }) else {
+        return false;
+    };
+    // 放在最后一个长属性的位置，保持与其他声明的先后顺序
+    let last = *positions.values().max().unwrap();
+    let removed: Vec<usize> = positions.values().copied().filter(|i| *i != last).collect();
+    children[last] = node;
+    remove_declarations(children, removed);
+    true
+}
+
+// ANCHOR: compose
+// 由长属性的值拼出最短的简写值，无法表示时返回 None
+pub fn compose(shorthand: &str, values: &[String]) -> Option<String> {
+    if values.iter().any(|v| v.to_ascii_lowercase().contains("var(") || v.is_empty()) {
+        return None;
+    }
+    let keywords: Vec<&String> = values
+        .iter()
+        .filter(|v| CSS_WIDE_KEYWORDS.contains(&v.to_ascii_lowercase().as_str()))
+        .collect();
+    if !keywords.is_empty() {
+        // 全部是同一个关键字时才能合并
+        return (keywords.len() == values.len() && values.iter().all(|v| v == values[0].as_str()))
+            .then(|| values[0].clone());
+    }
+    match shorthand {
+        "margin" | "padding" | "inset" | "border-width" | "border-style" | "border-color" => {
+            Some(compose_box(values))
+        }
+        "border" => {
+            let groups: Vec<&[String]> = values.chunks(4).collect();
+            if groups.iter().any(|g| g.iter().any(|v| *v != g[0])) {
+                return None;
+            }
+            Some(compose_border(&groups[0][0], &groups[1][0], &groups[2][0]))
+        }
+        "border-top" | "border-right" | "border-bottom" | "border-left" | "outline" => {
+            Some(compose_border(&values[0], &values[1], &values[2]))
+        }
+        "border-radius" => {
+            let mut horizontal = Vec::new();
+            let mut vertical = Vec::new();
+            for value in values {
+                let parts: Vec<&str> = value.split_whitespace().collect();
+                match parts.as_slice() {
+                    [both] => {
+                        horizontal.push(both.to_string());
+                        vertical.push(both.to_string());
+                    }
+                    [h, v] => {
+                        horizontal.push(h.to_string());
+                        vertical.push(v.to_string());
+                    }
+                    _ => return None,
+                }
+            }
+            let (horizontal, vertical) = (compose_box(&horizontal), compose_box(&vertical));
+            if horizontal == vertical {
+                Some(horizontal)
+            } else {
+                Some(format!("{} / {}", horizontal, vertical))
+            }
+        }
+        "flex" => {
+            let (grow, shrink, basis) = (&values[0], &values[1], &values[2]);
+            Some(match (grow.as_str(), shrink.as_str(), basis.as_str()) {
+                ("0", "0", "auto") => "none".to_string(),
+                ("1", "1", "auto") => "auto".to_string(),
+                (_, "1", "0%") => grow.clone(),
+                (_, _, "0%") => format!("{} {}", grow, shrink),
+                // 无单位的 0 会被当作 flex-shrink
+                (_, _, "0") => format!("{} {} 0px", grow, shrink),
+                (_, "1", _) => format!("{} {}", grow, basis),
+                _ => format!("{} {} {}", grow, shrink, basis),
+            })
+        }
+        "grid-area" => {
+            let is_ident = |line: &str| {
+                !line.contains(' ')
+                    && !matches!(line, "auto" | "span")
+                    && line.starts_with(|c: char| c.is_alphabetic() || c == '-' || c == '_')
+            };
+            let fallback = |line: &String| {
+                if is_ident(line) {
+                    line.clone()
+                } else {
+                    "auto".to_string()
+                }
+            };
+            let mut lines = values.to_vec();
+            // 依次省略可以由前面推导出的值
+            for (index, source) in [(3, 1), (2, 0), (1, 0)] {
+                if lines.len() == index + 1 && lines[index] == fallback(&values[source]) {
+                    lines.pop();
+                }
+            }
+            Some(lines.join(" / "))
+        }
+        "list-style" => {
+            let parts: Vec<&str> = [("disc", &values[0]), ("outside", &values[1]), ("none", &values[2])]
+                .iter()
+                .filter(|(initial, value)| value.as_str() != *initial)
+                .map(|(_, value)| value.as_str())
+                .collect();
+            // type 为 none 且 image 为初始值时 "none" 会同时设置两者，结果一致
+            Some(if parts.is_empty() {
+                "disc".to_string()
+            } else {
+                parts.join(" ")
+            })
+        }
+        _ => None,
+    }
+}
+// ANCHOR_END: compose
+
+// 上右下左四个值的最短写法
+pub fn compose_box(values: &[String]) -> String {
+    let [top, right, bottom, left] = [&values[0], &values[1], &values[2], &values[3]];
+    if top == right && top == bottom && top == left {
+        top.clone()
+    } else if top == bottom && right == left {
+        format!("{} {}", top, right)
+    } else if right == left {
+        format!("{} {} {}", top, right, bottom)
+    } else {
+        format!("{} {} {} {}", top, right, bottom, left)
+    }
+}
+
+// 省略初始值，全部省略时输出 none
+fn compose_border(width: &str, style: &str, color: &str) -> String {
+    let parts: Vec<&str> = [(width, "medium"), (style, "none"), (color, "currentcolor")]
+        .iter()
+        .filter(|(value, initial)| !value.eq_ignore_ascii_case(initial))
+        .map(|(value, _)| *value)
+        .collect();
+    if parts.is_empty() {
+        "none".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+// ANCHOR: remove_duplicate_declarations
+// 删除被覆盖的重复声明，返回删除的数量
+// 值不同的同名声明可能是回退写法，例如 display: flex; display: grid，保留
+pub fn remove_duplicate_declarations(tree: &mut AstTree<TokenType>) -> usize {
+    match &mut tree.root {
+        Some(root) => dedupe_node(root),
+        None => 0,
+    }
+}
+// ANCHOR_END: remove_duplicate_declarations
+
+fn dedupe_node(node: &mut AstNode<TokenType>) -> usize {
+    let is_list = node.check_type(TokenType::DeclarationList);
+    let Some(children) = &mut node.children else {
+        return 0;
+    };
+    let mut count: usize = children.iter_mut().map(dedupe_node).sum();
+    if !is_list {
+        return count;
+    }
+    let declarations: Vec<(usize, String, String, bool)> = children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| child.check_type(TokenType::Declaration))
+        .map(|(index, child)| {
+            let value = child
+                .find_child(TokenType::Expression)
+                .map(value_to_string)
+                .unwrap_or_default();
+            let important = child.find_child(TokenType::Important).is_some();
+            (index, property_name(child), value, important)
+        })
+        .collect();
+    let mut removed = Vec::new();
+    for (position, (index, property, value, important)) in declarations.iter().enumerate() {
+        let later = &declarations[position + 1..];
+        let earlier = &declarations[..position];
+        // 后面有完全相同的声明
+        let repeated = later
+            .iter()
+            .any(|(_, p, v, i)| p == property && v == value && i == important);
+        // 前面已有 !important 的同名声明，普通声明不会生效
+        let overridden = !important && earlier.iter().any(|(_, p, _, i)| p == property && *i);
+        if repeated || overridden {
+            removed.push(*index);
+        }
+    }
+    count += removed.len();
+    remove_declarations(children, removed);
+    count
+}
+
+// 从后往前删除，连同后面的分号
+fn remove_declarations(children: &mut Vec<AstNode<TokenType>>, mut removed: Vec<usize>) {
+    removed.sort_unstable_by(|a, b| b.cmp(a));
+    for index in removed {
+        if children
+            .get(index + 1)
+            .is_some_and(|n| n.check_type(TokenType::Semi))
+        {
+            children.remove(index + 1);
+        }
+        children.remove(index);
+    }
+}
+
+// ANCHOR: merge_adjacent_rules
+// 合并相邻的规则：选择器相同时合并声明，声明相同时合并选择器，返回合并的次数
+pub fn merge_adjacent_rules(tree: &mut AstTree<TokenType>) -> usize {
+    match &mut tree.root {
+        Some(root) => merge_rules_node(root),
+        None => 0,
+    }
+}
+// ANCHOR_END: merge_adjacent_rules
+
+fn merge_rules_node(node: &mut AstNode<TokenType>) -> usize {
+    let Some(children) = &mut node.children else {
+        return 0;
+    };
+    let mut count: usize = children.iter_mut().map(merge_rules_node).sum();
+    let mut prev: Option<usize> = None;
+    let mut index = 0;
+    while index < children.len() {
+        let child = &children[index];
+        if child.check_type(TokenType::Semi)
+            || child.check_type(TokenType::Comment) && !is_license_comment(child)
+        {
+            index += 1;
+            continue;
+        }
+        if !is_plain_rule(child) {
+            prev = None;
+            index += 1;
+            continue;
+        }
+        let Some(target) = prev else {
+            prev = Some(index);
+            index += 1;
+            continue;
+        };
+        let rule = children.remove(index);
+        match merge_rule_pair(&mut children[target], rule) {
+            Ok(()) => count += 1,
+            Err(rule) => {
+                children.insert(index, rule);
+                prev = Some(index);
+                index += 1;
+            }
+        }
+    }
+    count
+}
+
+// 合并失败时把规则原样返回
+fn merge_rule_pair(
+    target: &mut AstNode<TokenType>,
+    rule: AstNode<TokenType>,
+) -> Result<(), AstNode<TokenType>> {
+    let (Some(first), Some(second)) = (
+        target.find_child(TokenType::SelectorList),
+        rule.find_child(TokenType::SelectorList),
+    ) else {
+        return Err(rule);
+    };
+    if selector_to_string(first) == selector_to_string(second) {
+        let declarations: Vec<AstNode<TokenType>> = rule
+            .find_child(TokenType::DeclarationList)
+            .map(|list| list.child_nodes().to_vec())
+            .unwrap_or_default();
+        let list = target
+            .children
+            .iter_mut()
+            .flatten()
+            .find(|n| n.check_type(TokenType::DeclarationList))
+            .unwrap();
+        let children = list.children.get_or_insert_with(Vec::new);
+        let close = children.pop();
+        if children
+            .iter()
+            .rev()
+            .find(|n| !n.check_type(TokenType::Comment))
+            .is_some_and(|n| n.check_type(TokenType::Declaration))
+        {
+            children.push(leaf(TokenType::Semi, ";"));
+        }
+        children.extend(
+            declarations
+                .into_iter()
+                .filter(|n| !n.check_type(TokenType::LeftCurlyBracket))
+                .filter(|n| !n.check_type(TokenType::RightCurlyBracket)),
+        );
+        children.extend(close);
+        return Ok(());
+    }
+    let (first_text, second_text) = (selector_to_string(first), selector_to_string(second));
+    // 浏览器遇到不认识的带前缀伪类会丢弃整条规则，不能和其他选择器合并
+    let prefixed = |text: &str| text.contains(":-");
+    if prefixed(&first_text) || prefixed(&second_text) || rule_body(target) != rule_body(&rule) {
+        return Err(rule);
+    }
+    let mut selectors = first.child_nodes().to_vec();
+    selectors.push(leaf(TokenType::Comma, ","));
+    selectors.extend(second.child_nodes().iter().cloned());
+    let merged = AstNode::from_children(TokenType::SelectorList, selectors);
+    for child in target.children.iter_mut().flatten() {
+        if child.check_type(TokenType::SelectorList) {
+            *child = merged;
+            break;
+        }
+    }
+    Ok(())
+}
+
+// 只包含声明的普通规则
+fn is_plain_rule(node: &AstNode<TokenType>) -> bool {
+    node.check_type(TokenType::RuleList)
+        && node.find_child(TokenType::SelectorList).is_some()
+        && node.find_child(TokenType::DeclarationList).is_some_and(|list| {
+            list.child_nodes().iter().all(|n| {
+                matches!(
+                    n.node_type.0,
+                    TokenType::Declaration
+                        | TokenType::Semi
+                        | TokenType::Comment
+                        | TokenType::LeftCurlyBracket
+                        | TokenType::RightCurlyBracket
+                )
+            })
+        })
+}
+
+fn rule_body(rule: &AstNode<TokenType>) -> Vec<String> {
+    rule.find_child(TokenType::DeclarationList)
+        .map(|list| {
+            list.child_nodes()
+                .iter()
+                .filter(|n| n.check_type(TokenType::Declaration))
+                .map(declaration_to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn leaf(node_type: TokenType, raw: &str) -> AstNode<TokenType> {
+    AstNode {
+        node_type: AstNodeType(node_type),
+        raw: raw.to_string(),
+        ..Default::default()
+    }
+}
+
+// /*! 开头的注释通常是版权声明，压缩时需要保留
+pub fn is_license_comment(node: &AstNode<TokenType>) -> bool {
+    node.check_type(TokenType::Comment) && node.raw.starts_with("/*!")
+}
+
+// ANCHOR: remove_empty_rules
+// 删除没有声明的规则和空的 at 规则，返回删除的数量
+pub fn remove_empty_rules(tree: &mut AstTree<TokenType>) -> usize {
+    match &mut tree.root {
+        Some(root) => remove_empty_node(root),
+        None => 0,
+    }
+}
+// ANCHOR_END: remove_empty_rules
+
+fn remove_empty_node(node: &mut AstNode<TokenType>) -> usize {
+    let Some(children) = &mut node.children else {
+        return 0;
+    };
+    // 先处理子节点，@media 中的规则全部删除后 @media 本身也会变空
+    let mut count: usize = children.iter_mut().map(remove_empty_node).sum();
+    let before = children.len();
+    children.retain(|child| !is_empty_rule(child));
+    count += before - children.len();
+    count
+}
+
+fn is_empty_rule(node: &AstNode<TokenType>) -> bool {
+    let is_empty_block = |items: &[AstNode<TokenType>]| {
+        items.iter().all(|n| {
+            matches!(
+                n.node_type.0,
+                TokenType::Semi | TokenType::LeftCurlyBracket | TokenType::RightCurlyBracket
+            ) || n.check_type(TokenType::Comment) && !is_license_comment(n)
+        })
+    };
+    match node.node_type.0 {
+        TokenType::RuleList => node
+            .find_child(TokenType::DeclarationList)
+            .is_some_and(|list| is_empty_block(list.child_nodes())),
+        TokenType::AtRule => {
+            let children = node.child_nodes();
+            // 空的 @layer a {} 也会声明层的顺序
+            let name = children.first().map(|n| n.raw.to_ascii_lowercase());
+            if name.as_deref() == Some("@layer") {
+                return false;
+            }
+            if let Some(list) = node.find_child(TokenType::DeclarationList) {
+                is_empty_block(list.child_nodes())
+            } else if node.find_child(TokenType::LeftCurlyBracket).is_some() {
+                is_empty_block(&children[2..])
+            } else {
+                false
+            }
+        }
+        _ => false,
+    }
+}
@@ -1,7 +1,10 @@
-use crate::ast::AstTreeBuilder;
-use crate::lexer::Lexer;
+use crate::ast::{AstNode, AstTree, AstTreeBuilder};
+use crate::lexer::{Lexer, Syntax};
 use crate::token::Token;
 use crate::token_type::TokenType;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
 
 const CHARSET_SYM: &str = "@charset";
 const IMPORT_SYM: &str = "@import";
@@ -14,18 +17,100 @@ const W_KEY_FRAMES: &str = "@-webkit-keyframes";
 const M_KEY_FRAMES: &str = "@-moz-keyframes";
 const O_KEY_FRAMES: &str = "@-o-keyframes";
 const SUPPORTS: &str = "@supports";
+const LAYER: &str = "@layer";
+const CONTAINER: &str = "@container";
+const SCOPE: &str = "@scope";
+const STARTING_STYLE: &str = "@starting-style";
+
+// 参数为选择器列表的伪类函数
+const SELECTOR_PSEUDO_FUNCTIONS: [&str; 10] = [
+    "not(",
+    "is(",
+    "where(",
+    "has(",
+    "matches(",
+    "-webkit-any(",
+    "-moz-any(",
+    "host(",
+    "host-context(",
+    // less :extend(.a all)
+    "extend(",
+];
+
+// 内置的包含嵌套规则的 at 规则
+const NEST_AT_RULES: [&str; 10] = [
+    MEDIA_SYM,
+    KEY_FRAMES,
+    W_KEY_FRAMES,
+    M_KEY_FRAMES,
+    O_KEY_FRAMES,
+    SUPPORTS,
+    LAYER,
+    CONTAINER,
+    SCOPE,
+    STARTING_STYLE,
+];
+
+// ANCHOR: at_rule_handler
+// at 规则块中的内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtRuleBlock {
+    // 没有块，例如 @tailwind base;
+    None,
+    // 只有声明，例如 @theme { --color: red; }
+    Declarations,
+    // 嵌套规则，例如 @media
+    Rules,
+    // 声明和嵌套规则混合出现
+    Mixed,
+}
+
+// 按名称注册到 Parser 上，决定 at 规则的块和参数的解析方式
+pub trait AtRuleHandler: Debug {
+    fn block(&self) -> AtRuleBlock;
+
+    // 在 AtRuleParams 节点中解析参数，可以构建自定义节点，剩余的 token 按原样保留
+    fn parse_prelude(&self, _parser: &mut Parser) {}
+}
+
+impl AtRuleHandler for AtRuleBlock {
+    fn block(&self) -> AtRuleBlock {
+        *self
+    }
+}
+// ANCHOR_END: at_rule_handler
 
 // ANCHOR: parser
 #[derive(Debug)]
 pub struct Parser<'a> {
     lexer: &'a mut Lexer<'a>,
     builder: &'a mut AstTreeBuilder<TokenType>,
+    at_rules: HashMap<String, Rc<dyn AtRuleHandler>>,
 }
 // ANCHOR_END: parser
 
 impl<'a> Parser<'a> {
     pub fn new(lexer: &'a mut Lexer<'a>, builder: &'a mut AstTreeBuilder<TokenType>) -> Self {
-        Self { lexer, builder }
+        let mut parser = Self {
+            lexer,
+            builder,
+            at_rules: HashMap::new(),
+        };
+        for name in NEST_AT_RULES {
+            parser.register_at_rule(name, AtRuleBlock::Rules);
+        }
+        parser
+    }
+
+    // 注册 at 规则的处理器，名称不区分大小写，可以省略 @，会覆盖之前的注册
+    pub fn register_at_rule(&mut self, name: &str, handler: impl AtRuleHandler + 'static) {
+        let name = format!("@{}", name.trim_start_matches('@')).to_ascii_lowercase();
+        self.at_rules.insert(name, Rc::new(handler));
+    }
+
+    fn at_rule_handler(&mut self) -> Option<Rc<dyn AtRuleHandler>> {
+        let name = self.peek()?.get_source_code().to_ascii_lowercase();
+        self.at_rules.get(&name).cloned()
     }
     // ANCHOR: lexer_wrapper
     pub fn peek(&mut self) -> Option<&Token> {
@@ -70,6 +155,23 @@ impl<'a> Parser<'a> {
         true
     }
 
+    // 下一个 token 是指定的标识符，不区分大小写
+    pub fn check_ident(&mut self, name: &str) -> bool {
+        self.peek().is_some_and(|token| {
+            token.check_type(TokenType::IdentToken)
+                && token.get_source_code().eq_ignore_ascii_case(name)
+        })
+    }
+
+    // 供 at 规则处理器构建自定义节点
+    pub fn start_node(&mut self, token_type: TokenType) {
+        self.builder.start_node(token_type);
+    }
+
+    pub fn finish_node(&mut self) {
+        self.builder.finish_node();
+    }
+
     //ANCHOR_END:lexer_wrapper
     // ANCHOR: entry
     pub fn parse(&mut self) {
@@ -81,12 +183,12 @@ impl<'a> Parser<'a> {
 
     fn parse_entry(&mut self) {
         while let Some(token) = self.peek() {
-            let token_type = &token.r#type;
+            let token_type = token.r#type;
             match token_type {
                 TokenType::AtKeywordToken => {
                     self.parse_at_rule();
                 }
-                TokenType::CDCToken | TokenType::CDOToken => {
+                TokenType::CDCToken | TokenType::CDOToken | TokenType::Semi => {
                     self.advance();
                 }
                 TokenType::EOF => {
@@ -95,22 +197,121 @@ impl<'a> Parser<'a> {
                 TokenType::RightCurlyBracket => {
                     return;
                 }
+                TokenType::IdentToken | TokenType::Asterisk if self.is_declaration() => {
+                    self.parse_declaration();
+                }
+                TokenType::DollarVariable => self.parse_scss_variable_declaration(),
                 _ => self.parse_rule(),
             }
         }
     }
     // ANCHOR_END: entry
 
+    // 块中的声明和嵌套规则都可能以标识符开头，例如 `color: red;` 和 `a:hover {}`
+    // 向后查找，先遇到 `;` 或 `}` 的是声明，先遇到 `{` 的是规则
+    fn is_declaration(&mut self) -> bool {
+        let Some(token) = self.peek() else {
+            return false;
+        };
+        let start = token.get_range().start_pos;
+        if token.check_type(TokenType::IdentToken)
+            && (token.get_source_code() == "&"
+                || !self.lexer.check_peek_peek_token_by_type(TokenType::Colon))
+        {
+            return false;
+        }
+        self.ends_before_block(start)
+    }
+
+    // 从 start 向后查找，先遇到 `;` 或 `}` 时返回 true，先遇到 `{` 时返回 false
+    fn ends_before_block(&self, start: usize) -> bool {
+        let syntax = self.lexer.syntax();
+        let mut chars = self.lexer.source_code[start..].chars().peekable();
+        let mut depth = 0;
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' | '\'' => {
+                    while let Some(c) = chars.next() {
+                        if c == '\\' {
+                            chars.next();
+                        } else if c == ch {
+                            break;
+                        }
+                    }
+                }
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    let mut prev = ' ';
+                    for c in chars.by_ref() {
+                        if prev == '*' && c == '/' {
+                            break;
+                        }
+                        prev = c;
+                    }
+                }
+                '/' if syntax != Syntax::Css && chars.peek() == Some(&'/') => {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                // less 插值 @{name} 和 scss 插值 #{$name}
+                '@' | '#' if syntax != Syntax::Css && chars.peek() == Some(&'{') => {
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            break;
+                        }
+                    }
+                }
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                '{' if depth <= 0 => return false,
+                ';' | '}' if depth <= 0 => return true,
+                _ => {}
+            }
+        }
+        true
+    }
+
     pub fn parse_rule(&mut self) {
         self.builder.start_node(TokenType::RuleList);
 
         self.parse_selector_list();
 
+        // less mixin 调用 .m() !important;
+        if self.is_selector_flag() {
+            self.advance();
+        }
+
+        if self.is_guard() {
+            self.parse_guard();
+        }
+
         self.parse_declaration_list();
 
         self.builder.finish_node();
     }
 
+    // 选择器后面的 less mixin 调用 .m() !important 和 scss 的 @extend .a !optional
+    // css 中 ! 留在选择器中
+    fn is_selector_flag(&mut self) -> bool {
+        let syntax = self.lexer.syntax();
+        self.peek().is_some_and(|token| {
+            token.check_type(TokenType::Important)
+                && match syntax {
+                    Syntax::Less => true,
+                    Syntax::Scss => token.get_source_code().ends_with("optional"),
+                    Syntax::Css => false,
+                }
+        })
+    }
+
+    // less 守卫 .m() when (...)，普通 css 中 when 是类型选择器
+    fn is_guard(&mut self) -> bool {
+        self.lexer.syntax() == Syntax::Less && self.check_ident("when")
+    }
+
     pub fn parse_selector_list(&mut self) {
         self.builder.start_node(TokenType::SelectorList);
         self.parse_selector();
@@ -133,10 +334,14 @@ impl<'a> Parser<'a> {
                 || self.check_token_type(TokenType::RightParenthesis)
                 || self.check_token_type(TokenType::Comma)
                 || self.check_token_type(TokenType::RightCurlyBracket)
+                || self.check_token_type(TokenType::Semi)
+                || self.is_selector_flag()
+                || self.is_guard()
             {
                 break;
             }
             self.builder.start_node(TokenType::Selector);
+            let start = self.peek().map(|t| t.get_range().start_pos);
 
             self.parse_simple_select();
 
@@ -147,6 +352,11 @@ impl<'a> Parser<'a> {
                 self.advance();
             }
 
+            // 不能出现在选择器中的 token 原样保留，避免死循环
+            if self.peek().map(|t| t.get_range().start_pos) == start {
+                self.advance();
+            }
+
             self.builder.finish_node();
         }
     }
@@ -166,6 +376,17 @@ impl<'a> Parser<'a> {
         self.builder.start_node(TokenType::Import);
         self.advance();
 
+        // less 导入选项 @import (reference, optional) "a";
+        if self.lexer.syntax() == Syntax::Less && self.check_token_type(TokenType::LeftParenthesis)
+        {
+            while !self.check_token_type(TokenType::RightParenthesis)
+                && !self.check_token_type(TokenType::EOF)
+            {
+                self.advance();
+            }
+            self.check_token_and_advance(TokenType::RightParenthesis);
+        }
+
         if self.check_token_type(TokenType::Str) || self.check_token_type(TokenType::UrlToken) {
             self.advance();
 
@@ -202,7 +423,12 @@ impl<'a> Parser<'a> {
         self.builder.start_node(TokenType::Function);
 
         self.check_token_and_advance(TokenType::FunctionToken);
-        self.parse_expr();
+        // scss 的函数调用可以有关键字参数和可变参数，例如 f($a, $b: 2, $list...)
+        if self.lexer.syntax() == Syntax::Scss {
+            self.parse_argument_list();
+        } else {
+            self.parse_expr();
+        }
         self.check_token_and_advance(TokenType::RightParenthesis);
 
         self.builder.finish_node();
@@ -228,23 +454,8 @@ impl<'a> Parser<'a> {
         if self.check_token_type(TokenType::LeftCurlyBracket) {
             self.builder.start_node(TokenType::DeclarationList);
             self.check_token_and_advance(TokenType::LeftCurlyBracket);
-            if self.check_token_type(TokenType::IdentToken)
-                && self.peek().is_some_and(|t| t.get_source_code() != '&'.to_string())
-            {
-                self.parse_declaration();
-
-                // [ ';' S* declaration? ]*
-                loop {
-                    if !self.check_token_type(TokenType::Semi) {
-                        break;
-                    }
-                    self.check_token_and_advance(TokenType::Semi);
-                    self.parse_declaration();
-                }
-            } else {
-                self.parse_entry();
-            }
-
+            // 声明、变量、嵌套规则和 at 规则可以混合出现
+            self.parse_entry();
             self.check_token_and_advance(TokenType::RightCurlyBracket);
             self.builder.finish_node();
         }
@@ -277,10 +488,19 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_expr(&mut self) {
+        self.parse_expr_with(true);
+    }
+
+    // less mixin 参数用逗号分隔时，逗号不属于参数值
+    fn parse_expr_with(&mut self, comma: bool) {
+        if self.lexer.syntax() == Syntax::Scss {
+            self.parse_scss_expr(comma, &[]);
+            return;
+        }
         self.builder.start_node(TokenType::Expression);
         self.parse_term();
         loop {
-            if self.check_token_type(TokenType::Comma)
+            if (comma && self.check_token_type(TokenType::Comma))
                 || self.check_token_type(TokenType::ForwardSlash)
                 || self.check_token_type(TokenType::Equal)
             {
@@ -297,8 +517,8 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_term(&mut self) -> bool {
-        if let Some(token) = self.peek() {
-            match token.r#type {
+        if let Some(token_type) = self.peek().map(|t| t.r#type) {
+            match token_type {
                 TokenType::Digital
                 | TokenType::Dimension
                 | TokenType::Str
@@ -317,8 +537,22 @@ impl<'a> Parser<'a> {
                     self.builder.finish_node();
                     return true;
                 }
+                // less 转义字符串 ~"..."
+                TokenType::Wave if self.lexer.check_peek_peek_token_by_type(TokenType::Str) => {
+                    self.builder.start_node(TokenType::Term);
+                    self.advance();
+                    self.advance();
+                    self.builder.finish_node();
+                    return true;
+                }
                 TokenType::AtKeywordToken => {
-                    self.parse_variable_declaration(false);
+                    // less 变量引用 @a
+                    self.builder.start_node(TokenType::Term);
+                    self.builder.start_node(TokenType::Variable);
+                    self.advance();
+                    self.builder.finish_node();
+                    self.builder.finish_node();
+                    return true;
                 }
                 TokenType::FunctionToken => {
                     self.builder.start_node(TokenType::Term);
@@ -326,6 +560,20 @@ impl<'a> Parser<'a> {
                     self.builder.finish_node();
                     return true;
                 }
+                TokenType::DollarVariable => {
+                    self.builder.start_node(TokenType::Term);
+                    self.builder.start_node(TokenType::Variable);
+                    self.advance();
+                    self.builder.finish_node();
+                    self.builder.finish_node();
+                    return true;
+                }
+                TokenType::LeftParenthesis if self.lexer.syntax() == Syntax::Scss => {
+                    self.builder.start_node(TokenType::Term);
+                    self.parse_scss_paren();
+                    self.builder.finish_node();
+                    return true;
+                }
                 TokenType::LeftParenthesis => {
                     self.advance();
                     loop {
@@ -377,42 +625,69 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_at_rule(&mut self) {
-        if self.token_eq_str(IMPORT_SYM) {
-            self.parse_import_token();
-            return;
-        }
-        if self.token_eq_str(CHARSET_SYM) {
-            self.parse_charset();
-            return;
-        }
-        if self.token_eq_str(PAGE_SYM) {
-            self.parse_page();
-            return;
-        }
-        let is_nest_at_rule = self.token_eq_str(MEDIA_SYM)
-            || self.token_eq_str(KEY_FRAMES)
-            || self.token_eq_str(W_KEY_FRAMES)
-            || self.token_eq_str(O_KEY_FRAMES)
-            || self.token_eq_str(M_KEY_FRAMES)
-            || self.token_eq_str(SUPPORTS);
-
-        let is_variable =
-            !is_nest_at_rule && self.lexer.check_peek_peek_token_by_type(TokenType::Colon);
-        if is_variable {
-            self.parse_variable_declaration(true);
-            return;
+        let handler = self.at_rule_handler();
+        if handler.is_none() {
+            if self.lexer.syntax() == Syntax::Scss && self.parse_scss_at_rule() {
+                return;
+            }
+            if self.token_eq_str(IMPORT_SYM) {
+                self.parse_import_token();
+                return;
+            }
+            if self.token_eq_str(CHARSET_SYM) {
+                self.parse_charset();
+                return;
+            }
+            if self.token_eq_str(PAGE_SYM) {
+                self.parse_page();
+                return;
+            }
+            if self.lexer.check_peek_peek_token_by_type(TokenType::Colon) {
+                self.parse_variable_declaration(true);
+                return;
+            }
+            if self.lexer.syntax() == Syntax::Less
+                && self
+                    .lexer
+                    .check_peek_peek_token_by_type(TokenType::LeftParenthesis)
+            {
+                self.parse_detached_ruleset_call();
+                return;
+            }
         }
 
         self.builder.start_node(TokenType::AtRule);
         self.check_token_and_advance(TokenType::AtKeywordToken);
         self.builder.start_node(TokenType::AtRuleParams);
+        if let Some(handler) = &handler {
+            handler.parse_prelude(self);
+        }
+        // 未注册的 at 规则按混合块解析
+        let block = handler.map_or(AtRuleBlock::Mixed, |handler| handler.block());
+        // 没有块的 at 规则缺少 ; 时，换行后的内容属于下一条规则
+        let start = self.peek().map(|token| token.get_range().start_pos);
+        let missing_semi =
+            block == AtRuleBlock::None && start.is_some_and(|start| !self.ends_before_block(start));
+        let mut prev_end = self.builder.children.last().map(|node| node.range.end_pos);
         loop {
             if let Some(node) = self.peek() {
                 match node.r#type {
-                    TokenType::LeftCurlyBracket | TokenType::EOF => {
+                    TokenType::LeftCurlyBracket | TokenType::Semi | TokenType::EOF => {
+                        break;
+                    }
+                    TokenType::RightCurlyBracket if block == AtRuleBlock::None => {
                         break;
                     }
                     _ => {
+                        let range = node.get_range();
+                        if missing_semi
+                            && prev_end.is_some_and(|end| {
+                                self.lexer.source_code[end..range.start_pos].contains('\n')
+                            })
+                        {
+                            break;
+                        }
+                        prev_end = Some(range.end_pos);
                         self.advance();
                     }
                 }
@@ -421,150 +696,689 @@ impl<'a> Parser<'a> {
             }
         }
         self.builder.finish_node();
-        if is_nest_at_rule {
-            self.parse_nest_at_rule();
+        if self.check_token_type(TokenType::Semi) {
+            // @layer a, b;
+            self.advance();
         } else {
-            self.parse_simple_at_rule();
+            match block {
+                AtRuleBlock::Rules => self.parse_nest_at_rule(),
+                AtRuleBlock::Declarations => self.parse_declaration_block(),
+                // 没有块的 at 规则不解析块
+                AtRuleBlock::None => {}
+                AtRuleBlock::Mixed => self.parse_simple_at_rule(),
+            }
         }
 
         self.builder.finish_node();
     }
 
+    // 块中只有声明，其他 token 原样保留
+    fn parse_declaration_block(&mut self) {
+        if !self.check_token_type(TokenType::LeftCurlyBracket) {
+            return;
+        }
+        self.builder.start_node(TokenType::DeclarationList);
+        self.advance();
+        while let Some(token) = self.peek() {
+            match token.r#type {
+                TokenType::RightCurlyBracket | TokenType::EOF => break,
+                TokenType::IdentToken | TokenType::Asterisk => self.parse_declaration(),
+                TokenType::AtKeywordToken => self.parse_at_rule(),
+                _ => self.advance(),
+            }
+        }
+        self.check_token_and_advance(TokenType::RightCurlyBracket);
+        self.builder.finish_node();
+    }
+
     fn parse_variable_declaration(&mut self, force: bool) {
         self.builder.start_node(TokenType::VariableDeclaration);
         self.builder.start_node(TokenType::Variable);
         self.check_token_and_advance(TokenType::AtKeywordToken);
         self.builder.finish_node();
+        let mut ruleset = false;
         if self.check_token_type(TokenType::Colon) || force {
             self.check_token_and_advance(TokenType::Colon);
-            self.parse_expr();
+            // less 分离规则集 @detached: { ... }
+            ruleset = self.check_token_type(TokenType::LeftCurlyBracket);
+            if ruleset {
+                self.builder.start_node(TokenType::Expression);
+                self.builder.start_node(TokenType::Term);
+                self.parse_declaration_list();
+                self.builder.finish_node();
+                self.builder.finish_node();
+            } else {
+                self.parse_expr();
+            }
         }
-        if self.check_token_type(TokenType::Semi) || force {
+        // 规则集后面的分号可以省略
+        if self.check_token_type(TokenType::Semi) || (force && !ruleset) {
             self.check_token_and_advance(TokenType::Semi);
         }
         self.builder.finish_node();
     }
 
+    // @detached();
+    fn parse_detached_ruleset_call(&mut self) {
+        self.builder.start_node(TokenType::DetachedRulesetCall);
+        self.builder.start_node(TokenType::Variable);
+        self.check_token_and_advance(TokenType::AtKeywordToken);
+        self.builder.finish_node();
+        self.check_token_and_advance(TokenType::LeftParenthesis);
+        self.check_token_and_advance(TokenType::RightParenthesis);
+        if self.check_token_type(TokenType::Semi) {
+            self.advance();
+        }
+        self.builder.finish_node();
+    }
+
     pub fn parse_simple_select(&mut self) {
         self.builder.start_node(TokenType::SimpleSelect);
-        self.parse_element_name();
-        self.parse_hash();
-        self.parse_class();
-        self.parse_attrib();
-        self.parse_pseudo();
-        // dbg!(self.peek());
-        // // selector function;
-        if self.check_token_type(TokenType::Dot) {
-            self.advance();
-            self.parse_function();
+        let start = self.builder.children.len();
+        loop {
+            let consumed = self.builder.children.len() > start;
+            // 空白意味着后代选择器，当前复合选择器到此结束
+            if consumed && !self.is_adjacent_token() {
+                break;
+            }
+            match self.peek().map(|t| t.r#type) {
+                Some(TokenType::IdentToken | TokenType::Asterisk) if !consumed => {
+                    self.parse_element_name();
+                }
+                Some(TokenType::HashToken | TokenType::PercentageToken) => {
+                    self.parse_hash();
+                }
+                // scss 占位符 %name
+                Some(TokenType::PlaceholderToken) => {
+                    self.advance();
+                }
+                Some(TokenType::Dot)
+                    if self
+                        .lexer
+                        .check_peek_peek_token_by_type(TokenType::IdentToken) =>
+                {
+                    self.parse_class();
+                }
+                Some(TokenType::LeftSquareBracket) => {
+                    self.parse_attrib();
+                }
+                Some(TokenType::Colon) => {
+                    self.parse_pseudo();
+                }
+                _ => break,
+            }
+        }
+        if self.check_token_type(TokenType::Dot)
+            && self
+                .lexer
+                .check_peek_peek_token_by_type(TokenType::FunctionToken)
+        {
+            self.parse_selector_function();
         }
 
         self.builder.finish_node();
     }
 
-    pub fn parse_element_name(&mut self) {
+    // ANCHOR: selector_function
+    // less mixin 的定义和调用 .m(@a; @b: 2)、.m(1px, @b: 3)
+    pub fn parse_selector_function(&mut self) {
+        self.builder.start_node(TokenType::SelectorFunction);
+        self.check_token_and_advance(TokenType::Dot);
+        self.check_token_and_advance(TokenType::FunctionToken);
+        // 参数中出现分号时用分号分隔，逗号属于参数值
+        let comma = !self.has_semicolon_args();
         loop {
-            if self.check_token_type(TokenType::IdentToken)
-                || self.check_token_type(TokenType::Asterisk)
+            match self.peek().map(|t| t.r#type) {
+                None | Some(TokenType::RightParenthesis | TokenType::EOF) => break,
+                Some(TokenType::Semi) => self.advance(),
+                Some(TokenType::Comma) if comma => self.advance(),
+                Some(TokenType::Ellipsis) => self.advance(),
+                Some(TokenType::AtKeywordToken)
+                    if self.lexer.check_peek_peek_token_by_type(TokenType::Colon) =>
+                {
+                    self.builder.start_node(TokenType::VariableDeclaration);
+                    self.builder.start_node(TokenType::Variable);
+                    self.advance();
+                    self.builder.finish_node();
+                    self.advance();
+                    self.parse_expr_with(!comma);
+                    self.builder.finish_node();
+                }
+                Some(TokenType::AtKeywordToken)
+                    if self
+                        .lexer
+                        .check_peek_peek_token_by_type(TokenType::Ellipsis) =>
+                {
+                    self.builder.start_node(TokenType::Variable);
+                    self.advance();
+                    self.advance();
+                    self.builder.finish_node();
+                }
+                _ => {
+                    self.parse_expr_with(!comma);
+                    // 无法解析为参数值的 token 原样保留，避免死循环
+                    if self
+                        .builder
+                        .children
+                        .last()
+                        .is_some_and(|n| n.child_nodes().is_empty())
+                    {
+                        self.builder.children.pop();
+                        self.advance();
+                    }
+                }
+            }
+        }
+        self.check_token_and_advance(TokenType::RightParenthesis);
+        self.builder.finish_node();
+    }
+    // ANCHOR_END: selector_function
+
+    // ANCHOR: guard
+    // less 守卫 when (@a > 0) and not (iscolor(@c)), (default())
+    // 逗号表示或，条件可以用括号嵌套
+    pub fn parse_guard(&mut self) {
+        self.builder.start_node(TokenType::Guard);
+        self.advance();
+        self.parse_guard_conditions();
+        self.builder.finish_node();
+    }
+
+    fn parse_guard_conditions(&mut self) {
+        self.parse_guard_condition();
+        while self.check_token_type(TokenType::Comma)
+            || self.check_ident("and")
+            || self.check_ident("or")
+        {
+            self.advance();
+            self.parse_guard_condition();
+        }
+    }
+
+    fn parse_guard_condition(&mut self) {
+        self.builder.start_node(TokenType::GuardCondition);
+        if self.check_ident("not") {
+            self.advance();
+        }
+        self.check_token_and_advance(TokenType::LeftParenthesis);
+        if self.check_token_type(TokenType::LeftParenthesis) || self.check_ident("not") {
+            self.parse_guard_conditions();
+        } else {
+            self.parse_guard_operand();
+            // = > < >= <= =<
+            if self.check_token_type(TokenType::Equal)
+                || self.check_token_type(TokenType::MoreThan)
+                || self.check_token_type(TokenType::LessThan)
             {
+                self.builder.start_node(TokenType::Operator);
                 self.advance();
-            } else {
-                break;
+                if self.check_token_type(TokenType::Equal)
+                    || self.check_token_type(TokenType::LessThan)
+                {
+                    self.advance();
+                }
+                self.builder.finish_node();
+                self.parse_guard_operand();
             }
         }
+        self.check_token_and_advance(TokenType::RightParenthesis);
+        self.builder.finish_node();
     }
 
-    pub fn parse_hash(&mut self) {
+    fn parse_guard_operand(&mut self) {
+        self.builder.start_node(TokenType::Expression);
+        while self.parse_term() {}
+        self.builder.finish_node();
+    }
+    // ANCHOR_END: guard
+
+    // ANCHOR: scss
+    // $name: value !default !global;
+    fn parse_scss_variable_declaration(&mut self) {
+        self.builder.start_node(TokenType::VariableDeclaration);
+        self.builder.start_node(TokenType::Variable);
+        self.check_token_and_advance(TokenType::DollarVariable);
+        self.builder.finish_node();
+        self.check_token_and_advance(TokenType::Colon);
+        self.parse_scss_expr(true, &[]);
+        while self.check_token_type(TokenType::Important) {
+            self.advance();
+        }
+        if self.check_token_type(TokenType::Semi) {
+            self.advance();
+        }
+        self.builder.finish_node();
+    }
+
+    // scss 的 @mixin、@include 和控制指令等，不是这些 at 规则时返回 false
+    fn parse_scss_at_rule(&mut self) -> bool {
+        let Some(name) = self
+            .peek()
+            .map(|t| t.get_source_code().to_ascii_lowercase())
+        else {
+            return false;
+        };
+        let node_type = match name.as_str() {
+            "@mixin" => TokenType::MixinRule,
+            "@include" => TokenType::IncludeRule,
+            "@content" => TokenType::ContentRule,
+            "@function" => TokenType::FunctionRule,
+            "@return" => TokenType::ReturnRule,
+            "@if" => TokenType::IfRule,
+            "@each" => TokenType::EachRule,
+            "@for" => TokenType::ForRule,
+            "@while" => TokenType::WhileRule,
+            "@extend" => TokenType::ExtendRule,
+            _ => return false,
+        };
+        self.builder.start_node(node_type);
+        self.advance();
+        match node_type {
+            TokenType::MixinRule | TokenType::FunctionRule => {
+                self.parse_scss_callable();
+                self.parse_declaration_list();
+            }
+            TokenType::IncludeRule => {
+                self.parse_scss_callable();
+                // 传给 @content 的内容块
+                self.parse_declaration_list();
+            }
+            TokenType::ReturnRule => self.parse_scss_expr(true, &[]),
+            TokenType::IfRule => {
+                self.parse_scss_expr(true, &[]);
+                self.parse_declaration_list();
+                while self.token_eq_str("@else") {
+                    self.builder.start_node(TokenType::ElseRule);
+                    self.advance();
+                    if self.check_ident("if") {
+                        self.advance();
+                        self.parse_scss_expr(true, &[]);
+                    }
+                    self.parse_declaration_list();
+                    self.builder.finish_node();
+                }
+            }
+            TokenType::EachRule => {
+                while self.check_token_type(TokenType::DollarVariable) {
+                    self.builder.start_node(TokenType::Variable);
+                    self.advance();
+                    self.builder.finish_node();
+                    if self.check_token_type(TokenType::Comma) {
+                        self.advance();
+                    }
+                }
+                if self.check_ident("in") {
+                    self.advance();
+                }
+                self.parse_scss_expr(true, &[]);
+                self.parse_declaration_list();
+            }
+            TokenType::ForRule => {
+                self.builder.start_node(TokenType::Variable);
+                self.check_token_and_advance(TokenType::DollarVariable);
+                self.builder.finish_node();
+                if self.check_ident("from") {
+                    self.advance();
+                }
+                self.parse_scss_expr(false, &["through", "to"]);
+                if self.check_ident("through") || self.check_ident("to") {
+                    self.advance();
+                }
+                self.parse_scss_expr(false, &[]);
+                self.parse_declaration_list();
+            }
+            TokenType::WhileRule => {
+                self.parse_scss_expr(true, &[]);
+                self.parse_declaration_list();
+            }
+            TokenType::ExtendRule => {
+                self.parse_selector_list();
+                // !optional
+                self.parse_prio();
+            }
+            _ => {}
+        }
+        if self.check_token_type(TokenType::Semi) {
+            self.advance();
+        }
+        self.builder.finish_node();
+        true
+    }
+
+    // @mixin 和 @include 后面的名称和参数列表
+    fn parse_scss_callable(&mut self) {
+        if self.check_token_type(TokenType::FunctionToken) {
+            self.advance();
+            self.parse_argument_list();
+            self.check_token_and_advance(TokenType::RightParenthesis);
+        } else if self.check_token_type(TokenType::IdentToken) {
+            self.advance();
+        }
+    }
+
+    // ($a, $b: 2, $rest...)，调用时也可以是 (1, $b: 2, $list...)
+    fn parse_argument_list(&mut self) {
+        self.builder.start_node(TokenType::ArgumentList);
         loop {
-            if self.check_token_type(TokenType::HashToken)
-                || self.check_token_type(TokenType::PercentageToken)
-            {
-                self.advance();
-            } else {
-                break;
+            match self.peek().map(|t| t.r#type) {
+                None | Some(TokenType::RightParenthesis | TokenType::EOF) => break,
+                Some(TokenType::Comma) => self.advance(),
+                Some(TokenType::DollarVariable)
+                    if self.lexer.check_peek_peek_token_by_type(TokenType::Colon) =>
+                {
+                    self.builder.start_node(TokenType::VariableDeclaration);
+                    self.builder.start_node(TokenType::Variable);
+                    self.advance();
+                    self.builder.finish_node();
+                    self.advance();
+                    self.parse_scss_expr(false, &[]);
+                    self.builder.finish_node();
+                }
+                Some(TokenType::DollarVariable)
+                    if self
+                        .lexer
+                        .check_peek_peek_token_by_type(TokenType::Ellipsis) =>
+                {
+                    self.builder.start_node(TokenType::Variable);
+                    self.advance();
+                    self.advance();
+                    self.builder.finish_node();
+                }
+                _ => self.parse_scss_operand(),
             }
         }
+        self.builder.finish_node();
     }
 
-    pub fn parse_class(&mut self) {
+    // 无法解析为值的 token 原样保留，避免死循环
+    fn parse_scss_operand(&mut self) {
+        self.parse_scss_expr(false, &[]);
+        if self
+            .builder
+            .children
+            .last()
+            .is_some_and(|n| n.child_nodes().is_empty())
+        {
+            self.builder.children.pop();
+            self.advance();
+        }
+    }
+
+    // scss 表达式中还有比较运算符和 %，遇到 stop 中的关键字时结束
+    fn parse_scss_expr(&mut self, comma: bool, stop: &[&str]) {
+        self.builder.start_node(TokenType::Expression);
         loop {
-            if self.check_token_type(TokenType::Dot)
-                && self
-                    .lexer
-                    .check_peek_peek_token_by_type(TokenType::IdentToken)
-            {
-                self.builder.start_node(TokenType::Class);
-                self.advance();
-                self.check_token_and_advance(TokenType::IdentToken);
-                self.builder.finish_node();
-            } else {
+            if stop.iter().any(|word| self.check_ident(word)) {
                 break;
             }
+            let Some(token_type) = self.peek().map(|t| t.r#type) else {
+                break;
+            };
+            match token_type {
+                // map 的键和值之间的冒号
+                TokenType::Colon => break,
+                TokenType::Comma if !comma => break,
+                TokenType::Comma
+                | TokenType::ForwardSlash
+                | TokenType::Percent
+                | TokenType::NotEqual => {
+                    self.builder.start_node(TokenType::Operator);
+                    self.advance();
+                    self.builder.finish_node();
+                }
+                // == >= <= > <
+                TokenType::Equal | TokenType::MoreThan | TokenType::LessThan => {
+                    self.builder.start_node(TokenType::Operator);
+                    self.advance();
+                    if self.check_token_type(TokenType::Equal) {
+                        self.advance();
+                    }
+                    self.builder.finish_node();
+                }
+                _ => {
+                    if !self.parse_term() {
+                        break;
+                    }
+                }
+            }
         }
+        self.builder.finish_node();
     }
 
-    pub fn parse_attrib(&mut self) {
+    // (key: value, ...) 是 map，其他括号是分组的表达式
+    fn parse_scss_paren(&mut self) {
+        if !self.is_map() {
+            self.check_token_and_advance(TokenType::LeftParenthesis);
+            self.parse_scss_expr(true, &[]);
+            self.check_token_and_advance(TokenType::RightParenthesis);
+            return;
+        }
+        self.builder.start_node(TokenType::Map);
+        self.check_token_and_advance(TokenType::LeftParenthesis);
         loop {
-            if self.check_token_type(TokenType::LeftSquareBracket) {
-                self.builder.start_node(TokenType::Attrib);
-
-                self.advance();
-                self.check_token_and_advance(TokenType::IdentToken);
+            match self.peek().map(|t| t.r#type) {
+                None | Some(TokenType::RightParenthesis | TokenType::EOF) => break,
+                Some(TokenType::Comma | TokenType::Colon) => self.advance(),
+                _ => self.parse_scss_operand(),
+            }
+        }
+        self.check_token_and_advance(TokenType::RightParenthesis);
+        self.builder.finish_node();
+    }
 
-                if let Some(node) = self.peek() {
-                    match node.r#type {
-                        TokenType::Equal
-                        | TokenType::Includes
-                        | TokenType::Dashmatch
-                        | TokenType::Exclude
-                        | TokenType::AllMatch => {
-                            self.advance();
+    // 括号中第一个逗号之前有冒号
+    fn is_map(&mut self) -> bool {
+        let Some(token) = self.peek() else {
+            return false;
+        };
+        let start = token.get_range().end_pos;
+        let mut depth = 0;
+        let mut chars = self.lexer.source_code[start..].chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' | '\'' => {
+                    while let Some(c) = chars.next() {
+                        if c == '\\' {
+                            chars.next();
+                        } else if c == ch {
+                            break;
                         }
-                        _ => {}
                     }
                 }
-
-                if let Some(node) = self.peek() {
-                    match node.r#type {
-                        TokenType::IdentToken | TokenType::Str => {
-                            self.advance();
+                '(' | '[' => depth += 1,
+                ')' | ']' if depth == 0 => return false,
+                ')' | ']' => depth -= 1,
+                ',' | ';' | '{' | '}' if depth == 0 => return false,
+                ':' if depth == 0 => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+    // ANCHOR_END: scss
+
+    fn has_semicolon_args(&mut self) -> bool {
+        let Some(token) = self.peek() else {
+            return false;
+        };
+        let start = token.get_range().start_pos;
+        let mut depth = 0;
+        let mut chars = self.lexer.source_code[start..].chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' | '\'' => {
+                    while let Some(c) = chars.next() {
+                        if c == '\\' {
+                            chars.next();
+                        } else if c == ch {
+                            break;
                         }
-                        _ => {}
                     }
                 }
-                self.check_token_and_advance(TokenType::RightSquareBracket);
-                self.builder.finish_node();
-            } else {
-                break;
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' if depth == 0 => return false,
+                ')' | ']' | '}' => depth -= 1,
+                ';' if depth == 0 => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    // 下一个 token 与上一个 token 之间没有空白
+    fn is_adjacent_token(&mut self) -> bool {
+        let last_end = self.builder.children.last().map(|n| n.range.end_pos);
+        match (self.peek().map(|t| t.get_range().start_pos), last_end) {
+            (Some(start), Some(end)) => start == end,
+            _ => false,
+        }
+    }
+
+    pub fn parse_element_name(&mut self) {
+        if self.check_token_type(TokenType::IdentToken)
+            || self.check_token_type(TokenType::Asterisk)
+        {
+            self.advance();
+        }
+    }
+
+    pub fn parse_hash(&mut self) {
+        if self.check_token_type(TokenType::HashToken)
+            || self.check_token_type(TokenType::PercentageToken)
+        {
+            self.advance();
+        }
+    }
+
+    pub fn parse_class(&mut self) {
+        if self.check_token_type(TokenType::Dot)
+            && self
+                .lexer
+                .check_peek_peek_token_by_type(TokenType::IdentToken)
+        {
+            self.builder.start_node(TokenType::Class);
+            self.advance();
+            self.check_token_and_advance(TokenType::IdentToken);
+            self.builder.finish_node();
+        }
+    }
+
+    pub fn parse_attrib(&mut self) {
+        if self.check_token_type(TokenType::LeftSquareBracket) {
+            self.builder.start_node(TokenType::Attrib);
+
+            self.advance();
+            self.check_token_and_advance(TokenType::IdentToken);
+
+            if let Some(node) = self.peek() {
+                match node.r#type {
+                    TokenType::Equal
+                    | TokenType::Includes
+                    | TokenType::Dashmatch
+                    | TokenType::Exclude
+                    | TokenType::AllMatch
+                    | TokenType::SuffixMatch => {
+                        self.advance();
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(node) = self.peek() {
+                match node.r#type {
+                    TokenType::IdentToken | TokenType::Str => {
+                        self.advance();
+                    }
+                    _ => {}
+                }
             }
+            self.check_token_and_advance(TokenType::RightSquareBracket);
+            self.builder.finish_node();
         }
     }
 
     pub fn parse_pseudo(&mut self) {
-        loop {
+        if self.check_token_type(TokenType::Colon) {
+            self.advance();
             if self.check_token_type(TokenType::Colon) {
                 self.advance();
-                if self.check_token_type(TokenType::Colon) {
-                    self.advance();
-                }
+            }
 
-                if self.check_token_type(TokenType::IdentToken) {
-                    self.advance();
-                }
-                if self.check_token_type(TokenType::FunctionToken) {
-                    self.builder.start_node(TokenType::Function);
+            if self.check_token_type(TokenType::IdentToken) {
+                self.advance();
+            } else if self.check_token_type(TokenType::FunctionToken) {
+                let takes_selector = self.peek().is_some_and(|t| {
+                    let name = t.get_source_code().to_ascii_lowercase();
+                    SELECTOR_PSEUDO_FUNCTIONS.contains(&name.as_str())
+                });
+                self.builder.start_node(TokenType::Function);
 
-                    self.check_token_and_advance(TokenType::FunctionToken);
+                self.check_token_and_advance(TokenType::FunctionToken);
 
-                    self.parse_expr();
+                if takes_selector {
                     self.parse_selector_list();
-                    self.check_token_and_advance(TokenType::RightParenthesis);
-
-                    self.builder.finish_node();
+                } else {
+                    self.parse_expr();
                 }
-            } else {
-                break;
+                self.check_token_and_advance(TokenType::RightParenthesis);
+
+                self.builder.finish_node();
             }
         }
     }
 }
+
+// 解析完整样式表，返回语法树
+pub fn parse_stylesheet(source: &str) -> AstTree<TokenType> {
+    parse_stylesheet_with_syntax(source, Syntax::Css)
+}
+
+pub fn parse_stylesheet_with_syntax(source: &str, syntax: Syntax) -> AstTree<TokenType> {
+    let mut lexer = Lexer::with_syntax(source, syntax);
+    let mut builder = AstTreeBuilder::new();
+    let mut parser = Parser::new(&mut lexer, &mut builder);
+    parser.parse();
+    builder.ast_tree
+}
+
+// 解析单条声明，例如 "color: red !important"
+pub fn parse_single_declaration(source: &str) -> Option<AstNode<TokenType>> {
+    let tree = parse_stylesheet(&format!("a{{{}}}", source));
+    tree.root?
+        .find_child(TokenType::RuleList)?
+        .find_child(TokenType::DeclarationList)?
+        .find_child(TokenType::Declaration)
+        .cloned()
+}
+
+// 解析单个属性值，返回 Expression 节点
+pub fn parse_value(source: &str) -> Option<AstNode<TokenType>> {
+    parse_single_declaration(&format!("b:{}", source))?
+        .find_child(TokenType::Expression)
+        .cloned()
+}
+
+// 按指定语法解析单个属性值，例如 scss 插值 #{$a + 1} 中的表达式
+pub fn parse_value_with_syntax(source: &str, syntax: Syntax) -> Option<AstNode<TokenType>> {
+    let tree = parse_stylesheet_with_syntax(&format!("a{{b:{}}}", source), syntax);
+    tree.root?
+        .find_child(TokenType::RuleList)?
+        .find_child(TokenType::DeclarationList)?
+        .find_child(TokenType::Declaration)?
+        .find_child(TokenType::Expression)
+        .cloned()
+}
+
+// 解析选择器，返回 SelectorList 节点
+pub fn parse_selector(source: &str) -> Option<AstNode<TokenType>> {
+    parse_selector_with_syntax(source, Syntax::Css)
+}
+
+// scss 的选择器中可以有占位符 %name
+pub fn parse_selector_with_syntax(source: &str, syntax: Syntax) -> Option<AstNode<TokenType>> {
+    let tree = parse_stylesheet_with_syntax(&format!("{}{{}}", source), syntax);
+    tree.root?
+        .find_child(TokenType::RuleList)?
+        .find_child(TokenType::SelectorList)
+        .cloned()
+}
@@ -0,0 +1,876 @@
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use crate::{
+    ast::{AstNode, AstNodeType, AstTree},
+    cascade::property_name,
+    codegen::{declaration_to_string, selector_to_string, tokens_to_string},
+    parser::{parse_selector, parse_single_declaration},
+    targets::{
+        Browser::{self, *},
+        Targets, Version,
+    },
+    token_type::TokenType,
+    values::format_number,
+};
+
+// ANCHOR: prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Prefix {
+    Webkit,
+    Moz,
+    Ms,
+}
+// ANCHOR_END: prefix
+
+use Prefix::*;
+
+impl Prefix {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Webkit => "-webkit-",
+            Moz => "-moz-",
+            Ms => "-ms-",
+        }
+    }
+
+    // -webkit-transform => (Webkit, "transform")
+    pub fn strip(name: &str) -> Option<(Prefix, &str)> {
+        [Webkit, Moz, Ms].into_iter().find_map(|prefix| {
+            let len = prefix.as_str().len();
+            (name.len() > len && name[..len].eq_ignore_ascii_case(prefix.as_str()))
+                .then(|| (prefix, &name[len..]))
+        })
+    }
+}
+
+// ANCHOR: prefix_table
+// 目标浏览器的版本低于表中的版本时需要加对应的前缀
+type Support = &'static [(Browser, Version, Prefix)];
+
+const fn v(major: u32, minor: u32) -> Version {
+    Version::new(major, minor)
+}
+
+const ALL: Version = Version::ALL;
+
+const ANIMATION: Support = &[
+    (Chrome, v(43, 0), Webkit),
+    (Safari, v(9, 0), Webkit),
+    (IosSaf, v(9, 0), Webkit),
+    (Android, v(5, 0), Webkit),
+    (Opera, v(30, 0), Webkit),
+    (Firefox, v(16, 0), Moz),
+];
+
+const TRANSFORM: Support = &[
+    (Chrome, v(36, 0), Webkit),
+    (Safari, v(9, 0), Webkit),
+    (IosSaf, v(9, 0), Webkit),
+    (Android, v(5, 0), Webkit),
+    (Opera, v(23, 0), Webkit),
+    (Firefox, v(16, 0), Moz),
+    (Ie, v(10, 0), Ms),
+];
+
+const TRANSITION: Support = &[
+    (Chrome, v(26, 0), Webkit),
+    (Safari, v(7, 0), Webkit),
+    (IosSaf, v(7, 0), Webkit),
+    (Android, v(4, 4), Webkit),
+    (Firefox, v(16, 0), Moz),
+];
+
+const FLEXBOX: Support = &[
+    (Chrome, v(29, 0), Webkit),
+    (Safari, v(9, 0), Webkit),
+    (IosSaf, v(9, 0), Webkit),
+    (Android, v(4, 4), Webkit),
+    (Ie, v(11, 0), Ms),
+];
+
+// ie 10 使用 2012 年草案的语法，这些属性的名称不同，例如 -ms-flex-pack，只加 -webkit- 前缀
+const FLEXBOX_WEBKIT: Support = &[
+    (Chrome, v(29, 0), Webkit),
+    (Safari, v(9, 0), Webkit),
+    (IosSaf, v(9, 0), Webkit),
+    (Android, v(4, 4), Webkit),
+];
+
+const COLUMNS: Support = &[
+    (Chrome, v(50, 0), Webkit),
+    (Safari, v(9, 0), Webkit),
+    (IosSaf, v(9, 0), Webkit),
+    (Opera, v(37, 0), Webkit),
+    (Firefox, v(52, 0), Moz),
+];
+
+const MASK: Support = &[
+    (Chrome, v(120, 0), Webkit),
+    (Edge, v(120, 0), Webkit),
+    (Safari, v(15, 4), Webkit),
+    (IosSaf, v(15, 4), Webkit),
+    (Opera, v(106, 0), Webkit),
+    (Samsung, v(25, 0), Webkit),
+];
+
+const TEXT_DECORATION: Support = &[
+    (Safari, v(12, 1), Webkit),
+    (IosSaf, v(12, 2), Webkit),
+    (Firefox, v(36, 0), Moz),
+];
+
+const TEXT_EMPHASIS: Support = &[
+    (Chrome, v(99, 0), Webkit),
+    (Edge, v(99, 0), Webkit),
+    (Safari, v(7, 0), Webkit),
+    (Opera, v(85, 0), Webkit),
+];
+
+const PROPERTIES: &[(&str, Support)] = &[
+    ("animation", ANIMATION),
+    ("animation-delay", ANIMATION),
+    ("animation-direction", ANIMATION),
+    ("animation-duration", ANIMATION),
+    ("animation-fill-mode", ANIMATION),
+    ("animation-iteration-count", ANIMATION),
+    ("animation-name", ANIMATION),
+    ("animation-play-state", ANIMATION),
+    ("animation-timing-function", ANIMATION),
+    ("transform", TRANSFORM),
+    ("transform-origin", TRANSFORM),
+    ("transform-style", TRANSFORM),
+    ("perspective", TRANSFORM),
+    ("perspective-origin", TRANSFORM),
+    (
+        "backface-visibility",
+        &[
+            (Chrome, v(36, 0), Webkit),
+            (Safari, v(15, 4), Webkit),
+            (IosSaf, v(15, 4), Webkit),
+            (Firefox, v(16, 0), Moz),
+        ],
+    ),
+    ("transition", TRANSITION),
+    ("transition-delay", TRANSITION),
+    ("transition-duration", TRANSITION),
+    ("transition-property", TRANSITION),
+    ("transition-timing-function", TRANSITION),
+    ("flex", FLEXBOX),
+    ("flex-basis", FLEXBOX_WEBKIT),
+    ("flex-direction", FLEXBOX),
+    ("flex-flow", FLEXBOX),
+    ("flex-grow", FLEXBOX_WEBKIT),
+    ("flex-shrink", FLEXBOX_WEBKIT),
+    ("flex-wrap", FLEXBOX),
+    ("order", FLEXBOX_WEBKIT),
+    ("justify-content", FLEXBOX_WEBKIT),
+    ("align-items", FLEXBOX_WEBKIT),
+    ("align-self", FLEXBOX_WEBKIT),
+    ("align-content", FLEXBOX_WEBKIT),
+    ("columns", COLUMNS),
+    ("column-count", COLUMNS),
+    ("column-fill", COLUMNS),
+    ("column-gap", COLUMNS),
+    ("column-rule", COLUMNS),
+    ("column-rule-color", COLUMNS),
+    ("column-rule-style", COLUMNS),
+    ("column-rule-width", COLUMNS),
+    ("column-span", COLUMNS),
+    ("column-width", COLUMNS),
+    ("mask", MASK),
+    ("mask-clip", MASK),
+    ("mask-image", MASK),
+    ("mask-origin", MASK),
+    ("mask-position", MASK),
+    ("mask-repeat", MASK),
+    ("mask-size", MASK),
+    ("text-decoration-color", TEXT_DECORATION),
+    ("text-decoration-line", TEXT_DECORATION),
+    ("text-decoration-style", TEXT_DECORATION),
+    ("text-emphasis", TEXT_EMPHASIS),
+    ("text-emphasis-color", TEXT_EMPHASIS),
+    ("text-emphasis-position", TEXT_EMPHASIS),
+    ("text-emphasis-style", TEXT_EMPHASIS),
+    (
+        "user-select",
+        &[
+            (Chrome, v(54, 0), Webkit),
+            (Safari, ALL, Webkit),
+            (IosSaf, ALL, Webkit),
+            (Firefox, v(69, 0), Moz),
+            (Ie, ALL, Ms),
+            (Edge, v(79, 0), Ms),
+        ],
+    ),
+    (
+        "appearance",
+        &[
+            (Chrome, v(84, 0), Webkit),
+            (Edge, v(84, 0), Webkit),
+            (Safari, v(15, 4), Webkit),
+            (IosSaf, v(15, 4), Webkit),
+            (Opera, v(70, 0), Webkit),
+            (Samsung, v(14, 0), Webkit),
+            (Firefox, v(80, 0), Moz),
+        ],
+    ),
+    (
+        "backdrop-filter",
+        &[(Safari, v(18, 0), Webkit), (IosSaf, v(18, 0), Webkit)],
+    ),
+    (
+        "box-decoration-break",
+        &[
+            (Chrome, ALL, Webkit),
+            (Edge, ALL, Webkit),
+            (Safari, ALL, Webkit),
+            (IosSaf, ALL, Webkit),
+        ],
+    ),
+    (
+        "box-sizing",
+        &[
+            (Chrome, v(10, 0), Webkit),
+            (Safari, v(5, 1), Webkit),
+            (Android, v(4, 0), Webkit),
+            (Firefox, v(29, 0), Moz),
+        ],
+    ),
+    (
+        "clip-path",
+        &[
+            (Chrome, v(55, 0), Webkit),
+            (Safari, v(13, 1), Webkit),
+            (IosSaf, v(13, 4), Webkit),
+            (Opera, v(42, 0), Webkit),
+        ],
+    ),
+    (
+        "filter",
+        &[
+            (Chrome, v(53, 0), Webkit),
+            (Safari, v(9, 1), Webkit),
+            (IosSaf, v(9, 3), Webkit),
+            (Opera, v(40, 0), Webkit),
+        ],
+    ),
+    (
+        "font-feature-settings",
+        &[(Chrome, v(48, 0), Webkit), (Firefox, v(34, 0), Moz)],
+    ),
+    (
+        "hyphens",
+        &[
+            (Safari, v(17, 0), Webkit),
+            (IosSaf, v(17, 0), Webkit),
+            (Firefox, v(43, 0), Moz),
+            (Ie, ALL, Ms),
+            (Edge, v(79, 0), Ms),
+        ],
+    ),
+    (
+        "print-color-adjust",
+        &[
+            (Chrome, ALL, Webkit),
+            (Edge, ALL, Webkit),
+            (Safari, v(15, 4), Webkit),
+            (IosSaf, v(15, 4), Webkit),
+        ],
+    ),
+    ("tab-size", &[(Firefox, v(91, 0), Moz)]),
+    (
+        "text-size-adjust",
+        &[(IosSaf, ALL, Webkit), (Edge, v(79, 0), Ms)],
+    ),
+    ("text-orientation", &[(Safari, v(14, 0), Webkit)]),
+];
+
+const GRADIENT: Support = &[
+    (Chrome, v(26, 0), Webkit),
+    (Safari, v(7, 0), Webkit),
+    (IosSaf, v(7, 0), Webkit),
+    (Android, v(4, 4), Webkit),
+    (Firefox, v(16, 0), Moz),
+];
+
+const SIZING: &[&str] = &[
+    "width",
+    "height",
+    "min-width",
+    "min-height",
+    "max-width",
+    "max-height",
+];
+
+const INTRINSIC_SIZE: Support = &[
+    (Chrome, v(46, 0), Webkit),
+    (Safari, v(11, 0), Webkit),
+    (IosSaf, v(11, 0), Webkit),
+    (Firefox, v(66, 0), Moz),
+];
+
+// 属性值中的关键字和函数，属性列表为空时表示任意属性
+const VALUES: &[(&[&str], &str, Support)] = &[
+    (&[], "linear-gradient(", GRADIENT),
+    (&[], "repeating-linear-gradient(", GRADIENT),
+    (&[], "radial-gradient(", GRADIENT),
+    (&[], "repeating-radial-gradient(", GRADIENT),
+    (
+        &[],
+        "calc(",
+        &[
+            (Chrome, v(26, 0), Webkit),
+            (Safari, v(6, 1), Webkit),
+            (IosSaf, v(7, 0), Webkit),
+            (Firefox, v(16, 0), Moz),
+        ],
+    ),
+    (
+        &[],
+        "image-set(",
+        &[
+            (Chrome, v(113, 0), Webkit),
+            (Edge, v(113, 0), Webkit),
+            (Safari, v(14, 0), Webkit),
+            (IosSaf, v(14, 0), Webkit),
+            (Opera, v(99, 0), Webkit),
+        ],
+    ),
+    (
+        &[],
+        "cross-fade(",
+        &[
+            (Chrome, ALL, Webkit),
+            (Edge, ALL, Webkit),
+            (Safari, v(10, 0), Webkit),
+            (IosSaf, v(10, 0), Webkit),
+        ],
+    ),
+    (&[], "element(", &[(Firefox, ALL, Moz)]),
+    (
+        &["position"],
+        "sticky",
+        &[(Safari, v(13, 0), Webkit), (IosSaf, v(13, 0), Webkit)],
+    ),
+    (&["display"], "flex", FLEXBOX),
+    (&["display"], "inline-flex", FLEXBOX),
+    (SIZING, "min-content", INTRINSIC_SIZE),
+    (SIZING, "max-content", INTRINSIC_SIZE),
+    (
+        SIZING,
+        "fit-content",
+        &[
+            (Chrome, v(46, 0), Webkit),
+            (Safari, v(11, 0), Webkit),
+            (IosSaf, v(11, 0), Webkit),
+            (Firefox, v(94, 0), Moz),
+        ],
+    ),
+];
+
+// 伪类和伪元素的前缀写法各不相同，直接列出替换后的名字
+type SelectorSupport = &'static [(Browser, Version, &'static str)];
+
+const SELECTORS: &[(&str, SelectorSupport)] = &[
+    (
+        "::placeholder",
+        &[
+            (Chrome, v(57, 0), "::-webkit-input-placeholder"),
+            (Safari, v(10, 1), "::-webkit-input-placeholder"),
+            (IosSaf, v(10, 3), "::-webkit-input-placeholder"),
+            (Android, v(57, 0), "::-webkit-input-placeholder"),
+            (Firefox, v(51, 0), "::-moz-placeholder"),
+            (Ie, ALL, ":-ms-input-placeholder"),
+            (Edge, v(79, 0), "::-ms-input-placeholder"),
+        ],
+    ),
+    ("::selection", &[(Firefox, v(62, 0), "::-moz-selection")]),
+    (
+        ":fullscreen",
+        &[
+            (Chrome, v(71, 0), ":-webkit-full-screen"),
+            (Safari, v(16, 4), ":-webkit-full-screen"),
+            (Firefox, v(64, 0), ":-moz-full-screen"),
+            (Ie, ALL, ":-ms-fullscreen"),
+            (Edge, v(79, 0), ":-ms-fullscreen"),
+        ],
+    ),
+    (
+        "::file-selector-button",
+        &[
+            (Chrome, v(89, 0), "::-webkit-file-upload-button"),
+            (Edge, v(89, 0), "::-webkit-file-upload-button"),
+            (Safari, v(14, 1), "::-webkit-file-upload-button"),
+            (IosSaf, v(14, 5), "::-webkit-file-upload-button"),
+        ],
+    ),
+    (
+        "::backdrop",
+        &[
+            (Safari, v(15, 4), "::-webkit-backdrop"),
+            (IosSaf, v(15, 4), "::-webkit-backdrop"),
+        ],
+    ),
+    (
+        ":any-link",
+        &[
+            (Chrome, v(65, 0), ":-webkit-any-link"),
+            (Safari, v(9, 0), ":-webkit-any-link"),
+            (Firefox, v(50, 0), ":-moz-any-link"),
+        ],
+    ),
+    (
+        ":autofill",
+        &[
+            (Chrome, v(110, 0), ":-webkit-autofill"),
+            (Edge, v(110, 0), ":-webkit-autofill"),
+            (Safari, ALL, ":-webkit-autofill"),
+            (IosSaf, ALL, ":-webkit-autofill"),
+        ],
+    ),
+    (":read-only", &[(Firefox, v(78, 0), ":-moz-read-only")]),
+    (":read-write", &[(Firefox, v(78, 0), ":-moz-read-write")]),
+];
+
+const AT_RULES: &[(&str, Support)] = &[("keyframes", ANIMATION)];
+// ANCHOR_END: prefix_table
+
+// ANCHOR: prefix_options
+#[derive(Debug, Clone, Default)]
+pub struct PrefixOptions {
+    pub targets: Targets,
+    // 保留目标浏览器已经不需要的前缀
+    pub keep_outdated: bool,
+}
+// ANCHOR_END: prefix_options
+
+fn needed(support: Support, targets: &Targets, context: Option<Prefix>) -> Vec<Prefix> {
+    let mut prefixes: Vec<Prefix> = support
+        .iter()
+        .filter(|(browser, until, _)| targets.any_below(*browser, *until))
+        .map(|(_, _, prefix)| *prefix)
+        .filter(|prefix| context.is_none_or(|c| c == *prefix))
+        .collect();
+    prefixes.sort();
+    prefixes.dedup();
+    prefixes
+}
+
+fn property_support(property: &str) -> Option<Support> {
+    PROPERTIES
+        .iter()
+        .find(|(name, _)| *name == property)
+        .map(|(_, support)| *support)
+}
+
+fn value_support(property: &str, word: &str) -> Option<Support> {
+    VALUES
+        .iter()
+        .find(|(properties, name, _)| {
+            name.eq_ignore_ascii_case(word)
+                && (properties.is_empty() || properties.contains(&property))
+        })
+        .map(|(_, _, support)| *support)
+}
+
+// ANCHOR: autoprefix
+// 按目标浏览器补充带前缀的声明、属性值、选择器和 @keyframes，并删除过时的前缀
+// 返回新增和删除的节点数量
+pub fn autoprefix(tree: &mut AstTree<TokenType>, options: &PrefixOptions) -> usize {
+    match &mut tree.root {
+        Some(root) => prefix_node(root, options, None),
+        None => 0,
+    }
+}
+// ANCHOR_END: autoprefix
+
+fn prefix_node(
+    node: &mut AstNode<TokenType>,
+    options: &PrefixOptions,
+    context: Option<Prefix>,
+) -> usize {
+    let is_list = node.check_type(TokenType::DeclarationList);
+    let Some(children) = &mut node.children else {
+        return 0;
+    };
+    let mut count = 0;
+    if is_list {
+        count += prefix_declarations(children, options, context);
+    }
+    count += prefix_rules(children, options, context);
+    for child in children.iter_mut() {
+        // @-webkit-keyframes 中只需要 -webkit- 前缀
+        let context = at_rule_name(child)
+            .and_then(|name| Prefix::strip(&name[1..]).map(|(prefix, _)| prefix))
+            .or(context);
+        count += prefix_node(child, options, context);
+    }
+    count
+}
+
+fn at_rule_name(node: &AstNode<TokenType>) -> Option<String> {
+    if !node.check_type(TokenType::AtRule) {
+        return None;
+    }
+    node.child_nodes()
+        .first()
+        .map(|n| n.raw.to_ascii_lowercase())
+}
+
+fn at_rule_params(node: &AstNode<TokenType>) -> String {
+    node.find_child(TokenType::AtRuleParams)
+        .map(|params| tokens_to_string(params.child_nodes()))
+        .unwrap_or_default()
+}
+
+// 处理块中的规则：带前缀的选择器需要单独成为一条规则，否则整条规则都会失效
+fn prefix_rules(
+    children: &mut Vec<AstNode<TokenType>>,
+    options: &PrefixOptions,
+    context: Option<Prefix>,
+) -> usize {
+    let targets = &options.targets;
+    let selectors: Vec<String> = children
+        .iter()
+        .filter_map(|n| n.find_child(TokenType::SelectorList))
+        .map(selector_to_string)
+        .collect();
+    let at_rules: Vec<(String, String)> = children
+        .iter()
+        .filter_map(|n| at_rule_name(n).map(|name| (name, at_rule_params(n))))
+        .collect();
+    let mut count = 0;
+    let mut result = Vec::with_capacity(children.len());
+    for child in children.drain(..) {
+        if let Some(selector) = child
+            .find_child(TokenType::SelectorList)
+            .filter(|_| child.check_type(TokenType::RuleList))
+        {
+            let text = selector_to_string(selector);
+            if !options.keep_outdated
+                && outdated_selector(&text, targets).is_some_and(|s| selectors.contains(&s))
+            {
+                count += 1;
+                continue;
+            }
+            for prefixed in prefixed_selectors(&text, targets, context) {
+                if selectors.contains(&prefixed) {
+                    continue;
+                }
+                if let Some(list) = parse_selector(&prefixed) {
+                    let mut rule = child.clone();
+                    for node in rule.children.iter_mut().flatten() {
+                        if node.check_type(TokenType::SelectorList) {
+                            *node = list.clone();
+                        }
+                    }
+                    result.push(rule);
+                    count += 1;
+                }
+            }
+        } else if let Some(name) = at_rule_name(&child) {
+            let params = at_rule_params(&child);
+            if let Some((prefix, name)) = Prefix::strip(&name[1..]) {
+                let outdated = AT_RULES.iter().any(|(rule, support)| {
+                    *rule == name && !needed(support, targets, None).contains(&prefix)
+                });
+                let unprefixed = (format!("@{}", name), params.clone());
+                if !options.keep_outdated && outdated && at_rules.contains(&unprefixed) {
+                    count += 1;
+                    continue;
+                }
+            } else if let Some((rule, support)) =
+                AT_RULES.iter().find(|(rule, _)| name[1..] == **rule)
+            {
+                for prefix in needed(support, targets, context) {
+                    let prefixed = format!("@{}{}", prefix.as_str(), rule);
+                    if at_rules.contains(&(prefixed.clone(), params.clone())) {
+                        continue;
+                    }
+                    let mut at_rule = child.clone();
+                    if let Some(keyword) = at_rule.children.iter_mut().flatten().next() {
+                        keyword.raw = prefixed;
+                    }
+                    result.push(at_rule);
+                    count += 1;
+                }
+            }
+        }
+        result.push(child);
+    }
+    *children = result;
+    count
+}
+
+fn prefixed_selectors(text: &str, targets: &Targets, context: Option<Prefix>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for (pseudo, rows) in SELECTORS {
+        if !text.contains(pseudo) {
+            continue;
+        }
+        for (browser, until, name) in rows.iter() {
+            let matches_context = context.is_none_or(|c| name.contains(c.as_str()));
+            let prefixed = text.replace(pseudo, name);
+            if targets.any_below(*browser, *until) && matches_context && !result.contains(&prefixed)
+            {
+                result.push(prefixed);
+            }
+        }
+    }
+    result
+}
+
+// 选择器中含有目标浏览器都不需要的前缀时，返回对应的无前缀选择器
+fn outdated_selector(text: &str, targets: &Targets) -> Option<String> {
+    SELECTORS.iter().find_map(|(pseudo, rows)| {
+        rows.iter().find_map(|(_, _, name)| {
+            let used = rows
+                .iter()
+                .any(|(browser, until, n)| n == name && targets.any_below(*browser, *until));
+            (text.contains(name) && !used).then(|| text.replace(name, pseudo))
+        })
+    })
+}
+
+fn prefix_declarations(
+    children: &mut Vec<AstNode<TokenType>>,
+    options: &PrefixOptions,
+    context: Option<Prefix>,
+) -> usize {
+    let targets = &options.targets;
+    let properties: Vec<String> = children
+        .iter()
+        .filter(|n| n.check_type(TokenType::Declaration))
+        .map(property_name)
+        .collect();
+    let existing: Vec<String> = children
+        .iter()
+        .filter(|n| n.check_type(TokenType::Declaration))
+        .map(declaration_to_string)
+        .collect();
+    let mut count = 0;
+    let mut skip_semi = false;
+    let mut result = Vec::with_capacity(children.len());
+    for mut child in children.drain(..) {
+        if !child.check_type(TokenType::Declaration) {
+            if !(skip_semi && child.check_type(TokenType::Semi)) {
+                result.push(child);
+            }
+            skip_semi = false;
+            continue;
+        }
+        skip_semi = false;
+        let property = property_name(&child);
+        if !options.keep_outdated {
+            if let Some((prefix, name)) = Prefix::strip(&property) {
+                if property_support(name)
+                    .is_some_and(|support| !needed(support, targets, None).contains(&prefix))
+                {
+                    count += 1;
+                    // 没有无前缀的写法时直接改成无前缀的属性
+                    if properties.iter().any(|p| p == name) {
+                        skip_semi = true;
+                    } else {
+                        rename_property(&mut child, name);
+                        result.push(child);
+                    }
+                    continue;
+                }
+            }
+            let repeated = properties.iter().filter(|p| **p == property).count() > 1;
+            if repeated && has_outdated_value(&child, &property, targets) {
+                count += 1;
+                skip_semi = true;
+                continue;
+            }
+        }
+        let mut added = Vec::new();
+        if let Some(support) = property_support(&property) {
+            for prefix in needed(support, targets, context) {
+                let name = format!("{}{}", prefix.as_str(), property);
+                if properties.contains(&name) {
+                    continue;
+                }
+                let mut declaration = child.clone();
+                rename_property(&mut declaration, &name);
+                prefix_transition_value(&mut declaration, &property, prefix, targets);
+                added.push(declaration);
+            }
+        }
+        for prefix in [Webkit, Moz, Ms] {
+            let skip = Prefix::strip(&property).is_some_and(|(p, _)| p != prefix)
+                || context.is_some_and(|c| c != prefix);
+            if skip {
+                continue;
+            }
+            if let Some(declaration) = prefix_value(&child, &property, prefix, targets) {
+                if !existing.contains(&declaration_to_string(&declaration)) {
+                    added.push(declaration);
+                }
+            }
+        }
+        for declaration in added {
+            count += 1;
+            result.push(declaration);
+            result.push(semi());
+        }
+        result.push(child);
+    }
+    *children = result;
+    count
+}
+
+fn semi() -> AstNode<TokenType> {
+    AstNode {
+        node_type: AstNodeType(TokenType::Semi),
+        raw: ";".to_string(),
+        ..Default::default()
+    }
+}
+
+fn rename_property(declaration: &mut AstNode<TokenType>, name: &str) {
+    for node in declaration.children.iter_mut().flatten() {
+        if node.check_type(TokenType::Property) {
+            node.raw = name.to_string();
+            for ident in node.children.iter_mut().flatten() {
+                if ident.check_type(TokenType::IdentToken) {
+                    ident.raw = name.to_string();
+                }
+            }
+        }
+    }
+}
+
+// 值中的标识符和函数名，例如 sticky、linear-gradient(
+fn value_words(node: &AstNode<TokenType>, words: &mut Vec<AstNode<TokenType>>) {
+    for child in node.child_nodes() {
+        match child.node_type.0 {
+            TokenType::IdentToken | TokenType::FunctionToken => words.push(child.clone()),
+            _ => value_words(child, words),
+        }
+    }
+}
+
+fn has_outdated_value(declaration: &AstNode<TokenType>, property: &str, targets: &Targets) -> bool {
+    let mut words = Vec::new();
+    value_words(declaration, &mut words);
+    words.iter().any(|word| {
+        Prefix::strip(&word.raw).is_some_and(|(prefix, name)| {
+            value_support(property, name)
+                .is_some_and(|support| !needed(support, targets, None).contains(&prefix))
+        })
+    })
+}
+
+// 把值中需要前缀的关键字和函数加上前缀，生成新的声明
+fn prefix_value(
+    declaration: &AstNode<TokenType>,
+    property: &str,
+    prefix: Prefix,
+    targets: &Targets,
+) -> Option<AstNode<TokenType>> {
+    let unprefixed = Prefix::strip(property).map_or(property, |(_, name)| name);
+    let mut declaration = declaration.clone();
+    let mut changed = false;
+    rewrite_words(&mut declaration, &mut |word| {
+        let support = value_support(unprefixed, &word.raw)?;
+        if !needed(support, targets, Some(prefix)).is_empty() {
+            changed = true;
+            // ie 10 中是 display: -ms-flexbox
+            let suffix = match prefix {
+                Ms if word.raw.eq_ignore_ascii_case("flex")
+                    || word.raw.eq_ignore_ascii_case("inline-flex") =>
+                {
+                    "box"
+                }
+                _ => "",
+            };
+            return Some(format!("{}{}{}", prefix.as_str(), word.raw, suffix));
+        }
+        None
+    });
+    if !changed {
+        return None;
+    }
+    let text = legacy_gradient(&declaration_to_string(&declaration))?;
+    parse_single_declaration(&text)
+}
+
+fn rewrite_words(
+    node: &mut AstNode<TokenType>,
+    rewrite: &mut dyn FnMut(&AstNode<TokenType>) -> Option<String>,
+) {
+    for child in node.children.iter_mut().flatten() {
+        if child.check_type(TokenType::Property) {
+            continue;
+        }
+        if child.check_type(TokenType::IdentToken) || child.check_type(TokenType::FunctionToken) {
+            if let Some(raw) = rewrite(child) {
+                child.raw = raw;
+            }
+        } else {
+            rewrite_words(child, rewrite);
+        }
+    }
+}
+
+// 旧的渐变语法用起点表示方向，角度也按另一种方式计算
+fn legacy_gradient(text: &str) -> Option<String> {
+    static GRADIENT: OnceLock<Regex> = OnceLock::new();
+    let pattern = GRADIENT.get_or_init(|| {
+        Regex::new(
+            r"(-(?:webkit|moz)-(?:repeating-)?(?:linear|radial)-gradient\()\s*(to\s+[a-z]+(?:\s+[a-z]+)?|-?[\d.]+deg)?",
+        )
+        .unwrap()
+    });
+    let result = pattern.replace_all(text, |caps: &Captures| {
+        let direction = caps.get(2).map_or("", |m| m.as_str());
+        let direction = if let Some(sides) = direction.strip_prefix("to") {
+            sides
+                .split_whitespace()
+                .map(opposite)
+                .collect::<Vec<_>>()
+                .join(" ")
+        } else if let Some(angle) = direction.strip_suffix("deg") {
+            let angle: f64 = angle.parse().unwrap_or_default();
+            format!("{}deg", format_number((450.0 - angle).rem_euclid(360.0)))
+        } else {
+            String::new()
+        };
+        format!("{}{}", &caps[1], direction)
+    });
+    // 旧语法的径向渐变不支持 at 写法
+    if result.contains("radial-gradient(") && result.contains(" at ") {
+        return None;
+    }
+    Some(result.into_owned())
+}
+
+fn opposite(side: &str) -> &str {
+    match side {
+        "top" => "bottom",
+        "bottom" => "top",
+        "left" => "right",
+        "right" => "left",
+        other => other,
+    }
+}
+
+// -webkit-transition: transform 1s 中的属性名也需要加前缀
+fn prefix_transition_value(
+    declaration: &mut AstNode<TokenType>,
+    property: &str,
+    prefix: Prefix,
+    targets: &Targets,
+) {
+    if property != "transition" && property != "transition-property" {
+        return;
+    }
+    rewrite_words(declaration, &mut |word| {
+        let support = property_support(&word.raw)?;
+        (!needed(support, targets, Some(prefix)).is_empty())
+            .then(|| format!("{}{}", prefix.as_str(), word.raw))
+    });
+}
@@ -0,0 +1,298 @@
+use std::{collections::HashSet, fs, io, path::Path};
+
+use regex::Regex;
+
+use crate::{
+    ast::{AstNode, AstTree},
+    cascade::property_name,
+    codegen::{selector_to_string, tokens_to_string, value_to_string},
+    selector::{compile_selector_list, join_selector_list, split_selector_list, CompoundSelector},
+    token_type::TokenType,
+};
+
+// ANCHOR: purge_options
+#[derive(Debug, Clone)]
+pub struct PurgeOptions {
+    // 类名、id 或标签名完全相同时保留
+    pub safelist: Vec<String>,
+    // 类名、id 或标签名匹配任一正则时保留
+    pub safelist_patterns: Vec<Regex>,
+    // 删除没有被 animation 引用的 @keyframes
+    pub keyframes: bool,
+    // 删除没有被 font-family 引用的 @font-face
+    pub font_face: bool,
+}
+// ANCHOR_END: purge_options
+
+impl Default for PurgeOptions {
+    fn default() -> Self {
+        PurgeOptions {
+            safelist: Vec::new(),
+            safelist_patterns: Vec::new(),
+            keyframes: true,
+            font_face: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PurgeReport {
+    pub removed_selectors: Vec<String>,
+    pub removed_at_rules: Vec<String>,
+}
+
+// ANCHOR: purger
+#[derive(Debug, Default)]
+pub struct Purger {
+    pub options: PurgeOptions,
+    // 从模板中提取的候选词
+    candidates: HashSet<String>,
+}
+// ANCHOR_END: purger
+
+const ANIMATION_PROPERTIES: [&str; 4] = [
+    "animation",
+    "animation-name",
+    "-webkit-animation",
+    "-webkit-animation-name",
+];
+
+impl Purger {
+    pub fn new(options: PurgeOptions) -> Self {
+        Purger {
+            options,
+            candidates: HashSet::new(),
+        }
+    }
+
+    // 与 purgecss 默认提取器一致：按非标识符字符切分
+    pub fn add_content(&mut self, content: &str) {
+        for word in content.split(|ch: char| {
+            !(ch.is_alphanumeric() || matches!(ch, '-' | '_' | ':' | '/' | '.' | '[' | ']' | '%'))
+        }) {
+            if word.is_empty() {
+                continue;
+            }
+            self.candidates.insert(word.to_string());
+            // a.b 这类写法同时登记各部分
+            for part in word.split(['.', ':', '/']) {
+                if !part.is_empty() {
+                    self.candidates.insert(part.to_string());
+                }
+            }
+        }
+    }
+
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let content = fs::read_to_string(path)?;
+        self.add_content(&content);
+        Ok(())
+    }
+
+    pub fn is_candidate(&self, name: &str) -> bool {
+        self.candidates.contains(name)
+            || self.options.safelist.iter().any(|s| s == name)
+            || self.options.safelist_patterns.iter().any(|r| r.is_match(name))
+    }
+
+    fn compound_can_match(&self, compound: &CompoundSelector) -> bool {
+        if compound.nesting {
+            return true;
+        }
+        let tag_ok = match &compound.tag {
+            Some(tag) if tag != "*" => self.is_candidate(tag),
+            _ => true,
+        };
+        tag_ok
+            && compound.ids.iter().all(|id| self.is_candidate(id))
+            && compound.classes.iter().all(|class| self.is_candidate(class))
+    }
+
+    // ANCHOR: purge
+    pub fn purge(&self, tree: &mut AstTree<TokenType>) -> PurgeReport {
+        let mut report = PurgeReport::default();
+        if let Some(root) = &mut tree.root {
+            if let Some(children) = &mut root.children {
+                self.purge_items(children, &mut report);
+                if self.options.keyframes || self.options.font_face {
+                    let mut animations = HashSet::new();
+                    let mut fonts = Vec::new();
+                    collect_references(children, &mut animations, &mut fonts);
+                    self.purge_unreferenced(children, &animations, &fonts, &mut report);
+                }
+            }
+        }
+        report
+    }
+    // ANCHOR_END: purge
+
+    fn purge_items(&self, items: &mut Vec<AstNode<TokenType>>, report: &mut PurgeReport) {
+        items.retain_mut(|item| match item.node_type.0 {
+            TokenType::RuleList => self.purge_rule(item, report),
+            TokenType::AtRule => {
+                let name = at_rule_name(item);
+                if !matches!(
+                    name.as_str(),
+                    "@media" | "@supports" | "@layer" | "@container" | "@document"
+                ) {
+                    return true;
+                }
+                let Some(children) = &mut item.children else {
+                    return true;
+                };
+                let Some(start) = children
+                    .iter()
+                    .position(|n| n.check_type(TokenType::LeftCurlyBracket))
+                else {
+                    return true;
+                };
+                let end = children.len() - 1;
+                let mut inner: Vec<_> = children.drain(start + 1..end).collect();
+                self.purge_items(&mut inner, report);
+                let is_empty = !inner
+                    .iter()
+                    .any(|n| n.check_type(TokenType::RuleList) || n.check_type(TokenType::AtRule));
+                children.splice(start + 1..start + 1, inner);
+                if is_empty {
+                    report.removed_at_rules.push(at_rule_label(item));
+                }
+                !is_empty
+            }
+            _ => true,
+        });
+    }
+
+    // 返回 false 表示整条规则都可以删除
+    fn purge_rule(&self, rule: &mut AstNode<TokenType>, report: &mut PurgeReport) -> bool {
+        let Some(children) = &mut rule.children else {
+            return true;
+        };
+        let Some(selector) = children
+            .iter_mut()
+            .find(|n| n.check_type(TokenType::SelectorList))
+        else {
+            return true;
+        };
+        let groups = split_selector_list(selector);
+        let total = groups.len();
+        let (kept, removed): (Vec<_>, Vec<_>) = groups.into_iter().partition(|group| {
+            let compiled = compile_selector_list(&AstNode::from_children(
+                TokenType::SelectorList,
+                group.clone(),
+            ));
+            compiled
+                .0
+                .iter()
+                .all(|s| s.compounds.iter().all(|c| self.compound_can_match(c)))
+        });
+        for group in &removed {
+            report.removed_selectors.push(selector_to_string(
+                &AstNode::from_children(TokenType::SelectorList, group.clone()),
+            ));
+        }
+        if kept.len() != total && !kept.is_empty() {
+            *selector = join_selector_list(kept);
+            return true;
+        }
+        !kept.is_empty()
+    }
+
+    fn purge_unreferenced(
+        &self,
+        items: &mut Vec<AstNode<TokenType>>,
+        animations: &HashSet<String>,
+        fonts: &[String],
+        report: &mut PurgeReport,
+    ) {
+        items.retain_mut(|item| {
+            if !item.check_type(TokenType::AtRule) {
+                return true;
+            }
+            let name = at_rule_name(item);
+            if name.ends_with("keyframes") && self.options.keyframes {
+                let params = at_rule_params(item);
+                if !animations.contains(params.trim()) {
+                    report.removed_at_rules.push(at_rule_label(item));
+                    return false;
+                }
+            } else if name == "@font-face" && self.options.font_face {
+                let family = font_face_family(item);
+                if family.is_some_and(|family| !fonts.iter().any(|f| f.contains(&family))) {
+                    report.removed_at_rules.push(at_rule_label(item));
+                    return false;
+                }
+            } else if let Some(children) = &mut item.children {
+                self.purge_unreferenced(children, animations, fonts, report);
+            }
+            true
+        });
+    }
+}
+
+fn at_rule_name(node: &AstNode<TokenType>) -> String {
+    node.child_nodes()
+        .first()
+        .map(|n| n.raw.to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+fn at_rule_params(node: &AstNode<TokenType>) -> String {
+    node.find_child(TokenType::AtRuleParams)
+        .map(|p| tokens_to_string(p.child_nodes()))
+        .unwrap_or_default()
+}
+
+fn at_rule_label(node: &AstNode<TokenType>) -> String {
+    let params = at_rule_params(node);
+    if params.is_empty() {
+        at_rule_name(node)
+    } else {
+        format!("{} {}", at_rule_name(node), params)
+    }
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+        .to_ascii_lowercase()
+}
+
+fn font_face_family(node: &AstNode<TokenType>) -> Option<String> {
+    node.find_child(TokenType::DeclarationList)?
+        .child_nodes()
+        .iter()
+        .find(|d| d.check_type(TokenType::Declaration) && property_name(d) == "font-family")
+        .and_then(|d| d.find_child(TokenType::Expression))
+        .map(|expr| unquote(&value_to_string(expr)))
+}
+
+// 收集剩余规则中引用的动画名和字体
+fn collect_references(
+    items: &[AstNode<TokenType>],
+    animations: &mut HashSet<String>,
+    fonts: &mut Vec<String>,
+) {
+    for item in items {
+        if item.check_type(TokenType::Declaration) {
+            let property = property_name(item);
+            let value = item
+                .find_child(TokenType::Expression)
+                .map(value_to_string)
+                .unwrap_or_default();
+            if ANIMATION_PROPERTIES.contains(&property.as_str()) {
+                animations.extend(
+                    value
+                        .split(|c: char| c.is_whitespace() || c == ',')
+                        .map(str::to_string),
+                );
+            } else if property == "font-family" || property == "font" {
+                fonts.extend(value.split(',').map(unquote));
+            }
+        } else if item.check_type(TokenType::AtRule) && at_rule_name(item) == "@font-face" {
+            continue;
+        } else {
+            collect_references(item.child_nodes(), animations, fonts);
+        }
+    }
+}
@@ -0,0 +1,689 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use crate::{
+    ast::{AstNode, AstTree},
+    codegen::{generate, selector_to_string, tokens_to_string, value_to_string},
+    less::{
+        apply_extends, complex_selectors, declaration_list, join_selector, media_query, unquote,
+        Extend, ExtendMatch,
+    },
+    lexer::Syntax,
+    optimizer::leaf,
+    parser::{parse_selector_with_syntax, parse_stylesheet_with_syntax},
+    range::Range,
+    token_type::TokenType,
+};
+
+mod callable;
+mod control;
+mod expression;
+mod functions;
+mod value;
+
+use callable::{Call, Callable};
+use value::Value;
+
+// ANCHOR: scss_error
+#[derive(Debug, Clone)]
+pub struct ScssError {
+    pub message: String,
+    pub range: Range,
+}
+// ANCHOR_END: scss_error
+
+impl ScssError {
+    fn new(message: impl Into<String>, range: Range) -> Self {
+        ScssError {
+            message: message.into(),
+            range,
+        }
+    }
+}
+
+impl Display for ScssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// ANCHOR: compile_scss
+// 编译 scss 源码，输出普通的 css
+pub fn compile_scss(source: &str) -> Result<String, ScssError> {
+    let tree = parse_stylesheet_with_syntax(source, Syntax::Scss);
+    Ok(generate(&eval_scss(&tree)?))
+}
+
+// 执行控制指令、mixin 和函数并展开嵌套规则，返回的语法树中只有普通的 css 节点
+pub fn eval_scss(tree: &AstTree<TokenType>) -> Result<AstTree<TokenType>, ScssError> {
+    let Some(root) = &tree.root else {
+        return Ok(AstTree { root: None });
+    };
+    let mut out = Vec::new();
+    let mut evaluator = Evaluator::default();
+    evaluator.body(root.child_nodes(), None, &mut out)?;
+    let matches = apply_extends(&mut out, &evaluator.extends, false, Syntax::Scss);
+    evaluator.check_extends(&matches)?;
+    remove_placeholders(&mut out);
+    Ok(AstTree {
+        root: Some(Box::new(AstNode::from_children(
+            TokenType::Stylesheets,
+            out,
+        ))),
+    })
+}
+// ANCHOR_END: compile_scss
+
+// ANCHOR: scope
+// 一个块中定义的变量、mixin 和函数，与 less 不同，变量按顺序求值
+#[derive(Default)]
+struct Scope<'a> {
+    variables: HashMap<String, Value>,
+    mixins: HashMap<String, Callable<'a>>,
+    functions: HashMap<String, Callable<'a>>,
+    // @if、@each 等控制指令的块，其中的赋值会修改外层的同名变量
+    control: bool,
+}
+// ANCHOR_END: scope
+
+struct Evaluator<'a> {
+    // 作用域栈，第一个是全局作用域
+    scopes: Vec<Scope<'a>>,
+    // 正在执行的 mixin 和函数
+    calls: Vec<Call<'a>>,
+    // @use "sass:math" as m 的命名空间
+    modules: HashMap<String, String>,
+    // 求值过程中遇到的 @extend，最后统一处理
+    extends: Vec<Extend>,
+    // 与 extends 一一对应的 @extend 节点和目标选择器，用于报错
+    extend_rules: Vec<(&'a AstNode<TokenType>, String)>,
+    // 所在的 @media 的查询条件，从外到内
+    media: Vec<String>,
+    // 函数中 @return 的值，有值时停止执行后面的语句
+    returned: Option<Value>,
+}
+
+impl Default for Evaluator<'_> {
+    fn default() -> Self {
+        Evaluator {
+            scopes: vec![Scope::default()],
+            calls: Vec::new(),
+            modules: HashMap::new(),
+            extends: Vec::new(),
+            extend_rules: Vec::new(),
+            media: Vec::new(),
+            returned: None,
+        }
+    }
+}
+
+impl<'a> Evaluator<'a> {
+    // ANCHOR: body
+    // 求值块中的内容，selectors 为所在规则展开后的选择器
+    // 声明合并成一条规则放在最前面，嵌套的规则和冒泡的 at 规则依次放在后面
+    fn body(
+        &mut self,
+        items: &'a [AstNode<TokenType>],
+        selectors: Option<&[String]>,
+        out: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        let mut declarations = Vec::new();
+        let mut nested = Vec::new();
+        self.items(items, selectors, &mut declarations, &mut nested)?;
+        if let Some(selectors) = selectors {
+            if declarations
+                .iter()
+                .any(|n| n.check_type(TokenType::Declaration))
+            {
+                out.push(make_rule(selectors, declarations));
+            }
+        }
+        out.extend(nested);
+        Ok(())
+    }
+
+    // 不在规则中时声明也放在 nested 中，保持原有顺序
+    fn items(
+        &mut self,
+        items: &'a [AstNode<TokenType>],
+        selectors: Option<&[String]>,
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        for item in items {
+            if self.returned.is_some() {
+                break;
+            }
+            match item.node_type.0 {
+                TokenType::Declaration => {
+                    if let Some(declaration) = self.declaration(item)? {
+                        match selectors {
+                            Some(_) => declarations.push(declaration),
+                            None => nested.push(declaration),
+                        }
+                    }
+                }
+                TokenType::VariableDeclaration => self.assign(item)?,
+                TokenType::RuleList => self.rule(item, selectors, nested)?,
+                TokenType::AtRule => self.at_rule(item, selectors, nested)?,
+                TokenType::MixinRule => self.define(item, false),
+                TokenType::FunctionRule => self.define(item, true),
+                TokenType::IncludeRule => self.include(item, selectors, declarations, nested)?,
+                TokenType::ContentRule => self.content(selectors, declarations, nested)?,
+                TokenType::ReturnRule => {
+                    let value = match item.find_child(TokenType::Expression) {
+                        Some(expr) => self.expression(expr)?,
+                        None => Value::Null,
+                    };
+                    self.returned = Some(value);
+                }
+                TokenType::IfRule => self.if_rule(item, selectors, declarations, nested)?,
+                TokenType::EachRule => self.each(item, selectors, declarations, nested)?,
+                TokenType::ForRule => self.for_rule(item, selectors, declarations, nested)?,
+                TokenType::WhileRule => self.while_rule(item, selectors, declarations, nested)?,
+                TokenType::ExtendRule => self.extend(item, selectors)?,
+                // 单行注释不输出
+                TokenType::Comment if item.raw.starts_with("//") => {}
+                TokenType::Comment => match selectors {
+                    Some(_) => declarations.push(item.clone()),
+                    None => nested.push(item.clone()),
+                },
+                TokenType::Semi
+                | TokenType::LeftCurlyBracket
+                | TokenType::RightCurlyBracket
+                | TokenType::CDOToken
+                | TokenType::CDCToken => {}
+                _ => nested.push(item.clone()),
+            }
+        }
+        Ok(())
+    }
+    // ANCHOR_END: body
+
+    fn rule(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        parents: Option<&[String]>,
+        out: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        let own = match node.find_child(TokenType::SelectorList) {
+            Some(list) => self.selectors(list, node.range)?,
+            None => Vec::new(),
+        };
+        let selectors = match parents {
+            Some(parents) => own
+                .iter()
+                .flat_map(|selector| join_selector(parents, selector))
+                .collect(),
+            None => own,
+        };
+        let items = node
+            .find_child(TokenType::DeclarationList)
+            .map(|list| list.child_nodes())
+            .unwrap_or_default();
+        self.scopes.push(Scope::default());
+        let result = self.body(items, Some(&selectors), out);
+        self.scopes.pop();
+        result
+    }
+
+    // 选择器列表中的每个选择器，插值的结果中可能有逗号
+    fn selectors(
+        &mut self,
+        list: &AstNode<TokenType>,
+        range: Range,
+    ) -> Result<Vec<String>, ScssError> {
+        if !list.raw.contains("#{") {
+            return Ok(complex_selectors(list));
+        }
+        let text = self.interpolate(&selector_to_string(list), range)?;
+        Ok(parse_selector_with_syntax(&text, Syntax::Scss)
+            .map(|list| complex_selectors(&list))
+            .unwrap_or_else(|| vec![text]))
+    }
+
+    // ANCHOR: at_rule
+    // 规则中的 @media 等会冒泡到外层，内部的声明使用所在规则的选择器
+    fn at_rule(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+        out: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        let children = node.child_nodes();
+        let name = children
+            .first()
+            .map(|n| n.raw.to_ascii_lowercase())
+            .unwrap_or_default();
+        let params = node.find_child(TokenType::AtRuleParams);
+        match name.as_str() {
+            "@use" => return self.use_module(node),
+            "@forward" | "@debug" | "@warn" => return Ok(()),
+            "@error" => {
+                let text = params
+                    .map(|params| tokens_to_string(params.child_nodes()))
+                    .unwrap_or_default();
+                let message = self.eval_text(&text, node.range)?;
+                return Err(ScssError::new(message.text(), node.range));
+            }
+            "@at-root" => return self.at_root(node, selectors, out),
+            _ => {}
+        }
+
+        let mut head = Vec::new();
+        for child in children {
+            match child.node_type.0 {
+                TokenType::AtRuleParams => head.push(self.params(child)?),
+                TokenType::DeclarationList | TokenType::LeftCurlyBracket => break,
+                _ => head.push(child.clone()),
+            }
+        }
+        let query = media_query(&head);
+        if let Some(query) = &query {
+            self.media.push(query.clone());
+        }
+        let result = self.at_rule_block(node, &name, selectors, head, out);
+        if query.is_some() {
+            self.media.pop();
+        }
+        result
+    }
+
+    fn at_rule_block(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        name: &str,
+        selectors: Option<&[String]>,
+        mut head: Vec<AstNode<TokenType>>,
+        out: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        if let Some(list) = node.find_child(TokenType::DeclarationList) {
+            // @font-face 等只包含声明的 at 规则
+            let mut declarations = Vec::new();
+            self.scopes.push(Scope::default());
+            let result = self.body(list.child_nodes(), None, &mut declarations);
+            self.scopes.pop();
+            result?;
+            head.push(declaration_list(declarations));
+        } else if let Some(items) = block(node) {
+            // 关键帧中的选择器与外层规则无关
+            let selectors = selectors.filter(|_| !name.ends_with("keyframes"));
+            let mut inner = Vec::new();
+            self.scopes.push(Scope::default());
+            let result = self.body(items, selectors, &mut inner);
+            self.scopes.pop();
+            result?;
+            if inner.is_empty() {
+                return Ok(());
+            }
+            head.push(leaf(TokenType::LeftCurlyBracket, "{"));
+            head.extend(inner);
+            head.push(leaf(TokenType::RightCurlyBracket, "}"));
+        }
+        out.push(AstNode::from_children(TokenType::AtRule, head));
+        Ok(())
+    }
+    // ANCHOR_END: at_rule
+
+    // 参数中的变量和插值，例如 @media (min-width: $md + 1) and #{$query}
+    fn params(&mut self, node: &AstNode<TokenType>) -> Result<AstNode<TokenType>, ScssError> {
+        let tokens = node.child_nodes();
+        let mut children = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            match token.node_type.0 {
+                // 冒号后面到右括号之间是表达式
+                TokenType::Colon => {
+                    children.push(token.clone());
+                    let end = tokens[i + 1..]
+                        .iter()
+                        .position(|n| n.check_type(TokenType::RightParenthesis))
+                        .map_or(tokens.len(), |p| i + 1 + p);
+                    let value = &tokens[i + 1..end];
+                    if value
+                        .iter()
+                        .any(|n| n.check_type(TokenType::DollarVariable))
+                    {
+                        let text = self.eval_text(&tokens_to_string(value), node.range)?;
+                        let range =
+                            Range::new(value[0].range.start_pos, tokens[end - 1].range.end_pos);
+                        children.push(AstNode {
+                            range,
+                            ..leaf(TokenType::IdentToken, &text.to_string())
+                        });
+                        i = end;
+                        continue;
+                    }
+                }
+                TokenType::DollarVariable => {
+                    let value = self.variable(&token.raw, token.range)?;
+                    children.push(AstNode {
+                        range: token.range,
+                        ..leaf(TokenType::IdentToken, &value.text())
+                    });
+                }
+                TokenType::IdentToken | TokenType::Str if token.raw.contains("#{") => {
+                    let mut token = token.clone();
+                    token.raw = self.interpolate(&token.raw, token.range)?;
+                    children.push(token);
+                }
+                _ => children.push(token.clone()),
+            }
+            i += 1;
+        }
+        Ok(AstNode {
+            range: node.range,
+            ..AstNode::from_children(TokenType::AtRuleParams, children)
+        })
+    }
+
+    // @at-root .a { } 中的规则不与外层选择器组合，除非其中有 &
+    fn at_root(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        parents: Option<&[String]>,
+        out: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        // @at-root .a { } 的块被解析为声明列表
+        let items = match node.find_child(TokenType::DeclarationList) {
+            Some(list) => list.child_nodes(),
+            None => block(node).unwrap_or_default(),
+        };
+        let selectors = match node.find_child(TokenType::AtRuleParams) {
+            Some(params) => {
+                let text = self.interpolate(&tokens_to_string(params.child_nodes()), node.range)?;
+                let own = parse_selector_with_syntax(&text, Syntax::Scss)
+                    .map(|list| complex_selectors(&list))
+                    .unwrap_or_else(|| vec![text]);
+                let selectors = match parents {
+                    Some(parents) => own
+                        .iter()
+                        .flat_map(|selector| match selector.contains('&') {
+                            true => join_selector(parents, selector),
+                            false => vec![selector.clone()],
+                        })
+                        .collect(),
+                    None => own,
+                };
+                Some(selectors)
+            }
+            None => None,
+        };
+        self.scopes.push(Scope::default());
+        let result = self.body(items, selectors.as_deref(), out);
+        self.scopes.pop();
+        result
+    }
+
+    // @use "sass:math" as m，只支持内置模块
+    fn use_module(&mut self, node: &AstNode<TokenType>) -> Result<(), ScssError> {
+        let tokens = node
+            .find_child(TokenType::AtRuleParams)
+            .map(|params| params.child_nodes())
+            .unwrap_or_default();
+        let url = tokens
+            .iter()
+            .find(|n| n.check_type(TokenType::Str))
+            .map(|n| unquote(&n.raw).to_string())
+            .unwrap_or_default();
+        let Some(module) = url.strip_prefix("sass:") else {
+            return Err(ScssError::new(
+                format!("can't find stylesheet to import: {}", url),
+                node.range,
+            ));
+        };
+        let alias = tokens
+            .iter()
+            .position(|n| n.check_type(TokenType::IdentToken) && n.raw == "as")
+            .and_then(|i| tokens.get(i + 1))
+            .map_or(module, |n| n.raw.as_str());
+        self.modules.insert(alias.to_string(), module.to_string());
+        Ok(())
+    }
+
+    fn declaration(
+        &mut self,
+        node: &AstNode<TokenType>,
+    ) -> Result<Option<AstNode<TokenType>>, ScssError> {
+        let mut declaration = node.clone();
+        let custom = node
+            .find_child(TokenType::Property)
+            .is_some_and(|property| property.raw.starts_with("--"));
+        for child in declaration.children.iter_mut().flatten() {
+            if child.check_type(TokenType::Expression) {
+                // 自定义属性的值只处理插值
+                let text = if custom {
+                    self.interpolate(&value_to_string(child), child.range)?
+                } else {
+                    let value = self.expression(child)?;
+                    if let Value::Map(_) = value {
+                        return Err(ScssError::new(
+                            format!("{} isn't a valid CSS value", value.inspect()),
+                            child.range,
+                        ));
+                    }
+                    value.to_string()
+                };
+                // 值为 null 或空列表的声明不输出
+                if text.is_empty() {
+                    return Ok(None);
+                }
+                *child = AstNode {
+                    range: child.range,
+                    ..AstNode::from_children(
+                        TokenType::Expression,
+                        vec![leaf(TokenType::IdentToken, &text)],
+                    )
+                };
+            } else if child.check_type(TokenType::Property) && child.raw.contains("#{") {
+                let name = self.interpolate(&child.raw, child.range)?;
+                *child = AstNode {
+                    range: child.range,
+                    ..AstNode::from_children(
+                        TokenType::Property,
+                        vec![leaf(TokenType::IdentToken, &name)],
+                    )
+                };
+            }
+        }
+        Ok(Some(declaration))
+    }
+
+    // ANCHOR: variable
+    // $a: 1 !default !global;
+    fn assign(&mut self, node: &AstNode<TokenType>) -> Result<(), ScssError> {
+        let name = node
+            .find_child(TokenType::Variable)
+            .map(|n| variable_name(&n.raw))
+            .unwrap_or_default();
+        let flags: Vec<String> = node
+            .child_nodes()
+            .iter()
+            .filter(|n| n.check_type(TokenType::Important))
+            .map(|n| n.raw.to_ascii_lowercase())
+            .collect();
+        let global = flags.iter().any(|flag| flag == "!global");
+        let index = self.assign_index(&name, global);
+        // !default 只在变量未定义或为 null 时赋值
+        if flags.iter().any(|flag| flag == "!default")
+            && self.scopes[index]
+                .variables
+                .get(&name)
+                .is_some_and(|value| !value.is_null())
+        {
+            return Ok(());
+        }
+        let value = match node.find_child(TokenType::Expression) {
+            Some(expr) => self.expression(expr)?,
+            None => Value::Null,
+        };
+        self.scopes[index].variables.insert(name, value);
+        Ok(())
+    }
+
+    // 赋值给最近的同名局部变量，只在控制指令中时会修改全局变量，否则定义新的局部变量
+    fn assign_index(&self, name: &str, global: bool) -> usize {
+        if global {
+            return 0;
+        }
+        let len = self.scopes.len();
+        (1..len)
+            .rev()
+            .find(|&i| self.scopes[i].variables.contains_key(name))
+            .or_else(|| {
+                (self.scopes[1..].iter().all(|scope| scope.control)
+                    && self.scopes[0].variables.contains_key(name))
+                .then_some(0)
+            })
+            .unwrap_or(len - 1)
+    }
+
+    fn declare(&mut self, name: &str, value: Value) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.variables.insert(variable_name(name), value);
+        }
+    }
+
+    // 从内向外查找变量
+    fn variable(&self, name: &str, range: Range) -> Result<Value, ScssError> {
+        let name = variable_name(name);
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.variables.get(&name))
+            .cloned()
+            .ok_or_else(|| ScssError::new(format!("variable ${} is undefined", name), range))
+    }
+    // ANCHOR_END: variable
+
+    // @extend .a; 让所在规则的选择器出现在所有包含 .a 的规则中
+    fn extend(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+    ) -> Result<(), ScssError> {
+        let Some(selectors) = selectors else {
+            return Err(ScssError::new(
+                "@extend may only be used within style rules",
+                node.range,
+            ));
+        };
+        let Some(list) = node.find_child(TokenType::SelectorList) else {
+            return Ok(());
+        };
+        for target in self.selectors(list, node.range)? {
+            for selector in selectors {
+                let extend = Extend::new(selector.clone(), &target, true, Syntax::Scss);
+                self.extends.push(extend.in_media(&self.media));
+                self.extend_rules.push((node, target.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    // 没有带 !optional 的 @extend 必须匹配到规则，@media 中的 @extend 不能扩展外面的规则
+    fn check_extends(&self, matches: &[ExtendMatch]) -> Result<(), ScssError> {
+        for (matched, (node, target)) in matches.iter().zip(&self.extend_rules) {
+            match matched {
+                ExtendMatch::AcrossMedia => {
+                    return Err(ScssError::new(
+                        "You may not @extend selectors across media queries.",
+                        node.range,
+                    ))
+                }
+                ExtendMatch::None if node.find_child(TokenType::Important).is_none() => {
+                    return Err(ScssError::new(
+                        format!(
+                            "The target selector was not found.\nUse \"@extend {} !optional\" to avoid this error.",
+                            target
+                        ),
+                        node.range,
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+// $name 和 $name... 去掉 $，- 和 _ 是等价的
+fn variable_name(raw: &str) -> String {
+    raw.trim_start_matches('$')
+        .trim_end_matches("...")
+        .replace('_', "-")
+}
+
+// at 规则的 { } 中的内容
+fn block(node: &AstNode<TokenType>) -> Option<&[AstNode<TokenType>]> {
+    let children = node.child_nodes();
+    let start = children
+        .iter()
+        .position(|n| n.check_type(TokenType::LeftCurlyBracket))?;
+    let end = children.len() - 1;
+    Some(&children[start + 1..end.max(start + 1)])
+}
+
+fn make_rule(selectors: &[String], declarations: Vec<AstNode<TokenType>>) -> AstNode<TokenType> {
+    let text = selectors.join(", ");
+    let selector = parse_selector_with_syntax(&text, Syntax::Scss)
+        .unwrap_or_else(|| leaf(TokenType::SelectorList, &text));
+    AstNode::from_children(
+        TokenType::RuleList,
+        vec![selector, declaration_list(declarations)],
+    )
+}
+
+// ANCHOR: remove_placeholders
+// 处理 @extend 之后去掉包含占位符 %name 的选择器，没有选择器的规则不输出
+fn remove_placeholders(nodes: &mut Vec<AstNode<TokenType>>) {
+    nodes.retain_mut(|node| match node.node_type.0 {
+        TokenType::RuleList => {
+            let Some(list) = node.find_child(TokenType::SelectorList) else {
+                return true;
+            };
+            let selectors = complex_selectors(list);
+            let kept: Vec<String> = selectors
+                .iter()
+                .filter(|selector| !has_placeholder(selector))
+                .cloned()
+                .collect();
+            if kept.len() < selectors.len() && !kept.is_empty() {
+                if let Some(list) = parse_selector_with_syntax(&kept.join(", "), Syntax::Scss) {
+                    let mut children = node.child_nodes().to_vec();
+                    for child in children.iter_mut() {
+                        if child.check_type(TokenType::SelectorList) {
+                            *child = list;
+                            break;
+                        }
+                    }
+                    *node = AstNode::from_children(TokenType::RuleList, children);
+                }
+            }
+            !kept.is_empty()
+        }
+        TokenType::AtRule => {
+            let Some(children) = &mut node.children else {
+                return true;
+            };
+            remove_placeholders(children);
+            // 内容都被去掉的 at 规则不输出
+            children
+                .iter()
+                .position(|n| n.check_type(TokenType::LeftCurlyBracket))
+                .is_none_or(|start| children.len() > start + 2)
+        }
+        _ => true,
+    });
+}
+
+fn has_placeholder(selector: &str) -> bool {
+    selector
+        .split('%')
+        .skip(1)
+        .any(|rest| rest.starts_with(|c: char| c.is_alphabetic() || c == '-' || c == '_'))
+}
+// ANCHOR_END: remove_placeholders
@@ -0,0 +1,300 @@
+use super::{functions::Args, value::Separator, variable_name, Evaluator, Scope, ScssError, Value};
+use crate::{ast::AstNode, range::Range, token_type::TokenType};
+
+// mixin 和函数互相调用的最大深度
+const MAX_DEPTH: usize = 100;
+
+// ANCHOR: callable
+// @mixin 或 @function 的定义，depth 为定义所在作用域的位置
+#[derive(Clone, Copy)]
+pub(super) struct Callable<'a> {
+    node: &'a AstNode<TokenType>,
+    depth: usize,
+}
+
+// 一次 mixin 或函数调用，调用处的作用域移到这里，mixin 中只能看到定义处的作用域
+pub(super) struct Call<'a> {
+    depth: usize,
+    caller: Vec<Scope<'a>>,
+    // @include 传入的内容块
+    content: Option<&'a AstNode<TokenType>>,
+}
+// ANCHOR_END: callable
+
+// 定义中的参数 ($a, $b: 2, $rest...)
+enum Param<'a> {
+    Required(String),
+    Default(String, &'a AstNode<TokenType>),
+    Rest(String),
+}
+
+impl<'a> Evaluator<'a> {
+    pub(super) fn define(&mut self, node: &'a AstNode<TokenType>, function: bool) {
+        let name = callable_name(node);
+        let callable = Callable {
+            node,
+            depth: self.scopes.len() - 1,
+        };
+        if let Some(scope) = self.scopes.last_mut() {
+            if function {
+                scope.functions.insert(name, callable);
+            } else {
+                scope.mixins.insert(name, callable);
+            }
+        }
+    }
+
+    pub(super) fn find_function(&self, name: &str) -> Option<Callable<'a>> {
+        let name = name.replace('_', "-");
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.functions.get(&name))
+            .copied()
+    }
+
+    // ANCHOR: arguments
+    // 调用时的参数在调用处求值，$list... 展开列表，map 展开为关键字参数
+    pub(super) fn arguments(
+        &mut self,
+        list: Option<&AstNode<TokenType>>,
+    ) -> Result<Args, ScssError> {
+        let mut args = Args::default();
+        for child in list.map(|list| list.child_nodes()).unwrap_or_default() {
+            match child.node_type.0 {
+                TokenType::Expression => args.positional.push(self.expression(child)?),
+                TokenType::VariableDeclaration => {
+                    let name = child
+                        .find_child(TokenType::Variable)
+                        .map(|n| variable_name(&n.raw))
+                        .unwrap_or_default();
+                    let value = match child.find_child(TokenType::Expression) {
+                        Some(expr) => self.expression(expr)?,
+                        None => Value::Null,
+                    };
+                    args.named.push((name, value));
+                }
+                TokenType::Variable => match self.variable(&child.raw, child.range)? {
+                    Value::Map(pairs) => args.named.extend(
+                        pairs
+                            .into_iter()
+                            .map(|(key, value)| (variable_name(&key.text()), value)),
+                    ),
+                    value => args.positional.extend(value.items()),
+                },
+                _ => {}
+            }
+        }
+        Ok(args)
+    }
+    // ANCHOR_END: arguments
+
+    // ANCHOR: invoke
+    // 切换到定义处的作用域，在新的作用域中绑定参数后执行
+    fn invoke<T>(
+        &mut self,
+        callable: Callable<'a>,
+        args: Args,
+        content: Option<&'a AstNode<TokenType>>,
+        range: Range,
+        run: impl FnOnce(&mut Self) -> Result<T, ScssError>,
+    ) -> Result<T, ScssError> {
+        if self.calls.len() >= MAX_DEPTH {
+            return Err(ScssError::new(
+                format!("too many nested calls of {}", callable_name(callable.node)),
+                range,
+            ));
+        }
+        let caller = self.scopes.split_off(callable.depth + 1);
+        self.calls.push(Call {
+            depth: callable.depth,
+            caller,
+            content,
+        });
+        self.scopes.push(Scope::default());
+        let result = self
+            .bind(callable.node, args, range)
+            .and_then(|_| run(self));
+        self.scopes.truncate(callable.depth + 1);
+        if let Some(call) = self.calls.pop() {
+            self.scopes.extend(call.caller);
+        }
+        result
+    }
+
+    fn bind(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        args: Args,
+        range: Range,
+    ) -> Result<(), ScssError> {
+        let params = params(node);
+        let passed = args.positional.len();
+        let mut positional = args.positional.into_iter();
+        let mut named = args.named;
+        for param in &params {
+            let (name, default) = match param {
+                Param::Rest(name) => {
+                    let rest = positional.by_ref().collect();
+                    self.declare(name, Value::List(rest, Separator::Comma));
+                    continue;
+                }
+                Param::Required(name) => (name, None),
+                Param::Default(name, expr) => (name, Some(*expr)),
+            };
+            let value = match positional.next() {
+                Some(value) => value,
+                None => match named.iter().position(|(n, _)| n == name) {
+                    Some(index) => named.remove(index).1,
+                    // 默认值可以引用前面的参数
+                    None => match default {
+                        Some(expr) => self.expression(expr)?,
+                        None => {
+                            return Err(ScssError::new(
+                                format!("missing argument ${}", name),
+                                range,
+                            ))
+                        }
+                    },
+                },
+            };
+            self.declare(name, value);
+        }
+        if positional.next().is_some() {
+            return Err(ScssError::new(
+                format!(
+                    "only {} arguments allowed, but {} were passed",
+                    params.len(),
+                    passed
+                ),
+                range,
+            ));
+        }
+        if let Some((name, _)) = named.first() {
+            return Err(ScssError::new(
+                format!("no argument named ${}", name),
+                range,
+            ));
+        }
+        Ok(())
+    }
+    // ANCHOR_END: invoke
+
+    // ANCHOR: include
+    pub(super) fn include(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        let name = callable_name(node);
+        let Some(mixin) = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.mixins.get(&name))
+            .copied()
+        else {
+            return Err(ScssError::new(
+                format!("mixin {} is undefined", name),
+                node.range,
+            ));
+        };
+        let args = self.arguments(node.find_child(TokenType::ArgumentList))?;
+        let content = node.find_child(TokenType::DeclarationList);
+        let body = block_items(mixin.node);
+        self.invoke(mixin, args, content, node.range, |this| {
+            this.items(body, selectors, declarations, nested)
+        })
+    }
+
+    // @content 在调用处的作用域中执行，其中的 @content 属于外层的 mixin
+    pub(super) fn content(
+        &mut self,
+        selectors: Option<&[String]>,
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        let Some(mut call) = self.calls.pop() else {
+            return Ok(());
+        };
+        let Some(content) = call.content else {
+            self.calls.push(call);
+            return Ok(());
+        };
+        let own = self.scopes.split_off(call.depth + 1);
+        self.scopes.append(&mut call.caller);
+        self.scopes.push(Scope::default());
+        let result = self.items(content.child_nodes(), selectors, declarations, nested);
+        self.scopes.pop();
+        call.caller = self.scopes.split_off(call.depth + 1);
+        self.scopes.extend(own);
+        self.calls.push(call);
+        result
+    }
+    // ANCHOR_END: include
+
+    // 函数体中的规则和声明不输出，没有 @return 时报错
+    pub(super) fn call_function(
+        &mut self,
+        function: Callable<'a>,
+        args: Args,
+        range: Range,
+    ) -> Result<Value, ScssError> {
+        let body = block_items(function.node);
+        let returned = self.invoke(function, args, None, range, |this| {
+            let mut declarations = Vec::new();
+            let mut nested = Vec::new();
+            let result = this.items(body, None, &mut declarations, &mut nested);
+            let returned = this.returned.take();
+            result.map(|_| returned)
+        })?;
+        returned.ok_or_else(|| {
+            ScssError::new(
+                format!(
+                    "function {} finished without @return",
+                    callable_name(function.node)
+                ),
+                range,
+            )
+        })
+    }
+}
+
+// @mixin name(...) 或 @include name 中的名称
+fn callable_name(node: &AstNode<TokenType>) -> String {
+    node.child_nodes()
+        .iter()
+        .find(|n| n.check_type(TokenType::FunctionToken) || n.check_type(TokenType::IdentToken))
+        .map(|n| n.raw.trim_end_matches('(').replace('_', "-"))
+        .unwrap_or_default()
+}
+
+fn block_items(node: &AstNode<TokenType>) -> &[AstNode<TokenType>] {
+    node.find_child(TokenType::DeclarationList)
+        .map(|list| list.child_nodes())
+        .unwrap_or_default()
+}
+
+fn params(node: &AstNode<TokenType>) -> Vec<Param<'_>> {
+    let children = node
+        .find_child(TokenType::ArgumentList)
+        .map(|list| list.child_nodes())
+        .unwrap_or_default();
+    children
+        .iter()
+        .filter_map(|child| match child.node_type.0 {
+            TokenType::VariableDeclaration => {
+                let name = variable_name(&child.find_child(TokenType::Variable)?.raw);
+                match child.find_child(TokenType::Expression) {
+                    Some(expr) => Some(Param::Default(name, expr)),
+                    None => Some(Param::Required(name)),
+                }
+            }
+            TokenType::Variable => Some(Param::Rest(variable_name(&child.raw))),
+            TokenType::Expression => Some(Param::Required(variable_name(&child.raw))),
+            _ => None,
+        })
+        .collect()
+}
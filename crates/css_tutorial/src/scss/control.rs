@@ -0,0 +1,159 @@
+use super::{Evaluator, Scope, ScssError, Value};
+use crate::{ast::AstNode, token_type::TokenType};
+
+impl<'a> Evaluator<'a> {
+    // ANCHOR: control
+    // 在控制指令的作用域中执行块，vars 为 @each、@for 的循环变量
+    fn run_block(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        vars: Vec<(&str, Value)>,
+        selectors: Option<&[String]>,
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        let Some(list) = node.find_child(TokenType::DeclarationList) else {
+            return Ok(());
+        };
+        self.scopes.push(Scope {
+            control: true,
+            ..Scope::default()
+        });
+        for (name, value) in vars {
+            self.declare(name, value);
+        }
+        let result = self.items(list.child_nodes(), selectors, declarations, nested);
+        self.scopes.pop();
+        result
+    }
+
+    // @if ... @else if ... @else
+    pub(super) fn if_rule(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        let branches = std::iter::once(node).chain(
+            node.child_nodes()
+                .iter()
+                .filter(|n| n.check_type(TokenType::ElseRule)),
+        );
+        for branch in branches {
+            if let Some(condition) = branch.find_child(TokenType::Expression) {
+                if !self.expression(condition)?.is_truthy() {
+                    continue;
+                }
+            }
+            return self.run_block(branch, Vec::new(), selectors, declarations, nested);
+        }
+        Ok(())
+    }
+
+    // @each $key, $value in $map
+    pub(super) fn each(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        let names: Vec<&str> = node
+            .child_nodes()
+            .iter()
+            .filter(|n| n.check_type(TokenType::Variable))
+            .map(|n| n.raw.as_str())
+            .collect();
+        let list = match node.find_child(TokenType::Expression) {
+            Some(expr) => self.expression(expr)?,
+            None => Value::Null,
+        };
+        for item in list.items() {
+            // 多个变量时解构每一项
+            let vars = match names.as_slice() {
+                [name] => vec![(*name, item)],
+                _ => {
+                    let parts = item.items();
+                    names
+                        .iter()
+                        .enumerate()
+                        .map(|(i, name)| (*name, parts.get(i).cloned().unwrap_or(Value::Null)))
+                        .collect()
+                }
+            };
+            self.run_block(node, vars, selectors, declarations, nested)?;
+            if self.returned.is_some() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // @for $i from 1 through 3，to 不包含结束值
+    pub(super) fn for_rule(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        let children = node.child_nodes();
+        let name = node
+            .find_child(TokenType::Variable)
+            .map(|n| n.raw.as_str())
+            .unwrap_or_default();
+        let through = children
+            .iter()
+            .any(|n| n.check_type(TokenType::IdentToken) && n.raw == "through");
+        let mut bounds = Vec::new();
+        for expr in children
+            .iter()
+            .filter(|n| n.check_type(TokenType::Expression))
+        {
+            match self.expression(expr)? {
+                Value::Number(n, unit) => bounds.push((n.round() as i64, unit)),
+                value => {
+                    return Err(ScssError::new(
+                        format!("{} is not a number", value.inspect()),
+                        expr.range,
+                    ))
+                }
+            }
+        }
+        let [(from, unit), (to, _)] = <[_; 2]>::try_from(bounds)
+            .map_err(|_| ScssError::new("@for requires a start and an end value", node.range))?;
+        let step = if from <= to { 1 } else { -1 };
+        let end = if through { to + step } else { to };
+        let mut i = from;
+        while i != end {
+            let vars = vec![(name, Value::Number(i as f64, unit.clone()))];
+            self.run_block(node, vars, selectors, declarations, nested)?;
+            if self.returned.is_some() {
+                break;
+            }
+            i += step;
+        }
+        Ok(())
+    }
+
+    pub(super) fn while_rule(
+        &mut self,
+        node: &'a AstNode<TokenType>,
+        selectors: Option<&[String]>,
+        declarations: &mut Vec<AstNode<TokenType>>,
+        nested: &mut Vec<AstNode<TokenType>>,
+    ) -> Result<(), ScssError> {
+        let Some(condition) = node.find_child(TokenType::Expression) else {
+            return Ok(());
+        };
+        while self.expression(condition)?.is_truthy() {
+            self.run_block(node, Vec::new(), selectors, declarations, nested)?;
+            if self.returned.is_some() {
+                break;
+            }
+        }
+        Ok(())
+    }
+    // ANCHOR_END: control
+}
@@ -0,0 +1,578 @@
+use std::{
+    borrow::Cow,
+    f64::consts::{E, PI},
+};
+
+use super::{
+    functions::builtin,
+    value::{Separator, Value},
+    Evaluator, ScssError,
+};
+use crate::{
+    ast::AstNode, codegen::function_to_string, lexer::Syntax, optimizer::leaf,
+    parser::parse_value_with_syntax, range::Range, token_type::TokenType,
+};
+
+// 在 calc() 等 css 函数中只替换变量和插值，运算交给浏览器
+const CSS_FUNCTIONS: [&str; 6] = ["calc", "clamp", "var", "env", "url", "element"];
+
+// ANCHOR: expr_parser
+// 优先级从低到高：逗号、空格分隔的列表、or、and、== !=、> < >= <=、+ -、* / %、一元运算
+// 减号前有空格后面没有空格时是负号，例如 1 -$a 是两个值
+struct ExprParser<'n> {
+    items: Vec<&'n AstNode<TokenType>>,
+    pos: usize,
+    // 括号中的 / 总是除法
+    paren: bool,
+}
+
+impl<'n> ExprParser<'n> {
+    fn new(items: &'n [AstNode<TokenType>], paren: bool) -> Self {
+        ExprParser {
+            items: items
+                .iter()
+                .filter(|n| !n.check_type(TokenType::Comment))
+                .collect(),
+            pos: 0,
+            paren,
+        }
+    }
+
+    fn peek(&self) -> Option<&'n AstNode<TokenType>> {
+        self.items.get(self.pos).copied()
+    }
+
+    // 当前位置的运算符，+ - * 和 and or not 是 Term 节点
+    fn operator(&self) -> Option<String> {
+        let node = self.peek()?;
+        if node.check_type(TokenType::Operator) {
+            return Some(node.raw.trim().to_string());
+        }
+        match node.child_nodes() {
+            [child] if node.check_type(TokenType::Term) => match child.node_type.0 {
+                TokenType::Plus | TokenType::Minus | TokenType::Asterisk => Some(child.raw.clone()),
+                TokenType::IdentToken if matches!(child.raw.as_str(), "and" | "or" | "not") => {
+                    Some(child.raw.clone())
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn is_spaced(&self, index: usize) -> bool {
+        match (
+            index.checked_sub(1).and_then(|i| self.items.get(i)),
+            self.items.get(index),
+        ) {
+            (Some(prev), Some(node)) => prev.range.end_pos != node.range.start_pos,
+            _ => true,
+        }
+    }
+
+    fn comma_list(&mut self, ev: &mut Evaluator<'_>) -> Result<Value, ScssError> {
+        let mut items = vec![self.space_list(ev)?];
+        let mut comma = false;
+        while self.operator().as_deref() == Some(",") {
+            self.pos += 1;
+            comma = true;
+            // 末尾的逗号
+            if self.peek().is_none() {
+                break;
+            }
+            items.push(self.space_list(ev)?);
+        }
+        Ok(match comma {
+            true => Value::List(items, Separator::Comma),
+            false => items.pop().unwrap_or(Value::Null),
+        })
+    }
+
+    fn space_list(&mut self, ev: &mut Evaluator<'_>) -> Result<Value, ScssError> {
+        let mut items = Vec::new();
+        while self.peek().is_some() && self.operator().as_deref() != Some(",") {
+            items.push(self.or(ev)?);
+        }
+        Ok(match items.len() {
+            1 => items.pop().unwrap_or(Value::Null),
+            _ => Value::List(items, Separator::Space),
+        })
+    }
+
+    fn or(&mut self, ev: &mut Evaluator<'_>) -> Result<Value, ScssError> {
+        let mut left = self.and(ev)?;
+        while self.operator().as_deref() == Some("or") {
+            self.pos += 1;
+            let right = self.and(ev)?;
+            if !left.is_truthy() {
+                left = right;
+            }
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self, ev: &mut Evaluator<'_>) -> Result<Value, ScssError> {
+        let mut left = self.equality(ev)?;
+        while self.operator().as_deref() == Some("and") {
+            self.pos += 1;
+            let right = self.equality(ev)?;
+            if left.is_truthy() {
+                left = right;
+            }
+        }
+        Ok(left)
+    }
+
+    fn equality(&mut self, ev: &mut Evaluator<'_>) -> Result<Value, ScssError> {
+        let mut left = self.relational(ev)?;
+        while let Some(op) = self.operator().filter(|op| op == "==" || op == "!=") {
+            self.pos += 1;
+            let right = self.relational(ev)?;
+            left = Value::Bool(left.equals(&right) == (op == "=="));
+        }
+        Ok(left)
+    }
+
+    fn relational(&mut self, ev: &mut Evaluator<'_>) -> Result<Value, ScssError> {
+        let mut left = self.additive(ev)?.0;
+        while let Some(op) = self
+            .operator()
+            .filter(|op| matches!(op.as_str(), ">" | "<" | ">=" | "<="))
+        {
+            let range = self.peek().map(|n| n.range).unwrap_or_default();
+            self.pos += 1;
+            let right = self.additive(ev)?.0;
+            let difference = left
+                .operate("-", &right)
+                .ok()
+                .and_then(|value| match value {
+                    Value::Number(n, _) => Some(n),
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    ScssError::new(
+                        format!(
+                            "undefined operation \"{} {} {}\"",
+                            left.inspect(),
+                            op,
+                            right.inspect()
+                        ),
+                        range,
+                    )
+                })?;
+            left = Value::Bool(match op.as_str() {
+                ">" => difference > 0.0,
+                "<" => difference < 0.0,
+                ">=" => difference >= 0.0,
+                _ => difference <= 0.0,
+            });
+        }
+        Ok(left)
+    }
+
+    // 返回值和是否是直接写出的数值，两个直接写出的数值之间的 / 不是除法，例如 font: 12px/1.5
+    fn additive(&mut self, ev: &mut Evaluator<'_>) -> Result<(Value, bool), ScssError> {
+        let (mut left, mut literal) = self.multiplicative(ev)?;
+        while let Some(node) = self.peek() {
+            // 1-2 和 1+1 中带符号的数值紧跟在前一个值后面，是减法和加法
+            if let Some((op, number)) = signed_number(node).filter(|_| !self.is_spaced(self.pos)) {
+                self.pos += 1;
+                let right = Value::parse_number(number).unwrap_or(Value::Null);
+                let (right, _) = self.multiplicative_rest(ev, right, true)?;
+                left = operate(&left, op, &right, node.range)?;
+                literal = false;
+                continue;
+            }
+            let Some(op) = self.operator().filter(|op| op == "+" || op == "-") else {
+                break;
+            };
+            // 前面有空格后面没有空格时是下一个值的符号
+            if self.is_spaced(self.pos) && !self.is_spaced(self.pos + 1) {
+                break;
+            }
+            self.pos += 1;
+            let (right, _) = self.multiplicative(ev)?;
+            left = operate(&left, &op, &right, node.range)?;
+            literal = false;
+        }
+        Ok((left, literal))
+    }
+
+    fn multiplicative(&mut self, ev: &mut Evaluator<'_>) -> Result<(Value, bool), ScssError> {
+        let (left, literal) = self.unary(ev)?;
+        self.multiplicative_rest(ev, left, literal)
+    }
+
+    fn multiplicative_rest(
+        &mut self,
+        ev: &mut Evaluator<'_>,
+        mut left: Value,
+        mut literal: bool,
+    ) -> Result<(Value, bool), ScssError> {
+        while let Some(op) = self
+            .operator()
+            .filter(|op| matches!(op.as_str(), "*" | "/" | "%"))
+        {
+            let range = self.peek().map(|n| n.range).unwrap_or_default();
+            self.pos += 1;
+            let (right, right_literal) = self.unary(ev)?;
+            if op == "/" && literal && right_literal && !self.paren {
+                left = Value::ident(&format!("{}/{}", left, right));
+                continue;
+            }
+            left = operate(&left, &op, &right, range)?;
+            literal = false;
+        }
+        Ok((left, literal))
+    }
+
+    fn unary(&mut self, ev: &mut Evaluator<'_>) -> Result<(Value, bool), ScssError> {
+        match self.operator().as_deref() {
+            Some("not") => {
+                self.pos += 1;
+                let (value, _) = self.unary(ev)?;
+                Ok((Value::Bool(!value.is_truthy()), false))
+            }
+            Some(op @ ("-" | "+")) => {
+                let op = op.to_string();
+                self.pos += 1;
+                let (value, literal) = self.unary(ev)?;
+                let value = match value {
+                    Value::Number(n, unit) if op == "-" => Value::Number(-n, unit),
+                    Value::Number(n, unit) => Value::Number(n, unit),
+                    value => Value::ident(&format!("{}{}", op, value)),
+                };
+                Ok((value, literal))
+            }
+            _ => self.primary(ev),
+        }
+    }
+
+    fn primary(&mut self, ev: &mut Evaluator<'_>) -> Result<(Value, bool), ScssError> {
+        let Some(node) = self.peek() else {
+            return Ok((Value::Null, false));
+        };
+        self.pos += 1;
+        if !node.check_type(TokenType::Term) {
+            return Ok((Value::ident(node.raw.trim()), false));
+        }
+        let children = node.child_nodes();
+        let Some(first) = children.first() else {
+            return Ok((Value::Null, false));
+        };
+        let value = match first.node_type.0 {
+            TokenType::Digital | TokenType::Dimension | TokenType::PercentageToken => {
+                let value =
+                    Value::parse_number(&first.raw).unwrap_or_else(|| Value::ident(&first.raw));
+                return Ok((value, true));
+            }
+            TokenType::Str => Value::quoted(&ev.interpolate(&first.raw, first.range)?),
+            TokenType::IdentToken if first.raw.contains("#{") => {
+                Value::ident(&ev.interpolate(&first.raw, first.range)?)
+            }
+            TokenType::IdentToken => match first.raw.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                _ => match self.member(first) {
+                    Some(member) => ev.member(&first.raw, member)?,
+                    None => Value::ident(&first.raw),
+                },
+            },
+            TokenType::UrlToken => Value::ident(&ev.interpolate(&first.raw, first.range)?),
+            TokenType::Variable => ev.variable(&first.raw, first.range)?,
+            TokenType::Function => ev.function(None, first)?,
+            TokenType::Map => ev.map(first)?,
+            TokenType::LeftParenthesis => match node.find_child(TokenType::Expression) {
+                Some(expr) if !expr.child_nodes().is_empty() => {
+                    evaluate(expr.child_nodes(), true, ev)?
+                }
+                _ => Value::List(Vec::new(), Separator::Space),
+            },
+            _ => Value::ident(&first.raw),
+        };
+        Ok((value, false))
+    }
+
+    // math.div() 和 math.$pi 中紧跟在模块名后面的 . 和成员
+    fn member(&mut self, module: &AstNode<TokenType>) -> Option<&'n AstNode<TokenType>> {
+        let dot = self.peek()?;
+        let member = self.items.get(self.pos + 1)?;
+        let adjacent = module.range.end_pos == dot.range.start_pos
+            && dot.range.end_pos == member.range.start_pos;
+        let member = member.child_nodes().first()?;
+        if !adjacent
+            || dot.find_child(TokenType::Dot).is_none()
+            || !(member.check_type(TokenType::Function) || member.check_type(TokenType::Variable))
+        {
+            return None;
+        }
+        self.pos += 2;
+        Some(member)
+    }
+}
+// ANCHOR_END: expr_parser
+
+fn operate(left: &Value, op: &str, right: &Value, range: Range) -> Result<Value, ScssError> {
+    left.operate(op, right)
+        .map_err(|message| ScssError::new(message, range))
+}
+
+fn evaluate(
+    items: &[AstNode<TokenType>],
+    paren: bool,
+    ev: &mut Evaluator<'_>,
+) -> Result<Value, ScssError> {
+    let items = split_numbers(items);
+    ExprParser::new(&items, paren).comma_list(ev)
+}
+
+// 10px-3px 被解析为单位是 px-3px 的数值，拆成 10px 和 -3px 两项，按减法计算
+fn split_numbers(items: &[AstNode<TokenType>]) -> Cow<'_, [AstNode<TokenType>]> {
+    if items.iter().all(|item| subtractions(item).is_empty()) {
+        return Cow::Borrowed(items);
+    }
+    let mut result = Vec::new();
+    for item in items {
+        let splits = subtractions(item);
+        if splits.is_empty() {
+            result.push(item.clone());
+            continue;
+        }
+        let text = item.raw.as_str();
+        let bounds = std::iter::once(0)
+            .chain(splits)
+            .chain(std::iter::once(text.len()));
+        let bounds: Vec<usize> = bounds.collect();
+        for pair in bounds.windows(2) {
+            let part = &text[pair[0]..pair[1]];
+            let node_type = match part.trim_start_matches('-').parse::<f64>() {
+                Ok(_) => TokenType::Digital,
+                Err(_) => TokenType::Dimension,
+            };
+            let start = item.range.start_pos + pair[0];
+            let number = AstNode {
+                range: Range::new(start, start + part.len()),
+                ..leaf(node_type, part)
+            };
+            result.push(AstNode::from_children(TokenType::Term, vec![number]));
+        }
+    }
+    Cow::Owned(result)
+}
+
+// 数值中作为减号的 - 的位置，跳过 1e-3 中的指数
+fn subtractions(item: &AstNode<TokenType>) -> Vec<usize> {
+    let is_dimension = match item.child_nodes() {
+        [number] => number.check_type(TokenType::Dimension),
+        _ => false,
+    };
+    if !is_dimension {
+        return Vec::new();
+    }
+    let bytes = item.raw.as_bytes();
+    (1..bytes.len().saturating_sub(1))
+        .filter(|&i| {
+            let exponent =
+                i >= 2 && matches!(bytes[i - 1], b'e' | b'E') && bytes[i - 2].is_ascii_digit();
+            bytes[i] == b'-' && bytes[i + 1].is_ascii_digit() && bytes[i - 1] != b'-' && !exponent
+        })
+        .collect()
+}
+
+// +1、-2px 这类带符号的数值，返回符号和去掉符号的数值
+fn signed_number(node: &AstNode<TokenType>) -> Option<(&str, &str)> {
+    let number = node.child_nodes().first().filter(|n| {
+        matches!(
+            n.node_type.0,
+            TokenType::Digital | TokenType::Dimension | TokenType::PercentageToken
+        )
+    })?;
+    let sign = number.raw.get(..1).filter(|c| *c == "+" || *c == "-")?;
+    Some((sign, &number.raw[1..]))
+}
+
+impl<'a> Evaluator<'a> {
+    // ANCHOR: expression
+    pub(super) fn expression(&mut self, expr: &AstNode<TokenType>) -> Result<Value, ScssError> {
+        evaluate(expr.child_nodes(), false, self)
+    }
+
+    // 解析并求值一段文本，例如插值 #{$a + 1} 中的内容
+    pub(super) fn eval_text(&mut self, text: &str, range: Range) -> Result<Value, ScssError> {
+        if text.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        let expr = parse_value_with_syntax(text, Syntax::Scss)
+            .ok_or_else(|| ScssError::new(format!("invalid expression `{}`", text), range))?;
+        self.expression(&expr)
+    }
+
+    // 替换文本中的 #{...}，字符串插入时去掉引号
+    pub(super) fn interpolate(&mut self, text: &str, range: Range) -> Result<String, ScssError> {
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("#{") {
+            let inner = &rest[start + 2..];
+            let mut depth = 0;
+            let Some(end) = inner.char_indices().find_map(|(i, c)| {
+                match c {
+                    '{' => depth += 1,
+                    '}' if depth == 0 => return Some(i),
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                None
+            }) else {
+                break;
+            };
+            let value = self.eval_text(&inner[..end], range)?;
+            result.push_str(&rest[..start]);
+            result.push_str(&value.text());
+            rest = &inner[end + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+    // ANCHOR_END: expression
+
+    // (key: value, ...)
+    fn map(&mut self, node: &AstNode<TokenType>) -> Result<Value, ScssError> {
+        let exprs: Vec<&AstNode<TokenType>> = node
+            .child_nodes()
+            .iter()
+            .filter(|n| n.check_type(TokenType::Expression))
+            .collect();
+        let mut pairs = Vec::new();
+        for pair in exprs.chunks(2) {
+            let key = self.expression(pair[0])?;
+            let value = match pair.get(1) {
+                Some(expr) => self.expression(expr)?,
+                None => Value::Null,
+            };
+            pairs.push((key, value));
+        }
+        Ok(Value::Map(pairs))
+    }
+
+    // 模块成员 math.div() 或 math.$pi，模块名可以是 @use 时的别名
+    fn member(&mut self, alias: &str, member: &AstNode<TokenType>) -> Result<Value, ScssError> {
+        let module = self
+            .modules
+            .get(alias)
+            .cloned()
+            .unwrap_or_else(|| alias.to_string());
+        if member.check_type(TokenType::Function) {
+            return self.function(Some(&module), member);
+        }
+        match (module.as_str(), member.raw.as_str()) {
+            ("math", "$pi") => Ok(Value::Number(PI, String::new())),
+            ("math", "$e") => Ok(Value::Number(E, String::new())),
+            _ => Err(ScssError::new(
+                format!("variable {}.{} is undefined", alias, member.raw),
+                member.range,
+            )),
+        }
+    }
+
+    // ANCHOR: function
+    // 依次查找自定义函数、内置函数，都不是时作为 css 函数输出
+    fn function(
+        &mut self,
+        module: Option<&str>,
+        node: &AstNode<TokenType>,
+    ) -> Result<Value, ScssError> {
+        let name = node
+            .find_child(TokenType::FunctionToken)
+            .map(|n| n.raw.trim_end_matches('('))
+            .unwrap_or_default();
+        let key = name.to_ascii_lowercase().replace('_', "-");
+        let list = node.find_child(TokenType::ArgumentList);
+        if module.is_none() {
+            if CSS_FUNCTIONS.contains(&key.as_str()) {
+                return self.css_function(node);
+            }
+            // if() 只求值选中的参数
+            if key == "if" {
+                let exprs: Vec<&AstNode<TokenType>> = list
+                    .map(|list| list.child_nodes())
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|n| n.check_type(TokenType::Expression))
+                    .collect();
+                if let [condition, if_true, if_false] = exprs.as_slice() {
+                    return match self.expression(condition)?.is_truthy() {
+                        true => self.expression(if_true),
+                        false => self.expression(if_false),
+                    };
+                }
+            }
+            if let Some(function) = self.find_function(&key) {
+                let args = self.arguments(list)?;
+                return self.call_function(function, args, node.range);
+            }
+        }
+        let args = self.arguments(list)?;
+        // @use "sass:math" as * 的函数可以不带模块名调用
+        let global: Vec<String> = self
+            .modules
+            .iter()
+            .filter(|(alias, _)| *alias == "*")
+            .map(|(_, module)| module.clone())
+            .collect();
+        let modules = std::iter::once(module).chain(
+            global
+                .iter()
+                .map(|module| Some(module.as_str()))
+                .filter(|_| module.is_none()),
+        );
+        for module in modules {
+            if let Some(result) = builtin(module, &key, &args) {
+                return result.map_err(|message| ScssError::new(message, node.range));
+            }
+        }
+        if let Some(module) = module {
+            return Err(ScssError::new(
+                format!("function {}.{} is undefined", module, name),
+                node.range,
+            ));
+        }
+        if key == "min" || key == "max" {
+            return self.css_function(node);
+        }
+        let args: Vec<String> = args.positional.iter().map(|arg| arg.to_string()).collect();
+        Ok(Value::ident(&format!("{}({})", name, args.join(", "))))
+    }
+    // ANCHOR_END: function
+
+    // 只替换参数中的变量和插值
+    fn css_function(&mut self, node: &AstNode<TokenType>) -> Result<Value, ScssError> {
+        let mut function = node.clone();
+        self.substitute(&mut function)?;
+        Ok(Value::ident(&function_to_string(&function)))
+    }
+
+    fn substitute(&mut self, node: &mut AstNode<TokenType>) -> Result<(), ScssError> {
+        let Some(children) = &mut node.children else {
+            return Ok(());
+        };
+        for child in children.iter_mut() {
+            match child.node_type.0 {
+                TokenType::Variable => {
+                    let value = self.variable(&child.raw, child.range)?;
+                    *child = AstNode {
+                        range: child.range,
+                        ..leaf(TokenType::IdentToken, &value.to_string())
+                    };
+                }
+                TokenType::IdentToken | TokenType::Str | TokenType::UrlToken
+                    if child.raw.contains("#{") =>
+                {
+                    child.raw = self.interpolate(&child.raw, child.range)?;
+                }
+                _ => self.substitute(child)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,532 @@
+use super::value::{Separator, Value};
+use crate::{
+    color::{hsl_to_srgb, srgb_to_hsl},
+    values::convert,
+};
+
+// ANCHOR: args
+// 调用时的参数，参数名去掉了 $
+#[derive(Default)]
+pub(super) struct Args {
+    pub(super) positional: Vec<Value>,
+    pub(super) named: Vec<(String, Value)>,
+}
+
+impl Args {
+    // 按位置或参数名取参数
+    fn get(&self, index: usize, name: &str) -> Option<&Value> {
+        self.positional.get(index).or_else(|| self.named(name))
+    }
+
+    fn named(&self, name: &str) -> Option<&Value> {
+        self.named
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    fn required(&self, index: usize, name: &str) -> Result<&Value, String> {
+        self.get(index, name)
+            .ok_or_else(|| format!("missing argument ${}", name))
+    }
+}
+// ANCHOR_END: args
+
+// ANCHOR: builtin
+// sass:math、sass:color、sass:list、sass:map 等模块中的函数和对应的全局函数
+// module 为 None 时是全局函数，不是内置函数时返回 None
+pub(super) fn builtin(
+    module: Option<&str>,
+    name: &str,
+    args: &Args,
+) -> Option<Result<Value, String>> {
+    match (module, name) {
+        // 参数不是数值时作为 css 函数，例如 rgb(var(--rgb))、min(100%, 500px)
+        (None, "rgb" | "rgba") => rgb(args),
+        (None, "hsl" | "hsla") => hsl(args),
+        (None | Some("math"), "min" | "max") => extremum(name == "max", args),
+        _ => call(module, name, args).transpose(),
+    }
+}
+
+fn call(module: Option<&str>, name: &str, args: &Args) -> Result<Option<Value>, String> {
+    let value = match (module, name) {
+        // sass:math
+        (None | Some("math"), "percentage") => {
+            let (n, _) = number(args.required(0, "number")?, "number")?;
+            Value::Number(n * 100.0, "%".to_string())
+        }
+        (None | Some("math"), "round" | "ceil" | "floor" | "abs") => {
+            let (n, unit) = number(args.required(0, "number")?, "number")?;
+            let n = match name {
+                "round" => n.round(),
+                "ceil" => n.ceil(),
+                "floor" => n.floor(),
+                _ => n.abs(),
+            };
+            Value::Number(n, unit.to_string())
+        }
+        (Some("math"), "div") => args
+            .required(0, "number1")?
+            .operate("/", args.required(1, "number2")?)?,
+        (Some("math"), "sqrt") => {
+            Value::Number(unitless(args.required(0, "number")?)?.sqrt(), String::new())
+        }
+        (Some("math"), "pow") => {
+            let base = unitless(args.required(0, "base")?)?;
+            let exponent = unitless(args.required(1, "exponent")?)?;
+            Value::Number(base.powf(exponent), String::new())
+        }
+        (Some("math"), "clamp") => {
+            let (min, unit) = number(args.required(0, "min")?, "min")?;
+            let value = convert_to(args.required(1, "number")?, unit)?;
+            let max = convert_to(args.required(2, "max")?, unit)?;
+            Value::Number(value.clamp(min, max.max(min)), unit.to_string())
+        }
+        (None | Some("math"), "unit") => {
+            let (_, unit) = number(args.required(0, "number")?, "number")?;
+            Value::Str(unit.to_string(), true)
+        }
+        (None, "unitless") | (Some("math"), "is-unitless") => {
+            let (_, unit) = number(args.required(0, "number")?, "number")?;
+            Value::Bool(unit.is_empty())
+        }
+        (None, "comparable") | (Some("math"), "compatible") => {
+            let (_, a) = number(args.required(0, "number1")?, "number1")?;
+            let (_, b) = number(args.required(1, "number2")?, "number2")?;
+            Value::Bool(a.is_empty() || b.is_empty() || convert(1.0, b, a).is_some())
+        }
+
+        // sass:color
+        (None | Some("color"), "red" | "green" | "blue") => {
+            let rgba = color(args.required(0, "color")?)?;
+            let index = match name {
+                "red" => 0,
+                "green" => 1,
+                _ => 2,
+            };
+            Value::Number(rgba[index].round(), String::new())
+        }
+        (None | Some("color"), "alpha" | "opacity") => {
+            let [_, _, _, a] = color(args.required(0, "color")?)?;
+            Value::Number(a, String::new())
+        }
+        (None | Some("color"), "hue" | "saturation" | "lightness") => {
+            let [h, s, l, _] = hsla(args.required(0, "color")?)?;
+            match name {
+                "hue" => Value::Number(h, "deg".to_string()),
+                "saturation" => Value::Number(s, "%".to_string()),
+                _ => Value::Number(l, "%".to_string()),
+            }
+        }
+        (None, "lighten" | "darken" | "saturate" | "desaturate" | "adjust-hue") => {
+            let [h, s, l, a] = hsla(args.required(0, "color")?)?;
+            let (amount, _) = number(args.required(1, "amount")?, "amount")?;
+            from_hsla(match name {
+                "lighten" => [h, s, l + amount, a],
+                "darken" => [h, s, l - amount, a],
+                "saturate" => [h, s + amount, l, a],
+                "desaturate" => [h, s - amount, l, a],
+                _ => [h + amount, s, l, a],
+            })
+        }
+        (None, "opacify" | "fade-in" | "transparentize" | "fade-out") => {
+            let [r, g, b, a] = color(args.required(0, "color")?)?;
+            let (amount, _) = number(args.required(1, "amount")?, "amount")?;
+            let a = if name == "opacify" || name == "fade-in" {
+                a + amount
+            } else {
+                a - amount
+            };
+            Value::Color([r, g, b, a.clamp(0.0, 1.0)])
+        }
+        (None | Some("color"), "mix") => {
+            let first = color(args.required(0, "color1")?)?;
+            let second = color(args.required(1, "color2")?)?;
+            let weight = match args.get(2, "weight") {
+                Some(weight) => number(weight, "weight")?.0,
+                None => 50.0,
+            };
+            mix(first, second, weight / 100.0)
+        }
+        (None | Some("color"), "invert") => {
+            let [r, g, b, a] = color(args.required(0, "color")?)?;
+            let weight = match args.get(1, "weight") {
+                Some(weight) => number(weight, "weight")?.0,
+                None => 100.0,
+            };
+            mix(
+                [255.0 - r, 255.0 - g, 255.0 - b, a],
+                [r, g, b, a],
+                weight / 100.0,
+            )
+        }
+        (None | Some("color"), "grayscale") => {
+            let [h, _, l, a] = hsla(args.required(0, "color")?)?;
+            from_hsla([h, 0.0, l, a])
+        }
+        (None | Some("color"), "complement") => {
+            let [h, s, l, a] = hsla(args.required(0, "color")?)?;
+            from_hsla([h + 180.0, s, l, a])
+        }
+        (None, "adjust-color") | (Some("color"), "adjust") => {
+            adjust(args, |value, amount, _| value + amount)?
+        }
+        (None, "scale-color") | (Some("color"), "scale") => adjust(args, |value, amount, max| {
+            if amount > 0.0 {
+                value + (max - value) * amount / 100.0
+            } else {
+                value + value * amount / 100.0
+            }
+        })?,
+        (None, "change-color") | (Some("color"), "change") => adjust(args, |_, amount, _| amount)?,
+
+        // sass:list
+        (None | Some("list"), "length") => {
+            let len = args.required(0, "list")?.items().len();
+            Value::Number(len as f64, String::new())
+        }
+        (None | Some("list"), "nth") => {
+            let list = args.required(0, "list")?.items();
+            let index = index(&list, args.required(1, "n")?)?;
+            list[index].clone()
+        }
+        (None | Some("list"), "set-nth") => {
+            let list = args.required(0, "list")?;
+            let mut items = list.items();
+            let index = index(&items, args.required(1, "n")?)?;
+            items[index] = args.required(2, "value")?.clone();
+            Value::List(items, list.separator())
+        }
+        (None | Some("list"), "append") => {
+            let list = args.required(0, "list")?;
+            let mut items = list.items();
+            items.push(args.required(1, "val")?.clone());
+            let separator = separator(args.get(2, "separator"), list.separator())?;
+            Value::List(items, separator)
+        }
+        (None | Some("list"), "join") => {
+            let first = args.required(0, "list1")?;
+            let second = args.required(1, "list2")?;
+            // 第一个列表只有一个元素时使用第二个列表的分隔符
+            let auto = if first.items().len() > 1 || second.items().len() <= 1 {
+                first.separator()
+            } else {
+                second.separator()
+            };
+            let separator = separator(args.get(2, "separator"), auto)?;
+            let mut items = first.items();
+            items.extend(second.items());
+            Value::List(items, separator)
+        }
+        (None | Some("list"), "index") => {
+            let list = args.required(0, "list")?.items();
+            let value = args.required(1, "value")?;
+            list.iter()
+                .position(|item| item.equals(value))
+                .map_or(Value::Null, |i| {
+                    Value::Number(i as f64 + 1.0, String::new())
+                })
+        }
+        (None, "list-separator") | (Some("list"), "separator") => {
+            match args.required(0, "list")?.separator() {
+                Separator::Space => Value::ident("space"),
+                Separator::Comma => Value::ident("comma"),
+            }
+        }
+        (None | Some("list"), "zip") => {
+            let lists: Vec<Vec<Value>> = args.positional.iter().map(Value::items).collect();
+            let len = lists.iter().map(Vec::len).min().unwrap_or(0);
+            let items = (0..len)
+                .map(|i| {
+                    let items = lists.iter().map(|list| list[i].clone()).collect();
+                    Value::List(items, Separator::Space)
+                })
+                .collect();
+            Value::List(items, Separator::Comma)
+        }
+
+        // sass:map
+        (None, "map-get") | (Some("map"), "get") => {
+            let mut value = args.required(0, "map")?.clone();
+            for key in keys(args)? {
+                value = lookup(&map(&value)?, key).cloned().unwrap_or(Value::Null);
+            }
+            value
+        }
+        (None, "map-has-key") | (Some("map"), "has-key") => {
+            let mut value = Some(args.required(0, "map")?.clone());
+            for key in keys(args)? {
+                value = value
+                    .and_then(|value| value.pairs())
+                    .and_then(|pairs| lookup(&pairs, key).cloned());
+            }
+            Value::Bool(value.is_some())
+        }
+        (None, "map-merge") | (Some("map"), "merge") => {
+            let mut pairs = map(args.required(0, "map1")?)?;
+            for (key, value) in map(args.required(1, "map2")?)? {
+                match pairs.iter_mut().find(|(k, _)| k.equals(&key)) {
+                    Some(pair) => pair.1 = value,
+                    None => pairs.push((key, value)),
+                }
+            }
+            Value::Map(pairs)
+        }
+        (None, "map-remove") | (Some("map"), "remove") => {
+            let mut pairs = map(args.required(0, "map")?)?;
+            let keys = args.positional.get(1..).unwrap_or_default();
+            pairs.retain(|(key, _)| !keys.iter().any(|k| k.equals(key)));
+            Value::Map(pairs)
+        }
+        (None, "map-keys" | "map-values") | (Some("map"), "keys" | "values") => {
+            let pairs = map(args.required(0, "map")?)?;
+            let keys = name.ends_with("keys");
+            let items = pairs
+                .into_iter()
+                .map(|(key, value)| if keys { key } else { value })
+                .collect();
+            Value::List(items, Separator::Comma)
+        }
+
+        // sass:meta 和 sass:string
+        (None, "if") => {
+            if args.required(0, "condition")?.is_truthy() {
+                args.required(1, "if-true")?.clone()
+            } else {
+                args.required(2, "if-false")?.clone()
+            }
+        }
+        (None | Some("meta"), "type-of") => Value::ident(args.required(0, "value")?.type_name()),
+        (None | Some("meta"), "inspect") => Value::ident(&args.required(0, "value")?.inspect()),
+        (None | Some("string"), "unquote") => Value::ident(&args.required(0, "string")?.text()),
+        (None | Some("string"), "quote") => Value::Str(args.required(0, "string")?.text(), true),
+        (None | Some("string"), "to-upper-case" | "to-lower-case") => {
+            let value = args.required(0, "string")?;
+            let text = if name == "to-upper-case" {
+                value.text().to_ascii_uppercase()
+            } else {
+                value.text().to_ascii_lowercase()
+            };
+            Value::Str(text, matches!(value, Value::Str(_, true)))
+        }
+        (None, "str-length") | (Some("string"), "length") => {
+            let len = args.required(0, "string")?.text().chars().count();
+            Value::Number(len as f64, String::new())
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+// ANCHOR_END: builtin
+
+fn number<'v>(value: &'v Value, name: &str) -> Result<(f64, &'v str), String> {
+    match value {
+        Value::Number(n, unit) => Ok((*n, unit)),
+        _ => Err(format!("${}: {} is not a number", name, value.inspect())),
+    }
+}
+
+fn unitless(value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Number(n, unit) if unit.is_empty() => Ok(*n),
+        _ => Err(format!("{} must be unitless", value.inspect())),
+    }
+}
+
+// 换算成指定的单位，没有单位的数值直接使用
+fn convert_to(value: &Value, unit: &str) -> Result<f64, String> {
+    let (n, from) = number(value, "number")?;
+    if from.is_empty() || unit.is_empty() {
+        return Ok(n);
+    }
+    convert(n, from, unit).ok_or_else(|| format!("incompatible units {} and {}", from, unit))
+}
+
+fn extremum(max: bool, args: &Args) -> Option<Result<Value, String>> {
+    let (first, rest) = args.positional.split_first()?;
+    let Value::Number(mut best, unit) = first else {
+        return None;
+    };
+    for value in rest {
+        let n = convert_to(value, unit).ok()?;
+        if max && n > best || !max && n < best {
+            best = n;
+        }
+    }
+    Some(Ok(Value::Number(best, unit.clone())))
+}
+
+// ANCHOR: color
+fn color(value: &Value) -> Result<[f64; 4], String> {
+    value
+        .color()
+        .ok_or_else(|| format!("{} is not a color", value.inspect()))
+}
+
+// 色相 0..360，饱和度和亮度 0..100
+fn hsla(value: &Value) -> Result<[f64; 4], String> {
+    let [r, g, b, a] = color(value)?;
+    let [h, s, l] = srgb_to_hsl([r / 255.0, g / 255.0, b / 255.0]);
+    Ok([if h.is_nan() { 0.0 } else { h }, s, l, a])
+}
+
+fn from_hsla([h, s, l, a]: [f64; 4]) -> Value {
+    let [r, g, b] = hsl_to_srgb([h, s.clamp(0.0, 100.0), l.clamp(0.0, 100.0)]);
+    Value::Color([r * 255.0, g * 255.0, b * 255.0, a.clamp(0.0, 1.0)])
+}
+
+// rgb(255, 0, 0)、rgba(255, 0, 0, .5) 和 rgba($color, .5)，参数不是数值时作为 css 函数
+fn rgb(args: &Args) -> Option<Result<Value, String>> {
+    if let [value, alpha] = args.positional.as_slice() {
+        let [r, g, b, _] = value.color()?;
+        let Value::Number(alpha, unit) = alpha else {
+            return None;
+        };
+        let alpha = if unit == "%" { alpha / 100.0 } else { *alpha };
+        return Some(Ok(Value::Color([r, g, b, alpha.clamp(0.0, 1.0)])));
+    }
+    let mut rgba = [0.0, 0.0, 0.0, 1.0];
+    for (i, name) in ["red", "green", "blue", "alpha"].iter().enumerate() {
+        let value = match args.get(i, name) {
+            Some(Value::Number(n, unit)) => match (i, unit.as_str()) {
+                (3, "%") => n / 100.0,
+                (_, "%") => n * 2.55,
+                _ => *n,
+            },
+            None if i == 3 => 1.0,
+            _ => return None,
+        };
+        rgba[i] = value;
+    }
+    rgba[3] = rgba[3].clamp(0.0, 1.0);
+    Some(Ok(Value::Color(rgba)))
+}
+
+fn hsl(args: &Args) -> Option<Result<Value, String>> {
+    let mut hsla = [0.0, 0.0, 0.0, 1.0];
+    for (i, name) in ["hue", "saturation", "lightness", "alpha"]
+        .iter()
+        .enumerate()
+    {
+        let value = match args.get(i, name) {
+            Some(Value::Number(n, unit)) if i == 3 && unit == "%" => n / 100.0,
+            Some(Value::Number(n, _)) => *n,
+            None if i == 3 => 1.0,
+            _ => return None,
+        };
+        hsla[i] = value;
+    }
+    Some(Ok(from_hsla(hsla)))
+}
+
+// color.adjust、color.scale 和 color.change 的关键字参数
+fn adjust(args: &Args, apply: fn(f64, f64, f64) -> f64) -> Result<Value, String> {
+    let value = args.required(0, "color")?;
+    let mut rgba = color(value)?;
+    let amount = |name: &str| -> Result<Option<f64>, String> {
+        args.named(name)
+            .map(|value| number(value, name).map(|(n, _)| n))
+            .transpose()
+    };
+    for (i, name) in ["red", "green", "blue"].iter().enumerate() {
+        if let Some(n) = amount(name)? {
+            rgba[i] = apply(rgba[i], n, 255.0).clamp(0.0, 255.0);
+        }
+    }
+    if let Some(n) = amount("alpha")? {
+        rgba[3] = apply(rgba[3], n, 1.0).clamp(0.0, 1.0);
+    }
+    let hue = amount("hue")?;
+    let saturation = amount("saturation")?;
+    let lightness = amount("lightness")?;
+    if hue.is_none() && saturation.is_none() && lightness.is_none() {
+        return Ok(Value::Color(rgba));
+    }
+    let [mut h, mut s, mut l, a] = hsla(&Value::Color(rgba))?;
+    if let Some(n) = hue {
+        h = apply(h, n, 360.0);
+    }
+    if let Some(n) = saturation {
+        s = apply(s, n, 100.0);
+    }
+    if let Some(n) = lightness {
+        l = apply(l, n, 100.0);
+    }
+    Ok(from_hsla([h, s, l, a]))
+}
+
+// 与 sass 相同，权重同时考虑两个颜色的不透明度
+fn mix(first: [f64; 4], second: [f64; 4], p: f64) -> Value {
+    let w = p * 2.0 - 1.0;
+    let a = first[3] - second[3];
+    let w1 = (if w * a == -1.0 {
+        w
+    } else {
+        (w + a) / (1.0 + w * a)
+    } + 1.0)
+        / 2.0;
+    let w2 = 1.0 - w1;
+    let mut rgba = [0.0; 4];
+    for i in 0..3 {
+        rgba[i] = first[i] * w1 + second[i] * w2;
+    }
+    rgba[3] = first[3] * p + second[3] * (1.0 - p);
+    Value::Color(rgba)
+}
+// ANCHOR_END: color
+
+// ANCHOR: list
+// 从 1 开始的下标，负数从末尾开始
+fn index(list: &[Value], n: &Value) -> Result<usize, String> {
+    let (n, _) = number(n, "n")?;
+    let len = list.len() as f64;
+    let index = if n < 0.0 { len + n } else { n - 1.0 };
+    if n == 0.0 || index < 0.0 || index >= len {
+        return Err(format!(
+            "invalid index {} for a list with {} elements",
+            n,
+            list.len()
+        ));
+    }
+    Ok(index as usize)
+}
+
+fn separator(value: Option<&Value>, auto: Separator) -> Result<Separator, String> {
+    match value.map(Value::text).as_deref() {
+        None | Some("auto") => Ok(auto),
+        Some("comma") => Ok(Separator::Comma),
+        Some("space") => Ok(Separator::Space),
+        Some(other) => Err(format!(
+            "$separator: must be \"space\", \"comma\" or \"auto\", was {}",
+            other
+        )),
+    }
+}
+// ANCHOR_END: list
+
+// ANCHOR: map
+fn map(value: &Value) -> Result<Vec<(Value, Value)>, String> {
+    value
+        .pairs()
+        .ok_or_else(|| format!("{} is not a map", value.inspect()))
+}
+
+fn lookup<'m>(pairs: &'m [(Value, Value)], key: &Value) -> Option<&'m Value> {
+    pairs
+        .iter()
+        .find(|(k, _)| k.equals(key))
+        .map(|(_, value)| value)
+}
+
+// map.get($map, $key, $keys...) 依次查找嵌套的 map
+fn keys(args: &Args) -> Result<&[Value], String> {
+    match args.positional.get(1..) {
+        Some(keys) if !keys.is_empty() => Ok(keys),
+        _ => args
+            .named("key")
+            .map(std::slice::from_ref)
+            .ok_or_else(|| "missing argument $key".to_string()),
+    }
+}
+// ANCHOR_END: map
@@ -0,0 +1,299 @@
+use std::fmt::{self, Display};
+
+use crate::{
+    color::Color,
+    less::unquote,
+    values::{convert, format_number, split_number},
+};
+
+// ANCHOR: value
+// scss 表达式的值，颜色的 rgb 分量为 0..255，不透明度为 0..1
+#[derive(Debug, Clone)]
+pub(super) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64, String),
+    // 文本和是否带引号
+    Str(String, bool),
+    Color([f64; 4]),
+    List(Vec<Value>, Separator),
+    Map(Vec<(Value, Value)>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Separator {
+    Space,
+    Comma,
+}
+// ANCHOR_END: value
+
+impl Value {
+    // 12px、50%、.5 这类文本
+    pub(super) fn parse_number(text: &str) -> Option<Value> {
+        let (value, unit) = split_number(text)?;
+        Some(Value::Number(value, unit.to_string()))
+    }
+
+    pub(super) fn ident(text: &str) -> Value {
+        Value::Str(text.to_string(), false)
+    }
+
+    // 引号中的内容去掉引号
+    pub(super) fn quoted(text: &str) -> Value {
+        Value::Str(unquote(text).to_string(), true)
+    }
+
+    // 只有 false 和 null 是假值
+    pub(super) fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Null | Value::Bool(false))
+    }
+
+    pub(super) fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    // 颜色值，没有引号的颜色关键字和 #rgb 也是颜色
+    pub(super) fn color(&self) -> Option<[f64; 4]> {
+        match self {
+            Value::Color(rgba) => Some(*rgba),
+            Value::Str(text, false) if !text.contains('(') => {
+                let color = Color::parse(text)?;
+                let [r, g, b] = color.to_srgb().components.map(|c| c * 255.0);
+                Some([r, g, b, color.alpha])
+            }
+            _ => None,
+        }
+    }
+
+    // 列表中的元素，map 的每一项是键和值组成的列表，其他值是只有一个元素的列表
+    pub(super) fn items(&self) -> Vec<Value> {
+        match self {
+            Value::List(items, _) => items.clone(),
+            Value::Map(pairs) => pairs
+                .iter()
+                .map(|(key, value)| Value::List(vec![key.clone(), value.clone()], Separator::Space))
+                .collect(),
+            value => vec![value.clone()],
+        }
+    }
+
+    pub(super) fn separator(&self) -> Separator {
+        match self {
+            Value::List(_, separator) => *separator,
+            Value::Map(_) => Separator::Comma,
+            _ => Separator::Space,
+        }
+    }
+
+    // 空列表 () 也可以作为空的 map
+    pub(super) fn pairs(&self) -> Option<Vec<(Value, Value)>> {
+        match self {
+            Value::Map(pairs) => Some(pairs.clone()),
+            Value::List(items, _) if items.is_empty() => Some(Vec::new()),
+            _ => None,
+        }
+    }
+
+    pub(super) fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Number(..) => "number",
+            Value::Str(..) if self.color().is_some() => "color",
+            Value::Str(..) => "string",
+            Value::Color(_) => "color",
+            Value::List(..) => "list",
+            Value::Map(_) => "map",
+        }
+    }
+
+    // 插值时字符串去掉引号
+    pub(super) fn text(&self) -> String {
+        match self {
+            Value::Str(text, _) => text.clone(),
+            value => value.to_string(),
+        }
+    }
+
+    // ANCHOR: equals
+    // 数值换算单位后比较，字符串不区分有没有引号
+    pub(super) fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(a, unit_a), Value::Number(b, unit_b)) => {
+                if unit_a.is_empty() != unit_b.is_empty() {
+                    return false;
+                }
+                convert(*b, unit_b, unit_a).is_some_and(|b| (a - b).abs() < 1e-10)
+            }
+            (Value::Str(a, _), Value::Str(b, _)) if a == b => true,
+            (Value::List(a, sep_a), Value::List(b, sep_b)) => {
+                (sep_a == sep_b || a.len() <= 1)
+                    && a.len() == b.len()
+                    && a.iter().zip(b).all(|(a, b)| a.equals(b))
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|((ka, va), (kb, vb))| ka.equals(kb) && va.equals(vb))
+            }
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (a, b) => match (a.color(), b.color()) {
+                (Some(a), Some(b)) => a
+                    .iter()
+                    .zip(b)
+                    .all(|(a, b)| (a.round() - b.round()).abs() < 1e-10),
+                _ => false,
+            },
+        }
+    }
+    // ANCHOR_END: equals
+
+    // ANCHOR: operate
+    // + - * / % 运算，字符串的加法是拼接
+    pub(super) fn operate(&self, op: &str, other: &Value) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Number(a, unit_a), Value::Number(b, unit_b)) => {
+                number_operate(op, (*a, unit_a), (*b, unit_b))
+            }
+            (Value::Str(a, quoted), b) if op == "+" => {
+                Ok(Value::Str(format!("{}{}", a, b.text()), *quoted))
+            }
+            (a, Value::Str(b, quoted)) if op == "+" => {
+                Ok(Value::Str(format!("{}{}", a.text(), b), *quoted))
+            }
+            (Value::Str(..), _) | (_, Value::Str(..)) if op == "-" || op == "/" => {
+                Ok(Value::ident(&format!("{}{}{}", self, op, other)))
+            }
+            _ => Err(format!(
+                "undefined operation \"{} {} {}\"",
+                self.inspect(),
+                op,
+                other.inspect()
+            )),
+        }
+    }
+    // ANCHOR_END: operate
+
+    // 与 meta.inspect() 相同，可以输出 null 和 map
+    pub(super) fn inspect(&self) -> String {
+        match self {
+            Value::Null => "null".to_string(),
+            Value::List(items, _) if items.is_empty() => "()".to_string(),
+            Value::List(items, separator) => items
+                .iter()
+                .map(|item| match item {
+                    Value::List(inner, Separator::Comma) if inner.len() > 1 => {
+                        format!("({})", item.inspect())
+                    }
+                    _ => item.inspect(),
+                })
+                .collect::<Vec<_>>()
+                .join(separator.join()),
+            Value::Map(pairs) => {
+                let pairs: Vec<String> = pairs
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.inspect(), value.inspect()))
+                    .collect();
+                format!("({})", pairs.join(", "))
+            }
+            value => value.to_string(),
+        }
+    }
+}
+
+impl Separator {
+    pub(super) fn join(&self) -> &'static str {
+        match self {
+            Separator::Space => " ",
+            Separator::Comma => ", ",
+        }
+    }
+}
+
+// 加减和取余要求单位相同或者可以换算，没有单位的数值使用另一个数值的单位
+fn number_operate(
+    op: &str,
+    (a, unit_a): (f64, &str),
+    (b, unit_b): (f64, &str),
+) -> Result<Value, String> {
+    let unit = if unit_a.is_empty() { unit_b } else { unit_a };
+    let same = |b: f64| {
+        if unit_a.is_empty() || unit_b.is_empty() {
+            return Ok(b);
+        }
+        convert(b, unit_b, unit_a)
+            .ok_or_else(|| format!("incompatible units {} and {}", unit_b, unit_a))
+    };
+    let value = match op {
+        "+" => a + same(b)?,
+        "-" => a - same(b)?,
+        "%" => a % same(b)?,
+        "*" if unit_a.is_empty() || unit_b.is_empty() => a * b,
+        "/" if unit_b.is_empty() => a / b,
+        "/" => {
+            let b = convert(b, unit_b, unit_a)
+                .ok_or_else(|| format!("incompatible units {} and {}", unit_b, unit_a))?;
+            return Ok(Value::Number(a / b, String::new()));
+        }
+        _ => {
+            return Err(format!(
+                "{}{} {} {}{} isn't a valid CSS value",
+                format_number(a),
+                unit_a,
+                op,
+                format_number(b),
+                unit_b
+            ))
+        }
+    };
+    Ok(Value::Number(value, unit.to_string()))
+}
+
+// ANCHOR: display
+// 输出为 css 的值，null 不输出，不透明的颜色输出 #rrggbb
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Number(value, unit) => write!(f, "{}{}", format_number(*value), unit),
+            Value::Str(text, false) => write!(f, "{}", text),
+            Value::Str(text, true) => {
+                let quote = if text.contains('"') && !text.contains('\'') {
+                    '\''
+                } else {
+                    '"'
+                };
+                write!(f, "{}{}{}", quote, text, quote)
+            }
+            Value::Color([r, g, b, a]) => {
+                let byte = |c: f64| c.clamp(0.0, 255.0).round() as u8;
+                let alpha = a.clamp(0.0, 1.0);
+                if alpha == 1.0 {
+                    write!(f, "#{:02x}{:02x}{:02x}", byte(*r), byte(*g), byte(*b))
+                } else {
+                    write!(
+                        f,
+                        "rgba({}, {}, {}, {})",
+                        byte(*r),
+                        byte(*g),
+                        byte(*b),
+                        format_number(alpha)
+                    )
+                }
+            }
+            Value::List(items, separator) => {
+                let items: Vec<String> = items
+                    .iter()
+                    .filter(|item| !item.is_null())
+                    .map(|item| item.to_string())
+                    .collect();
+                write!(f, "{}", items.join(separator.join()))
+            }
+            Value::Map(_) => write!(f, "{}", self.inspect()),
+        }
+    }
+}
+// ANCHOR_END: display
@@ -0,0 +1,687 @@
+use std::ops::Add;
+
+use crate::{
+    ast::{AstNode, AstTreeBuilder},
+    codegen::{selector_combinator, tokens_to_string},
+    html::{Document, NodeId},
+    lexer::Lexer,
+    parser::Parser,
+    token_type::TokenType,
+};
+
+// ANCHOR: specificity
+// (id 数量, class/属性/伪类数量, 标签/伪元素数量)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Specificity(pub u32, pub u32, pub u32);
+// ANCHOR_END: specificity
+
+impl Add for Specificity {
+    type Output = Specificity;
+    fn add(self, rhs: Self) -> Self::Output {
+        Specificity(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combinator {
+    // 空白
+    Descendant,
+    // >
+    Child,
+    // +
+    NextSibling,
+    // ~
+    SubsequentSibling,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttribOperator {
+    Exists,
+    // =
+    Equal,
+    // ~=
+    Includes,
+    // |=
+    DashMatch,
+    // ^=
+    Prefix,
+    // $=
+    Suffix,
+    // *=
+    Substring,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttribSelector {
+    pub name: String,
+    pub operator: AttribOperator,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PseudoClass {
+    Not(SelectorList),
+    Is(SelectorList),
+    Where(SelectorList),
+    Has(SelectorList),
+    // an+b
+    Nth {
+        a: i32,
+        b: i32,
+        of_type: bool,
+        from_end: bool,
+    },
+    OnlyChild,
+    OnlyOfType,
+    Root,
+    Empty,
+    Link,
+    // :hover 等依赖交互状态的伪类，静态匹配时视为不匹配
+    Other(String),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CompoundSelector {
+    // None 表示通配符或省略
+    pub tag: Option<String>,
+    pub ids: Vec<String>,
+    pub classes: Vec<String>,
+    pub attribs: Vec<AttribSelector>,
+    pub pseudo_classes: Vec<PseudoClass>,
+    pub pseudo_element: Option<String>,
+    // 嵌套规则中的 &
+    pub nesting: bool,
+}
+
+// ANCHOR: complex_selector
+// compounds 从左到右排列，combinators[i] 连接 compounds[i] 和 compounds[i + 1]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ComplexSelector {
+    pub compounds: Vec<CompoundSelector>,
+    pub combinators: Vec<Combinator>,
+    // :has(> a) 这类相对选择器开头的组合符
+    pub leading_combinator: Option<Combinator>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SelectorList(pub Vec<ComplexSelector>);
+// ANCHOR_END: complex_selector
+
+const LEGACY_PSEUDO_ELEMENTS: [&str; 4] = ["before", "after", "first-line", "first-letter"];
+
+// 把字符串解析成选择器列表
+pub fn parse_selector_list(source: &str) -> SelectorList {
+    let mut lexer = Lexer::new(source);
+    let mut builder = AstTreeBuilder::new();
+    let mut parser = Parser::new(&mut lexer, &mut builder);
+    parser.parse_selector_list();
+    builder.finish();
+    match &builder.ast_tree.root {
+        Some(root) => compile_selector_list(root),
+        None => SelectorList::default(),
+    }
+}
+
+// ANCHOR: compile_selector_list
+// 把语法树中的 SelectorList 节点编译成便于匹配的结构
+pub fn compile_selector_list(node: &AstNode<TokenType>) -> SelectorList {
+    let mut list = Vec::new();
+    let mut current = ComplexSelector::default();
+    for item in node.child_nodes() {
+        match item.node_type.0 {
+            TokenType::Comma => {
+                list.push(std::mem::take(&mut current));
+            }
+            TokenType::Selector => {
+                let compound = item
+                    .find_child(TokenType::SimpleSelect)
+                    .map(compile_compound)
+                    .unwrap_or_default();
+                let combinator = selector_combinator(item).map(|n| match n.node_type.0 {
+                    TokenType::MoreThan => Combinator::Child,
+                    TokenType::Plus => Combinator::NextSibling,
+                    _ => Combinator::SubsequentSibling,
+                });
+                let is_empty = compound == CompoundSelector::default();
+                if is_empty && current.compounds.is_empty() {
+                    current.leading_combinator = combinator;
+                    continue;
+                }
+                if current.compounds.len() > current.combinators.len() {
+                    current.combinators.push(Combinator::Descendant);
+                }
+                current.compounds.push(compound);
+                if let Some(combinator) = combinator {
+                    current.combinators.push(combinator);
+                }
+            }
+            _ => {}
+        }
+    }
+    if !current.compounds.is_empty() {
+        list.push(current);
+    }
+    // 末尾多余的组合符
+    for selector in list.iter_mut() {
+        selector.combinators.truncate(selector.compounds.len().saturating_sub(1));
+    }
+    SelectorList(list)
+}
+// ANCHOR_END: compile_selector_list
+
+// 按逗号把选择器列表拆成多组
+pub fn split_selector_list(node: &AstNode<TokenType>) -> Vec<Vec<AstNode<TokenType>>> {
+    let mut groups = vec![Vec::new()];
+    for item in node.child_nodes() {
+        if item.check_type(TokenType::Comma) {
+            groups.push(Vec::new());
+        } else if let Some(group) = groups.last_mut() {
+            group.push(item.clone());
+        }
+    }
+    groups.retain(|g| !g.is_empty());
+    groups
+}
+
+// split_selector_list 的逆操作
+pub fn join_selector_list(groups: Vec<Vec<AstNode<TokenType>>>) -> AstNode<TokenType> {
+    let mut children = Vec::new();
+    for (index, group) in groups.into_iter().enumerate() {
+        if index > 0 {
+            children.push(AstNode {
+                raw: ",".to_string(),
+                ..TokenType::Comma.into()
+            });
+        }
+        children.extend(group);
+    }
+    AstNode::from_children(TokenType::SelectorList, children)
+}
+
+fn compile_compound(node: &AstNode<TokenType>) -> CompoundSelector {
+    let mut compound = CompoundSelector::default();
+    let children = node.child_nodes();
+    let mut index = 0;
+    while index < children.len() {
+        let child = &children[index];
+        index += 1;
+        match child.node_type.0 {
+            TokenType::IdentToken if child.raw == "&" => compound.nesting = true,
+            TokenType::IdentToken | TokenType::PercentageToken => {
+                compound.tag = Some(child.raw.to_ascii_lowercase())
+            }
+            TokenType::HashToken => compound.ids.push(unescape_ident(&child.raw[1..])),
+            TokenType::Class => {
+                if let Some(name) = child.find_child(TokenType::IdentToken) {
+                    compound.classes.push(unescape_ident(&name.raw));
+                }
+            }
+            TokenType::Attrib => compound.attribs.push(compile_attrib(child)),
+            TokenType::Colon => {
+                let mut is_element = false;
+                if children
+                    .get(index)
+                    .is_some_and(|n| n.check_type(TokenType::Colon))
+                {
+                    is_element = true;
+                    index += 1;
+                }
+                let Some(target) = children.get(index) else {
+                    break;
+                };
+                index += 1;
+                match target.node_type.0 {
+                    TokenType::IdentToken => {
+                        let name = target.raw.to_ascii_lowercase();
+                        if is_element || LEGACY_PSEUDO_ELEMENTS.contains(&name.as_str()) {
+                            compound.pseudo_element = Some(name);
+                        } else {
+                            compound.pseudo_classes.push(compile_pseudo_class(&name));
+                        }
+                    }
+                    TokenType::Function => {
+                        if is_element {
+                            compound.pseudo_element = Some(tokens_to_string(target.child_nodes()));
+                        } else {
+                            compound.pseudo_classes.push(compile_pseudo_function(target));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // .mixin() 调用
+            TokenType::Dot => break,
+            _ => {}
+        }
+    }
+    compound
+}
+
+// 去掉标识符中的转义，例如 md\:flex
+pub fn unescape_ident(raw: &str) -> String {
+    let mut result = String::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        let mut hex = String::new();
+        while hex.len() < 6 && chars.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+            hex.push(chars.next().unwrap());
+        }
+        if hex.is_empty() {
+            if let Some(next) = chars.next() {
+                result.push(next);
+            }
+        } else {
+            if chars.peek().is_some_and(|c| c.is_whitespace()) {
+                chars.next();
+            }
+            let code = u32::from_str_radix(&hex, 16).unwrap_or(0xFFFD);
+            result.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+        }
+    }
+    result
+}
+
+fn compile_attrib(node: &AstNode<TokenType>) -> AttribSelector {
+    let children = node.child_nodes();
+    let name = children
+        .get(1)
+        .map(|n| n.raw.to_ascii_lowercase())
+        .unwrap_or_default();
+    let operator = match children.get(2).map(|n| n.node_type.0) {
+        Some(TokenType::Equal) => AttribOperator::Equal,
+        Some(TokenType::Includes) => AttribOperator::Includes,
+        Some(TokenType::Dashmatch) => AttribOperator::DashMatch,
+        Some(TokenType::Exclude) => AttribOperator::Prefix,
+        Some(TokenType::SuffixMatch) => AttribOperator::Suffix,
+        Some(TokenType::AllMatch) => AttribOperator::Substring,
+        _ => AttribOperator::Exists,
+    };
+    let value = match children.get(3) {
+        Some(n) if n.check_type(TokenType::Str) => n.raw[1..n.raw.len() - 1].to_string(),
+        Some(n) if n.check_type(TokenType::IdentToken) => n.raw.clone(),
+        _ => String::new(),
+    };
+    AttribSelector {
+        name,
+        operator,
+        value,
+    }
+}
+
+fn compile_pseudo_class(name: &str) -> PseudoClass {
+    let nth = |a, b, of_type, from_end| PseudoClass::Nth {
+        a,
+        b,
+        of_type,
+        from_end,
+    };
+    match name {
+        "first-child" => nth(0, 1, false, false),
+        "last-child" => nth(0, 1, false, true),
+        "first-of-type" => nth(0, 1, true, false),
+        "last-of-type" => nth(0, 1, true, true),
+        "only-child" => PseudoClass::OnlyChild,
+        "only-of-type" => PseudoClass::OnlyOfType,
+        "root" => PseudoClass::Root,
+        "empty" => PseudoClass::Empty,
+        "link" | "any-link" => PseudoClass::Link,
+        _ => PseudoClass::Other(name.to_string()),
+    }
+}
+
+fn compile_pseudo_function(node: &AstNode<TokenType>) -> PseudoClass {
+    let name = node
+        .child_nodes()
+        .first()
+        .map(|n| n.raw.trim_end_matches('(').to_ascii_lowercase())
+        .unwrap_or_default();
+    let selectors = || {
+        node.find_child(TokenType::SelectorList)
+            .map(compile_selector_list)
+            .unwrap_or_default()
+    };
+    let argument: String = node
+        .find_child(TokenType::Expression)
+        .map(|expr| tokens_to_string(expr.child_nodes()))
+        .unwrap_or_default();
+    let nth = |of_type, from_end| match parse_nth(&argument) {
+        Some((a, b)) => PseudoClass::Nth {
+            a,
+            b,
+            of_type,
+            from_end,
+        },
+        None => PseudoClass::Other(format!("{}({})", name, argument)),
+    };
+    match name.as_str() {
+        "not" => PseudoClass::Not(selectors()),
+        "is" | "matches" | "-webkit-any" | "-moz-any" => PseudoClass::Is(selectors()),
+        "where" => PseudoClass::Where(selectors()),
+        "has" => PseudoClass::Has(selectors()),
+        "nth-child" => nth(false, false),
+        "nth-last-child" => nth(false, true),
+        "nth-of-type" => nth(true, false),
+        "nth-last-of-type" => nth(true, true),
+        _ => PseudoClass::Other(format!("{}({})", name, argument)),
+    }
+}
+
+// ANCHOR: parse_nth
+// 解析 an+b，支持 odd even
+pub fn parse_nth(source: &str) -> Option<(i32, i32)> {
+    let source: String = source
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match source.as_str() {
+        "odd" => return Some((2, 1)),
+        "even" => return Some((2, 0)),
+        _ => {}
+    }
+    match source.find('n') {
+        Some(index) => {
+            let a = match &source[..index] {
+                "" | "+" => 1,
+                "-" => -1,
+                a => a.parse().ok()?,
+            };
+            let b = match &source[index + 1..] {
+                "" => 0,
+                b => b.trim_start_matches('+').parse().ok()?,
+            };
+            Some((a, b))
+        }
+        None => Some((0, source.trim_start_matches('+').parse().ok()?)),
+    }
+}
+// ANCHOR_END: parse_nth
+
+impl SelectorList {
+    pub fn matches(&self, document: &Document, element: NodeId) -> bool {
+        self.0.iter().any(|s| s.matches(document, element))
+    }
+
+    // 命中的选择器中最高的优先级
+    pub fn matching_specificity(&self, document: &Document, element: NodeId) -> Option<Specificity> {
+        self.0
+            .iter()
+            .filter(|s| s.matches(document, element))
+            .map(|s| s.specificity())
+            .max()
+    }
+
+    pub fn max_specificity(&self) -> Specificity {
+        self.0
+            .iter()
+            .map(|s| s.specificity())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl ComplexSelector {
+    pub fn specificity(&self) -> Specificity {
+        self.compounds
+            .iter()
+            .fold(Specificity::default(), |acc, c| acc + c.specificity())
+    }
+
+    pub fn has_pseudo_element(&self) -> bool {
+        self.compounds.iter().any(|c| c.pseudo_element.is_some())
+    }
+
+    // 是否包含 :hover 这类无法静态判断的伪类
+    pub fn has_dynamic_pseudo_class(&self) -> bool {
+        self.compounds.iter().any(|c| {
+            c.pseudo_classes.iter().any(|p| match p {
+                PseudoClass::Other(_) => true,
+                PseudoClass::Not(list)
+                | PseudoClass::Is(list)
+                | PseudoClass::Where(list)
+                | PseudoClass::Has(list) => list.0.iter().any(|s| s.has_dynamic_pseudo_class()),
+                _ => false,
+            })
+        })
+    }
+
+    pub fn matches(&self, document: &Document, element: NodeId) -> bool {
+        match self.compounds.len() {
+            0 => false,
+            len => self.matches_from(document, element, len - 1, None),
+        }
+    }
+
+    // :has() 中的相对选择器，最左边的部分通过开头的组合符与 subject 相连
+    fn matches_relative(&self, document: &Document, element: NodeId, subject: NodeId) -> bool {
+        let combinator = self.leading_combinator.unwrap_or(Combinator::Descendant);
+        match self.compounds.len() {
+            0 => false,
+            len => self.matches_from(document, element, len - 1, Some((subject, combinator))),
+        }
+    }
+
+    // 从右往左匹配，后代和兄弟组合符需要回溯
+    fn matches_from(
+        &self,
+        document: &Document,
+        element: NodeId,
+        index: usize,
+        anchor: Option<(NodeId, Combinator)>,
+    ) -> bool {
+        if !self.compounds[index].matches(document, element) {
+            return false;
+        }
+        if index == 0 {
+            return anchor.is_none_or(|(subject, combinator)| {
+                is_related(document, subject, element, combinator)
+            });
+        }
+        let combinator = self.combinators[index - 1];
+        let next = |other| self.matches_from(document, other, index - 1, anchor);
+        match combinator {
+            Combinator::Child => document.parent_element(element).is_some_and(next),
+            Combinator::Descendant => {
+                let mut current = document.parent_element(element);
+                while let Some(parent) = current {
+                    if next(parent) {
+                        return true;
+                    }
+                    current = document.parent_element(parent);
+                }
+                false
+            }
+            Combinator::NextSibling => document.previous_element_sibling(element).is_some_and(next),
+            Combinator::SubsequentSibling => {
+                let mut current = document.previous_element_sibling(element);
+                while let Some(prev) = current {
+                    if next(prev) {
+                        return true;
+                    }
+                    current = document.previous_element_sibling(prev);
+                }
+                false
+            }
+        }
+    }
+}
+
+impl CompoundSelector {
+    pub fn specificity(&self) -> Specificity {
+        let mut result = Specificity(
+            self.ids.len() as u32,
+            (self.classes.len() + self.attribs.len()) as u32,
+            (self.tag.as_ref().is_some_and(|t| t != "*") as u32)
+                + self.pseudo_element.is_some() as u32,
+        );
+        for pseudo in &self.pseudo_classes {
+            result = result
+                + match pseudo {
+                    PseudoClass::Where(_) => Specificity::default(),
+                    PseudoClass::Not(list) | PseudoClass::Is(list) | PseudoClass::Has(list) => {
+                        list.max_specificity()
+                    }
+                    _ => Specificity(0, 1, 0),
+                };
+        }
+        result
+    }
+
+    pub fn matches(&self, document: &Document, id: NodeId) -> bool {
+        let Some(element) = document.element(id) else {
+            return false;
+        };
+        if self.pseudo_element.is_some() {
+            return false;
+        }
+        if let Some(tag) = &self.tag {
+            if tag != "*" && !element.tag_name.eq_ignore_ascii_case(tag) {
+                return false;
+            }
+        }
+        if !self.ids.iter().all(|i| element.id() == Some(i.as_str())) {
+            return false;
+        }
+        if !self
+            .classes
+            .iter()
+            .all(|class| element.classes().any(|c| c == class))
+        {
+            return false;
+        }
+        if !self.attribs.iter().all(|attrib| attrib.matches(element.get_attribute(&attrib.name))) {
+            return false;
+        }
+        self.pseudo_classes
+            .iter()
+            .all(|pseudo| pseudo.matches(document, id))
+    }
+}
+
+impl AttribSelector {
+    fn matches(&self, actual: Option<&str>) -> bool {
+        let Some(actual) = actual else {
+            return false;
+        };
+        let value = self.value.as_str();
+        match self.operator {
+            AttribOperator::Exists => true,
+            AttribOperator::Equal => actual == value,
+            AttribOperator::Includes => actual.split_ascii_whitespace().any(|v| v == value),
+            AttribOperator::DashMatch => {
+                actual == value || actual.starts_with(&format!("{}-", value))
+            }
+            AttribOperator::Prefix => !value.is_empty() && actual.starts_with(value),
+            AttribOperator::Suffix => !value.is_empty() && actual.ends_with(value),
+            AttribOperator::Substring => !value.is_empty() && actual.contains(value),
+        }
+    }
+}
+
+impl PseudoClass {
+    fn matches(&self, document: &Document, id: NodeId) -> bool {
+        match self {
+            PseudoClass::Not(list) => !list.matches(document, id),
+            PseudoClass::Is(list) | PseudoClass::Where(list) => list.matches(document, id),
+            PseudoClass::Has(list) => list.0.iter().any(|selector| {
+                relative_candidates(document, id, selector.leading_combinator)
+                    .into_iter()
+                    .any(|candidate| selector.matches_relative(document, candidate, id))
+            }),
+            PseudoClass::Nth {
+                a,
+                b,
+                of_type,
+                from_end,
+            } => {
+                let mut siblings = document.element_siblings(id);
+                if *of_type {
+                    let tag = &document.element(id).unwrap().tag_name;
+                    siblings.retain(|s| &document.element(*s).unwrap().tag_name == tag);
+                }
+                if *from_end {
+                    siblings.reverse();
+                }
+                let position = siblings.iter().position(|s| *s == id).unwrap() as i32 + 1;
+                match a {
+                    0 => position == *b,
+                    a => (position - b) % a == 0 && (position - b) / a >= 0,
+                }
+            }
+            PseudoClass::OnlyChild => document.element_siblings(id).len() == 1,
+            PseudoClass::OnlyOfType => {
+                let tag = &document.element(id).unwrap().tag_name;
+                document
+                    .element_siblings(id)
+                    .iter()
+                    .filter(|s| &document.element(**s).unwrap().tag_name == tag)
+                    .count()
+                    == 1
+            }
+            PseudoClass::Root => document.parent_element(id).is_none(),
+            PseudoClass::Empty => document.node(id).children.iter().all(|child| {
+                matches!(&document.node(*child).data, crate::html::NodeData::Comment(_))
+            }),
+            PseudoClass::Link => document.element(id).is_some_and(|e| {
+                matches!(e.tag_name.as_str(), "a" | "area") && e.get_attribute("href").is_some()
+            }),
+            PseudoClass::Other(_) => false,
+        }
+    }
+}
+
+// :has() 中相对选择器最右边部分可能匹配的元素：后面的兄弟或子元素，以及它们的后代
+fn relative_candidates(
+    document: &Document,
+    id: NodeId,
+    combinator: Option<Combinator>,
+) -> Vec<NodeId> {
+    let mut stack = match combinator.unwrap_or(Combinator::Descendant) {
+        Combinator::Child | Combinator::Descendant => document.element_children(id),
+        Combinator::NextSibling | Combinator::SubsequentSibling => {
+            let siblings = document.element_siblings(id);
+            let index = siblings.iter().position(|s| *s == id).unwrap();
+            let rest = siblings[index + 1..].to_vec();
+            if combinator == Some(Combinator::NextSibling) {
+                rest.into_iter().take(1).collect()
+            } else {
+                rest
+            }
+        }
+    };
+    let mut result = Vec::new();
+    while let Some(node) = stack.pop() {
+        result.push(node);
+        stack.extend(document.element_children(node));
+    }
+    result
+}
+
+// element 与 subject 之间是否满足组合符，subject 在左边
+fn is_related(
+    document: &Document,
+    subject: NodeId,
+    element: NodeId,
+    combinator: Combinator,
+) -> bool {
+    let walk = |next: &dyn Fn(NodeId) -> Option<NodeId>| {
+        let mut current = next(element);
+        while let Some(node) = current {
+            if node == subject {
+                return true;
+            }
+            current = next(node);
+        }
+        false
+    };
+    match combinator {
+        Combinator::Child => document.parent_element(element) == Some(subject),
+        Combinator::Descendant => walk(&|node| document.parent_element(node)),
+        Combinator::NextSibling => document.previous_element_sibling(element) == Some(subject),
+        Combinator::SubsequentSibling => walk(&|node| document.previous_element_sibling(node)),
+    }
+}
@@ -0,0 +1,743 @@
+use crate::{
+    ast::{AstNode, AstNodeType, AstTree},
+    cascade::property_name,
+    codegen::term_to_string,
+    color::is_color,
+    math::is_math_function,
+    parser::parse_single_declaration,
+    token_type::TokenType,
+    values::Value,
+};
+
+// ANCHOR: longhand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Longhand {
+    pub property: String,
+    pub value: String,
+    pub important: bool,
+}
+// ANCHOR_END: longhand
+
+pub const CSS_WIDE_KEYWORDS: [&str; 5] = ["inherit", "initial", "unset", "revert", "revert-layer"];
+
+const SIDES: [&str; 4] = ["top", "right", "bottom", "left"];
+const CORNERS: [&str; 4] = ["top-left", "top-right", "bottom-right", "bottom-left"];
+
+const BORDER_STYLES: [&str; 10] = [
+    "none", "hidden", "dotted", "dashed", "solid", "double", "groove", "ridge", "inset", "outset",
+];
+const TIMING_KEYWORDS: [&str; 7] = [
+    "ease",
+    "linear",
+    "ease-in",
+    "ease-out",
+    "ease-in-out",
+    "step-start",
+    "step-end",
+];
+const TIMING_FUNCTIONS: [&str; 4] = ["steps(", "cubic-bezier(", "linear(", "frames("];
+const FONT_SIZE_KEYWORDS: [&str; 10] = [
+    "xx-small", "x-small", "small", "medium", "large", "x-large", "xx-large", "xxx-large",
+    "larger", "smaller",
+];
+const FONT_STRETCH_KEYWORDS: [&str; 9] = [
+    "ultra-condensed",
+    "extra-condensed",
+    "condensed",
+    "semi-condensed",
+    "semi-expanded",
+    "expanded",
+    "extra-expanded",
+    "ultra-expanded",
+    "normal",
+];
+const SYSTEM_FONTS: [&str; 6] = ["caption", "icon", "menu", "message-box", "small-caption", "status-bar"];
+
+// ANCHOR: longhands
+// 简写属性对应的全部长属性，顺序即展开后的输出顺序
+pub fn longhands(property: &str) -> Option<Vec<String>> {
+    let sides = |f: &dyn Fn(&str) -> String| SIDES.iter().map(|s| f(s)).collect::<Vec<_>>();
+    let list = match property {
+        "margin" | "padding" => sides(&|side| format!("{}-{}", property, side)),
+        "inset" => sides(&|side| side.to_string()),
+        "border-width" | "border-style" | "border-color" => {
+            let kind = &property["border-".len()..];
+            sides(&|side| format!("border-{}-{}", side, kind))
+        }
+        "border-top" | "border-right" | "border-bottom" | "border-left" | "outline" => {
+            ["width", "style", "color"]
+                .iter()
+                .map(|kind| format!("{}-{}", property, kind))
+                .collect()
+        }
+        "border" => ["width", "style", "color"]
+            .iter()
+            .flat_map(|kind| SIDES.iter().map(move |side| format!("border-{}-{}", side, kind)))
+            .collect(),
+        "border-radius" => CORNERS
+            .iter()
+            .map(|corner| format!("border-{}-radius", corner))
+            .collect(),
+        "background" => [
+            "background-image",
+            "background-position",
+            "background-size",
+            "background-repeat",
+            "background-attachment",
+            "background-origin",
+            "background-clip",
+            "background-color",
+        ]
+        .map(String::from)
+        .to_vec(),
+        "font" => [
+            "font-style",
+            "font-variant",
+            "font-weight",
+            "font-stretch",
+            "font-size",
+            "line-height",
+            "font-family",
+        ]
+        .map(String::from)
+        .to_vec(),
+        "flex" => ["flex-grow", "flex-shrink", "flex-basis"].map(String::from).to_vec(),
+        "grid-area" => [
+            "grid-row-start",
+            "grid-column-start",
+            "grid-row-end",
+            "grid-column-end",
+        ]
+        .map(String::from)
+        .to_vec(),
+        "transition" => [
+            "transition-property",
+            "transition-duration",
+            "transition-timing-function",
+            "transition-delay",
+        ]
+        .map(String::from)
+        .to_vec(),
+        "animation" => [
+            "animation-name",
+            "animation-duration",
+            "animation-timing-function",
+            "animation-delay",
+            "animation-iteration-count",
+            "animation-direction",
+            "animation-fill-mode",
+            "animation-play-state",
+        ]
+        .map(String::from)
+        .to_vec(),
+        "list-style" => ["list-style-type", "list-style-position", "list-style-image"]
+            .map(String::from)
+            .to_vec(),
+        _ => return None,
+    };
+    Some(list)
+}
+// ANCHOR_END: longhands
+
+pub fn is_shorthand(property: &str) -> bool {
+    longhands(property).is_some()
+}
+
+// 把 Expression 拆成顶层的 term，逗号和斜杠单独作为一项
+pub fn value_components(expr: &AstNode<TokenType>) -> Vec<String> {
+    expr.child_nodes()
+        .iter()
+        .filter(|n| !n.check_type(TokenType::Comment))
+        .map(|n| match n.node_type.0 {
+            TokenType::Term => term_to_string(n),
+            _ => n.raw.trim().to_string(),
+        })
+        .collect()
+}
+
+fn lower(word: &str) -> String {
+    word.to_ascii_lowercase()
+}
+
+fn is_one_of(word: &str, list: &[&str]) -> bool {
+    list.contains(&lower(word).as_str())
+}
+
+fn is_math(word: &str) -> bool {
+    word.find('(')
+        .is_some_and(|index| is_math_function(&word[..index]))
+}
+
+pub fn is_length_percentage(word: &str) -> bool {
+    is_math(word)
+        || matches!(
+            Value::parse(word),
+            Some(Value::Length(_) | Value::Percentage(_))
+        )
+        || matches!(Value::parse(word), Some(Value::Number(n)) if n == 0.0)
+}
+
+fn is_number(word: &str) -> bool {
+    matches!(Value::parse(word), Some(Value::Number(_)))
+}
+
+fn is_time(word: &str) -> bool {
+    matches!(Value::parse(word), Some(Value::Time(_)))
+}
+
+fn is_color_value(word: &str) -> bool {
+    lower(word) == "currentcolor" || is_color(word)
+}
+
+fn is_image(word: &str) -> bool {
+    let word = lower(word);
+    word.starts_with("url(")
+        || word.contains("gradient(")
+        || word.starts_with("image(")
+        || word.starts_with("image-set(")
+        || word.starts_with("-webkit-image-set(")
+        || word.starts_with("cross-fade(")
+}
+
+// 按逗号切分成多层
+fn split_commas(words: &[String]) -> Vec<Vec<String>> {
+    words
+        .split(|w| w == ",")
+        .map(|group| group.to_vec())
+        .collect()
+}
+
+// ANCHOR: expand
+// 展开简写属性，不是简写或无法静态展开（例如含 var()）时返回 None
+pub fn expand(property: &str, words: &[String], important: bool) -> Option<Vec<Longhand>> {
+    let property = lower(property);
+    let names = longhands(&property)?;
+    if words.is_empty() || words.iter().any(|w| lower(w).contains("var(")) {
+        return None;
+    }
+    let values: Vec<String> = if words.len() == 1 && is_one_of(&words[0], &CSS_WIDE_KEYWORDS) {
+        vec![lower(&words[0]); names.len()]
+    } else {
+        match property.as_str() {
+            "margin" | "padding" | "inset" | "border-width" | "border-style" | "border-color" => {
+                expand_box(words)?.to_vec()
+            }
+            "border" => {
+                let [width, style, color] = expand_border(words)?;
+                [width, style, color]
+                    .iter()
+                    .flat_map(|v| std::iter::repeat_n(v.clone(), 4))
+                    .collect()
+            }
+            "border-top" | "border-right" | "border-bottom" | "border-left" | "outline" => {
+                expand_border(words)?.to_vec()
+            }
+            "border-radius" => expand_border_radius(words)?,
+            "background" => expand_background(words)?,
+            "font" => expand_font(words)?,
+            "flex" => expand_flex(words)?,
+            "grid-area" => expand_grid_area(words)?,
+            "transition" => expand_transition(words)?,
+            "animation" => expand_animation(words)?,
+            "list-style" => expand_list_style(words)?,
+            _ => return None,
+        }
+    };
+    Some(
+        names
+            .into_iter()
+            .zip(values)
+            .map(|(property, value)| Longhand {
+                property,
+                value,
+                important,
+            })
+            .collect(),
+    )
+}
+// ANCHOR_END: expand
+
+pub fn expand_declaration(declaration: &AstNode<TokenType>) -> Option<Vec<Longhand>> {
+    let expr = declaration.find_child(TokenType::Expression)?;
+    expand(
+        &property_name(declaration),
+        &value_components(expr),
+        declaration.find_child(TokenType::Important).is_some(),
+    )
+}
+
+// ANCHOR: expand_shorthands
+// 把样式表中的简写声明替换成长属性，返回展开的声明数量
+pub fn expand_shorthands(tree: &mut AstTree<TokenType>) -> usize {
+    match &mut tree.root {
+        Some(root) => expand_node(root),
+        None => 0,
+    }
+}
+// ANCHOR_END: expand_shorthands
+
+fn expand_node(node: &mut AstNode<TokenType>) -> usize {
+    let is_list = node.check_type(TokenType::DeclarationList);
+    let Some(children) = &mut node.children else {
+        return 0;
+    };
+    if !is_list {
+        return children.iter_mut().map(expand_node).sum();
+    }
+    let mut count = 0;
+    let mut result = Vec::with_capacity(children.len());
+    for mut child in children.drain(..) {
+        if !child.check_type(TokenType::Declaration) {
+            count += expand_node(&mut child);
+            result.push(child);
+            continue;
+        }
+        let declarations: Option<Vec<AstNode<TokenType>>> = expand_declaration(&child)
+            .and_then(|longhands| longhands.iter().map(longhand_to_node).collect());
+        match declarations {
+            Some(declarations) => {
+                count += 1;
+                for (index, declaration) in declarations.into_iter().enumerate() {
+                    if index > 0 {
+                        result.push(AstNode {
+                            node_type: AstNodeType(TokenType::Semi),
+                            raw: ";".to_string(),
+                            ..Default::default()
+                        });
+                    }
+                    result.push(declaration);
+                }
+            }
+            None => result.push(child),
+        }
+    }
+    *children = result;
+    count
+}
+
+pub fn longhand_to_node(longhand: &Longhand) -> Option<AstNode<TokenType>> {
+    parse_single_declaration(&format!(
+        "{}: {}{}",
+        longhand.property,
+        longhand.value,
+        if longhand.important { " !important" } else { "" }
+    ))
+}
+
+// margin: 1px 2px => top right bottom left
+fn expand_box(words: &[String]) -> Option<[String; 4]> {
+    let [top, right, bottom, left] = match words {
+        [a] => [a, a, a, a],
+        [a, b] => [a, b, a, b],
+        [a, b, c] => [a, b, c, b],
+        [a, b, c, d] => [a, b, c, d],
+        _ => return None,
+    };
+    if words.iter().any(|w| w == "," || w == "/") {
+        return None;
+    }
+    Some([top.clone(), right.clone(), bottom.clone(), left.clone()])
+}
+
+// border: <width> || <style> || <color>
+fn expand_border(words: &[String]) -> Option<[String; 3]> {
+    let (mut width, mut style, mut color) = (None, None, None);
+    for word in words {
+        if width.is_none() && (is_length_percentage(word) || is_one_of(word, &["thin", "medium", "thick"])) {
+            width = Some(word.clone());
+        } else if style.is_none() && (is_one_of(word, &BORDER_STYLES) || lower(word) == "auto") {
+            style = Some(lower(word));
+        } else if color.is_none() && is_color_value(word) {
+            color = Some(word.clone());
+        } else {
+            return None;
+        }
+    }
+    Some([
+        width.unwrap_or_else(|| "medium".to_string()),
+        style.unwrap_or_else(|| "none".to_string()),
+        color.unwrap_or_else(|| "currentcolor".to_string()),
+    ])
+}
+
+// border-radius: 水平半径 [/ 垂直半径]
+fn expand_border_radius(words: &[String]) -> Option<Vec<String>> {
+    let mut parts = words.split(|w| w == "/");
+    let horizontal = expand_box(parts.next()?)?;
+    let vertical = match parts.next() {
+        Some(vertical) => expand_box(vertical)?,
+        None => horizontal.clone(),
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(
+        horizontal
+            .iter()
+            .zip(vertical.iter())
+            .map(|(h, v)| if h == v { h.clone() } else { format!("{} {}", h, v) })
+            .collect(),
+    )
+}
+
+const BACKGROUND_INITIAL: [&str; 8] = [
+    "none",
+    "0% 0%",
+    "auto",
+    "repeat",
+    "scroll",
+    "padding-box",
+    "border-box",
+    "transparent",
+];
+
+fn is_position(word: &str) -> bool {
+    is_length_percentage(word) || is_one_of(word, &["left", "right", "top", "bottom", "center"])
+}
+
+// 每一层分别解析，长属性的值用逗号连接，颜色只能出现在最后一层
+fn expand_background(words: &[String]) -> Option<Vec<String>> {
+    let layers = split_commas(words);
+    let mut result: Vec<Vec<String>> = vec![Vec::new(); 8];
+    let count = layers.len();
+    for (index, layer) in layers.iter().enumerate() {
+        let mut values: [Option<String>; 8] = Default::default();
+        let mut boxes = Vec::new();
+        let mut i = 0;
+        while i < layer.len() {
+            let word = &layer[i];
+            if values[0].is_none() && (is_image(word) || lower(word) == "none") {
+                values[0] = Some(word.clone());
+            } else if values[1].is_none() && is_position(word) {
+                let mut position = vec![word.clone()];
+                while i + 1 < layer.len() && is_position(&layer[i + 1]) && position.len() < 4 {
+                    i += 1;
+                    position.push(layer[i].clone());
+                }
+                values[1] = Some(position.join(" "));
+                // position 后面可以跟 / size
+                if layer.get(i + 1).is_some_and(|w| w == "/") {
+                    i += 2;
+                    let first = layer.get(i)?;
+                    if !(is_length_percentage(first) || is_one_of(first, &["auto", "cover", "contain"])) {
+                        return None;
+                    }
+                    let mut size = vec![first.clone()];
+                    if layer
+                        .get(i + 1)
+                        .is_some_and(|w| is_length_percentage(w) || lower(w) == "auto")
+                        && !is_one_of(first, &["cover", "contain"])
+                    {
+                        i += 1;
+                        size.push(layer[i].clone());
+                    }
+                    values[2] = Some(size.join(" "));
+                }
+            } else if values[3].is_none()
+                && is_one_of(word, &["repeat-x", "repeat-y", "repeat", "space", "round", "no-repeat"])
+            {
+                let mut repeat = vec![lower(word)];
+                if !is_one_of(word, &["repeat-x", "repeat-y"])
+                    && layer
+                        .get(i + 1)
+                        .is_some_and(|w| is_one_of(w, &["repeat", "space", "round", "no-repeat"]))
+                {
+                    i += 1;
+                    repeat.push(lower(&layer[i]));
+                }
+                values[3] = Some(repeat.join(" "));
+            } else if values[4].is_none() && is_one_of(word, &["scroll", "fixed", "local"]) {
+                values[4] = Some(lower(word));
+            } else if boxes.len() < 2
+                && is_one_of(word, &["border-box", "padding-box", "content-box", "text"])
+            {
+                boxes.push(lower(word));
+            } else if values[7].is_none() && index == count - 1 && is_color_value(word) {
+                values[7] = Some(word.clone());
+            } else {
+                return None;
+            }
+            i += 1;
+        }
+        match boxes.as_slice() {
+            [both] => {
+                values[5] = Some(both.clone());
+                values[6] = Some(both.clone());
+            }
+            [origin, clip] => {
+                values[5] = Some(origin.clone());
+                values[6] = Some(clip.clone());
+            }
+            _ => {}
+        }
+        for (slot, value) in values.into_iter().enumerate() {
+            // 颜色不分层
+            if slot == 7 && index != count - 1 {
+                continue;
+            }
+            result[slot].push(value.unwrap_or_else(|| BACKGROUND_INITIAL[slot].to_string()));
+        }
+    }
+    Some(result.into_iter().map(|v| v.join(", ")).collect())
+}
+
+// font: [style || variant || weight || stretch]? size [/ line-height]? family
+fn expand_font(words: &[String]) -> Option<Vec<String>> {
+    if words.len() == 1 && is_one_of(&words[0], &SYSTEM_FONTS) {
+        return None;
+    }
+    let (mut style, mut variant, mut weight, mut stretch) = (None, None, None, None);
+    let mut normals = 0;
+    let mut i = 0;
+    let size = loop {
+        let word = words.get(i)?;
+        let keyword = lower(word);
+        if is_length_percentage(word) || FONT_SIZE_KEYWORDS.contains(&keyword.as_str()) {
+            break word.clone();
+        }
+        if keyword == "normal" {
+            normals += 1;
+        } else if style.is_none() && (keyword == "italic" || keyword == "oblique") {
+            let mut value = keyword;
+            // oblique 后可以跟角度
+            if value == "oblique"
+                && words
+                    .get(i + 1)
+                    .is_some_and(|w| matches!(Value::parse(w), Some(Value::Angle(_))))
+            {
+                i += 1;
+                value = format!("{} {}", value, words[i]);
+            }
+            style = Some(value);
+        } else if variant.is_none() && keyword == "small-caps" {
+            variant = Some(keyword);
+        } else if weight.is_none()
+            && (is_one_of(&keyword, &["bold", "bolder", "lighter"])
+                || matches!(Value::parse(word), Some(Value::Number(n)) if (1.0..=1000.0).contains(&n)))
+        {
+            weight = Some(keyword);
+        } else if stretch.is_none() && FONT_STRETCH_KEYWORDS.contains(&keyword.as_str()) {
+            stretch = Some(keyword);
+        } else {
+            return None;
+        }
+        i += 1;
+    };
+    if normals + [&style, &variant, &weight, &stretch].iter().filter(|v| v.is_some()).count() > 4 {
+        return None;
+    }
+    i += 1;
+    let mut line_height = None;
+    if words.get(i).is_some_and(|w| w == "/") {
+        let value = words.get(i + 1)?;
+        if !(is_length_percentage(value) || is_number(value) || lower(value) == "normal") {
+            return None;
+        }
+        line_height = Some(value.clone());
+        i += 2;
+    }
+    let family = &words[i..];
+    if family.is_empty() || family.iter().any(|w| w == "/") {
+        return None;
+    }
+    let family = split_commas(family)
+        .iter()
+        .map(|names| names.join(" "))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let normal = || "normal".to_string();
+    Some(vec![
+        style.unwrap_or_else(normal),
+        variant.unwrap_or_else(normal),
+        weight.unwrap_or_else(normal),
+        stretch.unwrap_or_else(normal),
+        size,
+        line_height.unwrap_or_else(normal),
+        family,
+    ])
+}
+
+// flex: none | auto | <grow> <shrink>? || <basis>
+fn expand_flex(words: &[String]) -> Option<Vec<String>> {
+    let (grow, shrink, basis): (&str, &str, &str) = match words {
+        [word] if lower(word) == "none" => ("0", "0", "auto"),
+        [word] if lower(word) == "auto" => ("1", "1", "auto"),
+        [grow] if is_number(grow) => (grow, "1", "0%"),
+        [basis] => ("1", "1", basis),
+        [grow, shrink] if is_number(grow) && is_number(shrink) => (grow, shrink, "0%"),
+        [grow, basis] if is_number(grow) => (grow, "1", basis),
+        [basis, grow] if is_number(grow) => (grow, "1", basis),
+        [grow, shrink, basis] if is_number(grow) && is_number(shrink) => (grow, shrink, basis),
+        [basis, grow, shrink] if is_number(grow) && is_number(shrink) => (grow, shrink, basis),
+        _ => return None,
+    };
+    if !(is_length_percentage(basis)
+        || is_one_of(basis, &["auto", "content", "max-content", "min-content", "fit-content"]))
+    {
+        return None;
+    }
+    Some(vec![grow.to_string(), shrink.to_string(), basis.to_string()])
+}
+
+fn is_custom_ident(line: &str) -> bool {
+    let first = line.split_whitespace().next().unwrap_or_default();
+    !line.contains(' ')
+        && !is_number(first)
+        && !is_one_of(first, &["auto", "span"])
+        && first.starts_with(|c: char| c.is_alphabetic() || c == '-' || c == '_')
+}
+
+// grid-area: row-start / column-start / row-end / column-end
+fn expand_grid_area(words: &[String]) -> Option<Vec<String>> {
+    let lines: Vec<String> = words.split(|w| w == "/").map(|p| p.join(" ")).collect();
+    if lines.len() > 4 || lines.iter().any(|l| l.is_empty()) {
+        return None;
+    }
+    let fallback = |line: &String| {
+        if is_custom_ident(line) {
+            line.clone()
+        } else {
+            "auto".to_string()
+        }
+    };
+    let row_start = lines[0].clone();
+    let column_start = lines.get(1).cloned().unwrap_or_else(|| fallback(&row_start));
+    let row_end = lines.get(2).cloned().unwrap_or_else(|| fallback(&row_start));
+    let column_end = lines.get(3).cloned().unwrap_or_else(|| fallback(&column_start));
+    Some(vec![row_start, column_start, row_end, column_end])
+}
+
+fn is_timing_function(word: &str) -> bool {
+    let word = lower(word);
+    TIMING_KEYWORDS.contains(&word.as_str()) || TIMING_FUNCTIONS.iter().any(|f| word.starts_with(f))
+}
+
+// 多层的值按层用逗号连接
+fn join_layers(layers: Vec<Vec<String>>, count: usize) -> Vec<String> {
+    (0..count)
+        .map(|slot| {
+            layers
+                .iter()
+                .map(|layer| layer[slot].clone())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect()
+}
+
+// transition: [property || duration || timing-function || delay]#
+fn expand_transition(words: &[String]) -> Option<Vec<String>> {
+    let mut layers = Vec::new();
+    for layer in split_commas(words) {
+        let (mut property, mut duration, mut timing, mut delay) = (None, None, None, None);
+        for word in &layer {
+            if is_time(word) || is_math(word) {
+                if duration.is_none() {
+                    duration = Some(word.clone());
+                } else if delay.is_none() {
+                    delay = Some(word.clone());
+                } else {
+                    return None;
+                }
+            } else if timing.is_none() && is_timing_function(word) {
+                timing = Some(word.clone());
+            } else if property.is_none() && !word.contains('(') && !is_number(word) {
+                property = Some(word.clone());
+            } else {
+                return None;
+            }
+        }
+        layers.push(vec![
+            property.unwrap_or_else(|| "all".to_string()),
+            duration.unwrap_or_else(|| "0s".to_string()),
+            timing.unwrap_or_else(|| "ease".to_string()),
+            delay.unwrap_or_else(|| "0s".to_string()),
+        ]);
+    }
+    Some(join_layers(layers, 4))
+}
+
+// animation 中的关键字优先匹配靠前的长属性，剩下的标识符作为动画名
+fn expand_animation(words: &[String]) -> Option<Vec<String>> {
+    const INITIAL: [&str; 8] = ["none", "0s", "ease", "0s", "1", "normal", "none", "running"];
+    let mut layers = Vec::new();
+    for layer in split_commas(words) {
+        let mut values: [Option<String>; 8] = Default::default();
+        for word in &layer {
+            let keyword = lower(word);
+            let slot = if is_time(word) || is_math(word) {
+                if values[1].is_none() {
+                    1
+                } else {
+                    3
+                }
+            } else if is_timing_function(word) {
+                2
+            } else if keyword == "infinite" || is_number(word) {
+                4
+            } else if is_one_of(&keyword, &["reverse", "alternate", "alternate-reverse"])
+                || (keyword == "normal" && values[5].is_none())
+            {
+                5
+            } else if is_one_of(&keyword, &["forwards", "backwards", "both"])
+                || (keyword == "none" && values[6].is_none() && values[0].is_some())
+            {
+                6
+            } else if is_one_of(&keyword, &["running", "paused"]) {
+                7
+            } else {
+                0
+            };
+            if values[slot].is_some() {
+                return None;
+            }
+            values[slot] = Some(word.clone());
+        }
+        layers.push(
+            values
+                .into_iter()
+                .zip(INITIAL)
+                .map(|(value, initial)| value.unwrap_or_else(|| initial.to_string()))
+                .collect(),
+        );
+    }
+    Some(join_layers(layers, 8))
+}
+
+// list-style: type || position || image，none 优先给没有指定的 image 和 type
+fn expand_list_style(words: &[String]) -> Option<Vec<String>> {
+    let (mut kind, mut position, mut image) = (None, None, None);
+    let mut nones = 0;
+    for word in words {
+        let keyword = lower(word);
+        if keyword == "none" {
+            nones += 1;
+        } else if position.is_none() && is_one_of(&keyword, &["inside", "outside"]) {
+            position = Some(keyword);
+        } else if image.is_none() && is_image(word) {
+            image = Some(word.clone());
+        } else if kind.is_none() && (!word.contains('(') || keyword.starts_with("symbols(")) {
+            kind = Some(word.clone());
+        } else {
+            return None;
+        }
+    }
+    match (nones, kind.is_some(), image.is_some()) {
+        (0, _, _) => {}
+        (1, true, false) | (1, false, false) => {
+            image = Some("none".to_string());
+            kind = kind.or(Some("none".to_string()));
+        }
+        (1, false, true) => kind = Some("none".to_string()),
+        (2, false, false) => {
+            kind = Some("none".to_string());
+            image = Some("none".to_string());
+        }
+        _ => return None,
+    }
+    Some(vec![
+        kind.unwrap_or_else(|| "disc".to_string()),
+        position.unwrap_or_else(|| "outside".to_string()),
+        image.unwrap_or_else(|| "none".to_string()),
+    ])
+}
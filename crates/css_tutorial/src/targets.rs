@@ -0,0 +1,421 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::{self, Display},
+    sync::OnceLock,
+};
+
+use serde::Deserialize;
+
+// ANCHOR: browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Browser {
+    Chrome,
+    Edge,
+    Firefox,
+    Safari,
+    IosSaf,
+    Opera,
+    Samsung,
+    Android,
+    Ie,
+}
+// ANCHOR_END: browser
+
+pub const BROWSERS: [Browser; 9] = [
+    Browser::Chrome,
+    Browser::Edge,
+    Browser::Firefox,
+    Browser::Safari,
+    Browser::IosSaf,
+    Browser::Opera,
+    Browser::Samsung,
+    Browser::Android,
+    Browser::Ie,
+];
+
+impl Browser {
+    // 与 browserslist 使用的名字一致
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "chrome" => Some(Browser::Chrome),
+            "edge" => Some(Browser::Edge),
+            "firefox" | "ff" => Some(Browser::Firefox),
+            "safari" => Some(Browser::Safari),
+            "ios_saf" | "ios" => Some(Browser::IosSaf),
+            "opera" => Some(Browser::Opera),
+            "samsung" => Some(Browser::Samsung),
+            "android" => Some(Browser::Android),
+            "ie" | "explorer" => Some(Browser::Ie),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Browser::Chrome => "chrome",
+            Browser::Edge => "edge",
+            Browser::Firefox => "firefox",
+            Browser::Safari => "safari",
+            Browser::IosSaf => "ios_saf",
+            Browser::Opera => "opera",
+            Browser::Samsung => "samsung",
+            Browser::Android => "android",
+            Browser::Ie => "ie",
+        }
+    }
+}
+
+// ANCHOR: version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+}
+// ANCHOR_END: version
+
+impl Version {
+    // 表示所有版本都满足条件
+    pub const ALL: Version = Version::new(u32::MAX, 0);
+
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Version { major, minor }
+    }
+
+    // 解析 14、14.1、4.4.3 这类版本号，只保留前两段
+    pub fn parse(source: &str) -> Option<Version> {
+        let mut parts = source.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = match parts.next() {
+            Some(minor) => minor.parse().ok()?,
+            None => 0,
+        };
+        Some(Version::new(major, minor))
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.minor == 0 {
+            write!(f, "{}", self.major)
+        } else {
+            write!(f, "{}.{}", self.major, self.minor)
+        }
+    }
+}
+
+// ANCHOR: targets
+// 每个浏览器需要支持的最低版本，没有列出的浏览器不需要考虑
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Targets {
+    versions: BTreeMap<Browser, Version>,
+}
+// ANCHOR_END: targets
+
+impl Targets {
+    pub fn from_list(list: &[(Browser, Version)]) -> Self {
+        let mut targets = Targets::default();
+        for (browser, version) in list {
+            targets.insert(*browser, *version);
+        }
+        targets
+    }
+
+    // 同一个浏览器保留较低的版本
+    pub fn insert(&mut self, browser: Browser, version: Version) {
+        let entry = self.versions.entry(browser).or_insert(version);
+        if version < *entry {
+            *entry = version;
+        }
+    }
+
+    pub fn version(&self, browser: Browser) -> Option<Version> {
+        self.versions.get(&browser).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Browser, Version)> + '_ {
+        self.versions.iter().map(|(b, v)| (*b, *v))
+    }
+
+    // 目标中的某个浏览器版本低于 until，即需要兼容 until 之前的版本
+    pub fn any_below(&self, browser: Browser, until: Version) -> bool {
+        self.version(browser).is_some_and(|v| v < until)
+    }
+}
+
+// ANCHOR: browser_data
+// 内置的浏览器版本和全球使用率数据，版本按发布顺序排列
+#[derive(Debug, Deserialize)]
+struct BrowserData {
+    versions: Vec<(String, f64)>,
+    #[serde(default)]
+    dead: Vec<String>,
+    #[serde(default)]
+    esr: Vec<String>,
+}
+// ANCHOR_END: browser_data
+
+struct Release {
+    browser: Browser,
+    versions: Vec<(Version, f64)>,
+    dead: Vec<Version>,
+    esr: Vec<Version>,
+}
+
+fn releases() -> &'static [Release] {
+    static DATA: OnceLock<Vec<Release>> = OnceLock::new();
+    DATA.get_or_init(|| {
+        let raw: HashMap<String, BrowserData> =
+            serde_json::from_str(include_str!("../data/browsers.json")).unwrap();
+        let parse = |list: &[String]| list.iter().filter_map(|v| Version::parse(v)).collect();
+        BROWSERS
+            .iter()
+            .filter_map(|browser| {
+                let data = raw.get(browser.name())?;
+                Some(Release {
+                    browser: *browser,
+                    versions: data
+                        .versions
+                        .iter()
+                        .filter_map(|(v, usage)| Some((Version::parse(v)?, *usage)))
+                        .collect(),
+                    dead: parse(&data.dead),
+                    esr: parse(&data.esr),
+                })
+            })
+            .collect()
+    })
+}
+
+fn release(browser: Browser) -> Option<&'static Release> {
+    releases().iter().find(|r| r.browser == browser)
+}
+
+// ANCHOR: query_error
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryError {
+    UnknownQuery(String),
+    UnknownBrowser(String),
+    UnknownVersion(String),
+}
+// ANCHOR_END: query_error
+
+type BrowserSet = BTreeSet<(Browser, Version)>;
+
+// ANCHOR: resolve_query
+// 解析 browserslist 风格的查询，逗号和 or 取并集，and 取交集，not 从结果中排除
+pub fn resolve_query(query: &str) -> Result<Vec<(Browser, Version)>, QueryError> {
+    let mut result = BrowserSet::new();
+    for part in split_or(query) {
+        let atoms: Vec<&str> = split_and(part);
+        let mut set: Option<BrowserSet> = None;
+        let mut excluded = BrowserSet::new();
+        for atom in atoms {
+            match strip_not(atom) {
+                Some(negated) => excluded.extend(resolve_atom(negated)?),
+                None => {
+                    let matched = resolve_atom(atom)?;
+                    set = Some(match set {
+                        Some(set) => set.intersection(&matched).copied().collect(),
+                        None => matched,
+                    });
+                }
+            }
+        }
+        match set {
+            Some(set) => result.extend(set.difference(&excluded).copied()),
+            // 单独的 not 查询作用于前面已经得到的结果
+            None => result.retain(|item| !excluded.contains(item)),
+        }
+    }
+    Ok(result.into_iter().collect())
+}
+// ANCHOR_END: resolve_query
+
+impl Targets {
+    pub fn from_query(query: &str) -> Result<Targets, QueryError> {
+        Ok(Targets::from_list(&resolve_query(query)?))
+    }
+}
+
+fn split_or(query: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    for part in query.split(',') {
+        let mut rest = part;
+        while let Some(index) = find_word(rest, "or") {
+            parts.push(&rest[..index]);
+            rest = &rest[index + 2..];
+        }
+        parts.push(rest);
+    }
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+fn split_and(part: &str) -> Vec<&str> {
+    let mut atoms = Vec::new();
+    let mut rest = part;
+    while let Some(index) = find_word(rest, "and") {
+        atoms.push(rest[..index].trim());
+        rest = &rest[index + 3..];
+    }
+    atoms.push(rest.trim());
+    atoms
+}
+
+// 查找前后都是空白的关键字
+fn find_word(source: &str, word: &str) -> Option<usize> {
+    let lower = source.to_ascii_lowercase();
+    let mut start = 0;
+    while let Some(index) = lower[start..].find(word) {
+        let index = start + index;
+        let before = lower[..index].ends_with(char::is_whitespace);
+        let after = lower[index + word.len()..].starts_with(char::is_whitespace);
+        if before && after {
+            return Some(index);
+        }
+        start = index + word.len();
+    }
+    None
+}
+
+fn strip_not(atom: &str) -> Option<&str> {
+    atom.to_ascii_lowercase()
+        .starts_with("not ")
+        .then(|| atom[4..].trim_start())
+}
+
+fn resolve_atom(atom: &str) -> Result<BrowserSet, QueryError> {
+    let lower = atom.to_ascii_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    let unknown = || QueryError::UnknownQuery(atom.to_string());
+    match words.as_slice() {
+        ["defaults"] => Ok(
+            resolve_query("> 0.5%, last 2 versions, firefox esr, not dead")?
+                .into_iter()
+                .collect(),
+        ),
+        ["dead"] => Ok(releases()
+            .iter()
+            .flat_map(|r| r.dead.iter().map(|v| (r.browser, *v)))
+            .collect()),
+        ["last", count, "versions"] | ["last", count, "major", "versions"] => {
+            let count: usize = count.parse().map_err(|_| unknown())?;
+            let major = words.contains(&"major");
+            Ok(releases()
+                .iter()
+                .flat_map(|r| last_versions(r, count, major))
+                .collect())
+        }
+        ["last", count, name, "versions"] | ["last", count, name, "major", "versions"] => {
+            let count: usize = count.parse().map_err(|_| unknown())?;
+            let release = find_release(name)?;
+            Ok(last_versions(release, count, words.contains(&"major")).collect())
+        }
+        [name, "esr"] => {
+            let release = find_release(name)?;
+            Ok(release.esr.iter().map(|v| (release.browser, *v)).collect())
+        }
+        [op, percent] if percent.ends_with('%') => {
+            let share: f64 = percent
+                .trim_end_matches('%')
+                .parse()
+                .map_err(|_| unknown())?;
+            // nan%、inf% 也能解析为 f64
+            if !share.is_finite() {
+                return Err(unknown());
+            }
+            let compare = comparator(op).ok_or_else(unknown)?;
+            Ok(releases()
+                .iter()
+                .flat_map(|r| {
+                    r.versions
+                        .iter()
+                        .filter(|(_, usage)| compare(usage.partial_cmp(&share).unwrap()))
+                        .map(|(v, _)| (r.browser, *v))
+                })
+                .collect())
+        }
+        [name, op, version] => {
+            let release = find_release(name)?;
+            let compare = comparator(op).ok_or_else(unknown)?;
+            let version = parse_version(version)?;
+            Ok(release
+                .versions
+                .iter()
+                .filter(|(v, _)| compare(v.cmp(&version)))
+                .map(|(v, _)| (release.browser, *v))
+                .collect())
+        }
+        [name, version] => {
+            let release = find_release(name)?;
+            let (from, to) = match version.split_once('-') {
+                Some((from, to)) => (parse_version(from)?, parse_version(to)?),
+                None => {
+                    let version = parse_version(version)?;
+                    if !release.versions.iter().any(|(v, _)| *v == version) {
+                        return Err(QueryError::UnknownVersion(atom.to_string()));
+                    }
+                    (version, version)
+                }
+            };
+            Ok(release
+                .versions
+                .iter()
+                .filter(|(v, _)| *v >= from && *v <= to)
+                .map(|(v, _)| (release.browser, *v))
+                .collect())
+        }
+        _ => Err(unknown()),
+    }
+}
+
+fn find_release(name: &str) -> Result<&'static Release, QueryError> {
+    Browser::from_name(name)
+        .and_then(release)
+        .ok_or_else(|| QueryError::UnknownBrowser(name.to_string()))
+}
+
+fn parse_version(version: &str) -> Result<Version, QueryError> {
+    Version::parse(version).ok_or_else(|| QueryError::UnknownVersion(version.to_string()))
+}
+
+fn comparator(op: &str) -> Option<fn(std::cmp::Ordering) -> bool> {
+    match op {
+        ">" => Some(|o| o.is_gt()),
+        ">=" => Some(|o| o.is_ge()),
+        "<" => Some(|o| o.is_lt()),
+        "<=" => Some(|o| o.is_le()),
+        _ => None,
+    }
+}
+
+// 最近的几个版本，major 时同一个主版本的小版本算作一个
+fn last_versions(
+    release: &'static Release,
+    count: usize,
+    major: bool,
+) -> impl Iterator<Item = (Browser, Version)> {
+    let mut majors: Vec<u32> = release.versions.iter().map(|(v, _)| v.major).collect();
+    majors.dedup();
+    let min_major = majors.iter().rev().take(count).next_back().copied();
+    let skip = release.versions.len().saturating_sub(count);
+    release
+        .versions
+        .iter()
+        .enumerate()
+        .filter(move |(index, (v, _))| {
+            if major {
+                min_major.is_some_and(|m| v.major >= m)
+            } else {
+                *index >= skip
+            }
+        })
+        .map(move |(_, (v, _))| (release.browser, *v))
+}
@@ -28,6 +28,10 @@ impl Token {
     pub fn get_source_code<'a>(&'a self) -> &str {
         &self.raw
     }
+
+    pub fn get_range(&self) -> Range {
+        self.loc
+    }
 }
 
 // ANCHOR_END: token
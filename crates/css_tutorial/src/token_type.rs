@@ -79,6 +79,18 @@ pub enum TokenType {
     /** *= */
     AllMatch,
 
+    /** $= */
+    SuffixMatch,
+
+    /** ... */
+    Ellipsis,
+
+    /** % */
+    Percent,
+
+    /** != */
+    NotEqual,
+
     /** 复杂token */
     Str,
     Digital,
@@ -90,6 +102,10 @@ pub enum TokenType {
     HashToken,
     UrlToken,
     PercentageToken,
+    // scss $name
+    DollarVariable,
+    // scss %name
+    PlaceholderToken,
     // ANCHOR_END: lexer_token_type
 
     // ast node type
@@ -123,8 +139,39 @@ pub enum TokenType {
     Variable,
     // @xxx:xxx;
     VariableDeclaration,
-    //.s .c()
+    // less mixin .m(@a; @b: 2)
     SelectorFunction,
+    // less when (@a > 0) and (iscolor(@c)), default()
+    Guard,
+    // not (@a = b)
+    GuardCondition,
+    // less 分离规则集调用 @detached();
+    DetachedRulesetCall,
+    // scss @mixin name($a, $b: 2) { ... }
+    MixinRule,
+    // scss @include name(1, $b: 2) { ... }
+    IncludeRule,
+    // scss @content;
+    ContentRule,
+    // scss @function name($a) { ... }
+    FunctionRule,
+    // scss @return $a * 2;
+    ReturnRule,
+    // scss @if $a == 1 { } @else if $a == 2 { } @else { }
+    IfRule,
+    ElseRule,
+    // scss @each $key, $value in $map { }
+    EachRule,
+    // scss @for $i from 1 through 3 { }
+    ForRule,
+    // scss @while $i > 0 { }
+    WhileRule,
+    // scss @extend %name;
+    ExtendRule,
+    // scss mixin 和函数的参数 ($a, $b: 2, $rest...)
+    ArgumentList,
+    // scss (key: value, key2: value2)
+    Map,
 
     //
     SelectorList
@@ -0,0 +1,354 @@
+use std::fmt::{self, Display};
+
+use crate::{ast::AstNode, token_type::TokenType};
+
+// ANCHOR: units
+// 定义单位枚举，factor 为换算到规范单位的倍数，None 表示无法静态换算
+macro_rules! units {
+    ( $name:ident { $( $variant:ident => $text:literal, $factor:expr ; )* } ) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $( $variant, )*
+        }
+
+        impl $name {
+            pub fn from_unit(unit: &str) -> Option<Self> {
+                let unit = unit.to_ascii_lowercase();
+                match unit.as_str() {
+                    $( $text => Some($name::$variant), )*
+                    _ => None,
+                }
+            }
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $( $name::$variant => $text, )*
+                }
+            }
+
+            pub fn factor(&self) -> Option<f64> {
+                match self {
+                    $( $name::$variant => $factor, )*
+                }
+            }
+        }
+    };
+}
+// ANCHOR_END: units
+
+units!(LengthUnit {
+    Px => "px", Some(1.0);
+    Cm => "cm", Some(96.0 / 2.54);
+    Mm => "mm", Some(96.0 / 25.4);
+    Q => "q", Some(96.0 / 101.6);
+    In => "in", Some(96.0);
+    Pt => "pt", Some(96.0 / 72.0);
+    Pc => "pc", Some(16.0);
+    Em => "em", None;
+    Rem => "rem", None;
+    Ex => "ex", None;
+    Rex => "rex", None;
+    Cap => "cap", None;
+    Ch => "ch", None;
+    Ic => "ic", None;
+    Lh => "lh", None;
+    Rlh => "rlh", None;
+    Vw => "vw", None;
+    Vh => "vh", None;
+    Vi => "vi", None;
+    Vb => "vb", None;
+    Vmin => "vmin", None;
+    Vmax => "vmax", None;
+    Svw => "svw", None;
+    Svh => "svh", None;
+    Lvw => "lvw", None;
+    Lvh => "lvh", None;
+    Dvw => "dvw", None;
+    Dvh => "dvh", None;
+    Cqw => "cqw", None;
+    Cqh => "cqh", None;
+    Cqi => "cqi", None;
+    Cqb => "cqb", None;
+    Cqmin => "cqmin", None;
+    Cqmax => "cqmax", None;
+});
+
+units!(AngleUnit {
+    Deg => "deg", Some(1.0);
+    Grad => "grad", Some(0.9);
+    Rad => "rad", Some(180.0 / std::f64::consts::PI);
+    Turn => "turn", Some(360.0);
+});
+
+units!(TimeUnit {
+    Ms => "ms", Some(1.0);
+    S => "s", Some(1000.0);
+});
+
+units!(FrequencyUnit {
+    Hz => "hz", Some(1.0);
+    Khz => "khz", Some(1000.0);
+});
+
+units!(ResolutionUnit {
+    Dppx => "dppx", Some(1.0);
+    X => "x", Some(1.0);
+    Dpi => "dpi", Some(1.0 / 96.0);
+    Dpcm => "dpcm", Some(2.54 / 96.0);
+});
+
+// ANCHOR: quantity
+// 带单位的数值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity<U> {
+    pub value: f64,
+    pub unit: U,
+}
+// ANCHOR_END: quantity
+
+pub type Length = Quantity<LengthUnit>;
+pub type Angle = Quantity<AngleUnit>;
+pub type Time = Quantity<TimeUnit>;
+pub type Frequency = Quantity<FrequencyUnit>;
+pub type Resolution = Quantity<ResolutionUnit>;
+
+macro_rules! quantity_impl {
+    ( $unit:ident, $canonical:ident ) => {
+        impl Quantity<$unit> {
+            pub fn new(value: f64, unit: $unit) -> Self {
+                Quantity { value, unit }
+            }
+
+            // 换算到规范单位
+            pub fn to_canonical(&self) -> Option<f64> {
+                self.unit.factor().map(|factor| self.value * factor)
+            }
+
+            pub fn convert_to(&self, unit: $unit) -> Option<Self> {
+                if unit == self.unit {
+                    return Some(*self);
+                }
+                let value = self.to_canonical()? / unit.factor()?;
+                // 换算后溢出时不能换算，canonicalize 保留原来的单位
+                value.is_finite().then(|| Self::new(value, unit))
+            }
+
+            pub fn canonicalize(&self) -> Self {
+                self.convert_to($unit::$canonical).unwrap_or(*self)
+            }
+        }
+
+        impl Display for Quantity<$unit> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}{}", format_number(self.value), self.unit.as_str())
+            }
+        }
+    };
+}
+
+quantity_impl!(LengthUnit, Px);
+quantity_impl!(AngleUnit, Deg);
+quantity_impl!(TimeUnit, Ms);
+quantity_impl!(FrequencyUnit, Hz);
+quantity_impl!(ResolutionUnit, Dppx);
+
+impl Length {
+    pub fn to_px(&self) -> Option<f64> {
+        self.to_canonical()
+    }
+
+    pub fn is_absolute(&self) -> bool {
+        self.unit.factor().is_some()
+    }
+}
+
+// ANCHOR: value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Percentage(f64),
+    Length(Length),
+    Angle(Angle),
+    Time(Time),
+    Frequency(Frequency),
+    Resolution(Resolution),
+    // fr
+    Flex(f64),
+    // 未知单位
+    Dimension(f64, String),
+}
+// ANCHOR_END: value
+
+impl Value {
+    // 解析 12px、50%、.5 这类文本
+    pub fn parse(source: &str) -> Option<Value> {
+        let (number, unit) = split_number(source.trim())?;
+        if unit.is_empty() {
+            return Some(Value::Number(number));
+        }
+        if unit == "%" {
+            return Some(Value::Percentage(number));
+        }
+        if let Some(unit) = LengthUnit::from_unit(unit) {
+            return Some(Value::Length(Length::new(number, unit)));
+        }
+        if let Some(unit) = AngleUnit::from_unit(unit) {
+            return Some(Value::Angle(Angle::new(number, unit)));
+        }
+        if let Some(unit) = TimeUnit::from_unit(unit) {
+            return Some(Value::Time(Time::new(number, unit)));
+        }
+        if let Some(unit) = FrequencyUnit::from_unit(unit) {
+            return Some(Value::Frequency(Frequency::new(number, unit)));
+        }
+        if let Some(unit) = ResolutionUnit::from_unit(unit) {
+            return Some(Value::Resolution(Resolution::new(number, unit)));
+        }
+        if unit.eq_ignore_ascii_case("fr") {
+            return Some(Value::Flex(number));
+        }
+        // 单位只能是标识符
+        if unit.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        Some(Value::Dimension(number, unit.to_string()))
+    }
+
+    // 从 Term 节点或数值 token 中解析
+    pub fn from_node(node: &AstNode<TokenType>) -> Option<Value> {
+        match node.node_type.0 {
+            TokenType::Term => match node.child_nodes() {
+                [child] => Value::from_node(child),
+                _ => None,
+            },
+            TokenType::Digital | TokenType::Dimension | TokenType::PercentageToken => {
+                Value::parse(&node.raw)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn number(&self) -> f64 {
+        match self {
+            Value::Number(n) | Value::Percentage(n) | Value::Flex(n) | Value::Dimension(n, _) => *n,
+            Value::Length(l) => l.value,
+            Value::Angle(a) => a.value,
+            Value::Time(t) => t.value,
+            Value::Frequency(f) => f.value,
+            Value::Resolution(r) => r.value,
+        }
+    }
+
+    pub fn unit(&self) -> &str {
+        match self {
+            Value::Number(_) => "",
+            Value::Percentage(_) => "%",
+            Value::Flex(_) => "fr",
+            Value::Dimension(_, unit) => unit,
+            Value::Length(l) => l.unit.as_str(),
+            Value::Angle(a) => a.unit.as_str(),
+            Value::Time(t) => t.unit.as_str(),
+            Value::Frequency(f) => f.unit.as_str(),
+            Value::Resolution(r) => r.unit.as_str(),
+        }
+    }
+
+    // 同类数值换算到规范单位
+    pub fn canonicalize(&self) -> Value {
+        match self {
+            Value::Length(l) => Value::Length(l.canonicalize()),
+            Value::Angle(a) => Value::Angle(a.canonicalize()),
+            Value::Time(t) => Value::Time(t.canonicalize()),
+            Value::Frequency(f) => Value::Frequency(f.canonicalize()),
+            Value::Resolution(r) => Value::Resolution(r.canonicalize()),
+            value => value.clone(),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.number() == 0.0
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", format_number(self.number()), self.unit())
+    }
+}
+
+// ANCHOR: split_number
+// 拆分数值和单位，例如 1.5e2px => (150, "px")
+pub fn split_number(source: &str) -> Option<(f64, &str)> {
+    let bytes = source.as_bytes();
+    let mut index = 0;
+    if matches!(bytes.first(), Some(b'+' | b'-')) {
+        index += 1;
+    }
+    let digits_start = index;
+    while index < bytes.len() && bytes[index].is_ascii_digit() {
+        index += 1;
+    }
+    if index < bytes.len() && bytes[index] == b'.' {
+        index += 1;
+        while index < bytes.len() && bytes[index].is_ascii_digit() {
+            index += 1;
+        }
+    }
+    if !source[digits_start..index].bytes().any(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // 指数部分后面必须是数字，避免把 2em 的 e 当作指数
+    if index < bytes.len() && matches!(bytes[index], b'e' | b'E') {
+        let mut exponent = index + 1;
+        if exponent < bytes.len() && matches!(bytes[exponent], b'+' | b'-') {
+            exponent += 1;
+        }
+        if exponent < bytes.len() && bytes[exponent].is_ascii_digit() {
+            while exponent < bytes.len() && bytes[exponent].is_ascii_digit() {
+                exponent += 1;
+            }
+            index = exponent;
+        }
+    }
+    // 1e400 超出浮点数范围
+    let number = source[..index].parse().ok().filter(|n: &f64| n.is_finite())?;
+    Some((number, &source[index..]))
+}
+// ANCHOR_END: split_number
+
+// 同类单位之间换算，例如 1cm 换算成 px
+pub fn convert(value: f64, from: &str, to: &str) -> Option<f64> {
+    if from.eq_ignore_ascii_case(to) {
+        return Some(value);
+    }
+    let from = Value::parse(&format!("1{}", from))?.canonicalize();
+    let to = Value::parse(&format!("1{}", to))?.canonicalize();
+    let value = value * from.number() / to.number();
+    (from.unit() == to.unit() && value.is_finite()).then_some(value)
+}
+
+// 输出数值，去掉浮点误差和多余的 0
+pub fn format_number(value: f64) -> String {
+    let mut rounded = (value * 1_000_000.0).round() / 1_000_000.0;
+    // 很大的数乘以 1000000 后会溢出，这时已经没有小数部分
+    if !rounded.is_finite() {
+        rounded = value;
+    }
+    if rounded == 0.0 {
+        return "0".to_string();
+    }
+    let text = format!("{:.6}", rounded);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// 去掉小数点前的 0，.5 比 0.5 短
+pub fn short_number(value: f64) -> String {
+    let text = format_number(value);
+    if let Some(rest) = text.strip_prefix("0.") {
+        format!(".{}", rest)
+    } else if let Some(rest) = text.strip_prefix("-0.") {
+        format!("-.{}", rest)
+    } else {
+        text
+    }
+}
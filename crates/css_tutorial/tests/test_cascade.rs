@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod test_cascade {
+    use css_tutorial::{
+        cascade::{print_styles, Cascade, Origin},
+        html::Document,
+        parser::parse_stylesheet,
+    };
+
+    fn compute(sheets: &[(Origin, &str)], html: &str) -> String {
+        let document = Document::parse(html);
+        let mut cascade = Cascade::new();
+        for (origin, source) in sheets {
+            cascade.add_stylesheet(*origin, &parse_stylesheet(source));
+        }
+        print_styles(&document, &cascade.compute(&document))
+    }
+
+    #[test]
+    fn specificity_and_order() {
+        let result = compute(
+            &[(
+                Origin::Author,
+                r#"
+                p { color: red; margin: 0 }
+                .note { color: blue }
+                p { margin: 1px 2px }
+                #x { color: green }
+                "#,
+            )],
+            r#"<p class="note">a</p><p id="x" class="note">b</p>"#,
+        );
+        assert_eq!(
+            result,
+            "p.note\n  margin: 1px 2px\n  color: blue\np#x.note\n  margin: 1px 2px\n  color: green\n"
+        );
+    }
+
+    #[test]
+    fn important_and_origin() {
+        let result = compute(
+            &[
+                (Origin::UserAgent, "div { display: block !important; color: black }"),
+                (Origin::User, "div { color: purple !important }"),
+                (Origin::Author, "#a { color: red !important; display: flex }"),
+            ],
+            r#"<div id="a" style="color: blue; width: 1px"></div>"#,
+        );
+        assert_eq!(
+            result,
+            "div#a\n  width: 1px\n  color: purple !important\n  display: block !important\n"
+        );
+    }
+
+    #[test]
+    fn layer_order() {
+        let result = compute(
+            &[(
+                Origin::Author,
+                r#"
+                @layer base, theme;
+                @layer theme { a { color: red; border: 1px } }
+                @layer base { a { color: blue !important; border: 0 } }
+                a { border: 2px }
+                @layer base { .x { border: 3px } }
+                "#,
+            )],
+            r#"<a class="x"></a>"#,
+        );
+        assert_eq!(result, "a.x\n  border: 2px\n  color: blue !important\n");
+    }
+
+    #[test]
+    fn media() {
+        let result = compute(
+            &[(
+                Origin::Author,
+                r#"
+                @media print { a { color: red } }
+                @media screen, print { a { margin: 0 } }
+                @media (min-width: 10px) { a { padding: 0 } }
+                "#,
+            )],
+            "<a></a>",
+        );
+        assert_eq!(result, "a\n  margin: 0\n");
+    }
+
+    #[test]
+    fn snapshot() {
+        let result = compute(
+            &[(
+                Origin::Author,
+                r#"
+                body { font-family: "Helvetica Neue", Arial, sans-serif; color: #333 }
+                .card > .title { font: bold 12px/1.5 serif }
+                .card :is(.title, .body):not(:first-child) { padding: calc(100% - 2px) }
+                ul li:nth-child(odd) { background: rgba(0, 0, 0, .1) }
+                "#,
+            )],
+            r#"<body><div class="card"><h1 class="title">t</h1><div class="body">b</div></div>
+            <ul><li>1</li><li>2</li><li>3</li></ul></body>"#,
+        );
+        test_tool::compart_to_snapshot(result, "cascade_snapshot");
+    }
+}
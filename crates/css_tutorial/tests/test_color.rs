@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod test_color {
+    use css_tutorial::{
+        color::{Color, ColorSpace},
+        parser::parse_stylesheet,
+        token_type::TokenType,
+    };
+
+    fn assert_close(left: [f64; 3], right: [f64; 3]) {
+        for (a, b) in left.iter().zip(right.iter()) {
+            assert!((a - b).abs() < 1e-3, "{:?} != {:?}", left, right);
+        }
+    }
+
+    #[test]
+    fn parse_syntaxes() {
+        let red = [1.0, 0.0, 0.0];
+        for source in [
+            "#f00",
+            "#ff0000",
+            "#F00F",
+            "#ff0000ff",
+            "red",
+            "RED",
+            "rgb(255, 0, 0)",
+            "rgba(255,0,0,1)",
+            "rgb(100% 0% 0%)",
+            "rgb(255 0 0 / 100%)",
+            "hsl(0, 100%, 50%)",
+            "hsl(360deg 100 50)",
+            "hsla(1turn 100% 50%)",
+            "hwb(0 0% 0%)",
+            "color(srgb 1 0 0)",
+        ] {
+            let color = Color::parse(source).unwrap_or_else(|| panic!("{}", source));
+            assert_close(color.to_srgb().components, red);
+            assert_eq!(color.alpha, 1.0, "{}", source);
+        }
+        assert_eq!(Color::parse("#ff000080").unwrap().to_rgb8()[3], 128);
+        assert_eq!(Color::parse("transparent").unwrap().alpha, 0.0);
+        assert!(Color::parse("rgb(1, 2)").is_none());
+        assert!(Color::parse("rgb(1 2, 3)").is_none());
+        assert!(Color::parse("#ff00").is_some());
+        assert!(Color::parse("#ff0").is_some());
+        assert!(Color::parse("#ff00000").is_none());
+        assert!(Color::parse("notacolor").is_none());
+        assert!(Color::parse("color(foo 1 0 0)").is_none());
+        assert!(Color::parse("oklch(none 0.1 30)").unwrap().components[0].is_nan());
+    }
+
+    #[test]
+    fn convert_spaces() {
+        let red = Color::parse("red").unwrap();
+        assert_close(
+            red.convert(ColorSpace::Oklch).components,
+            [0.627955, 0.257683, 29.233885],
+        );
+        assert_close(
+            red.convert(ColorSpace::Lab).components,
+            [54.290541, 80.804949, 69.890998],
+        );
+        assert_close(
+            red.convert(ColorSpace::Hsl).components,
+            [0.0, 100.0, 50.0],
+        );
+        let white = Color::parse("white").unwrap();
+        assert_close(white.convert(ColorSpace::Oklab).components, [1.0, 0.0, 0.0]);
+        assert_close(white.convert(ColorSpace::Lab).components, [100.0, 0.0, 0.0]);
+        // 各空间来回转换保持不变
+        let color = Color::parse("rgb(30 140 200)").unwrap();
+        for space in [
+            ColorSpace::SrgbLinear,
+            ColorSpace::DisplayP3,
+            ColorSpace::A98Rgb,
+            ColorSpace::ProphotoRgb,
+            ColorSpace::Rec2020,
+            ColorSpace::XyzD50,
+            ColorSpace::XyzD65,
+            ColorSpace::Hsl,
+            ColorSpace::Hwb,
+            ColorSpace::Lab,
+            ColorSpace::Lch,
+            ColorSpace::Oklab,
+            ColorSpace::Oklch,
+        ] {
+            assert_close(
+                color.convert(space).to_srgb().components,
+                color.components,
+            );
+        }
+        assert!(!Color::parse("color(display-p3 1 0 0)").unwrap().in_srgb_gamut());
+        assert!(Color::parse("oklch(0.5 0.05 200)").unwrap().in_srgb_gamut());
+    }
+
+    #[test]
+    fn color_mix() {
+        let mixed = Color::parse("color-mix(in srgb, red, blue)").unwrap();
+        assert_eq!(mixed.to_shortest(), "purple");
+        let mixed = Color::parse("color-mix(in srgb, red 25%, blue)").unwrap();
+        assert_eq!(mixed.to_rgb8(), [64, 0, 191, 255]);
+        let mixed = Color::parse("color-mix(in srgb, red 30%, blue 30%)").unwrap();
+        assert!((mixed.alpha - 0.6).abs() < 1e-9);
+        let mixed = Color::parse("color-mix(in hsl, hsl(10 100% 50%), hsl(350 100% 50%))").unwrap();
+        assert_close(mixed.components, [0.0, 100.0, 50.0]);
+        let mixed =
+            Color::parse("color-mix(in hsl longer hue, hsl(10 100% 50%), hsl(350 100% 50%))")
+                .unwrap();
+        assert_close(mixed.components, [180.0, 100.0, 50.0]);
+        // 白色没有色相，结果沿用另一个颜色的色相
+        let mixed = Color::parse("color-mix(in oklch, white, oklch(0.5 0.2 120))").unwrap();
+        assert!((mixed.components[2] - 120.0).abs() < 1e-6);
+        let mixed = Color::parse("color-mix(in srgb, transparent, red)").unwrap();
+        assert_eq!(mixed.to_rgb8(), [255, 0, 0, 128]);
+        assert!(Color::parse("color-mix(in srgb, red 0%, blue 0%)").is_none());
+        assert!(Color::parse("color-mix(in foo, red, blue)").is_none());
+    }
+
+    #[test]
+    fn serialize() {
+        let cases = [
+            ("#ff0000", "red", "rgb(255, 0, 0)"),
+            ("#FFFFFF", "#fff", "rgb(255, 255, 255)"),
+            ("#f0ffff", "azure", "rgb(240, 255, 255)"),
+            ("rgb(0 0 128)", "navy", "rgb(0, 0, 128)"),
+            ("#00ff00", "#0f0", "rgb(0, 255, 0)"),
+            ("rgb(255 0 0 / 50%)", "#ff000080", "rgba(255, 0, 0, 0.5)"),
+            ("transparent", "#0000", "rgba(0, 0, 0, 0)"),
+            ("hsl(120deg 100% 25%)", "green", "rgb(0, 128, 0)"),
+            ("rgb(18, 52, 86)", "#123456", "rgb(18, 52, 86)"),
+            ("lab(50% 20 -30 / 0.5)", "lab(50 20 -30 / .5)", "lab(50 20 -30 / 0.5)"),
+            ("oklch(0.5 0.1 none)", "oklch(.5 .1 none)", "oklch(0.5 0.1 none)"),
+            ("oklch(50% 25% 30)", "oklch(.5 .1 30)", "oklch(0.5 0.1 30)"),
+            (
+                "color(display-p3 1 0.5 0)",
+                "color(display-p3 1 .5 0)",
+                "color(display-p3 1 0.5 0)",
+            ),
+        ];
+        for (source, shortest, display) in cases {
+            let color = Color::parse(source).unwrap();
+            assert_eq!(color.to_shortest(), shortest, "{}", source);
+            assert_eq!(color.to_string(), display, "{}", source);
+        }
+    }
+
+    #[test]
+    fn from_node() {
+        let tree = parse_stylesheet("a { color: rgb(10, 20, 30); background: #abc; }");
+        let root = tree.root.unwrap();
+        let rule = root.find_child(TokenType::RuleList).unwrap();
+        let declarations = rule.find_child(TokenType::DeclarationList).unwrap();
+        let colors: Vec<String> = declarations
+            .child_nodes()
+            .iter()
+            .filter(|n| n.check_type(TokenType::Declaration))
+            .map(|d| d.find_child(TokenType::Expression).unwrap())
+            .map(|e| Color::from_node(e).unwrap().to_shortest())
+            .collect();
+        assert_eq!(colors, vec!["#0a141e", "#abc"]);
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod test_compat {
+    use css_tutorial::{
+        compat::{check_compat, CompatIssue, FeatureKind},
+        parser::parse_stylesheet,
+        targets::{Browser, Targets, Version},
+    };
+
+    fn check(source: &str, query: &str) -> Vec<CompatIssue> {
+        check_compat(
+            &parse_stylesheet(source),
+            &Targets::from_query(query).unwrap(),
+        )
+    }
+
+    fn messages(source: &str, query: &str) -> Vec<String> {
+        check(source, query).iter().map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn report_features() {
+        assert_eq!(
+            messages(
+                "@layer base { .a:has(> img) { container-type: inline-size } }",
+                "firefox 115, safari >= 15.6"
+            ),
+            [
+                "`:has()` is not supported by firefox 115 (supported since 121)",
+                "`container-type` is not supported by safari 15.6 (supported since 16)",
+            ]
+        );
+        // 连续的伪类都要检查，伪元素不算
+        assert_eq!(
+            messages("a:hover:has(b), p::before:has(i) {}", "firefox 115"),
+            [
+                "`:has()` is not supported by firefox 115 (supported since 121)",
+                "`:has()` is not supported by firefox 115 (supported since 121)",
+            ]
+        );
+        assert_eq!(
+            messages("a { grid-template-columns: subgrid }", "chrome 109"),
+            ["`grid-template-columns: subgrid` is not supported by chrome 109 (supported since 117)"]
+        );
+        assert_eq!(
+            messages("a { color: color-mix(in srgb, red, blue) }", "safari 15.6"),
+            ["`color-mix()` is not supported by safari 15.6 (supported since 16.2)"]
+        );
+    }
+
+    #[test]
+    fn issue_details() {
+        let source = "a:focus-visible { top: 0 }";
+        let issues = check(source, "ie 11");
+        assert_eq!(issues.len(), 1);
+        let issue = &issues[0];
+        assert_eq!(issue.feature, "css.selectors.focus-visible");
+        assert_eq!(issue.kind, FeatureKind::Selector);
+        assert_eq!(issue.browser, Browser::Ie);
+        assert_eq!(issue.target, Version::new(11, 0));
+        assert_eq!(issue.since, None);
+        assert_eq!(
+            &source[issue.range.start_pos..issue.range.end_pos],
+            "a:focus-visible"
+        );
+        assert_eq!(
+            issue.to_string(),
+            "`:focus-visible` is not supported by ie 11 (not supported)"
+        );
+    }
+
+    #[test]
+    fn prefixed_support() {
+        // safari 只支持带前缀的 user-select，无前缀的写法视为不支持
+        assert_eq!(
+            messages("a { user-select: none }", "safari 17.5"),
+            ["`user-select` is not supported by safari 17.5 (not supported)"]
+        );
+        assert_eq!(
+            messages("a { position: sticky }", "safari 12.1, safari 13.1"),
+            ["`position: sticky` is not supported by safari 12.1 (supported since 13)"]
+        );
+    }
+
+    #[test]
+    fn supported_targets() {
+        let source =
+            "@container (min-width: 400px) { .a:is(.b) { aspect-ratio: 1; display: flex } }";
+        assert!(check(source, "chrome >= 120, firefox >= 124, safari >= 17").is_empty());
+        // 没有数据的特性不报告
+        assert!(check("a { -webkit-box-reflect: below }", "ie 11").is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod test_custom_properties {
+    use css_tutorial::{
+        codegen::generate,
+        custom_properties::{
+            check_vars, collect_custom_properties, resolve_vars, VarIssue, VarOptions,
+        },
+        parser::parse_stylesheet,
+    };
+
+    fn resolve(source: &str, keep_vars: bool) -> (String, usize) {
+        let mut tree = parse_stylesheet(source);
+        let count = resolve_vars(&mut tree, &VarOptions { keep_vars });
+        (generate(&tree), count)
+    }
+
+    fn issues(source: &str) -> Vec<String> {
+        check_vars(&parse_stylesheet(source))
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn collect_scopes() {
+        let scopes = collect_custom_properties(&parse_stylesheet(
+            ":root { --a: 1px; color: red } .dark { --a: 2px; --b: red } @media print { :root { --a: 0 } } p { top: 0 }",
+        ));
+        assert_eq!(scopes.len(), 3);
+        assert_eq!(scopes[0].selector, ":root");
+        assert!(scopes[0].is_root());
+        assert_eq!(scopes[1].properties.len(), 2);
+        assert!(!scopes[1].is_root());
+        assert!(scopes[2].conditional);
+        assert!(!scopes[2].is_root());
+    }
+
+    #[test]
+    fn resolve_root_vars() {
+        let (result, count) = resolve(
+            ":root { --gap: 4px; --border: 1px solid var(--color, red) } a { --size: 2px; margin: var(--gap) calc(var(--size) * 2); border: var(--border); left: var(--missing, var(--gap)) }",
+            false,
+        );
+        assert_eq!(count, 3);
+        assert_eq!(
+            result,
+            ":root {\n  --gap: 4px;\n  --border: 1px solid var(--color, red);\n}\na {\n  --size: 2px;\n  margin: 4px calc(2px * 2);\n  border: 1px solid red;\n  left: 4px;\n}\n"
+        );
+    }
+
+    #[test]
+    fn keep_vars_as_fallback() {
+        let (result, count) = resolve(
+            ":root { --main: #333 } a { color: var(--main) !important }",
+            true,
+        );
+        assert_eq!(count, 1);
+        assert_eq!(
+            result,
+            ":root {\n  --main: #333;\n}\na {\n  color: #333 !important;\n  color: var(--main) !important;\n}\n"
+        );
+    }
+
+    #[test]
+    fn skip_dynamic_vars() {
+        // 在其他规则或 @media 中覆盖过的属性取决于具体元素
+        let source = ":root { --bg: white; --fg: black } .dark { --bg: black; color: var(--bg) } @media print { :root { --fg: gray } } a { background: var(--bg); color: var(--fg) }";
+        let (result, count) = resolve(source, false);
+        assert_eq!(count, 1);
+        assert!(result.contains("a {\n  background: var(--bg);\n  color: var(--fg);\n}"));
+        assert!(result.contains(".dark {\n  --bg: black;\n  color: black;\n}"));
+        // 其他规则也定义了同名属性时，所在规则的定义不一定生效，例如 .a.b
+        let (result, count) = resolve(".a { --x: 1px; width: var(--x) } .a.b { --x: 2px }", false);
+        assert_eq!(count, 0);
+        assert!(result.contains("width: var(--x);"));
+        // 未定义且没有默认值时保持原样
+        let (_, count) = resolve("a { top: var(--none) }", false);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn cycles() {
+        let source = ":root { --a: var(--b); --b: var(--c, 1px); --c: var(--a); --d: 2px } a { --e: var(--e); top: var(--a, 0); left: var(--d) }";
+        assert_eq!(
+            issues(source),
+            [
+                "custom properties form a cycle: --a -> --b -> --c -> --a",
+                "custom properties form a cycle: --e -> --e",
+            ]
+        );
+        // 循环中的属性无效，使用 var() 的默认值
+        let (result, count) = resolve(source, false);
+        assert_eq!(count, 2);
+        assert!(result.contains("top: 0;\n  left: 2px;"));
+    }
+
+    #[test]
+    fn undefined_vars() {
+        let source = ":root { --a: var(--x) } .b { --y: 1px } a { top: var(--y); left: var(--z, 0); right: var(--z) }";
+        let result = check_vars(&parse_stylesheet(source));
+        assert_eq!(result.len(), 2);
+        assert!(matches!(&result[0], VarIssue::Undefined { name, .. } if name == "--x"));
+        let range = result[1].range();
+        assert_eq!(&source[range.start_pos..range.end_pos], "var(--z)");
+        assert_eq!(
+            result[1].to_string(),
+            "`--z` is not defined and has no fallback"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod test_downlevel {
+    use css_tutorial::{
+        codegen::generate,
+        color::Color,
+        downlevel::{
+            downlevel_colors, fallback_color, is_modern_color, needs_fallback, DownlevelOptions,
+        },
+        parser::parse_stylesheet,
+        targets::Targets,
+    };
+
+    fn downlevel(source: &str, keep_modern: bool) -> (String, usize) {
+        let mut tree = parse_stylesheet(source);
+        let options = DownlevelOptions {
+            keep_modern,
+            ..Default::default()
+        };
+        let count = downlevel_colors(&mut tree, &options);
+        (generate(&tree), count)
+    }
+
+    #[test]
+    fn relative_color() {
+        let color = Color::parse("rgb(from red r g calc(b + 255))").unwrap();
+        assert_eq!(color.to_shortest(), "#f0f");
+        let color = Color::parse("hsl(from #00f h s calc(l + 20) / 0.5)").unwrap();
+        assert_eq!(color.to_string(), "rgba(102, 102, 255, 0.5)");
+        let color = Color::parse("oklch(from red l c calc(h + 180))").unwrap();
+        assert!((color.components[2] - 209.233885).abs() < 1e-3);
+        let color = Color::parse("color(from red srgb b g r)").unwrap();
+        assert_eq!(color.components, [0.0, 0.0, 1.0]);
+        assert!(Color::parse("rgb(from notacolor r g b)").is_none());
+    }
+
+    #[test]
+    fn gamut_mapping() {
+        // srgb 色域内的颜色不变
+        let color = Color::parse("oklch(0.627955 0.257683 29.2339)").unwrap();
+        assert_eq!(fallback_color(&color), "red");
+        let p3 = Color::parse("color(display-p3 1 0 0)").unwrap();
+        let mapped = p3.gamut_map_srgb();
+        assert!(mapped.in_gamut());
+        assert_eq!(mapped.to_rgb8()[0], 255);
+        // 超出色域的高彩度颜色降低彩度而不是直接裁剪
+        let vivid = Color::parse("oklch(0.7 0.4 150)").unwrap();
+        let mapped = vivid.gamut_map_srgb();
+        assert!(mapped.in_gamut());
+        let hue = mapped
+            .convert(css_tutorial::color::ColorSpace::Oklch)
+            .components[2];
+        assert!((hue - 150.0).abs() < 5.0, "{}", hue);
+        assert_eq!(
+            fallback_color(&Color::parse("oklch(1.2 0.1 20)").unwrap()),
+            "#fff"
+        );
+        assert_eq!(
+            fallback_color(&Color::parse("lab(0 0 0 / 0.5)").unwrap()),
+            "rgba(0, 0, 0, 0.5)"
+        );
+    }
+
+    #[test]
+    fn rewrite_declarations() {
+        assert!(is_modern_color("OKLCH(0.5 0.1 20)"));
+        assert!(!is_modern_color("rgb(0 0 0)"));
+        let (css, count) = downlevel(
+            "a { color: oklch(62.8% 0.2577 29.23); background: linear-gradient(lab(100 0 0), color-mix(in srgb, red, blue)); border-color: #000; }",
+            false,
+        );
+        assert_eq!(count, 2);
+        assert_eq!(
+            css,
+            "a {\n  color: red;\n  background: linear-gradient(#fff, purple);\n  border-color: #000;\n}\n"
+        );
+    }
+
+    #[test]
+    fn keep_modern() {
+        let (css, count) = downlevel(
+            "@media screen { .a { color: oklch(from red l c h / 0.5) !important; } }",
+            true,
+        );
+        assert_eq!(count, 1);
+        assert_eq!(
+            css,
+            "@media screen {\n  .a {\n    color: rgba(255, 0, 0, 0.5) !important;\n    color: oklch(from red l c h/0.5) !important;\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn skip_supported_targets() {
+        let source =
+            "a { color: oklch(62.8% 0.2577 29.23); background: color-mix(in srgb, red, blue) }";
+        let downlevel_for = |query: &str| {
+            let mut tree = parse_stylesheet(source);
+            let options = DownlevelOptions {
+                targets: Targets::from_query(query).unwrap(),
+                ..Default::default()
+            };
+            downlevel_colors(&mut tree, &options)
+        };
+        assert_eq!(downlevel_for("chrome >= 120"), 0);
+        // safari 16 支持 oklch 但不支持 color-mix
+        assert_eq!(downlevel_for("safari >= 16.6"), 0);
+        assert_eq!(downlevel_for("safari >= 15.6"), 1);
+        assert_eq!(downlevel_for("ie 11"), 2);
+        assert!(needs_fallback("lab(50 0 0)", &Targets::default()));
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod test_html {
+    use css_tutorial::html::{Document, NodeData};
+
+    #[test]
+    fn parse_elements() {
+        let document = Document::parse(
+            r#"<!DOCTYPE html>
+            <html><body><div id="main" class="card  big"><p>hi<br>there</p><img src=a.png></div></body></html>"#,
+        );
+        let tags: Vec<String> = document
+            .elements()
+            .into_iter()
+            .map(|id| document.element(id).unwrap().tag_name.clone())
+            .collect();
+        assert_eq!(tags, vec!["html", "body", "div", "p", "br", "img"]);
+        let div = document.elements_by_tag_name("div")[0];
+        let element = document.element(div).unwrap();
+        assert_eq!(element.id(), Some("main"));
+        assert_eq!(element.classes().collect::<Vec<_>>(), vec!["card", "big"]);
+        assert_eq!(document.element_children(div).len(), 2);
+    }
+
+    #[test]
+    fn raw_text_element() {
+        let document = Document::parse(r#"<style>a > b { color: red }</style><p>x</p>"#);
+        let style = document.elements_by_tag_name("style")[0];
+        assert_eq!(document.text_content(style), "a > b { color: red }");
+        assert_eq!(document.elements().len(), 2);
+    }
+
+    #[test]
+    fn serialize() {
+        let source = r#"<div class="a" data-x="1 &quot;2&quot;"><!-- c --><input disabled></div>"#;
+        let document = Document::parse(source);
+        assert_eq!(document.to_html(), source);
+        assert!(matches!(
+            document.node(document.node(1).children[0]).data,
+            NodeData::Comment(_)
+        ));
+    }
+
+    #[test]
+    fn implicit_close() {
+        let document = Document::parse("<ul><li>a<li>b</ul>");
+        let ul = document.elements_by_tag_name("ul")[0];
+        assert_eq!(document.element_children(ul).len(), 2);
+    }
+
+    #[test]
+    fn non_ascii_text() {
+        let document = Document::parse("<p>日本</p><p>ü < 1</p>");
+        let paragraphs = document.elements_by_tag_name("p");
+        assert_eq!(document.text_content(paragraphs[0]), "日本");
+        assert_eq!(document.text_content(paragraphs[1]), "ü < 1");
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod test_inline {
+    use css_tutorial::inline::{inline_css, InlineOptions};
+
+    #[test]
+    fn inline_rules() {
+        let html = r#"<html><head><style>
+            p { color: red; margin: 0 }
+            .a { color: blue }
+            #b { color: green !important }
+        </style></head><body><p class="a">x</p><p id="b" style="color: black; padding: 1px">y</p></body></html>"#;
+        assert_eq!(
+            inline_css(html, &InlineOptions::default()),
+            r#"<html><head></head><body><p class="a" style="margin: 0; color: blue;">x</p><p id="b" style="margin: 0; padding: 1px; color: green !important;">y</p></body></html>"#
+        );
+    }
+
+    #[test]
+    fn keep_residual() {
+        let html = r#"<style>a, a:hover { color: red } @media (max-width: 600px) { a { color: blue } }</style><a href="/">x</a>"#;
+        assert_eq!(
+            inline_css(html, &InlineOptions::default()),
+            "<style>\na:hover {\n  color: red;\n}\n@media (max-width: 600px) {\n  a {\n    color: blue;\n  }\n}\n</style><a href=\"/\" style=\"color: red;\">x</a>"
+        );
+        assert_eq!(
+            inline_css(
+                html,
+                &InlineOptions {
+                    keep_at_rules: false,
+                    keep_pseudos: false
+                }
+            ),
+            "<a href=\"/\" style=\"color: red;\">x</a>"
+        );
+    }
+
+    #[test]
+    fn embed_style() {
+        let html = r#"<style data-embed>a { color: red }</style><a>x</a>"#;
+        assert_eq!(
+            inline_css(html, &InlineOptions::default()),
+            "<style>a { color: red }</style><a>x</a>"
+        );
+    }
+}
@@ -0,0 +1,460 @@
+#[cfg(test)]
+mod test_less {
+    use std::{env, fs};
+
+    use css_tutorial::{
+        less::{compile_less, compile_less_file, less_to_scss, LessOptions, ScssTranslation},
+        lexer::Syntax,
+        parser::parse_stylesheet_with_syntax,
+        scss::compile_scss,
+    };
+
+    fn less(source: &str) -> String {
+        compile_less(source).unwrap()
+    }
+
+    fn translate(source: &str) -> ScssTranslation {
+        less_to_scss(&parse_stylesheet_with_syntax(source, Syntax::Less))
+    }
+
+    fn warnings(source: &str) -> Vec<String> {
+        translate(source)
+            .warnings
+            .iter()
+            .map(|warning| warning.to_string())
+            .collect()
+    }
+
+    fn less_error(source: &str) -> String {
+        compile_less(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(
+            less("@size: 10px; @color: #333; .a { width: @size; border: 1px solid @color; margin: 0 @size }"),
+            ".a {\n  width: 10px;\n  border: 1px solid #333;\n  margin: 0 10px;\n}\n"
+        );
+        // 变量可以引用其他变量，函数参数中的变量也会替换
+        assert_eq!(
+            less("@base: 2px; @gap: @base; .a { padding: @gap; color: rgba(0, 0, 0, @opacity) } @opacity: .5;"),
+            ".a {\n  padding: 2px;\n  color: rgba(0, 0, 0, .5);\n}\n"
+        );
+    }
+
+    #[test]
+    fn lexical_scope() {
+        assert_eq!(
+            less("@var: 1px; .scope { @var: 2px; .inner { width: @var } } .other { width: @var }"),
+            ".scope .inner {\n  width: 2px;\n}\n.other {\n  width: 1px;\n}\n"
+        );
+        // 惰性求值，同一作用域中最后一次定义生效
+        assert_eq!(
+            less("@a: 1; @a: 2; .x { width: @a; .y { width: @a } @a: 4; } @a: 3;"),
+            ".x {\n  width: 4;\n}\n.x .y {\n  width: 4;\n}\n"
+        );
+        assert_eq!(
+            less(".lazy { width: @var; @var: @a; @a: 9%; }"),
+            ".lazy {\n  width: 9%;\n}\n"
+        );
+    }
+
+    #[test]
+    fn variable_variables() {
+        assert_eq!(
+            less("@primary: green; .section { @color: primary; @name: \"primary\"; color: @@color; .inner { background: @@name } }"),
+            ".section {\n  color: green;\n}\n.section .inner {\n  background: green;\n}\n"
+        );
+    }
+
+    #[test]
+    fn string_interpolation() {
+        assert_eq!(
+            less("@base-url: \"http://example.com\"; @name: banner; .a { background: url(\"@{base-url}/images/@{name}.png\"); content: '@{name}' }"),
+            ".a {\n  background: url(\"http://example.com/images/banner.png\");\n  content: 'banner';\n}\n"
+        );
+    }
+
+    #[test]
+    fn nested_rules() {
+        assert_eq!(
+            less(".a, .b { color: red; .c { top: 0 } &:hover, &-x { left: 0 } > p { right: 0 } }"),
+            ".a, .b {\n  color: red;\n}\n.a .c, .b .c {\n  top: 0;\n}\n.a:hover, .b:hover, .a-x, .b-x {\n  left: 0;\n}\n.a > p, .b > p {\n  right: 0;\n}\n"
+        );
+        assert_eq!(
+            less(".a { color: red; @media print { color: blue; .b { top: 0 } } }"),
+            ".a {\n  color: red;\n}\n@media print {\n  .a {\n    color: blue;\n  }\n  .a .b {\n    top: 0;\n  }\n}\n"
+        );
+        assert_eq!(
+            less("@w: 600px; @media (max-width: @w) { .a { top: 0 } }"),
+            "@media (max-width: 600px) {\n  .a {\n    top: 0;\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn mixins() {
+        // 普通规则也可以作为 mixin 调用，带参数的定义不会输出
+        assert_eq!(
+            less(".bordered { border: 1px solid } .m() { top: 0 } .a { .bordered; .m(); color: red }"),
+            ".bordered {\n  border: 1px solid;\n}\n.a {\n  border: 1px solid;\n  top: 0;\n  color: red;\n}\n"
+        );
+        assert_eq!(
+            less(".hover() { &:hover { color: red } } .a { .hover(); }"),
+            ".a:hover {\n  color: red;\n}\n"
+        );
+        assert_eq!(
+            less(".m() { top: 0; left: 0 !important } .a { .m() !important; }"),
+            ".a {\n  top: 0 !important;\n  left: 0 !important;\n}\n"
+        );
+    }
+
+    #[test]
+    fn mixin_arguments() {
+        assert_eq!(
+            less(
+                ".m(@a; @b: 2px) { width: @a; height: @b } .a { .m(1px) } .b { .m(@b: 3px; 1px) }"
+            ),
+            ".a {\n  width: 1px;\n  height: 2px;\n}\n.b {\n  width: 1px;\n  height: 3px;\n}\n"
+        );
+        // 有分号时逗号属于参数值
+        assert_eq!(
+            less(
+                ".m(@a; @b: 0) { font: @a; top: @b } .a { .m(1px, 2px; 3px) } .b { .m(1px, 2px) }"
+            ),
+            ".a {\n  font: 1px, 2px;\n  top: 3px;\n}\n.b {\n  font: 1px;\n  top: 2px;\n}\n"
+        );
+        assert_eq!(
+            less(".box(@style: solid; @width: 1px) { border: @arguments } .a { .box(dashed) }"),
+            ".a {\n  border: dashed 1px;\n}\n"
+        );
+        assert_eq!(
+            less(".m(@a; @rest...) { padding: @a; margin: @rest } .a { .m(1px, 2px, 3px) }"),
+            ".a {\n  padding: 1px;\n  margin: 2px 3px;\n}\n"
+        );
+        // 参数在调用处求值，默认值在 mixin 的作用域中求值
+        assert_eq!(
+            less("@c: red; .m(@a; @b: @a) { color: @a; background: @b } .a { @x: blue; .m(@x) }"),
+            ".a {\n  color: blue;\n  background: blue;\n}\n"
+        );
+    }
+
+    #[test]
+    fn mixin_pattern_matching() {
+        let source = ".m(dark; @c) { color: black; background: @c } .m(light; @c) { color: white; background: @c } .m(@_; @c) { border-color: @c }";
+        assert_eq!(
+            less(&format!("{} .a {{ .m(dark; red) }}", source)),
+            ".a {\n  color: black;\n  background: red;\n  border-color: red;\n}\n"
+        );
+        assert_eq!(
+            less(&format!("{} .a {{ .m(other; red) }}", source)),
+            ".a {\n  border-color: red;\n}\n"
+        );
+    }
+
+    #[test]
+    fn mixin_namespaces() {
+        let source = "#ns { .m() { color: red } } .m() { color: blue }";
+        assert_eq!(
+            less(&format!(
+                "{} .a {{ #ns > .m(); }} .b {{ #ns.m(); }} .c {{ .m; }}",
+                source
+            )),
+            ".a {\n  color: red;\n}\n.b {\n  color: red;\n}\n.c {\n  color: blue;\n}\n"
+        );
+    }
+
+    #[test]
+    fn mixin_guards() {
+        let source = ".m(@c) when (iscolor(@c)) { color: @c } .m(@c) when (isnumber(@c)) and (@c > 10) { width: @c } .m(@c) when (default()) { content: @c }";
+        assert_eq!(
+            less(&format!(
+                "{} .a {{ .m(red); .m(20px); }} .b {{ .m(5) }}",
+                source
+            )),
+            ".a {\n  color: red;\n  width: 20px;\n}\n.b {\n  content: 5;\n}\n"
+        );
+        // 逗号表示或，守卫都不满足时不输出也不报错
+        assert_eq!(
+            less(".t(@a) when (ispixel(@a)), (isem(@a)) { size: @a } .t(@a) when not (ispixel(@a)) { other: @a } .a { .t(1px); .t(2em); .t(50%) }"),
+            ".a {\n  size: 1px;\n  size: 2em;\n  other: 2em;\n  other: 50%;\n}\n"
+        );
+        assert_eq!(
+            less(".s(@v) when (isstring(@v)) { s: @v } .s(@v) when (iskeyword(@v)) { k: @v } .s(@v) when (isunit(@v, \"%\")) and (@v >= 50%) { u: @v } .a { .s(\"x\"); .s(abc); .s(60%); .s(40%) }"),
+            ".a {\n  s: \"x\";\n  k: abc;\n  u: 60%;\n}\n"
+        );
+    }
+
+    #[test]
+    fn css_guards() {
+        assert_eq!(
+            less("@mode: dark; .a when (@mode = dark) { top: 0 } .b when (@mode = light) { top: 1px } .c { & when (@mode = dark) { left: 0 } }"),
+            ".a {\n  top: 0;\n}\n.c {\n  left: 0;\n}\n"
+        );
+    }
+
+    #[test]
+    fn operations() {
+        assert_eq!(
+            less("@a: 10px; @w: 300px; .a { width: @a * 2; height: (@w / 2); margin: @a + 5 (1 + 2) * 3; top: -@a; padding: @a -@a }"),
+            ".a {\n  width: 20px;\n  height: 150px;\n  margin: 15px 9;\n  top: -10px;\n  padding: 10px -10px;\n}\n"
+        );
+        // 括号外的除法和 calc() 中的运算保留给浏览器
+        assert_eq!(
+            less("@a: 10px; .a { font: 12px/1.5 sans-serif; width: calc(100% - @a * 2); left: 1in - 24px }"),
+            ".a {\n  font: 12px/1.5 sans-serif;\n  width: calc(100% - 10px * 2);\n  left: 0.75in;\n}\n"
+        );
+        assert_eq!(
+            less(".a { color: #111 + #222; background: #fff - 1 }"),
+            ".a {\n  color: #333333;\n  background: #fefefe;\n}\n"
+        );
+    }
+
+    #[test]
+    fn color_functions() {
+        assert_eq!(
+            less("@c: #336699; .a { a: darken(@c, 10%); b: lighten(#000, 20%); c: fade(red, 50%); d: spin(#f00, 120); e: mix(#f00, #00f) }"),
+            ".a {\n  a: #264d73;\n  b: #333333;\n  c: rgba(255, 0, 0, 0.5);\n  d: #00ff00;\n  e: #800080;\n}\n"
+        );
+        assert_eq!(
+            less_error(".a { color: darken(10px, 5%) }"),
+            "error evaluating function `darken`"
+        );
+    }
+
+    #[test]
+    fn math_and_string_functions() {
+        assert_eq!(
+            less(".a { a: percentage(0.5); b: round(1.67, 1) ceil(2.3px) floor(2.7) }"),
+            ".a {\n  a: 50%;\n  b: 1.7 3px 2;\n}\n"
+        );
+        assert_eq!(
+            less("@name: foo; .a { filter: e(\"ms:alwaysHasItsOwnSyntax()\"); width: ~\"calc(100% - @{name})\"; content: %(\"%d/%s\", 1 + 2, \"a b\"); b: %('%A', \"a/b\") }"),
+            ".a {\n  filter: ms:alwaysHasItsOwnSyntax();\n  width: calc(100% - foo);\n  content: \"3/a b\";\n  b: '%22a%2Fb%22';\n}\n"
+        );
+    }
+
+    #[test]
+    fn list_functions() {
+        assert_eq!(
+            less("@list: a, b c, d; @sizes: 1px 2px 3px; .a { a: extract(@list, 2); b: length(@list); c: extract(@sizes, 3); d: length(@sizes) }"),
+            ".a {\n  a: b c;\n  b: 3;\n  c: 3px;\n  d: 3;\n}\n"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            less_error(".a { width: @missing }"),
+            "variable @missing is undefined"
+        );
+        assert_eq!(
+            less_error("@a: @b; @b: @a; .x { top: @a }"),
+            "recursive variable definition for @a"
+        );
+        assert_eq!(less_error(".a { .missing(); }"), ".missing is undefined");
+        assert_eq!(
+            less_error(".m(@a) { top: @a } .a { .m(1; 2) }"),
+            "No matching definition was found for `.m(1;2)`"
+        );
+        assert_eq!(
+            less_error(".m() { .m(); } .a { .m(); }"),
+            "too many nested mixin calls at `.m()`"
+        );
+        let error = compile_less(".a { top: @x }").unwrap_err();
+        assert_eq!((error.range.start_pos, error.range.end_pos), (10, 12));
+    }
+
+    #[test]
+    fn comments_and_interpolation() {
+        let source = r#"// line comment
+@name: banner;
+@prop: color;
+@w: 10px;
+@q: ~"(min-width: 768px)";
+/* kept */
+.@{name}-icon {
+  @{prop}: red; // trailing
+  background-@{prop}: blue;
+  width: ~"calc(100% - @{w})";
+}
+@v: w;
+.b { width: @@v; }
+@media @q { .c { color: red; } }
+"#;
+        assert_eq!(
+            less(source),
+            "/* kept */\n.banner-icon {\n  color: red;\n  background-color: blue;\n  width: calc(100% - 10px);\n}\n.b {\n  width: 10px;\n}\n@media (min-width: 768px) {\n  .c {\n    color: red;\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn extend() {
+        let source = ".a { color: red }
+.a.x:hover, .b .a { top: 0 }
+.c:extend(.a all) { color: blue }
+.e { &:extend(.a); }
+.f:extend(.c) {}
+@media print { .a { color: black } }
+";
+        assert_eq!(
+            less(source),
+            ".a, .c, .e, .f {\n  color: red;\n}\n.a.x:hover, .b .a, .c.x:hover, .b .c {\n  top: 0;\n}\n.c, .f {\n  color: blue;\n}\n@media print {\n  .a, .c, .e, .f {\n    color: black;\n  }\n}\n"
+        );
+        // 没有 all 时只匹配完全相同的选择器
+        assert_eq!(
+            less(".ab, .a.x { top: 0 } .c:extend(.a) {}"),
+            ".ab, .a.x {\n  top: 0;\n}\n"
+        );
+    }
+
+    #[test]
+    fn detached_rulesets() {
+        let source = "@detached: { background: red; };
+@color: blue;
+.d {
+  @detached();
+  width: 1px;
+  @r: { @color: green; .in { color: @color; } }
+  @r();
+}
+.m(@x) when (isruleset(@x)) { @x(); }
+.m(@x) when (default()) { v: @x; }
+.g { @d: { left: 0; }; .m(@d); .m(2); }
+";
+        assert_eq!(
+            less(source),
+            ".d {\n  background: red;\n  width: 1px;\n}\n.d .in {\n  color: green;\n}\n.g {\n  left: 0;\n  v: 2;\n}\n"
+        );
+        assert_eq!(less_error(".a { @nope(); }"), "variable @nope is undefined");
+        assert_eq!(
+            less_error("@a: 1; .a { @a(); }"),
+            "@a is not a detached ruleset"
+        );
+    }
+
+    #[test]
+    fn imports() {
+        let dir = env::temp_dir().join("css_tutorial_less_import_test");
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::create_dir_all(dir.join("include")).unwrap();
+        let files = [
+            (
+                "main.less",
+                r#"@import (reference) "lib/ref";
+@import "lib/vars";
+@import "./lib/vars.less";
+@import (optional) "missing";
+@import (inline) "raw.css";
+@import "plain.css";
+@import (css) "other";
+@import "common";
+.btn:extend(.button all) { .mixin(); }
+.x { color: @primary; }
+"#,
+            ),
+            (
+                "lib/ref.less",
+                ".button { padding: 1px; }
+.button:hover { padding: 2px; }
+.unused { top: 0; }
+.mixin() { margin: 0; }
+@media print { .button { color: black; } .other { top: 0; } }
+@font-face { font-family: f; }
+",
+            ),
+            ("lib/vars.less", "@primary: red;\n.vars { top: 0; }\n"),
+            ("raw.css", "a{b:c}\n"),
+            ("include/common.less", ".common { top: 0; }\n"),
+            ("cycle-a.less", "@import \"lib/cycle-b\";\n"),
+            ("lib/cycle-b.less", "@import \"../cycle-a\";\n"),
+        ];
+        for (name, content) in files {
+            fs::write(dir.join(name), content).unwrap();
+        }
+        let options = LessOptions {
+            include_paths: vec![dir.join("include")],
+            ..Default::default()
+        };
+        assert_eq!(
+            compile_less_file(dir.join("main.less"), &options).unwrap(),
+            "@import \"plain.css\";\n@import \"other\";\n.btn {\n  padding: 1px;\n}\n.btn:hover {\n  padding: 2px;\n}\n@media print {\n  .btn {\n    color: black;\n  }\n}\n.vars {\n  top: 0;\n}\na{b:c}\n.common {\n  top: 0;\n}\n.btn {\n  margin: 0;\n}\n.x {\n  color: red;\n}\n"
+        );
+
+        let error = compile_less_file(dir.join("main.less"), &LessOptions::default()).unwrap_err();
+        assert!(error.message.starts_with("'common.less' wasn't found."));
+        let error = compile_less_file(dir.join("cycle-a.less"), &options).unwrap_err();
+        assert!(error
+            .message
+            .ends_with("cycle-a.less -> lib/cycle-b.less -> ../cycle-a.less"));
+        assert!(error.message.starts_with("circular import: "));
+    }
+
+    #[test]
+    fn plain_css() {
+        let source = "a:hover { color: red }\n@media print {\n  a {\n    top: 0;\n  }\n}\n";
+        assert_eq!(
+            less(source),
+            "a:hover {\n  color: red;\n}\n@media print {\n  a {\n    top: 0;\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn translate_to_scss() {
+        let result = translate("@c: red; .m(@a; @b: 2) when (@a > 1) { w: @a; } .x { .m(3; 4); .m(1, 2; 3); .m(@b: 3); c: @c; }");
+        assert_eq!(
+            result.code,
+            "$c: red;\n@mixin m($a, $b: 2) {\n  @if $a > 1 {\n    w: $a;\n  }\n}\n.x {\n  @include m(3, 4);\n  @include m((1, 2), 3);\n  @include m($b: 3);\n  c: $c;\n}\n"
+        );
+        assert!(result.warnings.is_empty());
+        // 字符串、插值和 less 特有的函数
+        assert_eq!(
+            translate(".a-@{w} { @{p}-color: red; c: ~\"calc(100% - @{w})\"; d: e(\"x\"); f: %(\"%d px\", @w); g: fade(@c, 50%); h: tint(@c, 10%); i: spin(@c, 10); }").code,
+            ".a-#{$w} {\n  #{$p}-color: red;\n  c: unquote(\"calc(100% - #{$w})\");\n  d: unquote(\"x\");\n  f: \"#{$w} px\";\n  g: rgba($c, 50%);\n  h: mix(white, $c, 10%);\n  i: adjust-hue($c, 10);\n}\n"
+        );
+        // :extend、守卫、分离规则集和 at 规则
+        assert_eq!(
+            translate(".b:extend(.a all) { x: y; } .c when (iscolor(@c)) and not (@m = dark), (ispixel(@w)) { d: e; } .d { &:extend(.a); @r: { a: b; }; @r(); } @media @phone and (min-width: @w) { .e { f: g; } } @import \"x.less\";").code,
+            ".b {\n  @extend .a;\n  x: y;\n}\n@if type-of($c) == color and not ($m == dark) or unit($w) == \"px\" {\n  .c {\n    d: e;\n  }\n}\n.d {\n  @mixin r {\n    a: b;\n  }\n  @extend .a;\n  @include r;\n}\n@media #{$phone} and (min-width: $w) {\n  .e {\n    f: g;\n  }\n}\n@import \"x\";\n"
+        );
+    }
+
+    #[test]
+    fn translate_warnings() {
+        assert_eq!(
+            warnings(".m(dark; @a) { x: @arguments; } .m(@a) when (default()) { y: @@n; } .r { a: b; } .x { .r; #ns > .m(1); .m(2) !important; } @plugin \"p\"; @import (reference) \"a\"; .y { k: contrast(@c); }"),
+            [
+                "mixin `m` has several definitions, SCSS mixins can't be overloaded",
+                "mixin `m` matches the value `dark`, SCSS mixins can't match arguments",
+                "`@arguments` has no SCSS equivalent, use a `$args...` parameter",
+                "mixin `m` has several definitions, SCSS mixins can't be overloaded",
+                "guard `default()` has no SCSS equivalent and was dropped",
+                "variable variable `@@n` has no SCSS equivalent",
+                "ruleset `.r` used as a mixin was translated to @extend",
+                "namespace `#ns` of mixin `m` was dropped",
+                "`!important` after the call of mixin `m` was dropped",
+                "@plugin has no SCSS equivalent and was dropped",
+                "@import option `reference` has no SCSS equivalent and was dropped",
+                "Less function `contrast()` has no SCSS equivalent",
+            ]
+        );
+    }
+
+    #[test]
+    fn translated_scss_compiles_the_same() {
+        let source = "@size: 10px; @color: #333; .bordered(@width: 2px; @style: solid) when (@width > 1px) { border: @width @style @color; } .a { .bordered(4px); width: @size * 2; content: ~\"@{size}\"; } @media (min-width: 768px) { .a { margin: (@size / 2); } }";
+        let result = translate(source);
+        assert!(result.warnings.is_empty());
+        assert_eq!(compile_scss(&result.code).unwrap(), less(source));
+        // 先使用后声明的变量
+        let source = ".a { w: (@x / 2); @x: 10px; .b { h: @y; } @y: @x; }";
+        let result = translate(source);
+        assert_eq!(compile_scss(&result.code).unwrap(), less(source));
+        // @@var 改为注释
+        let result = translate("@v: w; @w: 1px; .c { top: @@v; left: 0; }");
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(
+            compile_scss(&result.code).unwrap(),
+            ".c {\n  left: 0;\n}\n"
+        );
+    }
+}
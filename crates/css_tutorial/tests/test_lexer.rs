@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod test_lexer {
 
-    use css_tutorial::{lexer::Lexer, token_type::TokenType};
+    use css_tutorial::{lexer::{Lexer, Syntax}, token_type::TokenType};
 
     //ANCHOR:test_token
     macro_rules! test_token {
@@ -200,4 +200,60 @@ mod test_lexer {
        assert!(lexer.get_peek_token().unwrap().check_type(TokenType::IdentToken));
        assert!(lexer.get_peek_peek_token().unwrap().check_type(TokenType::Colon));
     }
+    #[test]
+    fn test_less_line_comment() {
+        let source = "// comment\n.a";
+        let mut lexer = Lexer::with_syntax(source, Syntax::Less);
+        let token = lexer.eat_token();
+        assert!(token.check_type(TokenType::Comment));
+        assert_eq!(token.get_source_code(), "// comment");
+        let mut lexer = Lexer::new(source);
+        assert!(lexer.eat_token().check_type(TokenType::ForwardSlash));
+    }
+
+    #[test]
+    fn test_less_interpolation() {
+        let mut lexer = Lexer::with_syntax("@{name}-icon {", Syntax::Less);
+        let token = lexer.eat_token();
+        assert!(token.check_type(TokenType::IdentToken));
+        assert_eq!(token.get_source_code(), "@{name}-icon");
+        let mut lexer = Lexer::with_syntax("background-@{prop}:", Syntax::Less);
+        assert_eq!(lexer.eat_token().get_source_code(), "background-@{prop}");
+    }
+
+    #[test]
+    fn test_less_escape_and_variable_variable() {
+        let mut lexer = Lexer::with_syntax(r#"~"calc(100% - @{w})""#, Syntax::Less);
+        let token = lexer.eat_token();
+        assert!(token.check_type(TokenType::Str));
+        assert_eq!(token.get_source_code(), r#"~"calc(100% - @{w})""#);
+        let mut lexer = Lexer::with_syntax("@@name", Syntax::Less);
+        let token = lexer.eat_token();
+        assert!(token.check_type(TokenType::AtKeywordToken));
+        assert_eq!(token.get_source_code(), "@@name");
+    }
+    #[test]
+    fn test_scss_tokens() {
+        let source = "$a: 1 !default; %ph #{$b}-x $a != 2 % // c";
+        let mut lexer = Lexer::with_syntax(source, Syntax::Scss);
+        let expected = [
+            (TokenType::DollarVariable, "$a"),
+            (TokenType::Colon, ":"),
+            (TokenType::Digital, "1"),
+            (TokenType::Important, "!default"),
+            (TokenType::Semi, ";"),
+            (TokenType::PlaceholderToken, "%ph"),
+            (TokenType::IdentToken, "#{$b}-x"),
+            (TokenType::DollarVariable, "$a"),
+            (TokenType::NotEqual, "!="),
+            (TokenType::Digital, "2"),
+            (TokenType::Percent, "%"),
+            (TokenType::Comment, "// c"),
+        ];
+        for (token_type, raw) in expected {
+            let token = lexer.eat_token();
+            assert!(token.check_type(token_type), "{:?}", token);
+            assert_eq!(token.get_source_code(), raw);
+        }
+    }
 }
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod test_math {
+    use css_tutorial::{
+        math::{parse_math, simplify_math, MathError, MathExpr, MathType},
+        values::Value,
+    };
+
+    #[test]
+    fn test_parse_precedence() {
+        let expr = parse_math("calc(100% - 2 * 10px)").unwrap();
+        assert_eq!(
+            expr,
+            MathExpr::Sum(vec![
+                MathExpr::Value(Value::Percentage(100.0)),
+                MathExpr::Negate(Box::new(MathExpr::Product(vec![
+                    MathExpr::Value(Value::Number(2.0)),
+                    MathExpr::Value(Value::parse("10px").unwrap()),
+                ]))),
+            ])
+        );
+        assert_eq!(expr.resolve_type(), Ok(MathType::Length));
+    }
+
+    #[test]
+    fn test_whitespace_rules() {
+        assert_eq!(
+            parse_math("calc(1px+2px)"),
+            Err(MathError::UnexpectedToken("Number(\"+2px\")".to_string()))
+        );
+        assert_eq!(
+            parse_math("calc(1px -2px)"),
+            Err(MathError::UnexpectedToken("Number(\"-2px\")".to_string()))
+        );
+        assert_eq!(
+            parse_math("calc((1px)+ 2px)"),
+            Err(MathError::MissingWhitespace("+".to_string()))
+        );
+        assert_eq!(
+            parse_math("calc(1px-2px)"),
+            Err(MathError::UnknownUnit("px-2px".to_string()))
+        );
+        assert!(parse_math("calc(1px*2)").is_ok());
+        assert!(parse_math("calc(-1px + -2px)").is_ok());
+    }
+
+    #[test]
+    fn test_type_check() {
+        assert_eq!(
+            parse_math("calc(10px + 2)"),
+            Err(MathError::TypeMismatch(MathType::Length, MathType::Number))
+        );
+        assert_eq!(
+            parse_math("calc(10px * 2px)"),
+            Err(MathError::TypeMismatch(MathType::Length, MathType::Length))
+        );
+        assert_eq!(
+            parse_math("calc(10px / 2s)"),
+            Err(MathError::DivideByNonNumber(MathType::Time))
+        );
+        assert_eq!(
+            parse_math("calc(var(--a) + 2px)").unwrap().resolve_type(),
+            Ok(MathType::Length)
+        );
+        assert!(parse_math("clamp(1px, 2px)").is_err());
+    }
+
+    #[test]
+    fn test_simplify() {
+        let cases = [
+            ("calc(10px + 2px)", "12px"),
+            ("calc(1in + 4px)", "100px"),
+            ("calc(2 * 3)", "6"),
+            ("calc(100% - 2 * 10px)", "calc(100% - 20px)"),
+            ("calc(calc(1px + 2px) * 2)", "6px"),
+            ("calc(1px + calc(100% - 1px))", "100%"),
+            ("calc(2 * (10px + 5%))", "calc(20px + 10%)"),
+            ("calc(10px / 4)", "2.5px"),
+            ("calc(1em + 2em - 10px)", "calc(3em - 10px)"),
+            ("calc(var(--gap) * 2 + 4px)", "calc(2 * var(--gap) + 4px)"),
+            ("calc(-1 * (var(--a) + 2px))", "calc(-1 * var(--a) - 2px)"),
+            ("min(10px, 2em)", "min(10px, 2em)"),
+            ("min(10px, 1in)", "10px"),
+            ("max(1s, 200ms)", "1s"),
+            ("max(96px, 1in)", "96px"),
+            ("min(2in, 10px, 1cm)", "10px"),
+            ("clamp(1s, 100ms, 2000ms)", "1s"),
+            ("clamp(1rem, 3rem, 2rem)", "2rem"),
+            ("min(calc(5px + 5px), 20px)", "10px"),
+            ("calc(min(1px, 2px) + max(10%, 20%))", "calc(1px + 20%)"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(simplify_math(source).unwrap(), expected, "{}", source);
+        }
+        assert_eq!(simplify_math("calc(1e300px * 1e300)"), Err(MathError::NotFinite));
+        assert!(simplify_math("calc(1e308in + 1px)").unwrap().starts_with("calc("));
+    }
+}
@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod test_minify {
+    use std::{env, fs, path::PathBuf};
+
+    use css_tutorial::{
+        codegen::generate,
+        minify::{minify, minify_number},
+        optimizer::{merge_adjacent_rules, remove_duplicate_declarations, remove_empty_rules},
+        parser::parse_stylesheet,
+    };
+    use test_tool::compart_to_snapshot;
+
+    fn min(source: &str) -> String {
+        minify(&parse_stylesheet(source))
+    }
+
+    #[test]
+    fn minify_whitespace_and_comments() {
+        assert_eq!(
+            min("/* x */\na  >  b , c {\n  color : red ;\n  /* y */\n  top: 1px !important;\n}\n"),
+            "a>b,c{color:red;top:1px!important}"
+        );
+        assert_eq!(
+            min("/*! license */\na { color: red }"),
+            "/*! license */a{color:red}"
+        );
+        assert_eq!(
+            min("@media screen and (max-width: 100px) { a { color: red } }"),
+            "@media screen and (max-width:100px){a{color:red}}"
+        );
+    }
+
+    #[test]
+    fn minify_numbers() {
+        assert_eq!(minify_number("0.50px", true), ".5px");
+        assert_eq!(minify_number("0px", true), "0");
+        assert_eq!(minify_number("0px", false), "0px");
+        assert_eq!(minify_number("0%", true), "0%");
+        assert_eq!(minify_number("-0.25em", true), "-.25em");
+        assert_eq!(minify_number("10.0", true), "10");
+        assert_eq!(minify_number("500ms", true), ".5s");
+        assert_eq!(minify_number("50ms", true), "50ms");
+        assert_eq!(
+            min("a { margin: 0px 0.50em; flex: 1 1 0px; width: calc(0px + 10px) }"),
+            "a{margin:0 .5em;flex:1 1 0px;width:10px}"
+        );
+        // 自定义属性和无法化简的 calc 保持原样
+        assert_eq!(
+            min("a { --gap: 0px; width: calc(100% - 0px * 2 - var(--gap)) }"),
+            "a{--gap:0px;width:calc(100% - var(--gap))}"
+        );
+    }
+
+    #[test]
+    fn minify_colors() {
+        assert_eq!(
+            min("a { color: #FFFFFF; background: WHITE url(a.png); border-color: rgb(255, 0, 0) }"),
+            "a{color:#fff;background:#fff url(a.png);border-color:red}"
+        );
+        // animation-name 中的颜色名不是颜色
+        assert_eq!(
+            min("a { animation-name: white }"),
+            "a{animation-name:white}"
+        );
+    }
+
+    #[test]
+    fn remove_duplicates() {
+        let mut tree = parse_stylesheet("a { color: red; color: red; display: flex; display: grid; top: 1px !important; top: 2px }");
+        assert_eq!(remove_duplicate_declarations(&mut tree), 2);
+        assert_eq!(
+            generate(&tree),
+            "a {\n  color: red;\n  display: flex;\n  display: grid;\n  top: 1px !important;\n}\n"
+        );
+    }
+
+    #[test]
+    fn merge_rules() {
+        let mut tree =
+            parse_stylesheet("a { color: red } a { top: 0 } b { top: 0 } c { top: 1px }");
+        assert_eq!(merge_adjacent_rules(&mut tree), 1);
+        assert_eq!(
+            generate(&tree),
+            "a {\n  color: red;\n  top: 0;\n}\nb {\n  top: 0;\n}\nc {\n  top: 1px;\n}\n"
+        );
+        assert_eq!(
+            min("a { top: 0 } b { top: 0 } c { top: 1px }"),
+            "a,b{top:0}c{top:1px}"
+        );
+        // 带前缀的伪类不能与其他选择器合并
+        assert_eq!(
+            min("a::-moz-selection { color: red } a::selection { color: red }"),
+            "a::-moz-selection{color:red}a::selection{color:red}"
+        );
+        // 中间隔着其他规则时不合并
+        assert_eq!(
+            min("a { top: 0 } b { top: 1px } a { top: 0 }"),
+            "a{top:0}b{top:1px}a{top:0}"
+        );
+    }
+
+    #[test]
+    fn remove_empty() {
+        let mut tree = parse_stylesheet(
+            "a {} @media print { b { } } @font-face {} @layer base {} c { top: 0 }",
+        );
+        assert_eq!(remove_empty_rules(&mut tree), 4);
+        assert_eq!(min("a {} @media print { b { } } c { top: 0 }"), "c{top:0}");
+    }
+
+    #[test]
+    fn minify_merges_longhands() {
+        assert_eq!(
+            min("a { margin-top: 0; margin-right: 0; margin-bottom: 0; margin-left: 0 }"),
+            "a{margin:0}"
+        );
+        let result = min("a { border-image: url(x.png) 30; border-top-width: 1px; border-right-width: 1px; border-bottom-width: 1px; border-left-width: 1px; border-top-style: solid; border-right-style: solid; border-bottom-style: solid; border-left-style: solid; border-top-color: red; border-right-color: red; border-bottom-color: red; border-left-color: red }");
+        assert!(result.contains("border-image:url(x.png) 30"));
+        assert!(!result.contains("border:"), "{}", result);
+    }
+
+    fn root_file(name: &str) -> PathBuf {
+        PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
+            .join("../..")
+            .join(name)
+    }
+
+    #[test]
+    fn minify_snapshot() {
+        let source = fs::read_to_string(root_file("test1.css")).unwrap();
+        let result = minify(&parse_stylesheet(&source));
+        assert!(result.len() < source.len());
+        // 压缩结果再次解析后输出不变
+        assert_eq!(minify(&parse_stylesheet(&result)), result);
+        compart_to_snapshot(result, "minify_test1");
+    }
+}
@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod test_optimizer {
+    use css_tutorial::{
+        codegen::generate,
+        optimizer::{compose, merge_longhands},
+        parser::parse_stylesheet,
+        shorthands::expand_shorthands,
+    };
+
+    fn merge(source: &str) -> (String, usize) {
+        let mut tree = parse_stylesheet(source);
+        let count = merge_longhands(&mut tree);
+        (generate(&tree), count)
+    }
+
+    fn values(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn compose_values() {
+        assert_eq!(compose("margin", &values(&["0", "auto", "0", "auto"])).unwrap(), "0 auto");
+        assert_eq!(compose("margin", &values(&["1px", "1px", "1px", "1px"])).unwrap(), "1px");
+        assert_eq!(compose("padding", &values(&["1px", "2px", "3px", "2px"])).unwrap(), "1px 2px 3px");
+        assert_eq!(
+            compose("padding", &values(&["1px", "2px", "3px", "4px"])).unwrap(),
+            "1px 2px 3px 4px"
+        );
+        assert_eq!(
+            compose("border-radius", &values(&["1px 2px", "1px 2px", "1px 2px", "1px 2px"])).unwrap(),
+            "1px / 2px"
+        );
+        assert_eq!(compose("border-top", &values(&["1px", "solid", "currentcolor"])).unwrap(), "1px solid");
+        assert_eq!(compose("outline", &values(&["medium", "none", "currentcolor"])).unwrap(), "none");
+        assert_eq!(compose("flex", &values(&["1", "1", "0%"])).unwrap(), "1");
+        assert_eq!(compose("flex", &values(&["0", "0", "auto"])).unwrap(), "none");
+        assert_eq!(compose("flex", &values(&["2", "1", "10px"])).unwrap(), "2 10px");
+        assert_eq!(compose("flex", &values(&["2", "3", "0"])).unwrap(), "2 3 0px");
+        assert_eq!(compose("grid-area", &values(&["a", "a", "a", "a"])).unwrap(), "a");
+        assert_eq!(compose("grid-area", &values(&["1", "2", "auto", "auto"])).unwrap(), "1 / 2");
+        assert_eq!(compose("grid-area", &values(&["a", "b", "a", "c"])).unwrap(), "a / b / a / c");
+        assert_eq!(compose("list-style", &values(&["none", "outside", "none"])).unwrap(), "none");
+        assert_eq!(compose("list-style", &values(&["square", "inside", "none"])).unwrap(), "square inside");
+        assert_eq!(compose("margin", &values(&["inherit", "inherit", "inherit", "inherit"])).unwrap(), "inherit");
+        assert_eq!(compose("margin", &values(&["inherit", "0", "0", "0"])), None);
+        assert_eq!(compose("margin", &values(&["var(--a)", "0", "0", "0"])), None);
+    }
+
+    #[test]
+    fn merge_rule() {
+        let (css, count) = merge(
+            "a { margin-top: 0; color: red; margin-right: auto; margin-bottom: 0; margin-left: auto; }",
+        );
+        assert_eq!(count, 1);
+        assert_eq!(css, "a {\n  color: red;\n  margin: 0 auto;\n}\n");
+    }
+
+    #[test]
+    fn merge_border() {
+        let (css, _) = merge(
+            "a { border-top-width: 1px; border-right-width: 1px; border-bottom-width: 1px; border-left-width: 1px; border-top-style: solid; border-right-style: solid; border-bottom-style: solid; border-left-style: solid; border-top-color: red; border-right-color: red; border-bottom-color: red; border-left-color: red; }",
+        );
+        assert_eq!(css, "a {\n  border: 1px solid red;\n}\n");
+        let (css, count) = merge(
+            "a { border-top-width: 1px; border-right-width: 2px; border-bottom-width: 1px; border-left-width: 2px; border-top-style: solid; border-right-style: solid; border-bottom-style: solid; border-left-style: solid; }",
+        );
+        assert_eq!(count, 2);
+        assert_eq!(css, "a {\n  border-width: 1px 2px;\n  border-style: solid;\n}\n");
+        // border 会重置 border-image，只合并到各个方向
+        let (css, _) = merge(
+            "a { border-image: url(x.png) 30; border-top-width: 1px; border-right-width: 1px; border-bottom-width: 1px; border-left-width: 1px; border-top-style: solid; border-right-style: solid; border-bottom-style: solid; border-left-style: solid; border-top-color: red; border-right-color: red; border-bottom-color: red; border-left-color: red; }",
+        );
+        assert!(css.contains("border-image: url(x.png) 30;"));
+        assert!(!css.contains("border: "));
+    }
+
+    #[test]
+    fn skip_unsafe_merges() {
+        // 缺少长属性
+        assert_eq!(merge("a { margin-top: 0; margin-right: 0; margin-bottom: 0; }").1, 0);
+        // !important 不一致
+        assert_eq!(
+            merge("a { margin-top: 0 !important; margin-right: 0; margin-bottom: 0; margin-left: 0; }").1,
+            0
+        );
+        // 重复的长属性可能是回退
+        assert_eq!(
+            merge("a { margin-top: 0; margin-top: 1vh; margin-right: 0; margin-bottom: 0; margin-left: 0; }").1,
+            0
+        );
+        // 简写本身也出现在规则中
+        assert_eq!(
+            merge("a { margin-top: 0; margin: 1px; margin-right: 0; margin-bottom: 0; margin-left: 0; }").1,
+            0
+        );
+        assert_eq!(
+            merge("a { padding-top: var(--p); padding-right: 0; padding-bottom: 0; padding-left: 0; }").1,
+            0
+        );
+        let (css, count) = merge(
+            "a { margin-top: 0 !important; margin-right: 0 !important; margin-bottom: 0 !important; margin-left: 0 !important; }",
+        );
+        assert_eq!(count, 1);
+        assert_eq!(css, "a {\n  margin: 0 !important;\n}\n");
+    }
+
+    #[test]
+    fn expand_then_merge() {
+        let source = "@media print { a { flex: 1 1 0%; border-radius: 2px 2px 2px 2px; inset: 0 0 0 0; } }";
+        let mut tree = parse_stylesheet(source);
+        expand_shorthands(&mut tree);
+        assert_eq!(merge_longhands(&mut tree), 3);
+        assert_eq!(
+            generate(&tree),
+            "@media print {\n  a {\n    flex: 1;\n    border-radius: 2px;\n    inset: 0;\n  }\n}\n"
+        );
+    }
+}
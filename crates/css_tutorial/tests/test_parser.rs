@@ -653,4 +653,239 @@ mod tests {
         println!("{}",serde_json::to_string_pretty(&builder.ast_tree).unwrap());
        
     }
+    #[test]
+    fn scss_syntax() {
+        use css_tutorial::{
+            ast::AstNode, codegen::generate, lexer::Syntax, parser::parse_stylesheet_with_syntax,
+            token_type::TokenType,
+        };
+
+        let source = r#"$base: 10px !default;
+$map: (primary: red, "secondary": #00f);
+%message { color: red; }
+@mixin button($size, $radius: 2px, $rest...) { padding: $size * 2; @content; }
+@function double($n) { @return $n * 2; }
+.btn-#{$i} {
+  @extend %message;
+  @include button(4px, $radius: 3px) { color: blue; }
+  #{$prop}-top: ($base + 1) / 2;
+  @if $a == 1 and $b != 2 { a: b; } @else if $a >= 3 { c: d; } @else { e: f; }
+  @each $key, $value in $map { .#{$key} { color: $value; } }
+  @for $i from 1 through 3 { .m-#{$i} { margin: $i % 2; } }
+  @while $i > 0 { $i: $i - 1; }
+}
+"#;
+        let tree = parse_stylesheet_with_syntax(source, Syntax::Scss);
+        fn collect(node: &AstNode<TokenType>, types: &mut Vec<TokenType>) {
+            types.push(node.node_type.0);
+            for child in node.child_nodes() {
+                collect(child, types);
+            }
+        }
+        let mut types = Vec::new();
+        collect(tree.root.as_ref().unwrap(), &mut types);
+        for node_type in [
+            TokenType::MixinRule,
+            TokenType::IncludeRule,
+            TokenType::ContentRule,
+            TokenType::FunctionRule,
+            TokenType::ReturnRule,
+            TokenType::IfRule,
+            TokenType::ElseRule,
+            TokenType::EachRule,
+            TokenType::ForRule,
+            TokenType::WhileRule,
+            TokenType::ExtendRule,
+            TokenType::ArgumentList,
+            TokenType::Map,
+            TokenType::DollarVariable,
+            TokenType::PlaceholderToken,
+        ] {
+            assert!(types.contains(&node_type), "{:?}", node_type);
+        }
+        assert_eq!(
+            generate(&tree),
+            r#"$base: 10px !default;
+$map: (primary: red, "secondary": #00f);
+%message {
+  color: red;
+}
+@mixin button($size, $radius: 2px, $rest...) {
+  padding: $size * 2;
+  @content;
+}
+@function double($n) {
+  @return $n * 2;
+}
+.btn-#{$i} {
+  @extend %message;
+  @include button(4px, $radius: 3px) {
+    color: blue;
+  }
+  #{$prop}-top: ($base + 1)/2;
+  @if $a == 1 and $b != 2 {
+    a: b;
+  } @else if $a >= 3 {
+    c: d;
+  } @else {
+    e: f;
+  }
+  @each $key, $value in $map {
+    .#{$key} {
+      color: $value;
+    }
+  }
+  @for $i from 1 through 3 {
+    .m-#{$i} {
+      margin: $i % 2;
+    }
+  }
+  @while $i > 0 {
+    $i: $i - 1;
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn at_rule_handlers() {
+        use css_tutorial::{
+            codegen::generate,
+            parser::{AtRuleBlock, AtRuleHandler},
+            token_type::TokenType,
+        };
+
+        // @screen md, lg { ... } 的参数解析为 MediumList
+        #[derive(Debug)]
+        struct Screen;
+        impl AtRuleHandler for Screen {
+            fn block(&self) -> AtRuleBlock {
+                AtRuleBlock::Rules
+            }
+            fn parse_prelude(&self, parser: &mut Parser) {
+                parser.parse_media_list();
+            }
+        }
+
+        let source = r#"@tailwind base;
+@custom-media --small (max-width: 30em);
+@theme { --color-primary: red; font-size: 1rem; }
+@screen md, lg { .a { @apply font-bold py-2; color: red; } }
+@media print { .b { c: d; } }
+"#;
+        let mut lexer = Lexer::new(source);
+        let mut builder = AstTreeBuilder::new();
+        let mut parser = Parser::new(&mut lexer, &mut builder);
+        parser.register_at_rule("tailwind", AtRuleBlock::None);
+        parser.register_at_rule("@Custom-Media", AtRuleBlock::None);
+        parser.register_at_rule("@apply", AtRuleBlock::None);
+        parser.register_at_rule("@theme", AtRuleBlock::Declarations);
+        parser.register_at_rule("@screen", Screen);
+        parser.parse();
+        let root = builder.ast_tree.root.as_ref().unwrap();
+        let rules = root.child_nodes();
+        assert_eq!(rules.len(), 5);
+        assert!(rules.iter().all(|n| n.check_type(TokenType::AtRule)));
+
+        let theme = rules[2].find_child(TokenType::DeclarationList).unwrap();
+        let declarations = theme.child_nodes().iter()
+            .filter(|n| n.check_type(TokenType::Declaration))
+            .count();
+        assert_eq!(declarations, 2);
+
+        let screen = &rules[3];
+        let params = screen.find_child(TokenType::AtRuleParams).unwrap();
+        assert!(params.find_child(TokenType::MediumList).is_some());
+        assert!(screen.find_child(TokenType::LeftCurlyBracket).is_some());
+        let apply = screen.find_child(TokenType::RuleList).unwrap()
+            .find_child(TokenType::DeclarationList).unwrap()
+            .find_child(TokenType::AtRule).unwrap();
+        assert!(apply.find_child(TokenType::DeclarationList).is_none());
+
+        assert_eq!(
+            generate(&builder.ast_tree),
+            r#"@tailwind base;
+@custom-media --small (max-width: 30em);
+@theme {
+  --color-primary: red;
+  font-size: 1rem;
+}
+@screen md, lg {
+  .a {
+    @apply font-bold py-2;
+    color: red;
+  }
+}
+@media print {
+  .b {
+    c: d;
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn plain_css_when_selector() {
+        use css_tutorial::{codegen::generate, parser::parse_stylesheet, token_type::TokenType};
+
+        // when 只在 less 中表示守卫
+        let tree = parse_stylesheet("a when { top: 0 }");
+        let rule = tree.root.as_ref().unwrap().find_child(TokenType::RuleList).unwrap();
+        assert!(rule.find_child(TokenType::Guard).is_none());
+        let selectors = rule.find_child(TokenType::SelectorList).unwrap();
+        assert_eq!(selectors.child_nodes().len(), 2);
+        assert_eq!(generate(&tree), "a when {\n  top: 0;\n}\n");
+    }
+
+    #[test]
+    fn plain_css_important_in_selector() {
+        use css_tutorial::{parser::parse_stylesheet, token_type::TokenType};
+
+        // !important 只在 less mixin 调用后面属于规则，css 中留在选择器里
+        let tree = parse_stylesheet(".x !important { c: d } b { e: f }");
+        let rules = tree.root.as_ref().unwrap().child_nodes();
+        assert_eq!(rules.len(), 2);
+        assert!(rules.iter().all(|n| n.check_type(TokenType::RuleList)));
+        assert!(rules[0].find_child(TokenType::Important).is_none());
+        let selectors = rules[0].find_child(TokenType::SelectorList).unwrap();
+        assert_eq!(selectors.raw, ".x!important");
+        assert!(rules[0].find_child(TokenType::DeclarationList).is_some());
+    }
+
+    #[test]
+    fn at_rule_without_block_missing_semi() {
+        use css_tutorial::{codegen::generate, parser::AtRuleBlock, token_type::TokenType};
+
+        let source = "@tailwind base\n.a { color: red; }\n.b { @apply font-bold }";
+        let mut lexer = Lexer::new(source);
+        let mut builder = AstTreeBuilder::new();
+        let mut parser = Parser::new(&mut lexer, &mut builder);
+        parser.register_at_rule("tailwind", AtRuleBlock::None);
+        parser.register_at_rule("apply", AtRuleBlock::None);
+        parser.parse();
+        let root = builder.ast_tree.root.as_ref().unwrap();
+        let rules = root.child_nodes();
+        assert_eq!(rules.len(), 3);
+        assert!(rules[0].check_type(TokenType::AtRule));
+        assert!(rules[0].find_child(TokenType::LeftCurlyBracket).is_none());
+        assert!(rules[0].find_child(TokenType::DeclarationList).is_none());
+        assert!(rules[1].check_type(TokenType::RuleList));
+        let apply = rules[2].find_child(TokenType::DeclarationList).unwrap()
+            .find_child(TokenType::AtRule).unwrap();
+        assert!(apply.find_child(TokenType::LeftCurlyBracket).is_none());
+
+        assert_eq!(
+            generate(&builder.ast_tree),
+            r#"@tailwind base;
+.a {
+  color: red;
+}
+.b {
+  @apply font-bold;
+}
+"#
+        );
+    }
 }
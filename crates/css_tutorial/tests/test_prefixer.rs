@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod test_prefixer {
+    use css_tutorial::{
+        codegen::generate,
+        parser::parse_stylesheet,
+        prefixer::{autoprefix, Prefix, PrefixOptions},
+        targets::{Browser, Targets, Version},
+    };
+
+    fn options(list: &[(Browser, u32, u32)]) -> PrefixOptions {
+        let list: Vec<(Browser, Version)> = list
+            .iter()
+            .map(|(browser, major, minor)| (*browser, Version::new(*major, *minor)))
+            .collect();
+        PrefixOptions {
+            targets: Targets::from_list(&list),
+            keep_outdated: false,
+        }
+    }
+
+    fn prefix(source: &str, options: &PrefixOptions) -> (String, usize) {
+        let mut tree = parse_stylesheet(source);
+        let count = autoprefix(&mut tree, options);
+        (generate(&tree), count)
+    }
+
+    fn old_browsers() -> PrefixOptions {
+        options(&[
+            (Browser::Safari, 6, 0),
+            (Browser::Firefox, 15, 0),
+            (Browser::Ie, 11, 0),
+        ])
+    }
+
+    #[test]
+    fn strip_prefix() {
+        assert_eq!(
+            Prefix::strip("-webkit-transform"),
+            Some((Prefix::Webkit, "transform"))
+        );
+        assert_eq!(
+            Prefix::strip("-MS-user-select"),
+            Some((Prefix::Ms, "user-select"))
+        );
+        assert_eq!(Prefix::strip("transform"), None);
+        assert_eq!(Prefix::strip("-moz-"), None);
+    }
+
+    #[test]
+    fn prefix_properties() {
+        let (result, count) = prefix(
+            "a { user-select: none; transition: transform 1s }",
+            &old_browsers(),
+        );
+        assert_eq!(count, 5);
+        assert_eq!(
+            result,
+            "a {\n  -webkit-user-select: none;\n  -moz-user-select: none;\n  -ms-user-select: none;\n  user-select: none;\n  -webkit-transition: -webkit-transform 1s;\n  -moz-transition: -moz-transform 1s;\n  transition: transform 1s;\n}\n"
+        );
+        // 已经写了前缀时不会重复添加
+        let (result, count) = prefix(
+            "a { -webkit-user-select: none; user-select: none }",
+            &options(&[(Browser::Safari, 16, 0)]),
+        );
+        assert_eq!(count, 0);
+        assert_eq!(
+            result,
+            "a {\n  -webkit-user-select: none;\n  user-select: none;\n}\n"
+        );
+        // 新版本浏览器不需要前缀
+        let (_, count) = prefix(
+            "a { transform: none }",
+            &options(&[(Browser::Chrome, 120, 0)]),
+        );
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn prefix_values() {
+        let (result, _) = prefix(
+            "a { background: linear-gradient(to right, red, blue); position: sticky; display: flex }",
+            &options(&[(Browser::Safari, 6, 0)]),
+        );
+        assert_eq!(
+            result,
+            "a {\n  background: -webkit-linear-gradient(left, red, blue);\n  background: linear-gradient(to right, red, blue);\n  position: -webkit-sticky;\n  position: sticky;\n  display: -webkit-flex;\n  display: flex;\n}\n"
+        );
+        let (result, _) = prefix(
+            "a { background-image: linear-gradient(90deg, red, blue) }",
+            &options(&[(Browser::Firefox, 15, 0)]),
+        );
+        assert_eq!(
+            result,
+            "a {\n  background-image: -moz-linear-gradient(0deg, red, blue);\n  background-image: linear-gradient(90deg, red, blue);\n}\n"
+        );
+        // 只在尺寸属性中处理 max-content
+        let (_, count) = prefix(
+            "a { grid-template-columns: max-content }",
+            &options(&[(Browser::Firefox, 60, 0)]),
+        );
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn prefix_selectors() {
+        let (result, count) = prefix("input::placeholder { color: gray }", &old_browsers());
+        assert_eq!(count, 3);
+        assert_eq!(
+            result,
+            "input::-webkit-input-placeholder {\n  color: gray;\n}\ninput::-moz-placeholder {\n  color: gray;\n}\ninput:-ms-input-placeholder {\n  color: gray;\n}\ninput::placeholder {\n  color: gray;\n}\n"
+        );
+    }
+
+    #[test]
+    fn prefix_keyframes() {
+        let (result, _) = prefix(
+            "@keyframes spin { to { transform: rotate(1turn) } }",
+            &options(&[(Browser::Safari, 8, 0)]),
+        );
+        assert_eq!(
+            result,
+            "@-webkit-keyframes spin {\n  to {\n    -webkit-transform: rotate(1turn);\n    transform: rotate(1turn);\n  }\n}\n@keyframes spin {\n  to {\n    -webkit-transform: rotate(1turn);\n    transform: rotate(1turn);\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn remove_outdated() {
+        let modern = options(&[(Browser::Chrome, 120, 0)]);
+        let (result, count) = prefix(
+            "a { -webkit-transform: none; transform: none; -moz-box-sizing: border-box; -webkit-box-shadow: none; display: -webkit-flex; display: flex }",
+            &modern,
+        );
+        assert_eq!(count, 3);
+        assert_eq!(
+            result,
+            "a {\n  transform: none;\n  box-sizing: border-box;\n  -webkit-box-shadow: none;\n  display: flex;\n}\n"
+        );
+        let (result, _) = prefix(
+            "a::-moz-selection { color: red } a::selection { color: red } @-webkit-keyframes x { to { top: 0 } } @keyframes x { to { top: 0 } }",
+            &modern,
+        );
+        assert_eq!(
+            result,
+            "a::selection {\n  color: red;\n}\n@keyframes x {\n  to {\n    top: 0;\n  }\n}\n"
+        );
+        let keep = PrefixOptions {
+            keep_outdated: true,
+            ..modern
+        };
+        let (_, count) = prefix("a { -webkit-transform: none; transform: none }", &keep);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn prefix_flexbox_for_ie() {
+        let ie10 = options(&[(Browser::Ie, 10, 0)]);
+        let (result, count) = prefix("a { display: flex; flex: 1; order: 2 }", &ie10);
+        assert_eq!(count, 2);
+        assert_eq!(
+            result,
+            "a {\n  display: -ms-flexbox;\n  display: flex;\n  -ms-flex: 1;\n  flex: 1;\n  order: 2;\n}\n"
+        );
+        // ie 10 需要的前缀不会被当作过时的前缀删除
+        let source = "a { display: -ms-flexbox; display: flex; -ms-flex: 1; flex: 1 }";
+        let (result, count) = prefix(source, &ie10);
+        assert_eq!(count, 0);
+        assert_eq!(result, generate(&parse_stylesheet(source)));
+        let (result, _) = prefix(source, &options(&[(Browser::Ie, 11, 0)]));
+        assert!(!result.contains("-ms-flex:"));
+    }
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod test_purge {
+    use std::{env, fs};
+
+    use css_tutorial::{
+        codegen::generate,
+        parser::parse_stylesheet,
+        purge::{PurgeOptions, Purger},
+    };
+    use regex::Regex;
+
+    const CSS: &str = r#"
+        html, body { margin: 0 }
+        .btn, .btn-unused { color: red }
+        #app > .title { font-family: "Brand Sans", serif }
+        .md\:flex { display: flex }
+        @media (min-width: 600px) {
+            .sidebar { display: none }
+            .btn:hover { color: blue }
+        }
+        @keyframes spin { from { opacity: 0 } to { opacity: 1 } }
+        @keyframes fade { from { opacity: 0 } to { opacity: 1 } }
+        .spinner { animation: spin 1s linear }
+        @font-face { font-family: "Brand Sans"; src: url(a.woff2) }
+        @font-face { font-family: Unused; src: url(b.woff2) }
+        .dynamic-red { color: red }
+        "#;
+
+    #[test]
+    fn purge_unused() {
+        let mut purger = Purger::new(PurgeOptions {
+            safelist: vec!["body".to_string()],
+            safelist_patterns: vec![Regex::new("^dynamic-").unwrap()],
+            ..Default::default()
+        });
+        purger.add_content(
+            r#"<html><div id="app"><h1 className="title md:flex">x</h1><button class="btn">b</button></div></html>"#,
+        );
+        let mut tree = parse_stylesheet(CSS);
+        let report = purger.purge(&mut tree);
+        assert_eq!(
+            report.removed_selectors,
+            vec![".btn-unused", ".sidebar", ".spinner"]
+        );
+        assert_eq!(
+            report.removed_at_rules,
+            vec!["@keyframes spin", "@keyframes fade", "@font-face"]
+        );
+        test_tool::compart_to_snapshot(generate(&tree), "purge_unused");
+    }
+
+    #[test]
+    fn purge_from_files() {
+        let dir = env::temp_dir().join("css_tutorial_purge_test");
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("App.jsx");
+        fs::write(&file, r#"export const App = () => <div className={`sidebar ${x}`} />;"#).unwrap();
+        let mut purger = Purger::new(PurgeOptions::default());
+        purger.add_file(&file).unwrap();
+        assert!(purger.add_file(dir.join("missing.html")).is_err());
+        let mut tree = parse_stylesheet(".sidebar { color: red } .header { color: blue }");
+        let report = purger.purge(&mut tree);
+        assert_eq!(report.removed_selectors, vec![".header"]);
+        assert_eq!(generate(&tree), ".sidebar {\n  color: red;\n}\n");
+    }
+}
@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod test_scss {
+    use css_tutorial::scss::compile_scss;
+
+    fn scss(source: &str) -> String {
+        compile_scss(source).unwrap()
+    }
+
+    fn scss_error(source: &str) -> String {
+        compile_scss(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(
+            scss("$a: 1 !default; $a: 2 !default; $g: 1; .a { $g: 5 !global; w: $a; } .b { g: $g; }"),
+            ".a {\n  w: 1;\n}\n.b {\n  g: 5;\n}\n"
+        );
+        // 块中的变量只在块中可见，不带 !global 时遮盖全局变量
+        assert_eq!(
+            scss("$c: red; .a { $c: blue; $d: 1px; color: $c; } .b { color: $c; }"),
+            ".a {\n  color: blue;\n}\n.b {\n  color: red;\n}\n"
+        );
+    }
+
+    #[test]
+    fn nesting() {
+        assert_eq!(
+            scss(".a { &:hover, .x & { c: red; } .b { d: 1; } &-e { f: g; } }"),
+            ".a:hover, .x .a {\n  c: red;\n}\n.a .b {\n  d: 1;\n}\n.a-e {\n  f: g;\n}\n"
+        );
+        assert_eq!(
+            scss(".t { @at-root .u { g: h; } @at-root &-w { k: l; } m: n; }"),
+            ".t {\n  m: n;\n}\n.u {\n  g: h;\n}\n.t-w {\n  k: l;\n}\n"
+        );
+    }
+
+    #[test]
+    fn operations() {
+        assert_eq!(
+            scss("$a: 10px; .a { w: $a * 2; h: $a + 5px; m: 0 -$a; font: 12px/1.5 sans-serif; d: (10px / 4); s: 1-2*3; n: 10px-3px; }"),
+            ".a {\n  w: 20px;\n  h: 15px;\n  m: 0 -10px;\n  font: 12px/1.5 sans-serif;\n  d: 2.5px;\n  s: -5;\n  n: 7px;\n}\n"
+        );
+        assert_eq!(
+            scss(".a { a: 1px == 1px; b: \"a\" != a; c: true and false; d: not true; e: 2 > 1 or false; f: 1in == 96px; }"),
+            ".a {\n  a: true;\n  b: false;\n  c: false;\n  d: false;\n  e: true;\n  f: true;\n}\n"
+        );
+    }
+
+    #[test]
+    fn control_flow() {
+        assert_eq!(
+            scss(".a { $i: 0; @each $k, $v in (a: 1, b: 2) { #{$k}: $v; } @for $j from 3 to 1 { f#{$j}: $j; } @while $i < 2 { w: $i; $i: $i + 1; } @if $i == 2 { i: two; } @else { i: other; } }"),
+            ".a {\n  a: 1;\n  b: 2;\n  f3: 3;\n  f2: 2;\n  w: 0;\n  w: 1;\n  i: two;\n}\n"
+        );
+        assert_eq!(
+            scss("@for $i from 1 through 2 { .m-#{$i} { margin: $i * 4px; } }"),
+            ".m-1 {\n  margin: 4px;\n}\n.m-2 {\n  margin: 8px;\n}\n"
+        );
+        // @while 的循环次数没有限制
+        assert_eq!(
+            scss(".a { $i: 0; @while $i < 20000 { $i: $i + 1; } n: $i; }"),
+            ".a {\n  n: 20000;\n}\n"
+        );
+    }
+
+    #[test]
+    fn mixins() {
+        assert_eq!(
+            scss("@mixin m($x, $y: 2) { v: $x $y; @content; } .a { @include m(1, $y: 3) { c: d; } }"),
+            ".a {\n  v: 1 3;\n  c: d;\n}\n"
+        );
+        // @content 中可以访问调用处的变量，mixin 中的规则与调用处的选择器组合
+        assert_eq!(
+            scss("@mixin hover { &:hover { @content; } } .a { $c: red; @include hover { color: $c; } }"),
+            ".a:hover {\n  color: red;\n}\n"
+        );
+        assert_eq!(
+            scss("@mixin m($args...) { l: length($args); } .a { @include m(1, 2, 3); }"),
+            ".a {\n  l: 3;\n}\n"
+        );
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(
+            scss("@function double($n) { @return $n * 2; } @function sum($ns...) { $s: 0; @each $n in $ns { $s: $s + $n; } @return $s; } .a { d: double(3px); s: sum(1, 2, 3); }"),
+            ".a {\n  d: 6px;\n  s: 6;\n}\n"
+        );
+        assert_eq!(
+            scss("@function fib($n) { @if $n <= 1 { @return $n; } @return fib($n - 1) + fib($n - 2); } .a { f: fib(10); }"),
+            ".a {\n  f: 55;\n}\n"
+        );
+        // 未知的函数作为 css 函数输出
+        assert_eq!(
+            scss("$w: 10px; .a { w: calc(100% - #{$w}); t: translate($w, 0); m: min(1px, 2vw); c: if(true, yes, no); }"),
+            ".a {\n  w: calc(100% - 10px);\n  t: translate(10px, 0);\n  m: min(1px, 2vw);\n  c: yes;\n}\n"
+        );
+    }
+
+    #[test]
+    fn extend() {
+        assert_eq!(
+            scss("%btn { padding: 0; } %unused { x: y; } .a { @extend %btn; color: red; } .b { @extend .a; }"),
+            ".a, .b {\n  padding: 0;\n}\n.a, .b {\n  color: red;\n}\n"
+        );
+        // 带 !optional 的 @extend 可以匹配不到规则，@media 中只能扩展同一个 @media 中的规则
+        assert_eq!(
+            scss(".b { @extend .nope !optional; c: d; } @media print { %p { a: b; } .x { @extend %p; } }"),
+            ".b {\n  c: d;\n}\n@media print {\n  .x {\n    a: b;\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn modules() {
+        assert_eq!(
+            scss("@use \"sass:math\"; @use \"sass:color\" as c; @use \"sass:map\"; @use \"sass:list\"; .a { w: math.div(10px, 4); x: c.adjust(#336699, $lightness: 10%); y: map.get((a: (b: 1)), a, b); z: list.nth(a b c, -1); k: map.keys(map.merge((a: 1), (b: 2))); r: math.round(math.$pi * 100) / 100; }"),
+            ".a {\n  w: 2.5px;\n  x: #4080bf;\n  y: 1;\n  z: c;\n  k: a, b;\n  r: 3.14;\n}\n"
+        );
+        assert_eq!(
+            scss(".a { b: lighten(#336699, 20%); c: darken(#336699, 10%); d: rgba(#336699, .5); e: mix(#f00, #00f, 25%); f: percentage(.5); }"),
+            ".a {\n  b: #6699cc;\n  c: #264d73;\n  d: rgba(51, 102, 153, 0.5);\n  e: #4000bf;\n  f: 50%;\n}\n"
+        );
+    }
+
+    #[test]
+    fn interpolation() {
+        assert_eq!(
+            scss("$name: foo; $bp: 768px; .icon-#{$name} { content: \"#{$name} #{1 + 1}\"; } @media (min-width: $bp + 1) { .a { b: c; } } .d { @media screen and (max-width: $bp) { e: f; } }"),
+            ".icon-foo {\n  content: \"foo 2\";\n}\n@media (min-width: 769px) {\n  .a {\n    b: c;\n  }\n}\n@media screen and (max-width: 768px) {\n  .d {\n    e: f;\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(scss_error(".a { b: $x }"), "variable $x is undefined");
+        assert_eq!(scss_error("@include nope;"), "mixin nope is undefined");
+        assert_eq!(scss_error(".a { b: 1px + 1s }"), "incompatible units s and px");
+        assert_eq!(
+            scss_error("@mixin m($a) {} .a { @include m(1, 2) }"),
+            "only 1 arguments allowed, but 2 were passed"
+        );
+        assert_eq!(scss_error("@error \"bad #{1 + 1}\";"), "bad 2");
+        assert_eq!(
+            scss_error("@extend .x;"),
+            "@extend may only be used within style rules"
+        );
+        assert_eq!(
+            scss_error(".b { @extend .nope; }"),
+            "The target selector was not found.\nUse \"@extend .nope !optional\" to avoid this error."
+        );
+        assert_eq!(
+            scss_error("%p { a: b; } @media print { .x { @extend %p; } }"),
+            "You may not @extend selectors across media queries."
+        );
+        assert_eq!(
+            scss_error("@function f() { @return f(); } .a { b: f() }"),
+            "too many nested calls of f"
+        );
+    }
+}
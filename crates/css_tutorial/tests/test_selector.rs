@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod test_selector {
+    use css_tutorial::{
+        html::Document,
+        selector::{parse_nth, parse_selector_list, Specificity},
+    };
+
+    const HTML: &str = r#"<html><body>
+        <ul id="list">
+            <li class="item first">a</li>
+            <li class="item" data-lang="en-US">b</li>
+            <li class="item last"><a href="/x">c</a></li>
+        </ul>
+        <p></p>
+    </body></html>"#;
+
+    fn select(selector: &str) -> Vec<String> {
+        let document = Document::parse(HTML);
+        let selector = parse_selector_list(selector);
+        document
+            .elements()
+            .into_iter()
+            .filter(|id| selector.matches(&document, *id))
+            .map(|id| {
+                let element = document.element(id).unwrap();
+                format!("{}{}", element.tag_name, element.classes().collect::<Vec<_>>().join("."))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compound_and_descendant() {
+        assert_eq!(select(".item.first"), vec!["liitem.first"]);
+        assert_eq!(select("ul .item"), vec!["liitem.first", "liitem", "liitem.last"]);
+        assert_eq!(select("body > li"), Vec::<String>::new());
+        assert_eq!(select("#list > li a"), vec!["a"]);
+        assert_eq!(select(".first + li"), vec!["liitem"]);
+        assert_eq!(select(".first ~ li"), vec!["liitem", "liitem.last"]);
+    }
+
+    #[test]
+    fn attribute() {
+        assert_eq!(select("[data-lang|=en]"), vec!["liitem"]);
+        assert_eq!(select("a[href^='/']"), vec!["a"]);
+        assert_eq!(select("li[class$=last]"), vec!["liitem.last"]);
+        assert_eq!(select("[class~=item]").len(), 3);
+    }
+
+    #[test]
+    fn pseudo_class() {
+        assert_eq!(select("li:nth-child(2n+1)"), vec!["liitem.first", "liitem.last"]);
+        assert_eq!(select("li:last-child"), vec!["liitem.last"]);
+        assert_eq!(select("li:not(.first, .last)"), vec!["liitem"]);
+        assert_eq!(select("li:has(> a)"), vec!["liitem.last"]);
+        assert_eq!(select("li:has(+ li > a)"), vec!["liitem"]);
+        assert_eq!(select("p:empty"), vec!["p"]);
+        assert_eq!(select(":root"), vec!["html"]);
+        assert!(select("li:hover").is_empty());
+        assert!(select("li::before").is_empty());
+    }
+
+    #[test]
+    fn relative_has() {
+        // :has() 中的选择器从当前元素开始匹配，外层祖先的 .a 不算
+        let document =
+            Document::parse(r#"<div class="a"><section><p class="b"></p></section></div>"#);
+        let matched = |selector: &str, tag: &str| {
+            let element = document.elements_by_tag_name(tag)[0];
+            parse_selector_list(selector).matches(&document, element)
+        };
+        assert!(!matched("section:has(.a .b)", "section"));
+        assert!(matched("section:has(.b)", "section"));
+        assert!(matched("div:has(section .b)", "div"));
+        assert!(!matched("div:has(div .b)", "div"));
+    }
+
+    #[test]
+    fn specificity() {
+        let specificity = |s: &str| parse_selector_list(s).max_specificity();
+        assert_eq!(specificity("#a .b c"), Specificity(1, 1, 1));
+        assert_eq!(specificity("a:not(#x) ::before"), Specificity(1, 0, 2));
+        assert_eq!(specificity(":where(#x) .a[b]"), Specificity(0, 2, 0));
+        assert_eq!(specificity("*"), Specificity(0, 0, 0));
+    }
+
+    #[test]
+    fn nth() {
+        assert_eq!(parse_nth("odd"), Some((2, 1)));
+        assert_eq!(parse_nth("-n+3"), Some((-1, 3)));
+        assert_eq!(parse_nth("3n-2"), Some((3, -2)));
+        assert_eq!(parse_nth("5"), Some((0, 5)));
+    }
+}
@@ -0,0 +1,216 @@
+#[cfg(test)]
+mod test_shorthands {
+    use css_tutorial::{
+        codegen::generate,
+        parser::{parse_single_declaration, parse_stylesheet},
+        shorthands::{expand_declaration, expand_shorthands},
+    };
+
+    fn expand(source: &str) -> Option<Vec<String>> {
+        let declaration = parse_single_declaration(source).unwrap();
+        expand_declaration(&declaration).map(|longhands| {
+            longhands
+                .iter()
+                .map(|l| {
+                    format!(
+                        "{}: {}{}",
+                        l.property,
+                        l.value,
+                        if l.important { " !important" } else { "" }
+                    )
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn box_shorthands() {
+        assert_eq!(
+            expand("margin: 0 auto").unwrap(),
+            vec!["margin-top: 0", "margin-right: auto", "margin-bottom: 0", "margin-left: auto"]
+        );
+        assert_eq!(
+            expand("padding: 1px 2px 3px").unwrap(),
+            vec!["padding-top: 1px", "padding-right: 2px", "padding-bottom: 3px", "padding-left: 2px"]
+        );
+        assert_eq!(
+            expand("inset: 0 !important").unwrap(),
+            vec!["top: 0 !important", "right: 0 !important", "bottom: 0 !important", "left: 0 !important"]
+        );
+        assert_eq!(
+            expand("border-radius: 10px 5% / 20px").unwrap(),
+            vec![
+                "border-top-left-radius: 10px 20px",
+                "border-top-right-radius: 5% 20px",
+                "border-bottom-right-radius: 10px 20px",
+                "border-bottom-left-radius: 5% 20px"
+            ]
+        );
+        assert_eq!(expand("margin: 1px 2px 3px 4px 5px"), None);
+    }
+
+    #[test]
+    fn border_and_outline() {
+        let border = expand("border: 1px solid red").unwrap();
+        assert_eq!(border.len(), 12);
+        assert_eq!(border[0], "border-top-width: 1px");
+        assert_eq!(border[4], "border-top-style: solid");
+        assert_eq!(border[11], "border-left-color: red");
+        assert_eq!(
+            expand("outline: dashed").unwrap(),
+            vec!["outline-width: medium", "outline-style: dashed", "outline-color: currentcolor"]
+        );
+        assert_eq!(
+            expand("border-top: #fff thick").unwrap(),
+            vec!["border-top-width: thick", "border-top-style: none", "border-top-color: #fff"]
+        );
+        assert_eq!(expand("border: 1px solid red blue"), None);
+    }
+
+    #[test]
+    fn keywords_and_var() {
+        assert_eq!(
+            expand("flex: inherit").unwrap(),
+            vec!["flex-grow: inherit", "flex-shrink: inherit", "flex-basis: inherit"]
+        );
+        assert_eq!(expand("margin: var(--gap) 0"), None);
+        assert_eq!(expand("color: red"), None);
+    }
+
+    #[test]
+    fn background() {
+        assert_eq!(
+            expand("background: url(a.png) no-repeat center / cover #fff").unwrap(),
+            vec![
+                "background-image: url(a.png)",
+                "background-position: center",
+                "background-size: cover",
+                "background-repeat: no-repeat",
+                "background-attachment: scroll",
+                "background-origin: padding-box",
+                "background-clip: border-box",
+                "background-color: #fff",
+            ]
+        );
+        assert_eq!(
+            expand("background: url(a.png) 0 0 repeat-x content-box, linear-gradient(red, blue) fixed red").unwrap(),
+            vec![
+                "background-image: url(a.png), linear-gradient(red, blue)",
+                "background-position: 0 0, 0% 0%",
+                "background-size: auto, auto",
+                "background-repeat: repeat-x, repeat",
+                "background-attachment: scroll, fixed",
+                "background-origin: content-box, padding-box",
+                "background-clip: content-box, border-box",
+                "background-color: red",
+            ]
+        );
+        // 颜色只能在最后一层
+        assert_eq!(expand("background: red, url(a.png)"), None);
+    }
+
+    #[test]
+    fn font() {
+        assert_eq!(
+            expand("font: italic bold 12px/30px Georgia, \"Times New Roman\", serif").unwrap(),
+            vec![
+                "font-style: italic",
+                "font-variant: normal",
+                "font-weight: bold",
+                "font-stretch: normal",
+                "font-size: 12px",
+                "line-height: 30px",
+                "font-family: Georgia, \"Times New Roman\", serif",
+            ]
+        );
+        assert_eq!(
+            expand("font: 600 condensed small-caps 1.2em Open Sans").unwrap()[..5],
+            [
+                "font-style: normal",
+                "font-variant: small-caps",
+                "font-weight: 600",
+                "font-stretch: condensed",
+                "font-size: 1.2em",
+            ]
+        );
+        assert_eq!(expand("font: menu"), None);
+        assert_eq!(expand("font: bold 12px"), None);
+    }
+
+    #[test]
+    fn flex_grid_list() {
+        let flex = |source| expand(source).unwrap().join("; ");
+        assert_eq!(flex("flex: none"), "flex-grow: 0; flex-shrink: 0; flex-basis: auto");
+        assert_eq!(flex("flex: auto"), "flex-grow: 1; flex-shrink: 1; flex-basis: auto");
+        assert_eq!(flex("flex: 2"), "flex-grow: 2; flex-shrink: 1; flex-basis: 0%");
+        assert_eq!(flex("flex: 10em"), "flex-grow: 1; flex-shrink: 1; flex-basis: 10em");
+        assert_eq!(flex("flex: 2 3"), "flex-grow: 2; flex-shrink: 3; flex-basis: 0%");
+        assert_eq!(flex("flex: 1 30px"), "flex-grow: 1; flex-shrink: 1; flex-basis: 30px");
+        assert_eq!(flex("flex: 2 0 10%"), "flex-grow: 2; flex-shrink: 0; flex-basis: 10%");
+        assert_eq!(expand("flex: 1 2 3"), None);
+
+        assert_eq!(
+            flex("grid-area: header"),
+            "grid-row-start: header; grid-column-start: header; grid-row-end: header; grid-column-end: header"
+        );
+        assert_eq!(
+            flex("grid-area: 1 / span 2"),
+            "grid-row-start: 1; grid-column-start: span 2; grid-row-end: auto; grid-column-end: auto"
+        );
+
+        assert_eq!(
+            flex("list-style: none"),
+            "list-style-type: none; list-style-position: outside; list-style-image: none"
+        );
+        assert_eq!(
+            flex("list-style: square inside url(a.png)"),
+            "list-style-type: square; list-style-position: inside; list-style-image: url(a.png)"
+        );
+        assert_eq!(
+            flex("list-style: none url(a.png)"),
+            "list-style-type: none; list-style-position: outside; list-style-image: url(a.png)"
+        );
+    }
+
+    #[test]
+    fn transition_animation() {
+        assert_eq!(
+            expand("transition: opacity .3s ease-in, transform 1s 200ms").unwrap(),
+            vec![
+                "transition-property: opacity, transform",
+                "transition-duration: .3s, 1s",
+                "transition-timing-function: ease-in, ease",
+                "transition-delay: 0s, 200ms",
+            ]
+        );
+        assert_eq!(
+            expand("animation: 3s ease-in 1s infinite reverse both running slidein").unwrap(),
+            vec![
+                "animation-name: slidein",
+                "animation-duration: 3s",
+                "animation-timing-function: ease-in",
+                "animation-delay: 1s",
+                "animation-iteration-count: infinite",
+                "animation-direction: reverse",
+                "animation-fill-mode: both",
+                "animation-play-state: running",
+            ]
+        );
+        assert_eq!(
+            expand("animation: spin 2s linear infinite").unwrap()[..2],
+            ["animation-name: spin", "animation-duration: 2s"]
+        );
+    }
+
+    #[test]
+    fn expand_stylesheet() {
+        let mut tree = parse_stylesheet(
+            "a { margin: 0 auto !important; color: red; padding: var(--p); flex: 1; }",
+        );
+        assert_eq!(expand_shorthands(&mut tree), 2);
+        assert_eq!(
+            generate(&tree),
+            "a {\n  margin-top: 0 !important;\n  margin-right: auto !important;\n  margin-bottom: 0 !important;\n  margin-left: auto !important;\n  color: red;\n  padding: var(--p);\n  flex-grow: 1;\n  flex-shrink: 1;\n  flex-basis: 0%;\n}\n"
+        );
+    }
+}
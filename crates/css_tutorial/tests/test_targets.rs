@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod test_targets {
+    use css_tutorial::targets::{resolve_query, Browser, QueryError, Targets, Version};
+
+    fn names(query: &str) -> Vec<String> {
+        resolve_query(query)
+            .unwrap()
+            .iter()
+            .map(|(browser, version)| format!("{} {}", browser.name(), version))
+            .collect()
+    }
+
+    #[test]
+    fn parse_version() {
+        assert_eq!(Version::parse("14"), Some(Version::new(14, 0)));
+        assert_eq!(Version::parse("4.4.3"), Some(Version::new(4, 4)));
+        assert_eq!(Version::parse("x"), None);
+        assert!(Version::new(15, 4) > Version::new(15, 0));
+        assert_eq!(Version::new(17, 5).to_string(), "17.5");
+    }
+
+    #[test]
+    fn browser_queries() {
+        assert_eq!(names("ie 11"), ["ie 11"]);
+        assert_eq!(names("safari >= 17.4"), ["safari 17.4", "safari 17.5"]);
+        assert_eq!(
+            names("chrome 100-110"),
+            ["chrome 100", "chrome 103", "chrome 109"]
+        );
+        assert_eq!(
+            names("last 2 firefox versions"),
+            ["firefox 124", "firefox 125"]
+        );
+        assert_eq!(names("Firefox ESR"), ["firefox 115"]);
+        assert_eq!(
+            names("last 1 safari major versions"),
+            [
+                "safari 17",
+                "safari 17.1",
+                "safari 17.2",
+                "safari 17.3",
+                "safari 17.4",
+                "safari 17.5"
+            ]
+        );
+    }
+
+    #[test]
+    fn combine_queries() {
+        assert_eq!(
+            names("chrome 100-110 and not chrome 103"),
+            ["chrome 100", "chrome 109"]
+        );
+        assert_eq!(names("ie 10, ie 11, not ie 10"), ["ie 11"]);
+        assert_eq!(names("> 5%"), ["chrome 124", "chrome 125"]);
+        assert_eq!(
+            names("> 5% or edge 125"),
+            ["chrome 124", "chrome 125", "edge 125"]
+        );
+        // dead 的浏览器不会出现在结果中
+        assert!(names("last 2 versions, not dead")
+            .iter()
+            .all(|n| !n.starts_with("ie")));
+        assert!(names("last 2 versions").iter().any(|n| n == "ie 11"));
+    }
+
+    #[test]
+    fn query_targets() {
+        let targets =
+            Targets::from_query("> 0.5%, last 2 versions, not dead, safari >= 14").unwrap();
+        assert_eq!(targets.version(Browser::Safari), Some(Version::new(14, 1)));
+        assert_eq!(targets.version(Browser::Chrome), Some(Version::new(109, 0)));
+        assert_eq!(targets.version(Browser::Ie), None);
+        let defaults = Targets::from_query("defaults").unwrap();
+        assert_eq!(
+            defaults.version(Browser::Firefox),
+            Some(Version::new(115, 0))
+        );
+    }
+
+    #[test]
+    fn query_errors() {
+        assert_eq!(
+            resolve_query("foo 3"),
+            Err(QueryError::UnknownBrowser("foo".to_string()))
+        );
+        assert_eq!(
+            resolve_query("chrome 3"),
+            Err(QueryError::UnknownVersion("chrome 3".to_string()))
+        );
+        assert_eq!(
+            resolve_query("hello world again"),
+            Err(QueryError::UnknownBrowser("hello".to_string()))
+        );
+        assert_eq!(
+            resolve_query("> a%"),
+            Err(QueryError::UnknownQuery("> a%".to_string()))
+        );
+        assert_eq!(
+            resolve_query("> nan%"),
+            Err(QueryError::UnknownQuery("> nan%".to_string()))
+        );
+        assert_eq!(
+            resolve_query("< inf%"),
+            Err(QueryError::UnknownQuery("< inf%".to_string()))
+        );
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod test_values {
+    use css_tutorial::{
+        parser::parse_stylesheet,
+        token_type::TokenType,
+        values::{
+            format_number, split_number, AngleUnit, Length, LengthUnit, ResolutionUnit, TimeUnit,
+            Value,
+        },
+    };
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(Value::parse("12px"), Some(Value::Length(Length::new(12.0, LengthUnit::Px))));
+        assert_eq!(Value::parse("1.5EM"), Some(Value::Length(Length::new(1.5, LengthUnit::Em))));
+        assert_eq!(Value::parse("-.5"), Some(Value::Number(-0.5)));
+        assert_eq!(Value::parse("50%"), Some(Value::Percentage(50.0)));
+        assert_eq!(Value::parse("1e2px").map(|v| v.to_string()), Some("100px".to_string()));
+        assert_eq!(Value::parse("2fr"), Some(Value::Flex(2.0)));
+        assert_eq!(Value::parse("3foo"), Some(Value::Dimension(3.0, "foo".to_string())));
+        assert_eq!(Value::parse("px"), None);
+        assert_eq!(split_number("2em"), Some((2.0, "em")));
+        assert_eq!(Value::parse("1e400px"), None);
+    }
+
+    #[test]
+    fn convert_units() {
+        let inch = Length::new(1.0, LengthUnit::In);
+        assert_eq!(inch.to_px(), Some(96.0));
+        assert_eq!(inch.convert_to(LengthUnit::Cm).unwrap().to_string(), "2.54cm");
+        assert_eq!(Length::new(12.0, LengthUnit::Pt).canonicalize().to_string(), "16px");
+        assert_eq!(Length::new(2.0, LengthUnit::Rem).convert_to(LengthUnit::Px), None);
+        let turn = Value::parse("0.25turn").unwrap().canonicalize();
+        assert_eq!(turn.to_string(), "90deg");
+        assert_eq!(Value::parse("1.5s").unwrap().canonicalize().to_string(), "1500ms");
+        assert_eq!(Value::parse("96dpi").unwrap().canonicalize().to_string(), "1dppx");
+        assert_eq!(AngleUnit::from_unit("RAD"), Some(AngleUnit::Rad));
+        assert_eq!(TimeUnit::Ms.as_str(), "ms");
+        assert_eq!(ResolutionUnit::from_unit("x").unwrap().factor(), Some(1.0));
+        // 换算溢出时保留原来的单位
+        let huge = Length::new(1e308, LengthUnit::In);
+        assert_eq!(huge.convert_to(LengthUnit::Px), None);
+        assert_eq!(huge.canonicalize(), huge);
+        assert!(!huge.to_string().contains("inf"));
+    }
+
+    #[test]
+    fn from_ast() {
+        let tree = parse_stylesheet("a { margin: 10px 2em -.5rem 5% }");
+        let mut values = Vec::new();
+        fn walk(node: &css_tutorial::ast::AstNode<TokenType>, values: &mut Vec<Value>) {
+            if node.check_type(TokenType::Term) {
+                values.extend(Value::from_node(node));
+            }
+            node.child_nodes().iter().for_each(|child| walk(child, values));
+        }
+        walk(tree.root.as_ref().unwrap(), &mut values);
+        assert_eq!(
+            values.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+            vec!["10px", "2em", "-0.5rem", "5%"]
+        );
+        assert_eq!(format_number(0.1 + 0.2), "0.3");
+    }
+}
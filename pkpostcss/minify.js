@@ -0,0 +1,18 @@
+var bench = require('nanobench')
+var postcss = require('postcss');
+var cssnano = require('cssnano');
+var fs = require('fs');
+const path = require('path');
+
+// 输出 cssnano 压缩 test1.css 的耗时和体积，用于和 minify 对比
+const css = fs.readFileSync(path.join(__dirname, '../test1.css'))
+
+bench('cssnano minify test1.css', async function (b) {
+    const processor = postcss([cssnano({ preset: 'default' })])
+
+    b.start()
+    const result = await processor.process(css, { from: undefined })
+    b.end()
+
+    console.log(css.length + ' -> ' + result.css.length)
+})